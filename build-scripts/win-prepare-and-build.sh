@@ -44,7 +44,6 @@ cp -rfv /tmp/mingw64/share/icons/hicolor/scalable/actions package/share/icons/hi
 cp -rfv /tmp/mingw64/share/gtksourceview-5 package/share/
 cp -rfv /tmp/mingw64/lib/girepository-1.0/ package/lib/
 cp -rfv /tmp/mingw64/share/gir-1.0/ package/share/
-export CHRONO_TZ_TIMEZONE_FILTER="(GMT|UTC|Brazil/.*)"
 git config --global --add safe.directory /mnt
 git config --global --list
 git describe
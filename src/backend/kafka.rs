@@ -3,47 +3,89 @@
 // found in the COPYING file.
 
 use futures::future;
+use once_cell::sync::Lazy;
 use rdkafka::admin::{AdminClient, AdminOptions, NewTopic, TopicReplication};
-use rdkafka::client::{ClientContext, DefaultClientContext};
+use rdkafka::client::ClientContext;
 use rdkafka::config::{ClientConfig, FromClientConfigAndContext, RDKafkaLogLevel};
 use rdkafka::consumer::BaseConsumer;
-use rdkafka::consumer::{Consumer, ConsumerContext};
-use rdkafka::error::{KafkaError, KafkaResult};
+use rdkafka::consumer::{CommitMode, Consumer, ConsumerContext};
+use rdkafka::error::{KafkaError, KafkaResult, RDKafkaErrorCode};
 use rdkafka::message::{Header, Headers, OwnedHeaders};
 
 use rdkafka::producer::{FutureProducer, FutureRecord};
 use rdkafka::topic_partition_list::TopicPartitionList;
-use rdkafka::{Message, Offset};
+use rdkafka::{bindings as rdsys, Message, Offset};
+use serde::Serialize;
 use tokio::select;
 use tokio::sync::mpsc::{self, Receiver, Sender};
 
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::ffi::{c_void, CStr};
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tracing::{debug, info, trace, warn};
+use tracing::{debug, error, info, trace, warn};
+use uuid::Uuid;
 
-use crate::backend::repository::{KrustConnection, KrustHeader, KrustMessage, Partition};
+use crate::backend::repository::{
+    KrustConnection, KrustHeader, KrustMessage, Partition, SaslMechanism,
+};
 use crate::component::task_manager::{Task, TaskManagerMsg, TASK_MANAGER_BROKER};
 use crate::config::ExternalError;
-use crate::Settings;
+use crate::{AppMsg, Settings, TOASTER_BROKER};
 
+use super::payload::PayloadDecoder;
+use super::repository::Repository;
 use super::repository::{
     FetchMode, KrustConnectionSecurityType, KrustTopic, KrustTopicCache, MessagesRepository,
 };
+use super::schema_registry::SchemaRegistryClient;
 
 const GROUP_ID: &str = "krust-kafka-client";
+/// Number of messages written to the cache per transaction.
+pub(crate) const WRITER_BATCH_SIZE: usize = 1000;
+/// Consecutive empty polls, each a fraction of the poll timeout, after which a consumer gives
+/// up on messages that never arrive.
+const CACHE_STALL_POLL_CYCLES: u32 = 5;
+/// Partition id used to let the producer partitioner choose the partition by key.
+pub const PARTITION_BY_KEY: i32 = -1;
+/// Shortest time between two authentication failure toasts, as librdkafka reports the
+/// failure again on every retry.
+const AUTH_FAILURE_TOAST_INTERVAL: Duration = Duration::from_secs(60);
+static LAST_AUTH_FAILURE_TOAST: Lazy<Mutex<Option<Instant>>> = Lazy::new(|| Mutex::new(None));
 
 // rdkafka: begin
 
 // A context can be used to change the behavior of producers and consumers by adding callbacks
 // that will be executed by librdkafka.
-// This particular context sets up custom callbacks to log rebalancing events.
+// This particular context sets up custom callbacks to log rebalancing events and to report
+// authentication failures, including OAUTHBEARER token fetches, to the user.
 struct CustomContext;
 
-impl ClientContext for CustomContext {}
+impl ClientContext for CustomContext {
+    fn error(&self, error: KafkaError, reason: &str) {
+        error!("librdkafka: {}: {}", error, reason);
+        if error.rdkafka_error_code() == Some(RDKafkaErrorCode::Authentication) {
+            toast_authentication_failure(reason);
+        }
+    }
+}
+
+fn toast_authentication_failure(reason: &str) {
+    let mut last_toast = LAST_AUTH_FAILURE_TOAST.lock().unwrap();
+    if last_toast.is_some_and(|at| at.elapsed() < AUTH_FAILURE_TOAST_INTERVAL) {
+        return;
+    }
+    *last_toast = Some(Instant::now());
+    let id = Uuid::new_v4().to_string();
+    TOASTER_BROKER.send(AppMsg::ShowToast(
+        id.clone(),
+        format!("Authentication failed: {}", reason),
+    ));
+    TOASTER_BROKER.send(AppMsg::HideToast(id));
+}
 
 impl ConsumerContext for CustomContext {
     fn commit_callback(&self, result: KafkaResult<()>, _offsets: &TopicPartitionList) {
@@ -56,7 +98,7 @@ type LoggingConsumer = BaseConsumer<CustomContext>;
 
 // rdkafka: end
 
-#[derive(Debug, Clone, Default, strum::EnumString, strum::Display)]
+#[derive(Debug, Clone, Default, PartialEq, strum::EnumString, strum::Display)]
 pub enum KafkaFetch {
     #[default]
     Newest,
@@ -67,11 +109,176 @@ impl KafkaFetch {
     pub const VALUES: [Self; 2] = [Self::Newest, Self::Oldest];
 }
 
+fn parse_extra_config(extra_config: &str) -> Vec<(String, String)> {
+    extra_config
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| match line.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Some((key.trim().to_string(), value.trim().to_string()))
+            }
+            _ => {
+                warn!("kafka::config::invalid extra config line: {}", line);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Marks a partition as read up to its high offset, returning whether it was still pending.
+fn partition_consumed(pending_partitions: &Mutex<HashSet<i32>>, partition: i32) -> bool {
+    pending_partitions.lock().unwrap().remove(&partition)
+}
+
+/// Whether a consumer position is at or past the partition's high offset, which also
+/// happens when the last offsets are transaction markers that are never delivered.
+fn position_reached(last_offsets: &HashMap<i32, i64>, partition: i32, position: Offset) -> bool {
+    last_offsets
+        .get(&partition)
+        .is_some_and(|max| position.to_raw().unwrap_or(-1) >= *max)
+}
+
+/// First offset to read and high offset of each assigned partition, resolving logical
+/// start offsets against the partition offsets.
+fn assigned_offset_ranges(
+    assigned: &TopicPartitionList,
+    partitions: &[Partition],
+) -> HashMap<i32, (i64, i64)> {
+    assigned
+        .elements()
+        .iter()
+        .filter_map(|elem| {
+            let partition = partitions.iter().find(|p| p.id == elem.partition())?;
+            let low = partition.offset_low.unwrap_or_default();
+            let high = partition.offset_high.unwrap_or_default();
+            let first = match elem.offset() {
+                Offset::Offset(offset) => offset,
+                Offset::OffsetTail(count) => (high - count).max(low),
+                Offset::End => high,
+                _ => low,
+            };
+            Some((elem.partition(), (first.min(high), high)))
+        })
+        .collect()
+}
+
+/// Assigned partitions with records left to read.
+fn partitions_with_records(ranges: &HashMap<i32, (i64, i64)>) -> HashSet<i32> {
+    ranges
+        .iter()
+        .filter(|(_, (first, high))| high > first)
+        .map(|(id, _)| *id)
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct KrustBroker {
+    pub id: i32,
+    pub host: String,
+    pub port: i32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct KrustClusterMetadata {
+    pub cluster_id: Option<String>,
+    pub controller_id: Option<i32>,
+    pub orig_broker_id: i32,
+    pub orig_broker_name: String,
+    pub topic_count: usize,
+    pub brokers: Vec<KrustBroker>,
+}
+
+/// Offsets and message count of one partition in a topics report.
+#[derive(Debug, Clone, Serialize)]
+pub struct PartitionReport {
+    pub partition: i32,
+    pub offset_low: i64,
+    pub offset_high: i64,
+    pub messages: i64,
+}
+
+/// Partition count and message totals of one topic, for capacity planning.
+#[derive(Debug, Clone, Serialize)]
+pub struct TopicReport {
+    pub topic: String,
+    pub partition_count: usize,
+    pub total_messages: usize,
+    pub partitions: Vec<PartitionReport>,
+}
+
+#[derive(Debug, Clone)]
+pub struct KrustConsumerGroup {
+    pub name: String,
+    pub state: String,
+    pub protocol: String,
+    pub members: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct KrustConsumerGroupOffset {
+    pub partition: i32,
+    /// Last committed offset, `None` when the group has not committed on the partition.
+    pub committed: Option<i64>,
+    pub high_watermark: i64,
+    pub lag: Option<i64>,
+}
+
+/// Where to move a consumer group's committed offsets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OffsetResetTarget {
+    Earliest,
+    Latest,
+    /// First offset at or after the epoch millis, latest when there is none.
+    Timestamp(i64),
+}
+
+impl OffsetResetTarget {
+    pub const VALUES: [Self; 3] = [Self::Earliest, Self::Latest, Self::Timestamp(0)];
+}
+
+impl std::fmt::Display for OffsetResetTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Earliest => write!(f, "Earliest"),
+            Self::Latest => write!(f, "Latest"),
+            Self::Timestamp(_) => write!(f, "Timestamp"),
+        }
+    }
+}
+
+/// Producer `acks` setting, i.e. how many replicas must acknowledge a record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, strum::EnumString, strum::Display)]
+pub enum ProducerAcks {
+    #[default]
+    #[strum(serialize = "all")]
+    All,
+    #[strum(serialize = "1")]
+    Leader,
+    #[strum(serialize = "0")]
+    None,
+}
+
+impl ProducerAcks {
+    pub const VALUES: [Self; 3] = [Self::All, Self::Leader, Self::None];
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SendOptions {
+    pub task: Option<Task>,
+    pub preserve_timestamp: bool,
+    /// Overrides librdkafka `acks` for this send.
+    pub acks: Option<ProducerAcks>,
+    /// Overrides the `message.timeout.ms` from settings for this send.
+    pub message_timeout_ms: Option<usize>,
+}
+
 #[derive(Debug, Clone)]
 pub struct CreateTopicRequest {
     pub name: String,
     pub partition_count: u16,
     pub replica_count: u8,
+    pub configs: Vec<(String, String)>,
 }
 
 #[derive(Debug, Clone)]
@@ -106,23 +313,91 @@ impl KafkaBackend {
         info!("kafka::connection::timeout: {:?}", timeout);
         timeout
     }
+    fn poll_timeout(&self) -> Duration {
+        let poll_timeout = Settings::read().unwrap_or_default().poll_timeout_ms as u64;
+        Duration::from_millis(poll_timeout)
+    }
+    fn payload_decoder(&self, topic: &str) -> Arc<PayloadDecoder> {
+        let schema_registry = self
+            .config
+            .schema_registry_url
+            .as_deref()
+            .filter(|url| !url.trim().is_empty())
+            .map(|url| SchemaRegistryClient::new(url, self.timeout()));
+        let format = self
+            .config
+            .id
+            .and_then(|conn_id| Repository::new().find_topic_format(conn_id, topic));
+        PayloadDecoder::new(schema_registry, format)
+    }
     fn create_config(&self) -> ClientConfig {
+        let group_id = self
+            .config
+            .group_id
+            .clone()
+            .filter(|g| !g.trim().is_empty())
+            .unwrap_or_else(|| GROUP_ID.to_string());
+        let settings = Settings::read().unwrap_or_default();
         let mut config = ClientConfig::new();
+        config
+            .set("bootstrap.servers", self.config.brokers_list.clone())
+            .set("group.id", group_id)
+            .set("enable.partition.eof", "false")
+            .set(
+                "session.timeout.ms",
+                settings.session_timeout_ms.to_string(),
+            )
+            .set("enable.auto.commit", "false")
+            .set(
+                "message.timeout.ms",
+                settings.message_timeout_ms.to_string(),
+            )
+            //.set("statistics.interval.ms", "30000")
+            .set("auto.offset.reset", settings.auto_offset_reset);
+        // unset values keep librdkafka defaults
+        let client_properties = [
+            ("client.id", &self.config.client_id),
+            ("client.rack", &self.config.client_rack),
+        ];
+        for (key, value) in client_properties {
+            if let Some(value) = value.as_ref().filter(|v| !v.is_empty()) {
+                config.set(key, value);
+            }
+        }
         match self.config.security_type {
-            KrustConnectionSecurityType::SASL_PLAINTEXT => {
+            KrustConnectionSecurityType::SASL_PLAINTEXT | KrustConnectionSecurityType::SASL_SSL
+                if self.config.sasl_mechanism == Some(SaslMechanism::OauthBearer) =>
+            {
+                config
+                    .set("security.protocol", self.config.security_type.to_string())
+                    .set("sasl.mechanisms", SaslMechanism::OauthBearer.to_string());
+                // librdkafka runs the client credentials grant itself, without a token
+                // endpoint the OAUTHBEARER method is left to the extra config
+                if let Some(token_endpoint) = self
+                    .config
+                    .oauth_token_endpoint
+                    .as_ref()
+                    .filter(|e| !e.trim().is_empty())
+                {
+                    config
+                        .set("sasl.oauthbearer.method", "oidc")
+                        .set("sasl.oauthbearer.token.endpoint.url", token_endpoint)
+                        .set(
+                            "sasl.oauthbearer.client.id",
+                            self.config.oauth_client_id.clone().unwrap_or_default(),
+                        )
+                        .set(
+                            "sasl.oauthbearer.client.secret",
+                            self.config.oauth_client_secret.clone().unwrap_or_default(),
+                        );
+                }
+            }
+            KrustConnectionSecurityType::SASL_PLAINTEXT | KrustConnectionSecurityType::SASL_SSL => {
                 config
-                    .set("bootstrap.servers", self.config.brokers_list.clone())
-                    .set("group.id", GROUP_ID)
-                    .set("enable.partition.eof", "false")
-                    .set("session.timeout.ms", "6000")
-                    .set("enable.auto.commit", "false")
-                    .set("message.timeout.ms", "10000")
-                    //.set("statistics.interval.ms", "30000")
-                    .set("auto.offset.reset", "earliest")
                     .set("security.protocol", self.config.security_type.to_string())
                     .set(
                         "sasl.mechanisms",
-                        self.config.sasl_mechanism.clone().unwrap_or_default(),
+                        self.config.sasl_mechanism.unwrap_or_default().to_string(),
                     )
                     .set(
                         "sasl.username",
@@ -133,24 +408,48 @@ impl KafkaBackend {
                         self.config.sasl_password.clone().unwrap_or_default(),
                     )
                     //.set("sasl.jaas.config", self.config.jaas_config.clone().unwrap_or_default())
-                    .set_log_level(RDKafkaLogLevel::Debug)
+                    .set_log_level(RDKafkaLogLevel::Debug);
             }
-            _ => {
-                config
-                    .set("bootstrap.servers", self.config.brokers_list.clone())
-                    .set("group.id", GROUP_ID)
-                    .set("enable.partition.eof", "false")
-                    .set("session.timeout.ms", "6000")
-                    .set("enable.auto.commit", "false")
-                    .set("message.timeout.ms", "10000")
-                    //.set("statistics.interval.ms", "30000")
-                    .set("auto.offset.reset", "earliest")
+            KrustConnectionSecurityType::SSL => {
+                config.set("security.protocol", self.config.security_type.to_string());
+            }
+            KrustConnectionSecurityType::PLAINTEXT => (),
+        };
+        if matches!(
+            self.config.security_type,
+            KrustConnectionSecurityType::SSL | KrustConnectionSecurityType::SASL_SSL
+        ) {
+            let ssl_locations = [
+                ("ssl.ca.location", &self.config.ssl_ca_location),
+                (
+                    "ssl.certificate.location",
+                    &self.config.ssl_certificate_location,
+                ),
+                ("ssl.key.location", &self.config.ssl_key_location),
+            ];
+            for (key, location) in ssl_locations {
+                if let Some(location) = location.as_ref().filter(|l| !l.is_empty()) {
+                    config.set(key, location);
+                }
+            }
+        }
+        if let Some(extra_config) = &self.config.extra_config {
+            for (key, value) in parse_extra_config(extra_config) {
+                debug!("kafka::config::override::{}", key);
+                config.set(key, value);
             }
         }
-        .to_owned()
+        config
     }
-    fn producer(&self) -> Result<FutureProducer, KafkaError> {
-        self.create_config().create()
+    fn producer(&self, options: &SendOptions) -> Result<FutureProducer<CustomContext>, KafkaError> {
+        let mut config = self.create_config();
+        if let Some(acks) = options.acks {
+            config.set("acks", acks.to_string());
+        }
+        if let Some(message_timeout_ms) = options.message_timeout_ms {
+            config.set("message.timeout.ms", message_timeout_ms.to_string());
+        }
+        config.create_with_context(CustomContext)
     }
     fn consumer<C, T>(&self, context: C) -> KafkaResult<T>
     where
@@ -159,19 +458,27 @@ impl KafkaBackend {
     {
         self.create_config().create_with_context(context)
     }
-    fn create_admin_client(&self) -> Result<AdminClient<DefaultClientContext>, KafkaError> {
-        self.create_config().create()
+    fn group_consumer(&self, group: &str) -> KafkaResult<LoggingConsumer> {
+        let mut config = self.create_config();
+        config.set("group.id", group);
+        config.create_with_context(CustomContext)
+    }
+    fn create_admin_client(&self) -> Result<AdminClient<CustomContext>, KafkaError> {
+        self.create_config().create_with_context(CustomContext)
         //.expect("admin client creation failed")
     }
 
     pub async fn create_topic(self, request: &CreateTopicRequest) -> Result<bool, ExternalError> {
         let admin_client = self.create_admin_client()?;
         let opts = AdminOptions::new().operation_timeout(Some(self.timeout()));
-        let topic = NewTopic::new(
+        let mut topic = NewTopic::new(
             &request.name,
             request.partition_count as i32,
             TopicReplication::Fixed(request.replica_count as i32),
         );
+        for (key, value) in request.configs.iter() {
+            topic = topic.set(key, value);
+        }
         admin_client.create_topics(vec![&topic], &opts).await?;
         Ok(true)
     }
@@ -185,6 +492,199 @@ impl KafkaBackend {
         Ok(true)
     }
 
+    /// Deletes all records before the given offset on each partition, returning the
+    /// partitions with their resulting watermarks.
+    pub async fn delete_records(
+        &self,
+        topic: &str,
+        per_partition_offsets: HashMap<i32, i64>,
+    ) -> Result<Vec<Partition>, ExternalError> {
+        let admin_client = self.create_admin_client()?;
+        let opts = AdminOptions::new().operation_timeout(Some(self.timeout()));
+        info!(
+            "kafka::delete_records::topic[{}]::{:?}",
+            topic, per_partition_offsets
+        );
+        let mut tpl = TopicPartitionList::with_capacity(per_partition_offsets.len());
+        for (partition, offset) in per_partition_offsets.iter() {
+            // a negative offset would delete every record up to the high watermark
+            if *offset < 0 {
+                return Err(ExternalError::DisplayError(
+                    "deleting records".to_string(),
+                    format!("invalid offset {} for partition {}", offset, partition),
+                ));
+            }
+            tpl.add_partition_offset(topic, *partition, Offset::Offset(*offset))?;
+        }
+        let result = admin_client.delete_records(&tpl, &opts).await?;
+        for elem in result.elements() {
+            elem.error()?;
+        }
+        Ok(self.fetch_partitions(&topic.to_string()).await)
+    }
+
+    pub async fn test_connection(&self) -> Result<(), ExternalError> {
+        let context = CustomContext;
+        let consumer: LoggingConsumer = self
+            .consumer(context)
+            .map_err(ExternalError::KafkaUnexpectedError)?;
+        let metadata = consumer
+            .fetch_metadata(None, self.timeout())
+            .map_err(ExternalError::KafkaUnexpectedError)?;
+        info!(
+            "kafka::test_connection::{} brokers found",
+            metadata.brokers().len()
+        );
+        Ok(())
+    }
+
+    pub async fn fetch_cluster_metadata(&self) -> Result<KrustClusterMetadata, ExternalError> {
+        let context = CustomContext;
+        let consumer: LoggingConsumer = self
+            .consumer(context)
+            .map_err(ExternalError::KafkaUnexpectedError)?;
+        let timeout = self.timeout();
+        let metadata = consumer
+            .fetch_metadata(None, timeout)
+            .map_err(ExternalError::KafkaUnexpectedError)?;
+        let brokers = metadata
+            .brokers()
+            .iter()
+            .map(|broker| KrustBroker {
+                id: broker.id(),
+                host: broker.host().to_string(),
+                port: broker.port(),
+            })
+            .collect::<Vec<_>>();
+        // cluster and controller ids are not exposed by rdkafka metadata, ask librdkafka directly
+        let native_client = consumer.client().native_ptr();
+        let timeout_ms = timeout.as_millis() as i32;
+        let controller_id = unsafe { rdsys::rd_kafka_controllerid(native_client, timeout_ms) };
+        let cluster_id = unsafe {
+            let cluster_id_ptr = rdsys::rd_kafka_clusterid(native_client, timeout_ms);
+            if cluster_id_ptr.is_null() {
+                None
+            } else {
+                let cluster_id = CStr::from_ptr(cluster_id_ptr)
+                    .to_string_lossy()
+                    .into_owned();
+                rdsys::rd_kafka_mem_free(native_client, cluster_id_ptr as *mut c_void);
+                Some(cluster_id)
+            }
+        };
+        info!(
+            "kafka::cluster_metadata::cluster[{:?}]::controller[{}]::brokers[{}]",
+            cluster_id,
+            controller_id,
+            brokers.len()
+        );
+        Ok(KrustClusterMetadata {
+            cluster_id,
+            controller_id: (controller_id >= 0).then_some(controller_id),
+            orig_broker_id: metadata.orig_broker_id(),
+            orig_broker_name: metadata.orig_broker_name().to_string(),
+            topic_count: metadata.topics().len(),
+            brokers,
+        })
+    }
+
+    pub async fn list_consumer_groups(&self) -> Result<Vec<KrustConsumerGroup>, ExternalError> {
+        let context = CustomContext;
+        let consumer: LoggingConsumer = self.consumer(context)?;
+        let group_list = consumer.fetch_group_list(None, self.timeout())?;
+        let mut groups = group_list
+            .groups()
+            .iter()
+            .map(|group| KrustConsumerGroup {
+                name: group.name().to_string(),
+                state: group.state().to_string(),
+                protocol: group.protocol().to_string(),
+                members: group.members().len(),
+            })
+            .collect::<Vec<_>>();
+        groups.sort_by(|a, b| a.name.cmp(&b.name));
+        info!("kafka::list_consumer_groups::{} groups found", groups.len());
+        Ok(groups)
+    }
+
+    /// Committed offsets of a consumer group on each topic partition, with the lag against the
+    /// partition high watermark.
+    pub async fn fetch_group_offsets(
+        &self,
+        group: &str,
+        topic: &str,
+    ) -> Result<Vec<KrustConsumerGroupOffset>, ExternalError> {
+        let partitions = self.fetch_partition_watermarks(topic)?;
+        let consumer = self.group_consumer(group)?;
+        let mut tpl = TopicPartitionList::with_capacity(partitions.len());
+        partitions.iter().for_each(|p| {
+            tpl.add_partition(topic, p.id);
+        });
+        let committed = consumer.committed_offsets(tpl, self.timeout())?;
+        let offsets = partitions
+            .iter()
+            .map(|p| {
+                let high_watermark = p.offset_high.unwrap_or_default();
+                let committed =
+                    committed
+                        .find_partition(topic, p.id)
+                        .and_then(|t| match t.offset() {
+                            Offset::Offset(offset) => Some(offset),
+                            _ => None,
+                        });
+                KrustConsumerGroupOffset {
+                    partition: p.id,
+                    committed,
+                    high_watermark,
+                    lag: committed.map(|offset| (high_watermark - offset).max(0)),
+                }
+            })
+            .collect();
+        Ok(offsets)
+    }
+
+    /// Commits new offsets for a consumer group on every topic partition, returning the
+    /// resulting offsets. Refused while the group has active members.
+    pub async fn reset_group_offsets(
+        &self,
+        group: &str,
+        topic: &str,
+        target: OffsetResetTarget,
+    ) -> Result<Vec<KrustConsumerGroupOffset>, ExternalError> {
+        let members = self.group_members(group)?;
+        if members > 0 {
+            return Err(ExternalError::DisplayError(
+                "resetting offsets".to_string(),
+                format!("consumer group {} has {} active members", group, members),
+            ));
+        }
+        let partitions = self.fetch_partition_watermarks(topic)?;
+        let offsets: HashMap<i32, i64> = match target {
+            OffsetResetTarget::Earliest => partitions
+                .iter()
+                .map(|p| (p.id, p.offset_low.unwrap_or_default()))
+                .collect(),
+            OffsetResetTarget::Latest => partitions
+                .iter()
+                .map(|p| (p.id, p.offset_high.unwrap_or_default()))
+                .collect(),
+            OffsetResetTarget::Timestamp(timestamp) => {
+                self.offsets_for_timestamp(topic, &partitions, timestamp)?
+            }
+        };
+        info!(
+            "kafka::reset_group_offsets::group[{}]::topic[{}]::target[{:?}]::{:?}",
+            group, topic, target, offsets
+        );
+        let mut tpl = TopicPartitionList::with_capacity(offsets.len());
+        for (partition, offset) in offsets.iter() {
+            tpl.add_partition_offset(topic, *partition, Offset::Offset(*offset))?;
+        }
+        let consumer = self.group_consumer(group)?;
+        consumer.commit(&tpl, CommitMode::Sync)?;
+        self.fetch_group_offsets(group, topic).await
+    }
+
     pub async fn list_topics(&self) -> Result<Vec<KrustTopic>, ExternalError> {
         let context = CustomContext;
         let consumer: LoggingConsumer = self
@@ -219,6 +719,90 @@ impl KafkaBackend {
         Ok(topics)
     }
 
+    /// Counts the messages of every topic, stopping early when the task is cancelled.
+    pub async fn topics_report(&self, task: &Task) -> Result<Vec<TopicReport>, ExternalError> {
+        let topics = self.list_topics().await?;
+        let total = topics.len();
+        let mut reports = Vec::with_capacity(total);
+        for (idx, topic) in topics.iter().enumerate() {
+            if task
+                .token
+                .as_ref()
+                .is_some_and(|token| token.is_cancelled())
+            {
+                info!("topics report cancelled after {} topics", idx);
+                break;
+            }
+            let counted = self
+                .topic_message_count(&topic.name, None, None, None)
+                .await;
+            let partitions = counted
+                .partitions
+                .iter()
+                .map(|p| {
+                    let offset_low = p.offset_low.unwrap_or_default();
+                    let offset_high = p.offset_high.unwrap_or_default();
+                    PartitionReport {
+                        partition: p.id,
+                        offset_low,
+                        offset_high,
+                        messages: offset_high - offset_low,
+                    }
+                })
+                .collect::<Vec<_>>();
+            reports.push(TopicReport {
+                topic: topic.name.clone(),
+                partition_count: partitions.len(),
+                total_messages: counted.total.unwrap_or_default(),
+                partitions,
+            });
+            let progress_step = (idx + 1) as f64 / total as f64;
+            TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), progress_step));
+        }
+        Ok(reports)
+    }
+
+    /// Number of active members of a consumer group.
+    fn group_members(&self, group: &str) -> Result<usize, ExternalError> {
+        let consumer: LoggingConsumer = self.consumer(CustomContext)?;
+        let group_list = consumer.fetch_group_list(Some(group), self.timeout())?;
+        let members = group_list
+            .groups()
+            .iter()
+            .find(|g| g.name() == group)
+            .map(|g| g.members().len())
+            .unwrap_or_default();
+        Ok(members)
+    }
+
+    /// Partitions of a topic with their watermarks, failing when any of them can't be fetched.
+    fn fetch_partition_watermarks(&self, topic: &str) -> Result<Vec<Partition>, ExternalError> {
+        let consumer: LoggingConsumer = self.consumer(CustomContext)?;
+        let metadata = consumer.fetch_metadata(Some(topic), self.timeout())?;
+        let Some(t) = metadata.topics().first() else {
+            return Err(ExternalError::DisplayError(
+                "fetching partitions".to_string(),
+                format!("topic {} not found", topic),
+            ));
+        };
+        let mut partitions = vec![];
+        for partition in t.partitions() {
+            let (low, high) = consumer.fetch_watermarks(topic, partition.id(), self.timeout())?;
+            if low < 0 || high < 0 {
+                return Err(ExternalError::DisplayError(
+                    "fetching partitions".to_string(),
+                    format!("invalid watermarks of partition {}", partition.id()),
+                ));
+            }
+            partitions.push(Partition {
+                id: partition.id(),
+                offset_low: Some(low),
+                offset_high: Some(high),
+            });
+        }
+        Ok(partitions)
+    }
+
     pub async fn fetch_partitions(&self, topic: &String) -> Vec<Partition> {
         info!("fetching partitions from topic {}", topic);
         let context = CustomContext;
@@ -255,9 +839,34 @@ impl KafkaBackend {
         }
         partitions
     }
-    pub async fn send_messages(&self, topic: &String, messages: &[KrustMessage]) {
+    pub async fn send_messages(
+        &self,
+        topic: &String,
+        messages: &[KrustMessage],
+    ) -> Result<(), Vec<(KrustMessage, KafkaError)>> {
+        self.send_messages_with_options(topic, messages, SendOptions::default())
+            .await
+    }
+    /// Sends the messages in order, stopping at the first delivery failure. On error, returns
+    /// the failed message along with the ones left unsent, marked as canceled.
+    pub async fn send_messages_with_options(
+        &self,
+        topic: &String,
+        messages: &[KrustMessage],
+        options: SendOptions,
+    ) -> Result<(), Vec<(KrustMessage, KafkaError)>> {
+        let preserve_timestamp = options.preserve_timestamp;
         info!("[send_messages] creating producer for topic {}", topic);
-        let producer: FutureProducer = self.producer().expect("Producer creation failed");
+        let producer: FutureProducer<CustomContext> = match self.producer(&options) {
+            Ok(producer) => producer,
+            Err(e) => {
+                warn!("[send_messages] producer creation failed: {}", e);
+                if let Some(task) = &options.task {
+                    TASK_MANAGER_BROKER.send(TaskManagerMsg::Failed(task.clone(), e.to_string()));
+                }
+                return Err(messages.iter().map(|m| (m.clone(), e.clone())).collect());
+            }
+        };
         let producer = producer.borrow();
 
         debug!("[send_messages] producer created");
@@ -273,16 +882,23 @@ impl KafkaBackend {
                     let header = Header { key, value };
                     kheaders = kheaders.insert(header);
                 }
-                let delivery_status = producer
-                    .send(
-                        FutureRecord::to(topic)
-                            .partition(message.partition)
-                            .payload(&message.value)
-                            .key(&message.key.clone().unwrap_or_default())
-                            .headers(kheaders),
-                        Duration::from_secs(0),
-                    )
-                    .await;
+                let key = message.key.clone().unwrap_or_default();
+                let mut record = FutureRecord::to(topic).key(&key).headers(kheaders);
+                if message.partition != PARTITION_BY_KEY {
+                    record = record.partition(message.partition);
+                }
+                // tombstone records are sent without payload, binary ones with their raw bytes
+                if let Some(raw_value) = &message.raw_value {
+                    record = record.payload(raw_value.as_slice());
+                } else if let Some(value) = &message.value {
+                    record = record.payload(value.as_bytes());
+                }
+                if preserve_timestamp {
+                    if let Some(timestamp) = message.timestamp {
+                        record = record.timestamp(timestamp);
+                    }
+                }
+                let delivery_status = producer.send(record, Duration::from_secs(0)).await;
 
                 // This will be executed when the result is received.
                 trace!("Delivery status for message {:?} received", message);
@@ -290,10 +906,35 @@ impl KafkaBackend {
             })
             .collect::<Vec<_>>();
         // This loop will wait until all delivery statuses have been received.
-        for future in messages_futures {
+        let total = messages_futures.len();
+        for (sent, future) in messages_futures.into_iter().enumerate() {
             let result = future.await;
             trace!("Message sent, future completed. Result: {}", result.is_ok());
+            if let Err((e, _)) = result {
+                warn!(
+                    "[send_messages] delivery failed after {} of {} messages: {}",
+                    sent, total, e
+                );
+                if let Some(task) = &options.task {
+                    TASK_MANAGER_BROKER.send(TaskManagerMsg::Failed(
+                        task.clone(),
+                        format!("Failed after {} of {} messages: {}", sent, total, e),
+                    ));
+                }
+                let mut failures = vec![(messages[sent].clone(), e)];
+                failures.extend(
+                    messages[sent + 1..]
+                        .iter()
+                        .map(|m| (m.clone(), KafkaError::Canceled)),
+                );
+                return Err(failures);
+            }
+            if let Some(task) = &options.task {
+                let progress_step = ((sent + 1) as f64) / (total as f64);
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), progress_step));
+            }
         }
+        Ok(())
     }
 
     pub async fn topic_message_count(
@@ -417,6 +1058,55 @@ impl KafkaBackend {
         );
         output
     }
+    /// Counts the messages from the given next offset of each partition, at most `max_messages`
+    /// per partition. Partitions without an offset had no messages, so they're read from the start.
+    pub async fn resume_message_count(
+        &self,
+        topic: &String,
+        offsets: &HashMap<i32, i64>,
+        max_messages: Option<i64>,
+    ) -> KrustTopic {
+        info!(
+            "counting messages for topic {} after offsets {:?}",
+            topic, offsets
+        );
+        let partitions = self
+            .fetch_partitions(topic)
+            .await
+            .into_iter()
+            .map(|p| {
+                let first = p.offset_low.unwrap_or_default();
+                let last = p.offset_high.unwrap_or_default();
+                let low = offsets
+                    .get(&p.id)
+                    .copied()
+                    .unwrap_or(first)
+                    .max(first)
+                    .min(last);
+                let high = match max_messages {
+                    Some(max) if max > 0 => last.min(low + max),
+                    _ => last,
+                };
+                Partition {
+                    id: p.id,
+                    offset_low: Some(low),
+                    offset_high: Some(high),
+                }
+            })
+            .collect::<Vec<_>>();
+        let message_count: i64 = partitions
+            .iter()
+            .map(|p| p.offset_high.unwrap_or_default() - p.offset_low.unwrap_or_default())
+            .sum();
+        KrustTopic {
+            connection_id: None,
+            name: topic.clone(),
+            cached: None,
+            partitions,
+            total: Some(message_count.max(0) as usize),
+            favourite: None,
+        }
+    }
     pub async fn fetch_and_build_partition_list<'a>(
         &self,
         request: &'a CacheMessagesRequest<'a>,
@@ -441,19 +1131,39 @@ impl KafkaBackend {
                 .iter()
                 .map(|p| (p.id, p))
                 .collect::<HashMap<_, _>>();
+            let mut summary = Vec::with_capacity(partitions.len());
             partitions.iter().for_each(|p| {
+                let broker_high = p.offset_high.unwrap_or_default();
+                let cached_high = part_map.get(&p.id).and_then(|cp| cp.offset_high);
+                let start = match cached_high {
+                    // cached high is the last stored offset, broker high is the next to be written
+                    Some(cached_high) if cached_high + 1 >= broker_high => {
+                        summary.push(format!("{}=up-to-date", p.id));
+                        return;
+                    }
+                    Some(cached_high) => Offset::Offset(cached_high + 1),
+                    None if broker_high <= p.offset_low.unwrap_or_default() => {
+                        summary.push(format!("{}=empty", p.id));
+                        return;
+                    }
+                    None => {
+                        warn!(
+                            "partition {} of topic {} has no cached messages, refreshing from beginning",
+                            p.id, topic
+                        );
+                        Offset::Beginning
+                    }
+                };
+                summary.push(format!("{}={:?}", p.id, start));
                 partition_list
-                    .add_partition_offset(
-                        topic,
-                        p.id,
-                        part_map
-                            .get(&p.id)
-                            .and_then(|cp| cp.offset_high)
-                            .map(Offset::from_raw)
-                            .unwrap_or(Offset::Beginning),
-                    )
+                    .add_partition_offset(topic, p.id, start)
                     .expect("should add partition/offset to list");
             });
+            info!(
+                "refreshing topic {} with start offsets [{}]",
+                topic,
+                summary.join(", ")
+            );
         } else {
             match fetch {
                 FetchMode::All | FetchMode::Head => partitions.iter().for_each(|p| {
@@ -466,7 +1176,7 @@ impl KafkaBackend {
                         .add_partition_offset(topic, p.id, Offset::OffsetTail(fetch_value))
                         .expect("should add partition/offset to list");
                 }),
-                FetchMode::FromTimestamp => {
+                FetchMode::FromTimestamp | FetchMode::TimeRange => {
                     let mut tpl = TopicPartitionList::with_capacity(partitions.len());
                     partitions.iter().for_each(|p| {
                         tpl.add_partition_offset(topic, p.id, Offset::from_raw(fetch_value))
@@ -487,6 +1197,48 @@ impl KafkaBackend {
         info!("topic {} partition list {:?}", topic, partition_list);
         partition_list
     }
+    /// Resolves, for each partition, the first offset with a timestamp at or after the given one,
+    /// falling back to the high watermark when there is none.
+    fn offsets_for_timestamp(
+        &self,
+        topic: &str,
+        partitions: &[Partition],
+        timestamp: i64,
+    ) -> Result<HashMap<i32, i64>, ExternalError> {
+        let context = CustomContext;
+        let consumer: LoggingConsumer = self.consumer(context)?;
+        let mut tpl = TopicPartitionList::with_capacity(partitions.len());
+        for p in partitions.iter() {
+            tpl.add_partition_offset(topic, p.id, Offset::from_raw(timestamp))?;
+        }
+        let resolved = consumer
+            .offsets_for_times(tpl, self.timeout())
+            .inspect_err(|e| {
+                warn!(
+                    "unable to resolve offsets for timestamp {}: {:?}",
+                    timestamp, e
+                )
+            })?
+            .elements()
+            .iter()
+            .filter_map(|t| {
+                t.offset()
+                    .to_raw()
+                    .filter(|offset| *offset >= 0)
+                    .map(|offset| (t.partition(), offset))
+            })
+            .collect::<HashMap<_, _>>();
+        Ok(partitions
+            .iter()
+            .map(|p| {
+                let offset = resolved
+                    .get(&p.id)
+                    .copied()
+                    .unwrap_or(p.offset_high.unwrap_or_default());
+                (p.id, offset)
+            })
+            .collect())
+    }
     pub async fn cache_messages<'a>(
         &self,
         request: &'a CacheMessagesRequest<'a>,
@@ -498,25 +1250,25 @@ impl KafkaBackend {
         let mrepo = request.messages_repository;
         let task = request.task.clone();
         let partitions = self.fetch_and_build_partition_list(request).await;
-        let (total, partitions_list) = match fetch {
+        let partitions_list = match fetch {
             FetchMode::All => {
                 let result = self
                     .topic_message_count(&topic_name, None, None, None)
                     .await;
                 info!("cache_messages[{:?}]::{:?}", fetch, &result.partitions);
-                (result.total.unwrap_or_default(), result.partitions)
+                result.partitions
             }
             FetchMode::Head => {
                 let result = self
                     .topic_message_count(&topic_name, Some(KafkaFetch::Oldest), fetch_value, None)
                     .await;
-                (result.total.unwrap_or_default(), result.partitions)
+                result.partitions
             }
             FetchMode::Tail => {
                 let result = self
                     .topic_message_count(&topic_name, Some(KafkaFetch::Newest), fetch_value, None)
                     .await;
-                (result.total.unwrap_or_default(), result.partitions)
+                result.partitions
             }
             FetchMode::FromTimestamp => {
                 let ts_partitions: Vec<Partition> = partitions
@@ -528,9 +1280,6 @@ impl KafkaBackend {
                         offset_low: None,
                     })
                     .collect();
-                let result = self
-                    .topic_message_count(&topic_name, None, None, Some(ts_partitions.clone()))
-                    .await;
                 let parts: Vec<Partition> = self
                     .fetch_partitions(&topic_name)
                     .await
@@ -545,25 +1294,66 @@ impl KafkaBackend {
                     })
                     .collect();
                 debug!("from_timestamp partitions: {:?}", &parts);
-                (result.total.unwrap_or_default(), parts)
+                parts
+            }
+            FetchMode::TimeRange => {
+                let fetch_value_end = request.cache_settings.fetch_value_end.unwrap_or(i64::MAX);
+                let topic_partitions = self.fetch_partitions(&topic_name).await;
+                let end_offsets =
+                    self.offsets_for_timestamp(&topic_name, &topic_partitions, fetch_value_end)?;
+                let parts: Vec<Partition> = topic_partitions
+                    .iter()
+                    .map(|fp| {
+                        let high = fp.offset_high.unwrap_or_default();
+                        let low = partitions
+                            .find_partition(&topic_name, fp.id)
+                            .and_then(|p| p.offset().to_raw())
+                            .filter(|offset| *offset >= 0)
+                            .unwrap_or(high);
+                        let end = end_offsets.get(&fp.id).copied().unwrap_or(high).max(low);
+                        Partition {
+                            id: fp.id,
+                            offset_low: Some(low),
+                            offset_high: Some(end),
+                        }
+                    })
+                    .collect();
+                debug!("time_range partitions: {:?}", &parts);
+                parts
             }
         };
+        // only the assigned partitions are read, a refresh skips the up to date ones
+        let ranges = assigned_offset_ranges(&partitions, &partitions_list);
+        let total: usize = ranges
+            .values()
+            .map(|(first, high)| (high - first).max(0) as usize)
+            .sum();
+        TASK_MANAGER_BROKER.send(TaskManagerMsg::ItemsTotal(task.clone(), total));
         let part_last_offset_map = partitions_list
             .iter()
             .map(|p| (p.id, p.offset_high.unwrap_or_default()))
             .collect::<HashMap<_, _>>();
+        // partitions still expecting records, the offset based total also counts transaction
+        // markers that are never delivered, so it can't tell alone when caching is done
+        let part_first_offset_map = Arc::new(
+            ranges
+                .iter()
+                .map(|(id, (first, _))| (*id, *first))
+                .collect::<HashMap<_, _>>(),
+        );
+        let pending_partitions = Arc::new(Mutex::new(partitions_with_records(&ranges)));
         let context = CustomContext;
-        let consumer: LoggingConsumer = self.consumer(context).expect("Consumer creation failed");
+        let consumer: LoggingConsumer = self.consumer(context)?;
         let consumer = Arc::new(consumer);
-        consumer
-            .assign(&partitions)
-            .expect("Can't subscribe to partition list");
+        consumer.assign(&partitions)?;
         let counter: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
         let (tx, rx) = mpsc::channel::<KrustMessage>(32);
         let writer_id = "worker-0".to_string();
         let writer_counter = Arc::new(AtomicUsize::new(0));
         let writer_task = task.clone();
         let writer_repo = mrepo.clone();
+        // fails the request up front when the cache can't be opened, e.g. an unwritable cache dir
+        let writer_conn = mrepo.get_connection()?;
         let writer_token = writer_task.token.clone().unwrap();
         let last_offset_map = Arc::new(part_last_offset_map.clone());
         let writer_handle = tokio::spawn(async move {
@@ -572,27 +1362,33 @@ impl KafkaBackend {
                     info!("writer-{}::request with task {:?} cancelled", writer_id.clone(), &writer_task);
                     TASK_MANAGER_BROKER.send(TaskManagerMsg::RemoveTask(writer_task.clone()));
                     // The token was cancelled
+                    Ok(())
                 }
-                _result = KafkaBackend::db_writer_worker(
+                result = KafkaBackend::db_writer_worker(
                     writer_id.clone(),
                     rx,
                     writer_task.clone(),
                     writer_counter,
                     writer_repo,
+                    writer_conn,
                     total,
                     last_offset_map,
-                ) => {}
+                ) => result
             }
         });
-        let timeout = self.timeout();
+        let timeout = self.poll_timeout();
+        let payload_decoder = self.payload_decoder(&topic_name);
         let mk_consumer = |worker_id: String| {
             let timeout = Arc::new(timeout);
+            let payload_decoder = payload_decoder.clone();
             let consumer = consumer.clone();
             let mcounter = counter.clone();
             let token = task.token.clone().unwrap();
             let tx = tx.clone();
             let consumer_task = task.clone();
             let last_offset_map = Arc::new(part_last_offset_map.clone());
+            let pending_partitions = pending_partitions.clone();
+            let first_offset_map = part_first_offset_map.clone();
             tokio::spawn(async move {
                 select! {
                     _ = token.cancelled() => {
@@ -600,19 +1396,14 @@ impl KafkaBackend {
                         TASK_MANAGER_BROKER.send(TaskManagerMsg::RemoveTask(consumer_task.clone()));
                         // The token was cancelled
                     }
-                    _result = KafkaBackend::consumer_worker(worker_id.clone(), timeout, tx, consumer, mcounter, total, last_offset_map) => {}
+                    _result = KafkaBackend::consumer_worker(worker_id.clone(), consumer_task.clone(), timeout, tx, consumer, mcounter, total, first_offset_map, last_offset_map, pending_partitions, payload_decoder) => {}
                 }
             })
         };
-        let max_threads = Settings::read()
-            .map(|st| st.threads_number - 1)
-            .unwrap_or(2) as usize;
-        let num_partitions = partitions.count();
-        let parallelism_factor = if num_partitions <= max_threads {
-            num_partitions
-        } else {
-            max_threads
-        };
+        let cache_workers = Settings::read().unwrap_or_default().cache_workers.max(1);
+        let num_partitions = pending_partitions.lock().unwrap().len();
+        // more consumers than partitions would just sit idle
+        let parallelism_factor = cache_workers.min(num_partitions);
         info!(
             "cache_messages::starting consumers with parallelism factor of {}",
             parallelism_factor
@@ -621,22 +1412,37 @@ impl KafkaBackend {
             future::join_all((0..parallelism_factor).map(|i| mk_consumer(format!("worker-{}", i))))
                 .await
         {
-            res.unwrap();
+            res?;
         }
         std::mem::drop(tx);
+        let cached = counter.load(Ordering::SeqCst);
+        let cancelled = task.token.as_ref().is_some_and(|t| t.is_cancelled());
+        // fewer records than the offset range is expected when transaction markers were skipped
+        let stalled = !pending_partitions.lock().unwrap().is_empty();
+        if cached < total && stalled && !cancelled {
+            warn!(
+                "cache_messages::stalled for topic {}: cached {} of {}",
+                topic_name, cached, total
+            );
+            TASK_MANAGER_BROKER.send(TaskManagerMsg::Status(
+                task.clone(),
+                format!("Cached {} of {} (stalled)", cached, total),
+            ));
+        }
         match writer_handle.await {
             Err(e) => {
                 let duration = start_mark.elapsed();
                 let seconds = duration.as_secs() % 60;
                 let minutes = (duration.as_secs() / 60) % 60;
                 let hours = (duration.as_secs() / 60) / 60;
-                let msg = format!(
-                    "error caching messages for topic {}, duration: {}:{}:{}: {}",
-                    topic_name, hours, minutes, seconds, e
-                );
+                let msg = format!("{}:{}:{}: {}", hours, minutes, seconds, e);
                 core::result::Result::Err(ExternalError::CachingError(topic_name.clone(), msg))
             }
-            Ok(_) => {
+            Ok(Err(e)) => core::result::Result::Err(ExternalError::CachingError(
+                topic_name.clone(),
+                format!("unable to save messages: {}", e),
+            )),
+            Ok(Ok(())) => {
                 let duration = start_mark.elapsed();
                 info!(
                     "finished caching messages for topic {}, duration: {:?}",
@@ -647,103 +1453,156 @@ impl KafkaBackend {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn db_writer_worker(
         worker_id: String,
         mut rx: Receiver<KrustMessage>,
         task: Task,
         counter: Arc<AtomicUsize>,
         repo: MessagesRepository,
+        mut conn: rusqlite::Connection,
         total: usize,
         part_last_offset_map: Arc<HashMap<i32, i64>>,
-    ) {
+    ) -> Result<(), ExternalError> {
         info!("Starting writer-{} total[{}]", worker_id, total);
-        let conn = repo.get_connection();
+        let mut batch: Vec<KrustMessage> = Vec::with_capacity(WRITER_BATCH_SIZE);
+        let mut result = Ok(());
         // Start receiving messages
         while let Some(message) = rx.recv().await {
-            trace!(
-                "writer-{}::message with offset {} trying to save",
-                worker_id,
-                &message.offset
-            );
-            match repo.save_message(&conn, &message) {
-                Ok(_) => {
-                    trace!(
-                        "writer-{}::message with offset {} saved",
-                        worker_id,
-                        &message.offset
-                    );
-                }
-                Err(err) => warn!(
-                    "writer-{}::unable to save message with offset {}: {}",
-                    worker_id,
-                    &message.offset,
-                    err.to_string()
-                ),
-            };
-            let _previous_count = counter.fetch_add(1, Ordering::SeqCst);
-            let current_count = counter.load(Ordering::SeqCst);
-            let progress_step = ((current_count as f64) * 1.0) / ((total as f64) * 1.0);
-            TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), progress_step));
-            let current_offset = message.offset;
-            let current_partition = message.partition;
             let max_offset = *part_last_offset_map
-                .get(&current_partition)
+                .get(&message.partition)
                 .expect("should have partition last offset");
             trace!(
-                "writer-{}::{}/{} [partition={}, offset={}, max_offset={}]",
+                "writer-{}::message [partition={}, offset={}, max_offset={}] buffered",
                 worker_id,
-                current_count,
-                total,
-                current_partition,
-                current_offset,
+                message.partition,
+                message.offset,
                 max_offset
             );
+            batch.push(message);
+            if batch.len() >= WRITER_BATCH_SIZE {
+                let flushed = KafkaBackend::flush_messages(
+                    &worker_id, &repo, &mut conn, &mut batch, &task, &counter, total,
+                );
+                result = result.and(flushed);
+            }
         }
+        let flushed = KafkaBackend::flush_messages(
+            &worker_id, &repo, &mut conn, &mut batch, &task, &counter, total,
+        );
         info!("writer-{} finished", worker_id);
+        result.and(flushed)
     }
+    fn flush_messages(
+        worker_id: &str,
+        repo: &MessagesRepository,
+        conn: &mut rusqlite::Connection,
+        batch: &mut Vec<KrustMessage>,
+        task: &Task,
+        counter: &AtomicUsize,
+        total: usize,
+    ) -> Result<(), ExternalError> {
+        if batch.is_empty() {
+            return Ok(());
+        }
+        match repo.save_messages(conn, batch) {
+            Ok(saved) => trace!("writer-{}::{} messages saved", worker_id, saved),
+            Err(err) => {
+                // the batch is lost, so it must not count towards the progress
+                warn!(
+                    "writer-{}::unable to save {} messages: {}",
+                    worker_id,
+                    batch.len(),
+                    err.to_string()
+                );
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::Failed(
+                    task.clone(),
+                    format!("Unable to save {} messages: {}", batch.len(), err),
+                ));
+                batch.clear();
+                return Err(err);
+            }
+        };
+        let current_count = counter.fetch_add(batch.len(), Ordering::SeqCst) + batch.len();
+        let progress_step = ((current_count as f64) * 1.0) / ((total as f64) * 1.0);
+        TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), progress_step));
+        trace!("writer-{}::{}/{}", worker_id, current_count, total);
+        batch.clear();
+        Ok(())
+    }
+    #[allow(clippy::too_many_arguments)]
     async fn consumer_worker(
         worker_id: String,
+        task: Task,
         timeout: Arc<Duration>,
         tx: Sender<KrustMessage>,
         consumer: Arc<BaseConsumer<CustomContext>>,
         mcounter: Arc<AtomicUsize>,
         total: usize,
+        part_first_offset_map: Arc<HashMap<i32, i64>>,
         part_last_offset_map: Arc<HashMap<i32, i64>>,
+        pending_partitions: Arc<Mutex<HashSet<i32>>>,
+        payload_decoder: Arc<PayloadDecoder>,
     ) {
-        let timeout = timeout.mul_f32(3.0);
         info!("Starting consumer-{}::timeout::{:?}", worker_id, timeout);
         let local_counter: Arc<AtomicUsize> = Arc::new(AtomicUsize::new(0));
+        let poll_cycle = *timeout / CACHE_STALL_POLL_CYCLES;
+        let mut idle_cycles = 0;
         loop {
-            match consumer.poll(timeout) {
-                None => {
-                    warn!("consumer-{} timeout", worker_id);
+            if pending_partitions.lock().unwrap().is_empty() {
+                info!("consumer-{}::done(high offsets reached)", worker_id);
+                break;
+            }
+            let polled = consumer.poll(poll_cycle);
+            if !matches!(polled, Some(Ok(_))) {
+                idle_cycles += 1;
+                if idle_cycles >= CACHE_STALL_POLL_CYCLES {
+                    let num = mcounter.load(Ordering::SeqCst);
+                    warn!(
+                        "consumer-{}::stalled after {} idle polls: {}/{}, {} messages missing",
+                        worker_id,
+                        idle_cycles,
+                        num,
+                        total,
+                        total.saturating_sub(num)
+                    );
                     break;
                 }
+            }
+            match polled {
+                None => {
+                    trace!("consumer-{}::idle poll {}", worker_id, idle_cycles);
+                    // control records move the position past the last delivered offset
+                    if let Ok(positions) = consumer.position() {
+                        for elem in positions.elements() {
+                            let reached = position_reached(
+                                &part_last_offset_map,
+                                elem.partition(),
+                                elem.offset(),
+                            );
+                            if reached && partition_consumed(&pending_partitions, elem.partition())
+                            {
+                                TASK_MANAGER_BROKER.send(TaskManagerMsg::PartitionProgress(
+                                    task.clone(),
+                                    elem.partition(),
+                                    1.0,
+                                ));
+                            }
+                        }
+                    }
+                }
                 Some(result) => match result {
                     Err(e) => warn!("Kafka Error: {}", e),
                     Ok(m) => {
+                        idle_cycles = 0;
                         let current_offset = m.offset();
                         let current_partition = m.partition();
                         let max_offset = *part_last_offset_map
                             .get(&current_partition)
                             .expect("should have partition last offset");
                         if current_offset < max_offset {
-                            let payload = match m.payload_view::<str>() {
-                                None => "",
-                                Some(Ok(s)) => s,
-                                Some(Err(e)) => {
-                                    warn!("Error while deserializing message payload: {:?}", e);
-                                    ""
-                                }
-                            };
-                            let key = match m.key_view::<str>() {
-                                None => "",
-                                Some(Ok(s)) => s,
-                                Some(Err(e)) => {
-                                    warn!("Error while deserializing message key: {:?}", e);
-                                    ""
-                                }
-                            };
+                            let (payload, raw_payload) = payload_decoder.value(m.payload()).await;
+                            let key = payload_decoder.key(m.key()).await;
                             trace!("message received: topic: {}, partition: {}, offset: {}, timestamp: {:?}",
                                 m.topic(),
                                 m.partition(),
@@ -768,10 +1627,11 @@ impl KafkaBackend {
                                 topic: m.topic().to_string(),
                                 partition: m.partition(),
                                 offset: m.offset(),
-                                key: Some(key.to_string()),
+                                key: Some(key),
                                 timestamp: m.timestamp().to_millis(),
-                                value: payload.to_string(),
+                                value: payload,
                                 headers,
+                                raw_value: raw_payload,
                             };
                             match tx.send(message).await {
                                 Err(e) => warn!(
@@ -804,7 +1664,25 @@ impl KafkaBackend {
                                 info!("consumer-{}::done::{}", worker_id, local);
                                 break;
                             }
+                            let partition_done = current_offset + 1 >= max_offset;
+                            if partition_done || local % WRITER_BATCH_SIZE == 0 {
+                                let first_offset = part_first_offset_map
+                                    .get(&current_partition)
+                                    .copied()
+                                    .unwrap_or_default();
+                                let step = (current_offset + 1 - first_offset) as f64
+                                    / (max_offset - first_offset).max(1) as f64;
+                                TASK_MANAGER_BROKER.send(TaskManagerMsg::PartitionProgress(
+                                    task.clone(),
+                                    current_partition,
+                                    step,
+                                ));
+                            }
+                            if partition_done {
+                                partition_consumed(&pending_partitions, current_partition);
+                            }
                         } else {
+                            partition_consumed(&pending_partitions, current_partition);
                             let local = local_counter.load(Ordering::SeqCst);
                             let global = mcounter.load(Ordering::SeqCst);
                             trace!(
@@ -833,18 +1711,27 @@ impl KafkaBackend {
         topic: &String,
         fetch: Option<KafkaFetch>,
         max_messages: Option<i64>,
+        resume_offsets: Option<&HashMap<i32, i64>>,
     ) -> Result<Vec<KrustMessage>, ExternalError> {
         let start_mark = Instant::now();
         info!("starting listing messages for topic {}", topic);
         let topic_name = topic.as_str();
         let context = CustomContext;
         let consumer: LoggingConsumer = self.consumer(context).expect("Consumer creation failed");
+        let payload_decoder = self.payload_decoder(topic_name);
 
         let mut counter = 0;
 
-        let topic = self
-            .topic_message_count(topic, fetch.clone(), max_messages, None)
-            .await;
+        let topic = match resume_offsets {
+            Some(offsets) => {
+                self.resume_message_count(topic, offsets, max_messages)
+                    .await
+            }
+            None => {
+                self.topic_message_count(topic, fetch.clone(), max_messages, None)
+                    .await
+            }
+        };
         let total = topic.total.unwrap_or_default();
         let partitions = topic.partitions.clone();
 
@@ -866,9 +1753,10 @@ impl KafkaBackend {
             .assign(&partition_list)
             .expect("Can't subscribe to partition list");
 
+        let poll_timeout = self.poll_timeout();
         let mut messages: Vec<KrustMessage> = Vec::with_capacity(total);
         while counter < total {
-            match consumer.poll(Duration::from_secs(5)) {
+            match consumer.poll(poll_timeout) {
                 None => warn!("Kafka timeout"),
                 Some(result) => match result {
                     Err(e) => warn!("Kafka error: {}", e),
@@ -878,23 +1766,9 @@ impl KafkaBackend {
                             None => 0,
                         };
                         if m.offset() <= max_offset {
-                            let payload = match m.payload_view::<str>() {
-                                None => "",
-                                Some(Ok(s)) => s,
-                                Some(Err(e)) => {
-                                    warn!("Error while deserializing message payload: {:?}", e);
-                                    ""
-                                }
-                            };
-                            let key = match m.key_view::<str>() {
-                                None => "",
-                                Some(Ok(s)) => s,
-                                Some(Err(e)) => {
-                                    warn!("Error while deserializing message key: {:?}", e);
-                                    ""
-                                }
-                            };
-                            trace!("key: '{:?}', payload: '{}', topic: {}, partition: {}, offset: {}, timestamp: {:?}",
+                            let (payload, raw_payload) = payload_decoder.value(m.payload()).await;
+                            let key = payload_decoder.key(m.key()).await;
+                            trace!("key: '{:?}', payload: '{:?}', topic: {}, partition: {}, offset: {}, timestamp: {:?}",
                                 key, payload, m.topic(), m.partition(), m.offset(), m.timestamp());
                             let headers = if let Some(headers) = m.headers() {
                                 let mut header_list: Vec<KrustHeader> = vec![];
@@ -915,10 +1789,11 @@ impl KafkaBackend {
                                 topic: m.topic().to_string(),
                                 partition: m.partition(),
                                 offset: m.offset(),
-                                key: Some(key.to_string()),
+                                key: Some(key),
                                 timestamp: m.timestamp().to_millis(),
-                                value: payload.to_string(),
+                                value: payload,
                                 headers,
+                                raw_value: raw_payload,
                             };
 
                             messages.push(message);
@@ -939,3 +1814,116 @@ impl KafkaBackend {
         Ok(messages)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partition(id: i32, low: i64, high: i64) -> Partition {
+        Partition {
+            id,
+            offset_low: Some(low),
+            offset_high: Some(high),
+        }
+    }
+
+    #[test]
+    fn extra_config_lines_are_parsed() {
+        let config = "
+            # comment
+            fetch.max.bytes = 1024
+
+            client.dns.lookup=use_all_dns_ips
+            sasl.jaas.config=a=b
+            empty.value=
+        ";
+
+        assert_eq!(
+            parse_extra_config(config),
+            vec![
+                ("fetch.max.bytes".to_string(), "1024".to_string()),
+                (
+                    "client.dns.lookup".to_string(),
+                    "use_all_dns_ips".to_string()
+                ),
+                ("sasl.jaas.config".to_string(), "a=b".to_string()),
+                ("empty.value".to_string(), "".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn malformed_extra_config_lines_are_skipped() {
+        assert!(parse_extra_config("").is_empty());
+        assert_eq!(
+            parse_extra_config("no_separator\n=value\n  = value\nacks=all"),
+            vec![("acks".to_string(), "all".to_string())]
+        );
+    }
+
+    #[test]
+    fn partition_with_transaction_markers_is_consumed_at_high_watermark() {
+        // offsets 0..=7 are records, 8 and 9 are commit markers never delivered
+        let partitions = vec![partition(0, 0, 10)];
+        let mut assigned = TopicPartitionList::new();
+        assigned
+            .add_partition_offset("topic", 0, Offset::Beginning)
+            .unwrap();
+        let ranges = assigned_offset_ranges(&assigned, &partitions);
+        let pending = Mutex::new(partitions_with_records(&ranges));
+        let last_offsets = HashMap::from([(0, 10)]);
+
+        assert!(!position_reached(&last_offsets, 0, Offset::Offset(8)));
+        assert!(position_reached(&last_offsets, 0, Offset::Offset(10)));
+        assert!(partition_consumed(&pending, 0));
+        assert!(pending.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn refresh_only_waits_for_assigned_partitions() {
+        let partitions = vec![partition(0, 0, 10), partition(1, 0, 20)];
+        // partition 0 is up to date and not assigned
+        let mut assigned = TopicPartitionList::new();
+        assigned
+            .add_partition_offset("topic", 1, Offset::Offset(15))
+            .unwrap();
+        let ranges = assigned_offset_ranges(&assigned, &partitions);
+
+        assert_eq!(ranges, HashMap::from([(1, (15, 20))]));
+        assert_eq!(partitions_with_records(&ranges), HashSet::from([1]));
+    }
+
+    #[test]
+    fn nothing_to_refresh_leaves_nothing_pending() {
+        let partitions = vec![partition(0, 0, 10)];
+        let ranges = assigned_offset_ranges(&TopicPartitionList::new(), &partitions);
+
+        assert!(ranges.is_empty());
+        assert!(partitions_with_records(&ranges).is_empty());
+    }
+
+    #[test]
+    fn logical_start_offsets_are_resolved() {
+        let partitions = vec![
+            partition(0, 5, 100),
+            partition(1, 0, 10),
+            partition(2, 0, 3),
+        ];
+        let mut assigned = TopicPartitionList::new();
+        assigned
+            .add_partition_offset("topic", 0, Offset::OffsetTail(10))
+            .unwrap();
+        assigned
+            .add_partition_offset("topic", 1, Offset::End)
+            .unwrap();
+        assigned
+            .add_partition_offset("topic", 2, Offset::OffsetTail(10))
+            .unwrap();
+        let ranges = assigned_offset_ranges(&assigned, &partitions);
+
+        assert_eq!(ranges[&0], (90, 100));
+        assert_eq!(ranges[&1], (10, 10));
+        assert_eq!(ranges[&2], (0, 3));
+        assert_eq!(partitions_with_records(&ranges), HashSet::from([0, 2]));
+    }
+}
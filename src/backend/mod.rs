@@ -2,7 +2,9 @@
 // this source code is governed by the GPL-3.0 license that can be
 // found in the COPYING file.
 
+pub(crate) mod payload;
 pub(crate) mod repository;
+pub(crate) mod schema_registry;
 pub(crate) mod kafka;
 pub(crate) mod worker;
 pub(crate) mod settings;
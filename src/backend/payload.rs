@@ -0,0 +1,277 @@
+// Copyright (c) 2024, Miguel A. Baldi Hörlle <miguel.horlle@gmail.com>. All rights reserved. Use of
+// this source code is governed by the GPL-3.0 license that can be
+// found in the COPYING file.
+
+use std::fmt::Debug;
+use std::fs;
+use std::sync::Arc;
+
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use prost_reflect::{DescriptorPool, DynamicMessage, MessageDescriptor};
+use tracing::{info, warn};
+
+use crate::backend::repository::{KeyFormat, KrustTopicFormat, ValueFormat};
+use crate::backend::schema_registry::{SchemaRegistryClient, HEADER_SIZE, MAGIC_BYTE};
+use crate::config::ExternalError;
+
+/// Formats bytes as space separated hexadecimal pairs.
+pub fn hex_dump(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Formats bytes as a classic hex view: offset, 16 hexadecimal pairs and the printable
+/// characters of each line.
+pub fn hex_view(bytes: &[u8]) -> String {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let ascii = chunk
+                .iter()
+                .map(|b| {
+                    if b.is_ascii_graphic() || *b == b' ' {
+                        *b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>();
+            format!("{:08x}  {:<47}  |{}|", line * 16, hex_dump(chunk), ascii)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Decodes base64 text, standard or URL safe alphabet, ignoring whitespace and padding.
+pub fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.is_empty() {
+        return Err("nothing to decode".to_string());
+    }
+    STANDARD
+        .decode(&compact)
+        .or_else(|_| URL_SAFE_NO_PAD.decode(compact.trim_end_matches('=')))
+        .map_err(|e| format!("not valid base64: {}", e))
+}
+
+pub fn encode_base64(bytes: &[u8]) -> String {
+    STANDARD.encode(bytes)
+}
+
+/// Turns message value bytes into text. Returns `None` when the bytes aren't in the
+/// decoder format, so the next decoder can try them.
+pub trait MessageDecoder: Debug + Send + Sync {
+    fn decode(&self, bytes: &[u8]) -> Option<String>;
+}
+
+/// Plain UTF-8 text, also used as the fallback for every other format.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RawDecoder;
+
+impl MessageDecoder for RawDecoder {
+    fn decode(&self, bytes: &[u8]) -> Option<String> {
+        std::str::from_utf8(bytes).ok().map(|s| s.to_string())
+    }
+}
+
+/// UTF-8 JSON documents, normalized to compact JSON.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct JsonDecoder;
+
+impl MessageDecoder for JsonDecoder {
+    fn decode(&self, bytes: &[u8]) -> Option<String> {
+        serde_json::from_slice::<serde_json::Value>(bytes)
+            .ok()
+            .map(|json| json.to_string())
+    }
+}
+
+/// Parses a value as JSON and, when a schema is given, validates it against the schema.
+pub fn validate_json(value: &str, schema: Option<&jsonschema::JSONSchema>) -> Result<(), String> {
+    let instance = serde_json::from_str::<serde_json::Value>(value)
+        .map_err(|e| format!("invalid JSON: {}", e))?;
+    if let Some(schema) = schema {
+        if let Err(errors) = schema.validate(&instance) {
+            let messages = errors
+                .map(|e| format!("{} at \"{}\"", e, e.instance_path))
+                .collect::<Vec<_>>();
+            return Err(messages.join("; "));
+        }
+    }
+    Ok(())
+}
+
+/// Compiles a JSON Schema document, as stored in the topic format.
+pub fn compile_json_schema(schema: &str) -> Result<jsonschema::JSONSchema, String> {
+    let schema = serde_json::from_str::<serde_json::Value>(schema)
+        .map_err(|e| format!("invalid JSON Schema: {}", e))?;
+    jsonschema::JSONSchema::compile(&schema).map_err(|e| format!("invalid JSON Schema: {}", e))
+}
+
+/// Decoder registry: new formats only need a `ValueFormat` variant and an entry here.
+pub fn value_decoder(format: ValueFormat) -> Box<dyn MessageDecoder> {
+    match format {
+        ValueFormat::Raw => Box::new(RawDecoder),
+        ValueFormat::Json => Box::new(JsonDecoder),
+    }
+}
+
+/// Decodes Protobuf payloads using a message from a compiled `FileDescriptorSet`.
+#[derive(Debug, Clone)]
+pub struct ProtobufDecoder {
+    descriptor: MessageDescriptor,
+}
+
+impl ProtobufDecoder {
+    pub fn load(descriptor_path: &str, message_name: &str) -> Result<Self, ExternalError> {
+        let bytes = fs::read(descriptor_path)?;
+        let pool = DescriptorPool::decode(bytes.as_slice()).map_err(|e| {
+            ExternalError::ConfigurationError(format!("invalid descriptor set: {}", e))
+        })?;
+        let descriptor = pool.get_message_by_name(message_name).ok_or_else(|| {
+            ExternalError::ConfigurationError(format!(
+                "message {} not found in descriptor set",
+                message_name
+            ))
+        })?;
+        Ok(Self { descriptor })
+    }
+
+    pub fn decode(&self, payload: &[u8]) -> Result<String, String> {
+        let data = strip_confluent_protobuf_prefix(payload)?;
+        let message =
+            DynamicMessage::decode(self.descriptor.clone(), data).map_err(|e| e.to_string())?;
+        serde_json::to_string(&message).map_err(|e| e.to_string())
+    }
+}
+
+/// Strips the schema registry prefix: magic byte, schema id and the message indexes array.
+/// Protobuf field tags are never zero, so a leading zero byte always means a prefixed payload.
+fn strip_confluent_protobuf_prefix(payload: &[u8]) -> Result<&[u8], String> {
+    if payload.first() != Some(&MAGIC_BYTE) {
+        return Ok(payload);
+    }
+    let mut data = payload
+        .get(HEADER_SIZE..)
+        .ok_or_else(|| "truncated schema registry prefix".to_string())?;
+    let count = zigzag(read_varint(&mut data)?);
+    for _ in 0..count {
+        read_varint(&mut data)?;
+    }
+    Ok(data)
+}
+
+fn read_varint(data: &mut &[u8]) -> Result<u64, String> {
+    let mut value = 0u64;
+    for (i, byte) in data.iter().enumerate().take(10) {
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            *data = &data[i + 1..];
+            return Ok(value);
+        }
+    }
+    Err("invalid varint in schema registry prefix".to_string())
+}
+
+fn zigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+/// Turns raw message payloads into the text shown and cached for each message.
+#[derive(Debug)]
+pub struct PayloadDecoder {
+    pub schema_registry: Option<SchemaRegistryClient>,
+    pub protobuf: Option<ProtobufDecoder>,
+    pub key_format: KeyFormat,
+    pub value_decoder: Box<dyn MessageDecoder>,
+}
+
+impl PayloadDecoder {
+    pub fn new(
+        schema_registry: Option<SchemaRegistryClient>,
+        format: Option<KrustTopicFormat>,
+    ) -> Arc<Self> {
+        let format = format.unwrap_or_default();
+        let protobuf = match (format.protobuf_descriptor, format.protobuf_message) {
+            (Some(path), Some(message)) => ProtobufDecoder::load(&path, &message)
+                .map_err(|e| warn!("unable to load protobuf descriptor {}: {}", path, e))
+                .ok(),
+            _ => None,
+        };
+        if protobuf.is_some() {
+            info!("decoding payloads as protobuf");
+        }
+        Arc::new(Self {
+            schema_registry,
+            protobuf,
+            key_format: format.key_format,
+            value_decoder: value_decoder(format.value_format),
+        })
+    }
+
+    /// Decodes the key with the topic key format, falling back to the raw UTF-8 string.
+    pub async fn key(&self, key: Option<&[u8]>) -> String {
+        let Some(key) = key else {
+            return String::new();
+        };
+        let decoded = match self.key_format {
+            KeyFormat::Raw => None,
+            KeyFormat::Json => JsonDecoder.decode(key),
+            KeyFormat::Avro => match &self.schema_registry {
+                Some(registry) => registry.decode(key).await,
+                None => None,
+            },
+        };
+        if let Some(decoded) = decoded {
+            return decoded;
+        }
+        match std::str::from_utf8(key) {
+            Ok(s) => s.to_string(),
+            Err(e) => {
+                warn!("Error while deserializing message key: {:?}", e);
+                String::new()
+            }
+        }
+    }
+
+    /// Decodes the payload as Protobuf or through the schema registry when configured,
+    /// then with the topic value decoder, falling back to the raw UTF-8 string. The raw bytes are returned alongside the text
+    /// when the payload couldn't be decoded as text.
+    pub async fn value(&self, payload: Option<&[u8]>) -> (Option<String>, Option<Vec<u8>>) {
+        let Some(payload) = payload else {
+            return (None, None);
+        };
+        if let Some(protobuf) = &self.protobuf {
+            return match protobuf.decode(payload) {
+                Ok(json) => (Some(json), None),
+                Err(e) => {
+                    warn!("unable to decode protobuf payload: {}", e);
+                    (Some(hex_dump(payload)), Some(payload.to_vec()))
+                }
+            };
+        }
+        if let Some(registry) = &self.schema_registry {
+            if let Some(decoded) = registry.decode(payload).await {
+                return (Some(decoded), None);
+            }
+        }
+        if let Some(decoded) = self.value_decoder.decode(payload) {
+            return (Some(decoded), None);
+        }
+        match RawDecoder.decode(payload) {
+            Some(s) => (Some(s), None),
+            None => {
+                warn!("payload is not valid UTF-8, keeping raw bytes");
+                (
+                    Some(String::from_utf8_lossy(payload).to_string()),
+                    Some(payload.to_vec()),
+                )
+            }
+        }
+    }
+}
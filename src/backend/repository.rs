@@ -2,18 +2,23 @@
 // this source code is governed by the GPL-3.0 license that can be
 // found in the COPYING file.
 
+use std::collections::HashSet;
 use std::fmt;
+use std::fs;
 use std::path::PathBuf;
 use std::string::ToString;
 use std::{fmt::Display, str::FromStr};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
-use rusqlite::{named_params, params, Connection, Row};
+use rusqlite::{
+    functions::FunctionFlags, named_params, params, params_from_iter, Connection, Row, ToSql,
+};
 use serde::{Deserialize, Serialize};
 use strum::EnumString;
 use tracing::*;
 
+use super::kafka::KafkaFetch;
 use crate::component::task_manager::{Task, TaskManagerMsg, TASK_MANAGER_BROKER};
 use crate::config::{
     database_connection, database_connection_with_name, destroy_database_with_name, ExternalError,
@@ -28,10 +33,51 @@ pub enum KrustConnectionSecurityType {
     #[default]
     PLAINTEXT,
     SASL_PLAINTEXT,
+    SASL_SSL,
+    SSL,
 }
 
 impl KrustConnectionSecurityType {
-    pub const VALUES: [Self; 2] = [Self::PLAINTEXT, Self::SASL_PLAINTEXT];
+    pub const VALUES: [Self; 4] = [
+        Self::PLAINTEXT,
+        Self::SASL_PLAINTEXT,
+        Self::SASL_SSL,
+        Self::SSL,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, EnumString, strum::Display)]
+pub enum SaslMechanism {
+    #[default]
+    #[strum(serialize = "PLAIN")]
+    Plain,
+    #[strum(serialize = "SCRAM-SHA-256")]
+    ScramSha256,
+    #[strum(serialize = "SCRAM-SHA-512")]
+    ScramSha512,
+    #[strum(serialize = "OAUTHBEARER")]
+    OauthBearer,
+    #[strum(serialize = "GSSAPI")]
+    GssApi,
+}
+
+impl SaslMechanism {
+    pub const VALUES: [Self; 5] = [
+        Self::Plain,
+        Self::ScramSha256,
+        Self::ScramSha512,
+        Self::OauthBearer,
+        Self::GssApi,
+    ];
+
+    fn from_stored(value: Option<String>) -> Option<Self> {
+        value.map(|v| {
+            Self::from_str(v.as_str()).unwrap_or_else(|_| {
+                warn!("unknown sasl mechanism '{}', falling back to PLAIN", v);
+                Self::Plain
+            })
+        })
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -40,12 +86,55 @@ pub struct KrustConnection {
     pub name: String,
     pub brokers_list: String,
     pub security_type: KrustConnectionSecurityType,
-    pub sasl_mechanism: Option<String>,
+    pub sasl_mechanism: Option<SaslMechanism>,
     pub sasl_username: Option<String>,
     pub sasl_password: Option<String>,
     pub color: Option<String>,
     pub timeout: Option<usize>,
+    pub ssl_ca_location: Option<String>,
+    pub ssl_certificate_location: Option<String>,
+    pub ssl_key_location: Option<String>,
+    pub extra_config: Option<String>,
+    pub group_id: Option<String>,
+    pub default_fetch: Option<KafkaFetch>,
+    pub schema_registry_url: Option<String>,
+    pub oauth_token_endpoint: Option<String>,
+    pub oauth_client_id: Option<String>,
+    pub oauth_client_secret: Option<String>,
+    pub client_id: Option<String>,
+    pub client_rack: Option<String>,
 }
+
+impl KrustConnection {
+    /// Checks that the brokers list is made of comma-separated `host:port` entries.
+    pub fn validate(&self) -> Result<(), ExternalError> {
+        let brokers = self.brokers_list.trim();
+        if brokers.is_empty() {
+            return Err(ExternalError::ConfigurationError(
+                "brokers list is required".to_string(),
+            ));
+        }
+        for broker in brokers.split(',') {
+            let broker = broker.trim();
+            let valid = match broker.rsplit_once(':') {
+                Some((host, port)) => {
+                    !host.is_empty()
+                        && !host.contains(char::is_whitespace)
+                        && port.parse::<u16>().is_ok_and(|p| p > 0)
+                }
+                None => false,
+            };
+            if !valid {
+                return Err(ExternalError::ConfigurationError(format!(
+                    "invalid broker \"{}\", expected host:port",
+                    broker
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Eq)]
 pub struct Partition {
     pub id: i32,
@@ -70,6 +159,7 @@ pub enum FetchMode {
     Tail,
     Head,
     FromTimestamp,
+    TimeRange,
 }
 
 impl ToString for FetchMode {
@@ -79,6 +169,7 @@ impl ToString for FetchMode {
             Self::Tail => "Newest".to_string(),
             Self::Head => "Oldest".to_string(),
             Self::FromTimestamp => "From date/time".to_string(),
+            Self::TimeRange => "Date/time range".to_string(),
         }
     }
 }
@@ -91,6 +182,7 @@ impl FromStr for FetchMode {
             "Newest" => Ok(Self::Tail),
             "Oldest" => Ok(Self::Head),
             "From date/time" => Ok(Self::FromTimestamp),
+            "Date/time range" => Ok(Self::TimeRange),
             _ => Err(ExternalError::DisplayError(
                 "fetch mode not found".to_string(),
                 text.to_string(),
@@ -105,8 +197,52 @@ pub struct KrustTopicCache {
     pub topic_name: String,
     pub fetch_mode: FetchMode,
     pub fetch_value: Option<i64>,
+    pub fetch_value_end: Option<i64>,
     pub default_page_size: u16,
     pub last_updated: Option<i64>,
+    /// Minutes between background refreshes of the cache, never refreshed in the background
+    /// when unset.
+    pub auto_refresh_minutes: Option<i64>,
+}
+
+/// How message keys are decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumString, strum::Display)]
+pub enum KeyFormat {
+    #[default]
+    Raw,
+    #[strum(serialize = "JSON")]
+    Json,
+    Avro,
+}
+
+impl KeyFormat {
+    pub const VALUES: [Self; 3] = [Self::Raw, Self::Json, Self::Avro];
+}
+
+/// Which registered decoder turns message values into text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, EnumString, strum::Display)]
+pub enum ValueFormat {
+    #[default]
+    Raw,
+    #[strum(serialize = "JSON")]
+    Json,
+}
+
+impl ValueFormat {
+    pub const VALUES: [Self; 2] = [Self::Raw, Self::Json];
+}
+
+/// Per topic payload decoding settings.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct KrustTopicFormat {
+    pub connection_id: usize,
+    pub topic_name: String,
+    pub protobuf_descriptor: Option<String>,
+    pub protobuf_message: Option<String>,
+    pub key_format: KeyFormat,
+    pub value_format: ValueFormat,
+    /// JSON Schema produced values are checked against when JSON validation is enabled.
+    pub value_json_schema: Option<String>,
 }
 
 impl Display for KrustTopic {
@@ -121,9 +257,11 @@ pub struct KrustMessage {
     pub partition: i32,
     pub offset: i64,
     pub key: Option<String>,
-    pub value: String,
+    pub value: Option<String>,
     pub timestamp: Option<i64>,
     pub headers: Vec<KrustHeader>,
+    /// Raw payload bytes, only kept when the payload isn't valid text.
+    pub raw_value: Option<Vec<u8>>,
 }
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct KrustHeader {
@@ -147,6 +285,68 @@ pub struct MessagesSearchOrder {
     pub order: String,
 }
 
+/// Cache columns the messages table can be sorted by.
+const SORTABLE_COLUMNS: [&str; 4] = ["offset", "partition", "key", "timestamp"];
+const SORT_DIRECTIONS: [&str; 2] = ["ASC", "DESC"];
+const DEFAULT_ORDER: &str = "timestamp DESC";
+
+/// `ORDER BY` clause for the cache queries, built only from allowlisted columns and
+/// directions since it can't be bound as a query parameter.
+fn order_by_clause(order: Option<MessagesSearchOrder>) -> String {
+    let Some(order) = order else {
+        return DEFAULT_ORDER.to_string();
+    };
+    let column = SORTABLE_COLUMNS
+        .iter()
+        .find(|c| c.eq_ignore_ascii_case(&order.column));
+    let direction = SORT_DIRECTIONS
+        .iter()
+        .find(|d| d.eq_ignore_ascii_case(&order.order));
+    match (column, direction) {
+        (Some(column), Some(direction)) => format!("{} {}", column, direction),
+        _ => {
+            warn!("rejecting unknown sort order: {:?}", order);
+            DEFAULT_ORDER.to_string()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, EnumString, strum::Display)]
+pub enum MessagesSearchMode {
+    #[default]
+    Substring,
+    #[strum(serialize = "Full-text")]
+    FullText,
+    Regex,
+}
+
+impl MessagesSearchMode {
+    pub const VALUES: [Self; 3] = [Self::Substring, Self::FullText, Self::Regex];
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, EnumString, strum::Display)]
+pub enum MessagesSearchField {
+    #[default]
+    Value,
+    Key,
+    #[strum(serialize = "Key and value")]
+    Both,
+}
+
+impl MessagesSearchField {
+    pub const VALUES: [Self; 3] = [Self::Value, Self::Key, Self::Both];
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MessagesSearchOptions {
+    pub mode: MessagesSearchMode,
+    pub field: MessagesSearchField,
+    pub case_insensitive: bool,
+}
+
+static CACHE_FILE_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^topic_(?P<connection_id>\d+)_(?P<topic_name>\S+)\.db$").unwrap());
+
 impl MessagesRepository {
     pub fn new(connection_id: usize, topic_name: &String) -> Self {
         let path = PathBuf::from(Settings::read().unwrap_or_default().cache_dir.as_str());
@@ -158,11 +358,12 @@ impl MessagesRepository {
             connection_id,
         }
     }
+    /// Whether a file of the cache directory holds the messages cache of a topic.
+    pub fn is_cache_file(filename: &str) -> bool {
+        CACHE_FILE_RE.is_match(filename)
+    }
     pub fn from_filename(filename: String) -> Self {
-        static RE: Lazy<Regex> = Lazy::new(|| {
-            Regex::new(r"topic_(?P<connection_id>\d+)_(?P<topic_name>\S+)\.db").unwrap()
-        });
-        let caps = RE.captures(&filename).unwrap();
+        let caps = CACHE_FILE_RE.captures(&filename).unwrap();
         let connection_id = caps["connection_id"].parse::<usize>().unwrap();
         let topic_name = &caps["topic_name"].to_string();
         let path = PathBuf::from(Settings::read().unwrap_or_default().cache_dir.as_str());
@@ -174,89 +375,174 @@ impl MessagesRepository {
             connection_id,
         }
     }
-    pub fn get_connection(&self) -> Connection {
-        let conn = database_connection_with_name(&self.path, &self.database_name)
-            .expect("problem acquiring database connection");
+    pub fn get_connection(&self) -> Result<Connection, ExternalError> {
+        let conn = database_connection_with_name(&self.path, &self.database_name)?;
         conn.execute_batch(
             "PRAGMA journal_mode = OFF;
             PRAGMA synchronous = 0;
             PRAGMA cache_size = 200000;
             PRAGMA locking_mode = EXCLUSIVE;
             PRAGMA temp_store = MEMORY;",
-        )
-        .unwrap();
-        conn
+        )?;
+        register_regexp_function(&conn)?;
+        register_casefold_function(&conn)?;
+        Ok(conn)
     }
-    pub fn get_init_connection(&mut self) -> Connection {
+    pub fn get_init_connection(&mut self) -> Result<Connection, ExternalError> {
         database_connection_with_name(&self.path, &self.database_name)
-            .expect("problem acquiring database connection")
     }
     pub fn init(&mut self) -> Result<(), ExternalError> {
-        let result = self.get_init_connection().execute_batch(
+        let conn = self.get_init_connection()?;
+        let result = conn.execute_batch(
             "CREATE TABLE IF NOT EXISTS kr_message
             (partition INTEGER, offset INTEGER, key TEXT, value TEXT, timestamp INTEGER, headers TEXT, PRIMARY KEY (partition, offset));"
         ).map_err(ExternalError::DatabaseError);
-        let _ = self
-            .get_init_connection()
+        let _ = conn
             .execute_batch("ALTER TABLE kr_message ADD COLUMN key TEXT;")
             .ok();
+        let _ = conn
+            .execute_batch("ALTER TABLE kr_message ADD COLUMN raw_value BLOB;")
+            .ok();
+        self.init_full_text_index(&conn);
         result
     }
 
+    fn init_full_text_index(&self, conn: &Connection) {
+        let has_index = has_full_text_index(conn);
+        let index_result = conn.execute_batch(
+            "CREATE VIRTUAL TABLE IF NOT EXISTS kr_message_fts
+            USING fts5(key, value, headers, content='kr_message');
+            CREATE TRIGGER IF NOT EXISTS kr_message_fts_insert AFTER INSERT ON kr_message BEGIN
+                INSERT INTO kr_message_fts(rowid, key, value, headers)
+                VALUES (new.rowid, new.key, new.value, new.headers);
+            END;
+            CREATE TRIGGER IF NOT EXISTS kr_message_fts_delete AFTER DELETE ON kr_message BEGIN
+                INSERT INTO kr_message_fts(kr_message_fts, rowid, key, value, headers)
+                VALUES ('delete', old.rowid, old.key, old.value, old.headers);
+            END;",
+        );
+        match index_result {
+            Ok(_) if !has_index => {
+                info!("building full-text index for {}", self.database_name);
+                if let Err(e) = conn
+                    .execute_batch("INSERT INTO kr_message_fts(kr_message_fts) VALUES('rebuild');")
+                {
+                    warn!("unable to build full-text index: {:?}", e);
+                }
+            }
+            Ok(_) => (),
+            Err(e) => warn!("full-text search not available: {:?}", e),
+        }
+    }
+
     pub fn destroy(&mut self) -> Result<(), ExternalError> {
         destroy_database_with_name(self.path.clone(), &self.database_name)
     }
 
-    pub fn save_message(
-        &self,
-        conn: &Connection,
-        message: &KrustMessage,
-    ) -> Result<KrustMessage, ExternalError> {
-        //let conn = self.get_connection();
-        let mut stmt_by_id = conn.prepare_cached(
-            "INSERT INTO kr_message(partition, offset, key, value, timestamp, headers)
-            VALUES (:p, :o, :k, :v, :t, :h)",
+    /// Deletes the cached messages of a partition within an inclusive offset range.
+    pub fn delete_messages(
+        &mut self,
+        partition: i32,
+        offset_from: i64,
+        offset_to: i64,
+    ) -> Result<usize, ExternalError> {
+        if offset_from > offset_to {
+            return Ok(0);
+        }
+        let conn = self.get_connection()?;
+        let mut stmt = conn.prepare_cached(
+            "DELETE FROM kr_message WHERE partition = :p AND offset BETWEEN :from AND :to",
         )?;
-        let headers =
-            ron::ser::to_string::<Vec<KrustHeader>>(message.headers.as_ref()).unwrap_or_default();
-        let maybe_message = stmt_by_id
-        .execute(
-            named_params! { ":p": message.partition, ":o": message.offset, ":k": message.key, ":v": message.value, ":t": message.timestamp, ":h": headers},
-        )
-        .map_err(ExternalError::DatabaseError);
+        stmt.execute(named_params! { ":p": partition, ":from": offset_from, ":to": offset_to })
+            .map_err(ExternalError::DatabaseError)
+    }
+
+    /// Size of the cache database file on disk.
+    pub fn database_size_bytes(&self) -> Result<u64, ExternalError> {
+        let data_file = self.path.join(format!("{}.db", self.database_name));
+        fs::metadata(data_file)
+            .map(|metadata| metadata.len())
+            .map_err(ExternalError::FileSystemError)
+    }
 
-        match maybe_message {
-            Ok(_) => Ok(message.to_owned()),
-            Err(e) => Err(e),
+    /// Rebuilds the cache database, reclaiming space left by deleted messages.
+    pub fn vacuum(&mut self) -> Result<(), ExternalError> {
+        let conn = self.get_connection()?;
+        conn.execute_batch("VACUUM;")
+            .map_err(ExternalError::DatabaseError)?;
+        // VACUUM may renumber the rowids the full-text index is keyed by
+        if has_full_text_index(&conn) {
+            conn.execute_batch("INSERT INTO kr_message_fts(kr_message_fts) VALUES('rebuild');")
+                .map_err(ExternalError::DatabaseError)?;
         }
+        Ok(())
     }
 
-    pub fn count_messages(&mut self, search: Option<String>) -> Result<usize, ExternalError> {
-        let conn = self.get_connection();
-        let mut stmt_count = match search {
-            Some(_) => {
-                conn.prepare_cached("SELECT COUNT(1) FROM kr_message WHERE value LIKE :search")?
-            }
+    /// Saves a batch of messages with a single multi-row insert inside one transaction,
+    /// ignoring messages already cached.
+    pub fn save_messages(
+        &self,
+        conn: &mut Connection,
+        messages: &[KrustMessage],
+    ) -> Result<usize, ExternalError> {
+        if messages.is_empty() {
+            return Ok(0);
+        }
+        let headers = messages
+            .iter()
+            .map(|m| {
+                ron::ser::to_string::<Vec<KrustHeader>>(m.headers.as_ref()).unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+        let mut values: Vec<&dyn ToSql> = Vec::with_capacity(messages.len() * 7);
+        for (message, headers) in messages.iter().zip(headers.iter()) {
+            values.push(&message.partition);
+            values.push(&message.offset);
+            values.push(&message.key);
+            values.push(&message.value);
+            values.push(&message.timestamp);
+            values.push(headers);
+            values.push(&message.raw_value);
+        }
+        let sql = format!(
+            "INSERT OR IGNORE INTO kr_message(partition, offset, key, value, timestamp, headers, raw_value) VALUES {}",
+            vec!["(?, ?, ?, ?, ?, ?, ?)"; messages.len()].join(", ")
+        );
+        let tx = conn.transaction()?;
+        let inserted = {
+            let mut stmt = tx.prepare_cached(sql.as_str())?;
+            stmt.execute(params_from_iter(values))?
+        };
+        tx.commit()?;
+        Ok(inserted)
+    }
+
+    pub fn count_messages(
+        &mut self,
+        search: Option<String>,
+        options: &MessagesSearchOptions,
+    ) -> Result<usize, ExternalError> {
+        let conn = self.get_connection()?;
+        let filter = search_filter(&conn, search, options);
+        let mut stmt_count = match &filter {
+            Some((clause, _)) => conn.prepare_cached(
+                format!("SELECT COUNT(1) FROM kr_message WHERE {}", clause).as_str(),
+            )?,
             None => conn.prepare_cached("SELECT COUNT(1) FROM kr_message")?,
         };
-        let params_with_search =
-            named_params! { ":search": format!("%{}%", search.clone().unwrap_or_default()) };
-        stmt_count
-            .query_row(
-                if search.is_some() {
-                    params_with_search
-                } else {
-                    named_params![]
-                },
-                move |row| row.get(0),
-            )
-            .map_err(ExternalError::DatabaseError)
+        match filter {
+            Some((_, term)) => {
+                stmt_count.query_row(named_params! { ":search": term }, move |row| row.get(0))
+            }
+            None => stmt_count.query_row(named_params![], move |row| row.get(0)),
+        }
+        .map_err(ExternalError::DatabaseError)
     }
 
     // TODO: find latest offsets/partitions
 
     pub fn find_offsets(&mut self) -> Result<Vec<Partition>, ExternalError> {
-        let conn = self.get_connection();
+        let conn = self.get_connection()?;
         let mut stmt_by_id = conn.prepare_cached(
             "SELECT high.partition partition, offset_low, offset_high
             FROM (SELECT partition, MAX(offset) offset_high
@@ -296,60 +582,39 @@ impl MessagesRepository {
         page_size: u16,
         order: Option<MessagesSearchOrder>,
         search: Option<String>,
+        options: &MessagesSearchOptions,
     ) -> Result<Vec<KrustMessage>, ExternalError> {
-        let conn = self.get_connection();
-        let order = order
-            .map(|o| format!("{} {}", o.column, o.order))
-            .unwrap_or("timestamp DESC".to_string());
+        let conn = self.get_connection()?;
+        let order = order_by_clause(order);
         let from = self.get_pagination_from(page, page_size);
         let to = self.get_pagination_to(page, page_size);
-        let mut stmt_query = match search {
-            Some(_) => conn.prepare_cached(
+        let filter = search_filter(&conn, search, options);
+        let mut stmt_query = match &filter {
+            Some((clause, _)) => conn.prepare_cached(
                 format!(
-                "SELECT partition, offset, key, value, timestamp, headers FROM (
-                    SELECT ROW_NUMBER () OVER (ORDER BY {}) rownum, partition, offset, key, value, timestamp, headers
+                "SELECT partition, offset, key, value, timestamp, headers, raw_value FROM (
+                    SELECT ROW_NUMBER () OVER (ORDER BY {}) rownum, partition, offset, key, value, timestamp, headers, raw_value
                     FROM kr_message
-                    WHERE value LIKE :search)
-                WHERE rownum > {} AND rownum <= {}", order, from, to).as_str(),
+                    WHERE {})
+                WHERE rownum > {} AND rownum <= {}", order, clause, from, to).as_str(),
             )?,
             None => conn.prepare_cached(
                 format!(
-                "SELECT partition, offset, key, value, timestamp, headers FROM (
-                    SELECT ROW_NUMBER () OVER (ORDER BY {}) rownum, partition, offset, key, value, timestamp, headers
+                "SELECT partition, offset, key, value, timestamp, headers, raw_value FROM (
+                    SELECT ROW_NUMBER () OVER (ORDER BY {}) rownum, partition, offset, key, value, timestamp, headers, raw_value
                     FROM kr_message)
                 WHERE rownum > {} AND rownum <= {}", order, from, to).as_str(),
             )?,
         };
-        let string_to_headers = move |sheaders: String| {
-            let headers: Result<Vec<KrustHeader>, rusqlite::Error> = ron::from_str(&sheaders)
-                .map_err(|e| rusqlite::Error::InvalidColumnName(e.to_string()));
-            headers
-        };
         let topic_name = self.topic_name.clone();
-        let row_to_model = move |row: &Row<'_>| {
-            Ok(KrustMessage {
-                partition: row.get(0)?,
-                offset: row.get(1)?,
-                key: row.get(2)?,
-                value: row.get(3)?,
-                timestamp: Some(row.get(4)?),
-                headers: string_to_headers(row.get(5)?)?,
-                topic: topic_name.clone(),
-            })
-        };
-        let params_with_search =
-            named_params! { ":search": format!("%{}%", search.clone().unwrap_or_default()) };
-        let params = named_params! {};
-        let rows = stmt_query
-            .query_map(
-                if search.is_some() {
-                    params_with_search
-                } else {
-                    params
-                },
-                row_to_model,
-            )
-            .map_err(ExternalError::DatabaseError)?;
+        let row_to_model = move |row: &Row<'_>| message_from_row(row, &topic_name);
+        let rows = match &filter {
+            Some((_, term)) => {
+                stmt_query.query_map(named_params! { ":search": term }, row_to_model)
+            }
+            None => stmt_query.query_map(named_params! {}, row_to_model),
+        }
+        .map_err(ExternalError::DatabaseError)?;
         let mut messages = Vec::new();
         for row in rows {
             messages.push(row?);
@@ -358,6 +623,69 @@ impl MessagesRepository {
         }
         Ok(messages)
     }
+
+    /// Streams every cached message in the given order to `write_row` one row at a time, so
+    /// exporting a large cache never holds it in memory. Stops early when the task is
+    /// cancelled.
+    pub fn stream_all<F>(
+        &mut self,
+        task: &Task,
+        order: Option<MessagesSearchOrder>,
+        mut write_row: F,
+    ) -> Result<usize, ExternalError>
+    where
+        F: FnMut(&KrustMessage) -> Result<(), ExternalError>,
+    {
+        let total = self.count_messages(None, &MessagesSearchOptions::default())?;
+        let conn = self.get_connection()?;
+        let order = order_by_clause(order);
+        let mut stmt = conn.prepare(
+            format!(
+                "SELECT partition, offset, key, value, timestamp, headers, raw_value FROM kr_message ORDER BY {}",
+                order
+            )
+            .as_str(),
+        )?;
+        let topic_name = self.topic_name.clone();
+        let rows = stmt.query_map([], |row| message_from_row(row, &topic_name))?;
+        let mut written = 0;
+        for row in rows {
+            if task
+                .token
+                .as_ref()
+                .is_some_and(|token| token.is_cancelled())
+            {
+                info!("export of topic {} cancelled", &self.topic_name);
+                break;
+            }
+            write_row(&row?)?;
+            written += 1;
+            if written % EXPORT_PROGRESS_STEP == 0 {
+                let progress_step = written as f64 / total.max(1) as f64;
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), progress_step));
+            }
+        }
+        Ok(written)
+    }
+}
+
+/// Number of exported rows between progress updates.
+const EXPORT_PROGRESS_STEP: usize = 10_000;
+
+fn message_from_row(row: &Row<'_>, topic_name: &str) -> Result<KrustMessage, rusqlite::Error> {
+    let headers: Vec<KrustHeader> = ron::from_str(row.get::<usize, String>(5)?.as_str())
+        .map_err(|e| rusqlite::Error::InvalidColumnName(e.to_string()))?;
+    Ok(KrustMessage {
+        partition: row.get(0)?,
+        offset: row.get(1)?,
+        key: row.get(2)?,
+        value: row.get(3)?,
+        // records without a timestamp are cached as NULL
+        timestamp: row.get::<_, Option<i64>>(4)?,
+        headers,
+        raw_value: row.get(6)?,
+        topic: topic_name.to_string(),
+    })
 }
 
 impl Default for Repository {
@@ -418,6 +746,76 @@ impl Repository {
             .unwrap_or_else(|e| {
                 warn!("kr_topic.timeout: {:?}", e);
             });
+        self.conn
+            .execute_batch(
+                "ALTER TABLE kr_connection ADD COLUMN sslCaLocation TEXT DEFAULT NULL;
+                ALTER TABLE kr_connection ADD COLUMN sslCertificateLocation TEXT DEFAULT NULL;
+                ALTER TABLE kr_connection ADD COLUMN sslKeyLocation TEXT DEFAULT NULL;",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.ssl: {:?}", e);
+            });
+        self.conn
+            .execute_batch("ALTER TABLE kr_connection ADD COLUMN extraConfig TEXT DEFAULT NULL;")
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.extraConfig: {:?}", e);
+            });
+        self.conn
+            .execute_batch("ALTER TABLE kr_connection ADD COLUMN groupId TEXT DEFAULT NULL;")
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.groupId: {:?}", e);
+            });
+        self.conn
+            .execute_batch("ALTER TABLE kr_connection ADD COLUMN defaultFetch TEXT DEFAULT NULL;")
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.defaultFetch: {:?}", e);
+            });
+        self.conn
+            .execute_batch(
+                "ALTER TABLE kr_connection ADD COLUMN schemaRegistryUrl TEXT DEFAULT NULL;",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.schemaRegistryUrl: {:?}", e);
+            });
+        self.conn
+            .execute_batch(
+                "ALTER TABLE kr_connection ADD COLUMN oauthTokenEndpoint TEXT DEFAULT NULL;",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.oauthTokenEndpoint: {:?}", e);
+            });
+        self.conn
+            .execute_batch("ALTER TABLE kr_connection ADD COLUMN oauthClientId TEXT DEFAULT NULL;")
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.oauthClientId: {:?}", e);
+            });
+        self.conn
+            .execute_batch(
+                "ALTER TABLE kr_connection ADD COLUMN oauthClientSecret TEXT DEFAULT NULL;",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.oauthClientSecret: {:?}", e);
+            });
+        self.conn
+            .execute_batch("ALTER TABLE kr_connection ADD COLUMN clientId TEXT DEFAULT NULL;")
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.clientId: {:?}", e);
+            });
+        self.conn
+            .execute_batch("ALTER TABLE kr_connection ADD COLUMN clientRack TEXT DEFAULT NULL;")
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_connection.clientRack: {:?}", e);
+            });
         self.conn
             .execute_batch(
                 "
@@ -462,12 +860,115 @@ impl Repository {
             .unwrap_or_else(|e| {
                 warn!("kr_topic_cache: {:?}", e);
             });
+        self.conn
+            .execute_batch(
+                "ALTER TABLE kr_topic_cache ADD COLUMN fetch_value_end INTEGER DEFAULT NULL;",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_topic_cache.fetch_value_end: {:?}", e);
+            });
+        self.conn
+            .execute_batch(
+                "ALTER TABLE kr_topic_cache ADD COLUMN auto_refresh_minutes INTEGER DEFAULT NULL;",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_topic_cache.auto_refresh_minutes: {:?}", e);
+            });
+        self.conn
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS kr_ui_state
+                   (connection_id INTEGER,
+                    scope TEXT,
+                    key TEXT,
+                    value TEXT,
+                    PRIMARY KEY (connection_id, scope, key),
+                    FOREIGN KEY (connection_id) REFERENCES kr_connection(id) ON DELETE CASCADE);
+                ",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_ui_state: {:?}", e);
+            });
+        self.conn
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS kr_topic_format
+                   (connection_id INTEGER,
+                    topic_name TEXT,
+                    protobuf_descriptor TEXT,
+                    protobuf_message TEXT,
+                    PRIMARY KEY (connection_id, topic_name),
+                    FOREIGN KEY (connection_id) REFERENCES kr_connection(id) ON DELETE CASCADE);
+                ",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_topic_format: {:?}", e);
+            });
+        self.conn
+            .execute_batch("ALTER TABLE kr_topic_format ADD COLUMN key_format TEXT DEFAULT NULL;")
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_topic_format.key_format: {:?}", e);
+            });
+        self.conn
+            .execute_batch("ALTER TABLE kr_topic_format ADD COLUMN value_format TEXT DEFAULT NULL;")
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_topic_format.value_format: {:?}", e);
+            });
+        self.conn
+            .execute_batch(
+                "ALTER TABLE kr_topic_format ADD COLUMN value_json_schema TEXT DEFAULT NULL;",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_topic_format.value_json_schema: {:?}", e);
+            });
+        info!("repository::create kr_partition");
+        self.conn
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS kr_partition
+                   (connection_id INTEGER,
+                    topic_name TEXT,
+                    partition INTEGER,
+                    offset_low INTEGER,
+                    offset_high INTEGER,
+                    PRIMARY KEY (connection_id, topic_name, partition),
+                    FOREIGN KEY (connection_id) REFERENCES kr_connection(id) ON DELETE CASCADE);
+                ",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_partition: {:?}", e);
+            });
+        info!("repository::create kr_bookmark");
+        self.conn
+            .execute_batch(
+                "
+                CREATE TABLE IF NOT EXISTS kr_bookmark
+                   (connection_id INTEGER,
+                    topic_name TEXT,
+                    partition INTEGER,
+                    offset INTEGER,
+                    PRIMARY KEY (connection_id, topic_name, partition, offset),
+                    FOREIGN KEY (connection_id) REFERENCES kr_connection(id) ON DELETE CASCADE);
+                ",
+            )
+            .map_err(ExternalError::DatabaseError)
+            .unwrap_or_else(|e| {
+                warn!("kr_bookmark: {:?}", e);
+            });
         Ok(())
     }
 
     pub fn connection_by_id(&mut self, id: usize) -> Option<KrustConnection> {
         let mut stmt = self.conn.prepare_cached("
-            SELECT id, name, brokersList, securityType, saslMechanism, saslUsername, saslPassword, color, timeout
+            SELECT id, name, brokersList, securityType, saslMechanism, saslUsername, saslPassword, color, timeout, sslCaLocation, sslCertificateLocation, sslKeyLocation, extraConfig, groupId, defaultFetch, schemaRegistryUrl, oauthTokenEndpoint, oauthClientId, oauthClientSecret, clientId, clientRack
             FROM kr_connection WHERE id = ?")
         .expect("Should return prepared statement");
         let rows = stmt
@@ -480,11 +981,26 @@ impl Repository {
                         row.get::<usize, String>(3).unwrap_or_default().as_str(),
                     )
                     .unwrap_or_default(),
-                    sasl_mechanism: row.get(4).unwrap_or(None),
+                    sasl_mechanism: SaslMechanism::from_stored(row.get(4).unwrap_or(None)),
                     sasl_username: row.get(5).unwrap_or(None),
                     sasl_password: row.get(6).unwrap_or(None),
                     color: row.get(7).unwrap_or(None),
                     timeout: row.get(8).unwrap_or(None),
+                    ssl_ca_location: row.get(9).unwrap_or(None),
+                    ssl_certificate_location: row.get(10).unwrap_or(None),
+                    ssl_key_location: row.get(11).unwrap_or(None),
+                    extra_config: row.get(12).unwrap_or(None),
+                    group_id: row.get(13).unwrap_or(None),
+                    default_fetch: row
+                        .get::<usize, Option<String>>(14)
+                        .unwrap_or(None)
+                        .and_then(|f| KafkaFetch::from_str(f.as_str()).ok()),
+                    schema_registry_url: row.get(15).unwrap_or(None),
+                    oauth_token_endpoint: row.get(16).unwrap_or(None),
+                    oauth_client_id: row.get(17).unwrap_or(None),
+                    oauth_client_secret: row.get(18).unwrap_or(None),
+                    client_id: row.get(19).unwrap_or(None),
+                    client_rack: row.get(20).unwrap_or(None),
                 })
             })
             .map_err(ExternalError::DatabaseError);
@@ -506,6 +1022,18 @@ impl Repository {
             , saslPassword
             , color
             , timeout
+            , sslCaLocation
+            , sslCertificateLocation
+            , sslKeyLocation
+            , extraConfig
+            , groupId
+            , defaultFetch
+            , schemaRegistryUrl
+            , oauthTokenEndpoint
+            , oauthClientId
+            , oauthClientSecret
+            , clientId
+            , clientRack
         FROM kr_connection
         ORDER BY name",
         )?;
@@ -519,11 +1047,25 @@ impl Repository {
                         row.get::<usize, String>(3)?.as_str(),
                     )
                     .unwrap_or_default(),
-                    sasl_mechanism: row.get(4)?,
+                    sasl_mechanism: SaslMechanism::from_stored(row.get(4)?),
                     sasl_username: row.get(5)?,
                     sasl_password: row.get(6)?,
                     color: row.get(7)?,
                     timeout: row.get(8)?,
+                    ssl_ca_location: row.get(9)?,
+                    ssl_certificate_location: row.get(10)?,
+                    ssl_key_location: row.get(11)?,
+                    extra_config: row.get(12)?,
+                    group_id: row.get(13)?,
+                    default_fetch: row
+                        .get::<usize, Option<String>>(14)?
+                        .and_then(|f| KafkaFetch::from_str(f.as_str()).ok()),
+                    schema_registry_url: row.get(15)?,
+                    oauth_token_endpoint: row.get(16)?,
+                    oauth_client_id: row.get(17)?,
+                    oauth_client_secret: row.get(18)?,
+                    client_id: row.get(19)?,
+                    client_rack: row.get(20)?,
                 })
             })
             .map_err(ExternalError::DatabaseError)?;
@@ -542,13 +1084,25 @@ impl Repository {
         let name = konn.name.clone();
         let brokers = konn.brokers_list.clone();
         let security = konn.security_type.clone();
-        let sasl = konn.sasl_mechanism.clone();
+        let sasl = konn.sasl_mechanism;
         let sasl_username = konn.sasl_username.clone();
         let sasl_password = konn.sasl_password.clone();
         let color = konn.color.clone();
         let timeout = konn.timeout;
-        let mut stmt_by_id = self.conn.prepare_cached("SELECT id, name, brokersList, securityType, saslMechanism, saslUsername, saslPassword, color, timeout from kr_connection where id = ?1")?;
-        let mut stmt_by_name = self.conn.prepare_cached("SELECT id, name, brokersList, securityType, saslMechanism, saslUsername, saslPassword, color, timeout from kr_connection where name = ?1")?;
+        let ssl_ca_location = konn.ssl_ca_location.clone();
+        let ssl_certificate_location = konn.ssl_certificate_location.clone();
+        let ssl_key_location = konn.ssl_key_location.clone();
+        let extra_config = konn.extra_config.clone();
+        let group_id = konn.group_id.clone();
+        let default_fetch = konn.default_fetch.clone();
+        let schema_registry_url = konn.schema_registry_url.clone();
+        let oauth_token_endpoint = konn.oauth_token_endpoint.clone();
+        let oauth_client_id = konn.oauth_client_id.clone();
+        let oauth_client_secret = konn.oauth_client_secret.clone();
+        let client_id = konn.client_id.clone();
+        let client_rack = konn.client_rack.clone();
+        let mut stmt_by_id = self.conn.prepare_cached("SELECT id, name, brokersList, securityType, saslMechanism, saslUsername, saslPassword, color, timeout, sslCaLocation, sslCertificateLocation, sslKeyLocation, extraConfig, groupId, defaultFetch, schemaRegistryUrl, oauthTokenEndpoint, oauthClientId, oauthClientSecret, clientId, clientRack from kr_connection where id = ?1")?;
+        let mut stmt_by_name = self.conn.prepare_cached("SELECT id, name, brokersList, securityType, saslMechanism, saslUsername, saslPassword, color, timeout, sslCaLocation, sslCertificateLocation, sslKeyLocation, extraConfig, groupId, defaultFetch, schemaRegistryUrl, oauthTokenEndpoint, oauthClientId, oauthClientSecret, clientId, clientRack from kr_connection where name = ?1")?;
         let row_to_model = move |row: &Row<'_>| {
             Ok(KrustConnection {
                 id: row.get(0)?,
@@ -558,11 +1112,25 @@ impl Repository {
                     row.get::<usize, String>(3)?.as_str(),
                 )
                 .unwrap_or_default(),
-                sasl_mechanism: row.get(4)?,
+                sasl_mechanism: SaslMechanism::from_stored(row.get(4)?),
                 sasl_username: row.get(5)?,
                 sasl_password: row.get(6)?,
                 color: row.get(7)?,
                 timeout: row.get(8)?,
+                ssl_ca_location: row.get(9)?,
+                ssl_certificate_location: row.get(10)?,
+                ssl_key_location: row.get(11)?,
+                extra_config: row.get(12)?,
+                group_id: row.get(13)?,
+                default_fetch: row
+                    .get::<usize, Option<String>>(14)?
+                    .and_then(|f| KafkaFetch::from_str(f.as_str()).ok()),
+                schema_registry_url: row.get(15)?,
+                oauth_token_endpoint: row.get(16)?,
+                oauth_client_id: row.get(17)?,
+                oauth_client_secret: row.get(18)?,
+                client_id: row.get(19)?,
+                client_rack: row.get(20)?,
             })
         };
         let maybe_konn = match id {
@@ -587,6 +1155,18 @@ impl Repository {
                     , saslPassword = :sasl_p
                     , color = :color
                     , timeout = :timeout
+                    , sslCaLocation = :ssl_ca
+                    , sslCertificateLocation = :ssl_cert
+                    , sslKeyLocation = :ssl_key
+                    , extraConfig = :extra_config
+                    , groupId = :group_id
+                    , defaultFetch = :default_fetch
+                    , schemaRegistryUrl = :schema_registry_url
+                    , oauthTokenEndpoint = :oauth_token_endpoint
+                    , oauthClientId = :oauth_client_id
+                    , oauthClientSecret = :oauth_client_secret
+                    , clientId = :client_id
+                    , clientRack = :client_rack
                     WHERE id = :id",
                 )?;
                 up_stmt
@@ -595,11 +1175,23 @@ impl Repository {
                         ":name": &name,
                         ":brokers": &brokers,
                         ":security": security.to_string(),
-                        ":sasl": &sasl,
+                        ":sasl": sasl.map(|m| m.to_string()),
                         ":sasl_u": &sasl_username,
                         ":sasl_p": &sasl_password,
                         ":color": &color,
                         ":timeout": &timeout,
+                        ":ssl_ca": &ssl_ca_location,
+                        ":ssl_cert": &ssl_certificate_location,
+                        ":ssl_key": &ssl_key_location,
+                        ":extra_config": &extra_config,
+                        ":group_id": &group_id,
+                        ":default_fetch": default_fetch.as_ref().map(|f| f.to_string()),
+                        ":schema_registry_url": &schema_registry_url,
+                        ":oauth_token_endpoint": &oauth_token_endpoint,
+                        ":oauth_client_id": &oauth_client_id,
+                        ":oauth_client_secret": &oauth_client_secret,
+                        ":client_id": &client_id,
+                        ":client_rack": &client_rack,
                     })
                     .map_err(ExternalError::DatabaseError)
                     .map(|_| KrustConnection {
@@ -612,12 +1204,24 @@ impl Repository {
                         sasl_password,
                         color,
                         timeout,
+                        ssl_ca_location,
+                        ssl_certificate_location,
+                        ssl_key_location,
+                        extra_config,
+                        group_id,
+                        default_fetch,
+                        schema_registry_url,
+                        oauth_token_endpoint,
+                        oauth_client_id,
+                        oauth_client_secret,
+                        client_id,
+                        client_rack,
                     })
             }
             Err(_) => {
                 let mut ins_stmt = self.conn.prepare_cached("
-                    INSERT INTO kr_connection (id, name, brokersList, securityType, saslMechanism, saslUsername, saslPassword, color, timeout)
-                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    INSERT INTO kr_connection (id, name, brokersList, securityType, saslMechanism, saslUsername, saslPassword, color, timeout, sslCaLocation, sslCertificateLocation, sslKeyLocation, extraConfig, groupId, defaultFetch, schemaRegistryUrl, oauthTokenEndpoint, oauthClientId, oauthClientSecret, clientId, clientRack)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                     RETURNING id")?;
                 ins_stmt
                     .query_row(
@@ -626,11 +1230,23 @@ impl Repository {
                             &konn.name,
                             &konn.brokers_list,
                             &konn.security_type.to_string(),
-                            &konn.sasl_mechanism,
+                            &konn.sasl_mechanism.map(|m| m.to_string()),
                             &konn.sasl_username,
                             &konn.sasl_password,
                             &konn.color,
                             &konn.timeout,
+                            &konn.ssl_ca_location,
+                            &konn.ssl_certificate_location,
+                            &konn.ssl_key_location,
+                            &konn.extra_config,
+                            &konn.group_id,
+                            &konn.default_fetch.as_ref().map(|f| f.to_string()),
+                            &konn.schema_registry_url,
+                            &konn.oauth_token_endpoint,
+                            &konn.oauth_client_id,
+                            &konn.oauth_client_secret,
+                            &konn.client_id,
+                            &konn.client_rack,
                         ],
                         |row| {
                             Ok(KrustConnection {
@@ -643,6 +1259,18 @@ impl Repository {
                                 sasl_password,
                                 color,
                                 timeout,
+                                ssl_ca_location,
+                                ssl_certificate_location,
+                                ssl_key_location,
+                                extra_config,
+                                group_id,
+                                default_fetch,
+                                schema_registry_url,
+                                oauth_token_endpoint,
+                                oauth_client_id,
+                                oauth_client_secret,
+                                client_id,
+                                client_rack,
                             })
                         },
                     )
@@ -695,6 +1323,8 @@ impl Repository {
         let last_updated = cache.last_updated;
         let default_page_size = cache.default_page_size;
         let fetch_value = cache.fetch_value;
+        let fetch_value_end = cache.fetch_value_end;
+        let auto_refresh_minutes = cache.auto_refresh_minutes;
 
         let topic = self.find_topic(conn_id, &topic_name);
         if topic.is_none() {
@@ -716,14 +1346,16 @@ impl Repository {
         };
 
         let mut stmt_by_id = self.conn.prepare_cached(
-            "INSERT INTO kr_topic_cache(connection_id, topic_name, fetch_mode, fetch_value, last_updated, default_page_size)
-            VALUES (:cid, :topic, :fetch_mode, :fetch_value, :last_updated, :default_page_size)
+            "INSERT INTO kr_topic_cache(connection_id, topic_name, fetch_mode, fetch_value, fetch_value_end, last_updated, default_page_size, auto_refresh_minutes)
+            VALUES (:cid, :topic, :fetch_mode, :fetch_value, :fetch_value_end, :last_updated, :default_page_size, :auto_refresh_minutes)
             ON CONFLICT(connection_id, topic_name)
             DO UPDATE SET
                             fetch_mode=excluded.fetch_mode,
                             fetch_value=excluded.fetch_value,
+                            fetch_value_end=excluded.fetch_value_end,
                             last_updated=excluded.last_updated,
-                            default_page_size=excluded.default_page_size",
+                            default_page_size=excluded.default_page_size,
+                            auto_refresh_minutes=excluded.auto_refresh_minutes",
         )?;
         let t_name = topic_name.clone();
         let row_to_model = move |_| {
@@ -732,8 +1364,10 @@ impl Repository {
                 topic_name: t_name.clone(),
                 fetch_mode,
                 fetch_value,
+                fetch_value_end,
                 last_updated,
                 default_page_size,
+                auto_refresh_minutes,
             })
         };
 
@@ -743,8 +1377,10 @@ impl Repository {
             ":topic": topic_name.clone(),
             ":fetch_mode": &fetch_mode.to_string(),
             ":fetch_value": &fetch_value,
+            ":fetch_value_end": &fetch_value_end,
             ":last_updated": &last_updated,
-            ":default_page_size": &default_page_size })
+            ":default_page_size": &default_page_size,
+            ":auto_refresh_minutes": &auto_refresh_minutes })
             .map(row_to_model)?
             .map_err(ExternalError::DatabaseError)
     }
@@ -782,6 +1418,92 @@ impl Repository {
             .map_err(ExternalError::DatabaseError)
     }
 
+    /// Stores a UI state value for a connection. Connection wide state uses an empty scope,
+    /// topic state uses the topic name.
+    pub fn save_ui_state(
+        &mut self,
+        conn_id: usize,
+        scope: &str,
+        key: &str,
+        value: &str,
+    ) -> Result<usize, ExternalError> {
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO kr_ui_state(connection_id, scope, key, value)
+            VALUES (:cid, :scope, :key, :value)
+            ON CONFLICT(connection_id, scope, key)
+            DO UPDATE SET value=excluded.value",
+        )?;
+        stmt.execute(
+            named_params! { ":cid": &conn_id, ":scope": &scope, ":key": &key, ":value": &value },
+        )
+        .map_err(ExternalError::DatabaseError)
+    }
+
+    pub fn find_ui_state(&mut self, conn_id: usize, scope: &str, key: &str) -> Option<String> {
+        let stmt = self.conn.prepare_cached(
+            "SELECT value FROM kr_ui_state
+            WHERE connection_id = :cid AND scope = :scope AND key = :key",
+        );
+        stmt.ok()?
+            .query_row(
+                named_params! { ":cid": &conn_id, ":scope": &scope, ":key": &key },
+                |row| row.get(0),
+            )
+            .ok()
+    }
+
+    pub fn save_topic_format(&mut self, format: &KrustTopicFormat) -> Result<usize, ExternalError> {
+        let mut stmt = self.conn.prepare_cached(
+            "INSERT INTO kr_topic_format(connection_id, topic_name, protobuf_descriptor, protobuf_message, key_format, value_format, value_json_schema)
+            VALUES (:cid, :topic, :protobuf_descriptor, :protobuf_message, :key_format, :value_format, :value_json_schema)
+            ON CONFLICT(connection_id, topic_name)
+            DO UPDATE SET protobuf_descriptor=excluded.protobuf_descriptor, protobuf_message=excluded.protobuf_message, key_format=excluded.key_format, value_format=excluded.value_format, value_json_schema=excluded.value_json_schema",
+        )?;
+        stmt.execute(named_params! {
+            ":cid": &format.connection_id,
+            ":topic": &format.topic_name,
+            ":protobuf_descriptor": &format.protobuf_descriptor,
+            ":protobuf_message": &format.protobuf_message,
+            ":key_format": format.key_format.to_string(),
+            ":value_format": format.value_format.to_string(),
+            ":value_json_schema": &format.value_json_schema,
+        })
+        .map_err(ExternalError::DatabaseError)
+    }
+
+    pub fn find_topic_format(
+        &mut self,
+        conn_id: usize,
+        topic_name: &str,
+    ) -> Option<KrustTopicFormat> {
+        let stmt = self.conn.prepare_cached(
+            "SELECT connection_id, topic_name, protobuf_descriptor, protobuf_message, key_format, value_format, value_json_schema
+            FROM kr_topic_format WHERE connection_id = :cid AND topic_name = :topic",
+        );
+        stmt.ok()?
+            .query_row(
+                named_params! { ":cid": &conn_id, ":topic": &topic_name },
+                |row| {
+                    Ok(KrustTopicFormat {
+                        connection_id: row.get(0)?,
+                        topic_name: row.get(1)?,
+                        protobuf_descriptor: row.get(2)?,
+                        protobuf_message: row.get(3)?,
+                        key_format: row
+                            .get::<usize, Option<String>>(4)?
+                            .and_then(|f| KeyFormat::from_str(f.as_str()).ok())
+                            .unwrap_or_default(),
+                        value_format: row
+                            .get::<usize, Option<String>>(5)?
+                            .and_then(|f| ValueFormat::from_str(f.as_str()).ok())
+                            .unwrap_or_default(),
+                        value_json_schema: row.get(6)?,
+                    })
+                },
+            )
+            .ok()
+    }
+
     pub fn delete_connection(&mut self, conn_id: usize) -> Result<usize, ExternalError> {
         let mut stmt_by_id = self.conn.prepare_cached(
             "DELETE FROM kr_connection
@@ -792,6 +1514,16 @@ impl Repository {
             .map_err(ExternalError::DatabaseError)
     }
 
+    /// Connection id and topic name of every stored topic cache.
+    pub fn list_topic_caches(&mut self) -> Result<Vec<(usize, String)>, ExternalError> {
+        let mut stmt = self
+            .conn
+            .prepare_cached("SELECT connection_id, topic_name FROM kr_topic_cache")?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(ExternalError::DatabaseError)
+    }
+
     pub fn find_topic_cache(
         &mut self,
         conn_id: usize,
@@ -805,7 +1537,9 @@ impl Repository {
                 fetch_mode,
                 fetch_value,
                 last_updated,
-                default_page_size
+                default_page_size,
+                fetch_value_end,
+                auto_refresh_minutes
             FROM kr_topic_cache WHERE connection_id = :cid AND topic_name = :topic",
         );
         stmt.ok()?
@@ -818,16 +1552,190 @@ impl Repository {
                         fetch_mode: FetchMode::from_str(row.get::<usize, String>(2)?.as_str())
                             .unwrap_or_default(),
                         fetch_value: row.get(3)?,
+                        fetch_value_end: row.get(6)?,
                         last_updated: row.get(4)?,
                         default_page_size: row.get(5)?,
+                        auto_refresh_minutes: row.get(7)?,
                     })
                 },
             )
             .ok()
     }
 
+    /// Topic caches refreshed in the background.
+    pub fn list_auto_refresh_caches(&mut self) -> Result<Vec<KrustTopicCache>, ExternalError> {
+        let mut stmt = self.conn.prepare_cached(
+            "
+            SELECT
+                connection_id,
+                topic_name,
+                fetch_mode,
+                fetch_value,
+                last_updated,
+                default_page_size,
+                fetch_value_end,
+                auto_refresh_minutes
+            FROM kr_topic_cache WHERE auto_refresh_minutes > 0",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(KrustTopicCache {
+                connection_id: row.get(0)?,
+                topic_name: row.get(1)?,
+                fetch_mode: FetchMode::from_str(row.get::<usize, String>(2)?.as_str())
+                    .unwrap_or_default(),
+                fetch_value: row.get(3)?,
+                fetch_value_end: row.get(6)?,
+                last_updated: row.get(4)?,
+                default_page_size: row.get(5)?,
+                auto_refresh_minutes: row.get(7)?,
+            })
+        })?;
+        rows.collect::<Result<Vec<_>, _>>()
+            .map_err(ExternalError::DatabaseError)
+    }
+
+    pub fn save_topic_cache_auto_refresh(
+        &mut self,
+        conn_id: usize,
+        topic_name: &str,
+        auto_refresh_minutes: Option<i64>,
+    ) -> Result<usize, ExternalError> {
+        let mut stmt = self.conn.prepare_cached(
+            "UPDATE kr_topic_cache SET auto_refresh_minutes = :minutes
+            WHERE connection_id = :cid AND topic_name = :topic",
+        )?;
+        stmt.execute(named_params! {
+            ":cid": &conn_id,
+            ":topic": topic_name,
+            ":minutes": &auto_refresh_minutes,
+        })
+        .map_err(ExternalError::DatabaseError)
+    }
+
+    /// Replaces the last-seen low/high offsets of every partition of a topic.
+    pub fn save_partitions(
+        &mut self,
+        conn_id: usize,
+        topic_name: &str,
+        partitions: &[Partition],
+    ) -> Result<usize, ExternalError> {
+        let tx = self.conn.transaction()?;
+        let mut saved = 0;
+        {
+            tx.execute(
+                "DELETE FROM kr_partition WHERE connection_id = :cid AND topic_name = :topic",
+                named_params! { ":cid": &conn_id, ":topic": &topic_name },
+            )?;
+            let mut stmt = tx.prepare_cached(
+                "INSERT INTO kr_partition(connection_id, topic_name, partition, offset_low, offset_high)
+                VALUES (:cid, :topic, :partition, :low, :high)",
+            )?;
+            for partition in partitions {
+                saved += stmt.execute(named_params! {
+                    ":cid": &conn_id,
+                    ":topic": &topic_name,
+                    ":partition": &partition.id,
+                    ":low": &partition.offset_low,
+                    ":high": &partition.offset_high,
+                })?;
+            }
+        }
+        tx.commit()?;
+        Ok(saved)
+    }
+
+    /// Drops the bookmarks of offsets below the partitions' low offsets, which the broker no
+    /// longer retains.
+    pub fn delete_expired_bookmarks(
+        &mut self,
+        conn_id: usize,
+        topic_name: &str,
+        partitions: &[Partition],
+    ) -> Result<usize, ExternalError> {
+        let mut stmt = self.conn.prepare_cached(
+            "DELETE FROM kr_bookmark
+            WHERE connection_id = :cid AND topic_name = :topic AND partition = :partition AND offset < :low",
+        )?;
+        let mut deleted = 0;
+        for partition in partitions {
+            if let Some(low) = partition.offset_low {
+                deleted += stmt.execute(named_params! {
+                    ":cid": &conn_id,
+                    ":topic": &topic_name,
+                    ":partition": &partition.id,
+                    ":low": &low,
+                })?;
+            }
+        }
+        Ok(deleted)
+    }
+
+    /// Adds or removes a message bookmark, returns whether the message is now bookmarked.
+    pub fn toggle_bookmark(
+        &mut self,
+        conn_id: usize,
+        topic_name: &str,
+        partition: i32,
+        offset: i64,
+    ) -> Result<bool, ExternalError> {
+        let deleted = self.conn.execute(
+            "DELETE FROM kr_bookmark
+            WHERE connection_id = :cid AND topic_name = :topic AND partition = :partition AND offset = :offset",
+            named_params! { ":cid": &conn_id, ":topic": &topic_name, ":partition": &partition, ":offset": &offset },
+        )?;
+        if deleted > 0 {
+            return Ok(false);
+        }
+        self.conn.execute(
+            "INSERT INTO kr_bookmark(connection_id, topic_name, partition, offset)
+            VALUES (:cid, :topic, :partition, :offset)",
+            named_params! { ":cid": &conn_id, ":topic": &topic_name, ":partition": &partition, ":offset": &offset },
+        )?;
+        Ok(true)
+    }
+
+    /// Bookmarked (partition, offset) pairs of a topic.
+    pub fn find_bookmarks(&mut self, conn_id: usize, topic_name: &str) -> HashSet<(i32, i64)> {
+        let stmt = self.conn.prepare_cached(
+            "SELECT partition, offset FROM kr_bookmark WHERE connection_id = :cid AND topic_name = :topic",
+        );
+        let Ok(mut stmt) = stmt else {
+            return HashSet::new();
+        };
+        stmt.query_map(
+            named_params! { ":cid": &conn_id, ":topic": &topic_name },
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
+    pub fn find_partitions(&mut self, conn_id: usize, topic_name: &str) -> Vec<Partition> {
+        let stmt = self.conn.prepare_cached(
+            "SELECT partition, offset_low, offset_high
+            FROM kr_partition WHERE connection_id = :cid AND topic_name = :topic
+            ORDER BY partition",
+        );
+        let Ok(mut stmt) = stmt else {
+            return vec![];
+        };
+        stmt.query_map(
+            named_params! { ":cid": &conn_id, ":topic": &topic_name },
+            |row| {
+                Ok(Partition {
+                    id: row.get(0)?,
+                    offset_low: row.get(1)?,
+                    offset_high: row.get(2)?,
+                })
+            },
+        )
+        .map(|rows| rows.filter_map(|r| r.ok()).collect())
+        .unwrap_or_default()
+    }
+
     pub fn find_topic(&mut self, conn_id: usize, topic_name: &String) -> Option<KrustTopic> {
         let cache = self.find_topic_cache(conn_id, topic_name);
+        let partitions = self.find_partitions(conn_id, topic_name);
         let stmt = self.conn
         .prepare_cached("SELECT connection_id, name, favourite FROM kr_topic WHERE connection_id = :cid AND name = :topic");
         stmt.ok()?
@@ -838,7 +1746,7 @@ impl Repository {
                         connection_id: row.get(0)?,
                         name: row.get(1)?,
                         cached: cache.clone(),
-                        partitions: vec![],
+                        partitions: partitions.clone(),
                         total: None,
                         favourite: row.get(2)?,
                     })
@@ -851,7 +1759,7 @@ impl Repository {
         conn_id: usize,
     ) -> Result<Vec<KrustTopic>, ExternalError> {
         let mut stmt = self.conn.prepare_cached(
-            "SELECT t.connection_id, t.name, t.favourite, c.fetch_mode, c.fetch_value, c.default_page_size, c.last_updated
+            "SELECT t.connection_id, t.name, t.favourite, c.fetch_mode, c.fetch_value, c.default_page_size, c.last_updated, c.fetch_value_end, c.auto_refresh_minutes
             FROM kr_topic t
             LEFT JOIN kr_topic_cache c ON c.connection_id = t.connection_id AND c.topic_name = t.name
             WHERE t.connection_id = :cid",
@@ -868,8 +1776,10 @@ impl Repository {
                         topic_name: topic_name.clone(),
                         fetch_mode,
                         fetch_value: row.get(4)?,
+                        fetch_value_end: row.get(7)?,
                         default_page_size: row.get(5)?,
                         last_updated: row.get(6)?,
+                        auto_refresh_minutes: row.get(8)?,
                     })
                 } else {
                     None
@@ -891,3 +1801,323 @@ impl Repository {
         Ok(topics)
     }
 }
+
+fn has_full_text_index(conn: &Connection) -> bool {
+    conn.query_row(
+        "SELECT COUNT(1) FROM sqlite_master WHERE type = 'table' AND name = 'kr_message_fts'",
+        params![],
+        |row| row.get::<usize, usize>(0),
+    )
+    .map(|count| count > 0)
+    .unwrap_or(false)
+}
+
+/// Builds the `WHERE` clause and `:search` parameter for the given search options,
+/// falling back to substring search when the full-text index is not available.
+fn search_filter(
+    conn: &Connection,
+    search: Option<String>,
+    options: &MessagesSearchOptions,
+) -> Option<(String, String)> {
+    let search = search?;
+    match options.mode {
+        MessagesSearchMode::FullText if has_full_text_index(conn) => {
+            let query = full_text_query(&search);
+            // headers are only matched when searching on every column
+            let query = match options.field {
+                MessagesSearchField::Value => format!("{{value}} : ({})", query),
+                MessagesSearchField::Key => format!("{{key}} : ({})", query),
+                MessagesSearchField::Both => query,
+            };
+            Some((
+                "rowid IN (SELECT rowid FROM kr_message_fts WHERE kr_message_fts MATCH :search)"
+                    .to_string(),
+                query,
+            ))
+        }
+        MessagesSearchMode::Regex => {
+            let clause = match options.field {
+                MessagesSearchField::Value => "value REGEXP :search",
+                MessagesSearchField::Key => "key REGEXP :search",
+                MessagesSearchField::Both => "(key REGEXP :search OR value REGEXP :search)",
+            };
+            let pattern = if options.case_insensitive {
+                format!("(?i){}", search)
+            } else {
+                search
+            };
+            Some((clause.to_string(), pattern))
+        }
+        _ if options.case_insensitive => {
+            let clause = match options.field {
+                MessagesSearchField::Value => "casefold(value) LIKE casefold(:search)",
+                MessagesSearchField::Key => "casefold(key) LIKE casefold(:search)",
+                MessagesSearchField::Both => {
+                    "(casefold(key) LIKE casefold(:search) OR casefold(value) LIKE casefold(:search))"
+                }
+            };
+            Some((clause.to_string(), format!("%{}%", search)))
+        }
+        _ => {
+            let clause = match options.field {
+                MessagesSearchField::Value => "value LIKE :search",
+                MessagesSearchField::Key => "key LIKE :search",
+                MessagesSearchField::Both => "(key LIKE :search OR value LIKE :search)",
+            };
+            Some((clause.to_string(), format!("%{}%", search)))
+        }
+    }
+}
+
+/// Backs the `REGEXP` operator, caching the compiled pattern for the whole statement.
+fn register_regexp_function(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "regexp",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        move |ctx| {
+            let regex = ctx.get_or_create_aux(0, |vr| -> Result<Regex, regex::Error> {
+                Regex::new(vr.as_str().unwrap_or_default())
+            })?;
+            let is_match = ctx
+                .get_raw(1)
+                .as_str_or_null()
+                .map_err(|e| rusqlite::Error::UserFunctionError(e.into()))?
+                .map(|text| regex.is_match(text))
+                .unwrap_or(false);
+            Ok(is_match)
+        },
+    )
+}
+
+/// Unicode aware lowercase, as SQLite `LOWER` only handles ASCII.
+fn register_casefold_function(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_scalar_function(
+        "casefold",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        move |ctx| {
+            let text = ctx
+                .get_raw(0)
+                .as_str_or_null()
+                .map_err(|e| rusqlite::Error::UserFunctionError(e.into()))?;
+            Ok(text.map(|text| text.to_lowercase()))
+        },
+    )
+}
+
+/// Quotes every term so user input is never parsed as FTS5 query syntax.
+fn full_text_query(search: &str) -> String {
+    search
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::kafka::WRITER_BATCH_SIZE;
+
+    #[test]
+    fn message_without_timestamp_is_read_from_cache() {
+        let conn = Connection::open_in_memory().unwrap();
+        let message = conn
+            .query_row(
+                "SELECT 0, 1, 'key', 'value', NULL, '[]', NULL",
+                params![],
+                |row| message_from_row(row, "topic"),
+            )
+            .unwrap();
+
+        assert_eq!(message.timestamp, None);
+        assert_eq!(message.offset, 1);
+        assert_eq!(message.topic, "topic");
+    }
+
+    fn test_messages_repository(path: PathBuf) -> MessagesRepository {
+        MessagesRepository {
+            topic_name: "topic".to_string(),
+            path,
+            database_name: "topic_0_topic".to_string(),
+            connection_id: 0,
+        }
+    }
+
+    #[test]
+    fn cache_is_created_in_missing_cache_dir() {
+        let cache_dir = std::env::temp_dir()
+            .join(format!("krust-test-{}", uuid::Uuid::new_v4()))
+            .join("cache");
+        let mut mrepo = test_messages_repository(cache_dir.clone());
+
+        mrepo.init().unwrap();
+        let mut conn = mrepo.get_connection().unwrap();
+        let message = KrustMessage {
+            topic: "topic".to_string(),
+            value: Some("value".to_string()),
+            ..Default::default()
+        };
+        mrepo.save_messages(&mut conn, &[message]).unwrap();
+        drop(conn);
+
+        let count = mrepo
+            .count_messages(None, &MessagesSearchOptions::default())
+            .unwrap();
+        assert_eq!(count, 1);
+        fs::remove_dir_all(cache_dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn unusable_cache_dir_is_an_error() {
+        let base = std::env::temp_dir().join(format!("krust-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&base).unwrap();
+        let file = base.join("not-a-dir");
+        fs::write(&file, "").unwrap();
+        let mut mrepo = test_messages_repository(file.join("cache"));
+
+        assert!(mrepo.init().is_err());
+        assert!(mrepo.get_connection().is_err());
+        fs::remove_dir_all(base).unwrap();
+    }
+
+    fn connection_with_brokers(brokers_list: &str) -> KrustConnection {
+        KrustConnection {
+            brokers_list: brokers_list.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn valid_brokers_lists() {
+        for brokers in [
+            "localhost:9092",
+            " kafka-1:9092 , kafka-2:9093 ",
+            "[::1]:9092",
+            "10.0.0.1:65535",
+        ] {
+            assert!(
+                connection_with_brokers(brokers).validate().is_ok(),
+                "{}",
+                brokers
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_brokers_lists() {
+        for brokers in [
+            "",
+            "   ",
+            "localhost",
+            "localhost:",
+            ":9092",
+            "localhost:0",
+            "localhost:65536",
+            "localhost:port",
+            "local host:9092",
+            "localhost:9092,",
+            "localhost:9092,,kafka:9092",
+        ] {
+            assert!(
+                connection_with_brokers(brokers).validate().is_err(),
+                "{:?}",
+                brokers
+            );
+        }
+    }
+
+    fn synthetic_messages(offsets: std::ops::Range<i64>) -> Vec<KrustMessage> {
+        offsets
+            .map(|offset| KrustMessage {
+                topic: "topic".to_string(),
+                partition: (offset % 3) as i32,
+                offset,
+                key: Some(format!("key-{}", offset)),
+                value: (offset % 100 != 0).then(|| format!("{{\"offset\":{}}}", offset)),
+                timestamp: Some(1_700_000_000_000 + offset),
+                headers: vec![KrustHeader {
+                    key: "h".to_string(),
+                    value: Some(offset.to_string()),
+                }],
+                raw_value: None,
+            })
+            .collect()
+    }
+
+    /// Saves the messages the way the cache writer does, one batch per transaction.
+    fn save_in_batches(
+        mrepo: &MessagesRepository,
+        conn: &mut Connection,
+        messages: &[KrustMessage],
+    ) -> usize {
+        messages
+            .chunks(WRITER_BATCH_SIZE)
+            .map(|batch| mrepo.save_messages(conn, batch).unwrap())
+            .sum()
+    }
+
+    #[test]
+    fn messages_are_saved_across_batches() {
+        let cache_dir = std::env::temp_dir().join(format!("krust-test-{}", uuid::Uuid::new_v4()));
+        let mut mrepo = test_messages_repository(cache_dir.clone());
+        mrepo.init().unwrap();
+        let mut conn = mrepo.get_connection().unwrap();
+        let messages = synthetic_messages(0..(WRITER_BATCH_SIZE as i64 * 2 + 500));
+
+        let inserted = save_in_batches(&mrepo, &mut conn, &messages);
+        // already cached records are ignored
+        let reinserted = save_in_batches(&mrepo, &mut conn, &messages[..WRITER_BATCH_SIZE]);
+
+        assert_eq!(inserted, messages.len());
+        assert_eq!(reinserted, 0);
+        let saved = conn
+            .query_row(
+                "SELECT partition, offset, key, value, timestamp, headers, raw_value
+                FROM kr_message WHERE partition = 1 AND offset = 1300",
+                params![],
+                |row| message_from_row(row, "topic"),
+            )
+            .unwrap();
+        assert_eq!(saved.key, messages[1300].key);
+        assert_eq!(saved.value, messages[1300].value);
+        assert_eq!(saved.timestamp, messages[1300].timestamp);
+        assert_eq!(saved.headers[0].value, messages[1300].headers[0].value);
+        let tombstones: usize = conn
+            .query_row(
+                "SELECT COUNT(1) FROM kr_message WHERE value IS NULL",
+                params![],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tombstones, 25);
+        drop(conn);
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+
+    /// Benchmark of caching a million records, run with `cargo test --release -- --ignored`.
+    #[test]
+    #[ignore]
+    fn bench_cache_one_million_messages() {
+        let cache_dir = std::env::temp_dir().join(format!("krust-bench-{}", uuid::Uuid::new_v4()));
+        let mut mrepo = test_messages_repository(cache_dir.clone());
+        mrepo.init().unwrap();
+        let mut conn = mrepo.get_connection().unwrap();
+        let messages = synthetic_messages(0..1_000_000);
+
+        let start = std::time::Instant::now();
+        let inserted = save_in_batches(&mrepo, &mut conn, &messages);
+        let elapsed = start.elapsed();
+
+        assert_eq!(inserted, messages.len());
+        println!(
+            "cached {} messages in {:?} ({:.0} messages/s)",
+            inserted,
+            elapsed,
+            inserted as f64 / elapsed.as_secs_f64()
+        );
+        drop(conn);
+        fs::remove_dir_all(cache_dir).unwrap();
+    }
+}
@@ -0,0 +1,107 @@
+// Copyright (c) 2024, Miguel A. Baldi Hörlle <miguel.horlle@gmail.com>. All rights reserved. Use of
+// this source code is governed by the GPL-3.0 license that can be
+// found in the COPYING file.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use apache_avro::{from_avro_datum, Schema};
+use serde::Deserialize;
+use tracing::{trace, warn};
+
+/// Confluent wire format: one magic byte followed by a 4-byte big-endian schema id.
+pub(crate) const MAGIC_BYTE: u8 = 0;
+pub(crate) const HEADER_SIZE: usize = 5;
+/// How long a schema that failed to load is not fetched again.
+const FAILED_SCHEMA_RETRY: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Deserialize)]
+struct SchemaResponse {
+    schema: String,
+}
+
+/// Schema Registry client used to decode Confluent-Avro encoded payloads.
+/// Schemas are cached by id. Failed fetches are remembered for a short while, so a
+/// batch of messages doesn't hit an unavailable registry once per message.
+#[derive(Debug)]
+pub struct SchemaRegistryClient {
+    url: String,
+    http: reqwest::Client,
+    schemas: Mutex<HashMap<u32, Arc<Schema>>>,
+    failures: Mutex<HashMap<u32, Instant>>,
+}
+
+impl SchemaRegistryClient {
+    pub fn new(url: &str, timeout: Duration) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .unwrap_or_default();
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            http,
+            schemas: Mutex::new(HashMap::new()),
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Decodes an Avro payload into JSON. Returns `None` when the payload isn't in the
+    /// Confluent wire format or can't be decoded.
+    pub async fn decode(&self, payload: &[u8]) -> Option<String> {
+        if payload.len() < HEADER_SIZE || payload[0] != MAGIC_BYTE {
+            return None;
+        }
+        let id = u32::from_be_bytes([payload[1], payload[2], payload[3], payload[4]]);
+        let schema = self.schema(id).await?;
+        let mut datum = &payload[HEADER_SIZE..];
+        let value = from_avro_datum(&schema, &mut datum, None)
+            .map_err(|e| warn!("unable to decode avro payload[schema={}]: {}", id, e))
+            .ok()?;
+        let json = serde_json::Value::try_from(value)
+            .map_err(|e| warn!("unable to convert avro payload[schema={}]: {}", id, e))
+            .ok()?;
+        Some(json.to_string())
+    }
+
+    async fn schema(&self, id: u32) -> Option<Arc<Schema>> {
+        let cached = self.schemas.lock().unwrap().get(&id).cloned();
+        if cached.is_some() {
+            return cached;
+        }
+        let failed_at = self.failures.lock().unwrap().get(&id).copied();
+        if failed_at.is_some_and(|failed_at| failed_at.elapsed() < FAILED_SCHEMA_RETRY) {
+            return None;
+        }
+        match self.fetch_schema(id).await {
+            Ok(schema) => {
+                let schema = Arc::new(schema);
+                self.failures.lock().unwrap().remove(&id);
+                self.schemas.lock().unwrap().insert(id, schema.clone());
+                Some(schema)
+            }
+            Err(e) => {
+                warn!("unable to fetch schema {}: {}", id, e);
+                self.failures.lock().unwrap().insert(id, Instant::now());
+                None
+            }
+        }
+    }
+
+    async fn fetch_schema(&self, id: u32) -> Result<Schema, String> {
+        let url = format!("{}/schemas/ids/{}", self.url, id);
+        trace!("fetching schema from {}", url);
+        let response = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| e.to_string())?;
+        let body = response
+            .json::<SchemaResponse>()
+            .await
+            .map_err(|e| e.to_string())?;
+        Schema::parse_str(body.schema.as_str()).map_err(|e| e.to_string())
+    }
+}
@@ -2,12 +2,16 @@
 // this source code is governed by the GPL-3.0 license that can be
 // found in the COPYING file.
 
+use chrono::{TimeZone, Utc};
+use chrono_tz::Tz;
 use serde::{Deserialize, Serialize};
 use std::fs::File;
 use std::path::PathBuf;
+use std::str::FromStr;
 use tracing::*;
 
 use crate::{
+    backend::kafka::KafkaFetch,
     component::settings_dialog::MessagesSortOrder,
     config::{ensure_app_config_dir, ExternalError},
     DATE_TIME_FORMAT, DATE_TIME_WITH_MILLIS_FORMAT,
@@ -23,7 +27,40 @@ pub struct Settings {
     pub messages_sort_column: String,
     pub messages_sort_column_order: String,
     pub threads_number: u8,
+    /// GTK renderer (`GSK_RENDERER`) used on launch unless the environment variable is set.
+    pub gsk_renderer: String,
+    /// Consumers reading a topic in parallel while caching, bounded by its partition count.
+    pub cache_workers: usize,
     pub default_connection_timeout: usize,
+    pub auto_offset_reset: String,
+    pub session_timeout_ms: usize,
+    pub message_timeout_ms: usize,
+    /// How long a consumer poll waits for messages before giving up on a read. Partitions are
+    /// assigned manually, so there is no group membership for `session_timeout_ms` to expire
+    /// and this can safely exceed it on slow clusters.
+    pub poll_timeout_ms: usize,
+    /// Largest message value, in bytes, rendered with JSON syntax highlighting.
+    pub json_highlight_max_bytes: usize,
+    /// Characters of a message value shown in the messages list, the full value is still
+    /// shown in its tooltip, the viewer and copies.
+    pub value_preview_max_chars: usize,
+    /// Initial "Max messages (per partition)" value for live mode.
+    pub live_max_messages_default: usize,
+    /// Upper bound of the live mode "Max messages (per partition)" input.
+    pub live_max_messages_max: usize,
+    /// Live mode quick fetch buttons, comma separated "First N" (oldest) or "Last N" (newest).
+    pub live_quick_fetch_presets: String,
+    /// Age in hours after which a topic cache is flagged as stale in the topics list,
+    /// never when zero.
+    pub stale_cache_hours: usize,
+    /// Page size selected for topics without cache settings yet.
+    pub default_cache_page_size: u16,
+    /// IANA timezone name, system local timezone when empty.
+    pub timezone: String,
+    /// Level of the application log, `RUST_LOG` takes precedence when set.
+    pub log_level: String,
+    /// File the log is also written to, no log file when empty.
+    pub log_file: String,
 }
 
 impl Settings {
@@ -66,29 +103,130 @@ impl Settings {
         }
         .to_string()
     }
+    /// Parsed live mode quick fetch presets, invalid ones are skipped.
+    pub fn live_quick_fetch_presets(&self) -> Vec<(KafkaFetch, usize)> {
+        parse_quick_fetch_presets(&self.live_quick_fetch_presets)
+            .into_iter()
+            .filter_map(Result::ok)
+            .collect()
+    }
+    /// Parsed log level, falling back to `TRACE` when it can't be parsed.
+    pub fn log_level(&self) -> Level {
+        Level::from_str(self.log_level.trim()).unwrap_or(Level::TRACE)
+    }
+    pub fn log_file_path(&self) -> Option<PathBuf> {
+        let path = self.log_file.trim();
+        (!path.is_empty()).then(|| PathBuf::from(path))
+    }
+    /// Timezone used to display timestamps, falling back to UTC when it can't be resolved.
+    pub fn timezone(&self) -> Tz {
+        let name = if self.timezone.trim().is_empty() {
+            system_timezone_name()
+        } else {
+            Some(self.timezone.trim().to_string())
+        };
+        name.and_then(|name| {
+            Tz::from_str(name.as_str())
+                .map_err(|e| warn!("invalid timezone {}: {}", name, e))
+                .ok()
+        })
+        .unwrap_or(Tz::UTC)
+    }
 }
 
 impl Default for Settings {
     fn default() -> Self {
         let default_cache_dir = default_cache_path()
-            .ok()
-            .and_then(move |pathbuf| pathbuf.to_str().map(|path_str| path_str.to_string()))
-            .expect("should get default cache path");
+            .map_err(|e| warn!("unable to create default cache path: {}", e))
+            .unwrap_or_else(|_| std::env::temp_dir().join("krust-cache"))
+            .to_string_lossy()
+            .to_string();
         Settings {
             cache_dir: default_cache_dir,
             is_full_timestamp: false,
             messages_sort_column: "Offset".to_string(),
             messages_sort_column_order: MessagesSortOrder::Default.to_string(),
             threads_number: 4,
+            gsk_renderer: "gl".to_string(),
+            cache_workers: 3,
             default_connection_timeout: 5,
+            auto_offset_reset: "earliest".to_string(),
+            session_timeout_ms: 6000,
+            message_timeout_ms: 10000,
+            poll_timeout_ms: 15000,
+            json_highlight_max_bytes: 256 * 1024,
+            value_preview_max_chars: 500,
+            live_max_messages_default: 1000,
+            live_max_messages_max: 100000,
+            live_quick_fetch_presets: "First 100, Last 100, Last 1000".to_string(),
+            stale_cache_hours: 24,
+            default_cache_page_size: 1000,
+            timezone: String::default(),
+            log_level: "trace".to_string(),
+            log_file: String::default(),
         }
     }
 }
 
+/// Formats epoch millis in the given timezone, `None` when the timestamp is missing,
+/// negative (Kafka uses -1 for records without one) or out of range.
+pub fn format_timestamp(
+    timestamp: Option<i64>,
+    timestamp_format: &str,
+    timezone: &Tz,
+) -> Option<String> {
+    timestamp
+        .filter(|ts| *ts >= 0)
+        .and_then(|ts| Utc.timestamp_millis_opt(ts).single())
+        .map(|ts| {
+            ts.with_timezone(timezone)
+                .format(timestamp_format)
+                .to_string()
+        })
+}
+
+/// Parses "First 100, Last 1000" like presets, one result per preset.
+pub fn parse_quick_fetch_presets(presets: &str) -> Vec<Result<(KafkaFetch, usize), String>> {
+    presets
+        .split(',')
+        .map(str::trim)
+        .filter(|preset| !preset.is_empty())
+        .map(|preset| {
+            let (fetch, count) = preset
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| format!("invalid quick fetch preset: {}", preset))?;
+            let fetch = match fetch.to_lowercase().as_str() {
+                "first" => KafkaFetch::Oldest,
+                "last" => KafkaFetch::Newest,
+                _ => return Err(format!("invalid quick fetch preset: {}", preset)),
+            };
+            let count = count
+                .trim()
+                .parse::<usize>()
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or_else(|| format!("invalid quick fetch preset: {}", preset))?;
+            Ok((fetch, count))
+        })
+        .collect()
+}
+
 fn settings_path() -> Result<PathBuf, ExternalError> {
     Ok(ensure_app_config_dir()?.join("settings.json"))
 }
 
+fn system_timezone_name() -> Option<String> {
+    if let Ok(tz) = std::env::var("TZ") {
+        let tz = tz.trim_start_matches(':').trim();
+        if !tz.is_empty() {
+            return Some(tz.to_string());
+        }
+    }
+    iana_time_zone::get_timezone()
+        .map_err(|e| warn!("unable to get system timezone: {}", e))
+        .ok()
+}
+
 fn default_cache_path() -> Result<PathBuf, ExternalError> {
     Ok(ensure_app_config_dir()?.join("cache"))
 }
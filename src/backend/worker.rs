@@ -2,7 +2,13 @@
 // this source code is governed by the GPL-3.0 license that can be
 // found in the COPYING file.
 
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
 use chrono::Utc;
+use once_cell::sync::Lazy;
 use tokio::select;
 use tracing::*;
 
@@ -12,14 +18,14 @@ use crate::{
         task_manager::{Task, TaskManagerMsg, TASK_MANAGER_BROKER},
     },
     config::ExternalError,
-    Repository,
+    Repository, Settings,
 };
 
 use super::{
     kafka::{CacheMessagesRequest, KafkaBackend, KafkaFetch},
     repository::{
         FetchMode, KrustConnection, KrustMessage, KrustTopic, KrustTopicCache, MessagesRepository,
-        MessagesSearchOrder,
+        MessagesSearchOptions, MessagesSearchOrder, Partition,
     },
 };
 
@@ -42,9 +48,12 @@ pub struct MessagesRequest {
     pub page: usize,
     pub search_order: Option<MessagesSearchOrder>,
     pub search: Option<String>,
+    pub search_options: MessagesSearchOptions,
     pub fetch: KafkaFetch,
     pub max_messages: i64,
     pub cache: Option<KrustTopicCache>,
+    /// Live mode only: next offset per partition to continue a paused stream from.
+    pub resume_offsets: Option<HashMap<i32, i64>>,
 }
 
 #[derive(Debug, Clone)]
@@ -68,6 +77,46 @@ pub struct MessagesTotalCounterRequest {
     pub topic: KrustTopic,
 }
 
+/// Message totals of a topic. The offset based one also counts compacted records and
+/// transaction markers, so it's usually above what can actually be consumed.
+#[derive(Debug, Default)]
+pub struct MessagesTotal {
+    pub offset_based: usize,
+    /// Records stored in the topic cache, if the topic is cached.
+    pub cached: Option<usize>,
+    /// Low and high watermarks of every partition.
+    pub partitions: Vec<Partition>,
+}
+
+/// Caches being written, by connection id and topic name, shared by the messages tabs and the
+/// background refresh so only one of them writes to a cache at a time.
+static CACHES_IN_FLIGHT: Lazy<Mutex<HashSet<(usize, String)>>> =
+    Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Marks a cache as being written until dropped.
+#[derive(Debug)]
+pub struct CacheWriteGuard {
+    key: (usize, String),
+}
+
+impl CacheWriteGuard {
+    /// `None` when the cache is already being written.
+    pub fn acquire(connection_id: usize, topic_name: &str) -> Option<Self> {
+        let key = (connection_id, topic_name.to_string());
+        CACHES_IN_FLIGHT
+            .lock()
+            .unwrap()
+            .insert(key.clone())
+            .then_some(Self { key })
+    }
+}
+
+impl Drop for CacheWriteGuard {
+    fn drop(&mut self) {
+        CACHES_IN_FLIGHT.lock().unwrap().remove(&self.key);
+    }
+}
+
 pub struct MessagesWorker;
 
 impl MessagesWorker {
@@ -75,6 +124,83 @@ impl MessagesWorker {
         MessagesWorker {}
     }
 
+    /// Finds caches, on disk or in the database, whose connection was removed or whose topic
+    /// no longer exists on the broker. Connections whose brokers can't be reached are skipped,
+    /// but failing to read the caches or the connections is an error.
+    pub async fn find_orphan_caches(self) -> Result<Vec<(usize, String)>, ExternalError> {
+        let mut repo = Repository::new();
+        let mut caches: HashSet<(usize, String)> = repo.list_topic_caches()?.into_iter().collect();
+        let cache_dir = Settings::read().unwrap_or_default().cache_dir;
+        if let Ok(entries) = fs::read_dir(Path::new(&cache_dir)) {
+            for entry in entries.flatten() {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if MessagesRepository::is_cache_file(&file_name) {
+                    let mrepo = MessagesRepository::from_filename(file_name);
+                    caches.insert((mrepo.connection_id, mrepo.topic_name));
+                }
+            }
+        }
+        let connections = repo.list_all_connections()?;
+        let mut topics_by_connection: HashMap<usize, Option<HashSet<String>>> = HashMap::new();
+        let mut orphans = vec![];
+        for (connection_id, topic_name) in caches {
+            let Some(connection) = connections.iter().find(|c| c.id == Some(connection_id)) else {
+                orphans.push((connection_id, topic_name));
+                continue;
+            };
+            if !topics_by_connection.contains_key(&connection_id) {
+                let topics = KafkaBackend::new(connection)
+                    .list_topics()
+                    .await
+                    .map(|topics| topics.into_iter().map(|t| t.name).collect())
+                    .map_err(|e| warn!("unable to list topics of {}: {:?}", connection.name, e))
+                    .ok();
+                topics_by_connection.insert(connection_id, topics);
+            }
+            if let Some(Some(topics)) = topics_by_connection.get(&connection_id) {
+                if !topics.contains(&topic_name) {
+                    orphans.push((connection_id, topic_name));
+                }
+            }
+        }
+        orphans.sort();
+        Ok(orphans)
+    }
+
+    /// Fetches new messages into an existing topic cache, as a manual refresh does.
+    pub async fn refresh_cache(
+        self,
+        connection: &KrustConnection,
+        cache: KrustTopicCache,
+        task: Task,
+    ) -> Result<(), ExternalError> {
+        let mut mrepo = MessagesRepository::new(cache.connection_id, &cache.topic_name);
+        mrepo.init()?;
+        let cache = KrustTopicCache {
+            last_updated: Some(Utc::now().timestamp_millis()),
+            ..cache
+        };
+        let cache_request = CacheMessagesRequest {
+            cache_settings: cache.clone(),
+            task,
+            messages_repository: &mrepo,
+            refresh: true,
+        };
+        KafkaBackend::new(connection)
+            .cache_messages(&cache_request)
+            .await?;
+        Repository::new().save_topic_cache(
+            cache.connection_id,
+            cache.topic_name.clone(),
+            &cache,
+        )?;
+        MESSAGES_PAGE_BROKER.send(MessagesPageMsg::RefreshTopicTab {
+            connection_id: cache.connection_id,
+            topic_name: cache.topic_name,
+        });
+        Ok(())
+    }
+
     pub fn cleanup_messages(self, request: &MessagesCleanupRequest) -> Option<KrustTopic> {
         let mut repo = Repository::new();
         let conn_id = request.connection_id;
@@ -111,14 +237,28 @@ impl MessagesWorker {
             }
         }
     }
-    pub async fn count_messages(self, request: &MessagesTotalCounterRequest) -> Option<usize> {
+    pub async fn count_messages(self, request: &MessagesTotalCounterRequest) -> MessagesTotal {
         let kafka = KafkaBackend::new(&request.connection);
         let mtopic = kafka
             .topic_message_count(&request.topic.name, Some(KafkaFetch::Oldest), None, None)
             .await;
 
-        let total = mtopic.total.unwrap_or_default();
-        Some(total)
+        let offset_based = mtopic.total.unwrap_or_default();
+        if let Some(conn_id) = request.connection.id {
+            record_partition_offsets(conn_id, &request.topic.name, &mtopic.partitions);
+        }
+        let cached = match (&request.topic.cached, request.connection.id) {
+            (Some(_), Some(conn_id)) => MessagesRepository::new(conn_id, &request.topic.name)
+                .count_messages(None, &MessagesSearchOptions::default())
+                .map_err(|e| warn!("unable to count cached messages: {:?}", e))
+                .ok(),
+            _ => None,
+        };
+        MessagesTotal {
+            offset_based,
+            cached,
+            partitions: mtopic.partitions,
+        }
     }
     pub async fn get_messages(
         self,
@@ -185,8 +325,10 @@ impl MessagesWorker {
                 topic_name: cached.topic_name,
                 fetch_mode: cached.fetch_mode,
                 fetch_value: cached.fetch_value,
+                fetch_value_end: cached.fetch_value_end,
                 default_page_size: cached.default_page_size,
                 last_updated: cached_ts,
+                auto_refresh_minutes: cached.auto_refresh_minutes,
             }
         } else {
             KrustTopicCache {
@@ -194,8 +336,10 @@ impl MessagesWorker {
                 topic_name: topic.name.clone(),
                 fetch_mode: FetchMode::default(),
                 fetch_value: None,
+                fetch_value_end: None,
                 default_page_size: 0,
                 last_updated: cached_ts,
+                auto_refresh_minutes: None,
             }
         };
         let kafka = KafkaBackend::new(&request.connection);
@@ -209,30 +353,44 @@ impl MessagesWorker {
 
         // Run async background task
         let mut mrepo = MessagesRepository::new(topic.connection_id.unwrap(), &topic.name);
+        let cache_guard = CacheWriteGuard::acquire(topic.connection_id.unwrap(), &topic.name);
         let total = match current_cache {
             Some(_) => {
-                if refresh {
+                // makes sure caches created by older versions get the latest schema
+                mrepo
+                    .init()
+                    .unwrap_or_else(|e| warn!("unable to migrate cache: {:?}", e));
+                if refresh && cache_guard.is_none() {
+                    // the tab is reloaded once the running refresh finishes
+                    info!("cache of topic {} is already being refreshed", topic_name);
+                } else if refresh {
                     let cache_request = CacheMessagesRequest {
                         cache_settings: cached.clone(),
                         task: task.clone(),
                         messages_repository: &mrepo,
                         refresh: true,
                     };
-                    kafka.cache_messages(&cache_request).await.unwrap();
+                    kafka.cache_messages(&cache_request).await?;
                     info!("cache refreshed");
                 }
                 mrepo
-                    .count_messages(request.search.clone())
+                    .count_messages(request.search.clone(), &request.search_options)
                     .unwrap_or_default()
             }
             None => {
+                if cache_guard.is_none() {
+                    return Err(ExternalError::DisplayError(
+                        "caching topic".to_string(),
+                        format!("topic {} is already being cached", topic_name),
+                    ));
+                }
                 let mtopic = kafka
                     .topic_message_count(&topic.name, Some(KafkaFetch::Oldest), None, None)
                     .await;
 
                 let total = mtopic.total.unwrap_or_default();
 
-                mrepo.init().unwrap();
+                mrepo.init()?;
                 if total > 0 {
                     let cache_request = CacheMessagesRequest {
                         cache_settings: cached.clone(),
@@ -240,13 +398,14 @@ impl MessagesWorker {
                         messages_repository: &mrepo,
                         refresh: false,
                     };
-                    kafka.cache_messages(&cache_request).await.unwrap();
+                    kafka.cache_messages(&cache_request).await?;
                 }
                 mrepo
-                    .count_messages(request.search.clone())
+                    .count_messages(request.search.clone(), &request.search_options)
                     .unwrap_or_default()
             }
         };
+        drop(cache_guard);
 
         let save_cache_result =
             repo.save_topic_cache(topic.connection_id.unwrap(), topic.name.clone(), &cached);
@@ -267,6 +426,7 @@ impl MessagesWorker {
                 request.page_size,
                 request.search_order.clone(),
                 request.search.clone(),
+                &request.search_options,
             )
             .unwrap();
         TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), 1.0));
@@ -295,6 +455,7 @@ impl MessagesWorker {
                 topic,
                 Some(request.fetch.clone()),
                 Some(request.max_messages),
+                request.resume_offsets.as_ref(),
             )
             .await?;
         if messages.is_empty() {
@@ -310,3 +471,18 @@ impl MessagesWorker {
         })
     }
 }
+
+/// Stores the last-seen offsets of a topic's partitions for offline inspection and drops the
+/// bookmarks of offsets the broker no longer retains.
+pub fn record_partition_offsets(conn_id: usize, topic_name: &str, partitions: &[Partition]) {
+    let mut repo = Repository::new();
+    if let Err(e) = repo.save_partitions(conn_id, topic_name, partitions) {
+        warn!("unable to save partitions of topic {}: {:?}", topic_name, e);
+    }
+    if let Err(e) = repo.delete_expired_bookmarks(conn_id, topic_name, partitions) {
+        warn!(
+            "unable to delete expired bookmarks of topic {}: {:?}",
+            topic_name, e
+        );
+    }
+}
@@ -4,7 +4,7 @@
 
 //! Application entrypoint.
 
-use std::{collections::HashMap, time::Duration};
+use std::{collections::HashMap, sync::atomic::Ordering, time::Duration};
 
 use adw::{prelude::*, Toast};
 use gtk::glib;
@@ -20,9 +20,13 @@ use relm4_components::alert::{Alert, AlertMsg, AlertResponse, AlertSettings};
 use tracing::*;
 
 use crate::{
-    backend::repository::{KrustConnection, KrustTopic, Repository},
+    backend::{
+        kafka::KafkaBackend,
+        repository::{KrustConnection, KrustTopic, Repository},
+    },
     component::{
         cache_manager_dialog::{CacheManagerDialogInit, CacheManagerDialogModel},
+        cache_scheduler::CacheSchedulerModel,
         connection_list::{KrustConnectionMsg, KrustConnectionOutput},
         connection_page::{ConnectionPageModel, ConnectionPageMsg, ConnectionPageOutput},
         settings_dialog::{SettingsDialogInit, SettingsDialogMsg},
@@ -30,7 +34,7 @@ use crate::{
         task_manager::{TaskManagerModel, TASK_MANAGER_BROKER},
         topics::topics_page::{TopicsPageMsg, TopicsPageOutput},
     },
-    config::State,
+    config::{OpenTab, State},
     modals::about::AboutDialog,
     APP_ID, APP_NAME, APP_RESOURCE_PATH,
 };
@@ -39,6 +43,7 @@ use super::{
     cache_manager_dialog::CacheManagerDialogMsg,
     connection_list::ConnectionListModel,
     messages::messages_page::{MessagesPageModel, MessagesPageMsg, MESSAGES_PAGE_BROKER},
+    messages::messages_tab::MESSAGES_SEPARATOR_POSITION,
     settings_dialog::SettingsDialogModel,
     topics::topics_page::TopicsPageModel,
 };
@@ -67,6 +72,7 @@ pub enum AppMsg {
 #[derive(Debug)]
 pub enum AppCommand {
     LateHide(String),
+    RestoreTabs(Vec<(KrustConnection, KrustTopic)>),
 }
 
 pub struct AppModel {
@@ -74,6 +80,7 @@ pub struct AppModel {
     toasts: HashMap<String, Toast>,
     _status_bar: Controller<StatusBarModel>,
     _task_manager: Controller<TaskManagerModel>,
+    _cache_scheduler: Controller<CacheSchedulerModel>,
     close_dialog: Controller<Alert>,
     _about_dialog: Controller<AboutDialog>,
     connections: FactoryVecDeque<ConnectionListModel>,
@@ -254,6 +261,9 @@ impl Component for AppModel {
             .launch_with_broker((), &TASK_MANAGER_BROKER)
             .detach();
 
+        let cache_scheduler: Controller<CacheSchedulerModel> =
+            CacheSchedulerModel::builder().launch(()).detach();
+
         let connections = FactoryVecDeque::builder()
             .launch(gtk::ListBox::default())
             .forward(sender.input_sender(), |output| match output {
@@ -343,11 +353,18 @@ impl Component for AppModel {
             }
             Err(e) => error!("error loading connections: {:?}", e),
         }
+        let restore_tabs = tabs_to_restore(&mut repo, &state);
+        if !restore_tabs.is_empty() {
+            sender.oneshot_command(async move {
+                AppCommand::RestoreTabs(existing_topic_tabs(restore_tabs).await)
+            });
+        }
         let model = AppModel {
             toaster,
             toasts: HashMap::new(),
             _status_bar: status_bar,
             _task_manager: task_manager,
+            _cache_scheduler: cache_scheduler,
             close_dialog: Alert::builder()
                 .transient_for(&root)
                 .launch(AlertSettings {
@@ -470,6 +487,19 @@ impl Component for AppModel {
                                 conn_to_update.sasl_password = new_conn.sasl_password;
                                 conn_to_update.color = new_conn.color;
                                 conn_to_update.timeout = new_conn.timeout;
+                                conn_to_update.ssl_ca_location = new_conn.ssl_ca_location;
+                                conn_to_update.ssl_certificate_location =
+                                    new_conn.ssl_certificate_location;
+                                conn_to_update.ssl_key_location = new_conn.ssl_key_location;
+                                conn_to_update.extra_config = new_conn.extra_config;
+                                conn_to_update.group_id = new_conn.group_id;
+                                conn_to_update.default_fetch = new_conn.default_fetch;
+                                conn_to_update.schema_registry_url = new_conn.schema_registry_url;
+                                conn_to_update.oauth_token_endpoint = new_conn.oauth_token_endpoint;
+                                conn_to_update.oauth_client_id = new_conn.oauth_client_id;
+                                conn_to_update.oauth_client_secret = new_conn.oauth_client_secret;
+                                conn_to_update.client_id = new_conn.client_id;
+                                conn_to_update.client_rack = new_conn.client_rack;
                             }
                             None => warn!("no connection to update"),
                         };
@@ -561,7 +591,7 @@ impl Component for AppModel {
         &mut self,
         _widgets: &mut Self::Widgets,
         message: Self::CommandOutput,
-        _sender: ComponentSender<Self>,
+        sender: ComponentSender<Self>,
         _root: &Self::Root,
     ) {
         match message {
@@ -571,17 +601,82 @@ impl Component for AppModel {
                     toast.dismiss();
                 }
             }
+            AppCommand::RestoreTabs(tabs) => {
+                info!("restoring {} messages tabs", tabs.len());
+                for (connection, topic) in tabs {
+                    sender.input(AppMsg::ShowMessagesPage(connection, topic));
+                }
+            }
         }
     }
 
     fn shutdown(&mut self, widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
         info!("app::saving window state");
+        let (open_tabs, active_tab) = self.messages_page.model().open_tabs();
         widgets
-            .save_window_size()
+            .save_window_size(open_tabs, active_tab)
             .expect("window state should be saved");
     }
 }
 
+/// Saved tabs whose connection still exists, in opening order. Tabs are pushed to the front, so
+/// they are opened last to first, with the active one at the very end to get it selected.
+fn tabs_to_restore(repo: &mut Repository, state: &State) -> Vec<(KrustConnection, KrustTopic)> {
+    let mut tabs = state.open_tabs.clone();
+    if let Some(active) = &state.active_tab {
+        tabs.retain(|tab| tab != active);
+        tabs.insert(0, active.clone());
+    }
+    tabs.iter()
+        .rev()
+        .filter_map(|tab| {
+            let Some(connection) = repo.connection_by_id(tab.connection_id) else {
+                warn!(
+                    "skipping tab {}, connection {} not found",
+                    tab.topic_name, tab.connection_id
+                );
+                return None;
+            };
+            let topic = repo
+                .find_topic(tab.connection_id, &tab.topic_name)
+                .unwrap_or_else(|| KrustTopic {
+                    connection_id: Some(tab.connection_id),
+                    name: tab.topic_name.clone(),
+                    ..Default::default()
+                });
+            Some((connection, topic))
+        })
+        .collect()
+}
+
+/// Drops the tabs whose topic no longer exists, or whose cluster can't be reached.
+async fn existing_topic_tabs(
+    tabs: Vec<(KrustConnection, KrustTopic)>,
+) -> Vec<(KrustConnection, KrustTopic)> {
+    let mut topics_by_connection: HashMap<usize, Option<Vec<String>>> = HashMap::new();
+    let mut existing = Vec::with_capacity(tabs.len());
+    for (connection, topic) in tabs {
+        let conn_id = connection.id.unwrap_or_default();
+        if !topics_by_connection.contains_key(&conn_id) {
+            let names = KafkaBackend::new(&connection)
+                .list_topics()
+                .await
+                .map(|topics| topics.into_iter().map(|t| t.name).collect())
+                .map_err(|e| warn!("unable to list topics for {}: {:?}", connection.name, e))
+                .ok();
+            topics_by_connection.insert(conn_id, names);
+        }
+        match topics_by_connection.get(&conn_id) {
+            Some(Some(names)) if names.contains(&topic.name) => existing.push((connection, topic)),
+            _ => warn!(
+                "skipping tab {}, topic not found on {}",
+                topic.name, connection.name
+            ),
+        }
+    }
+    existing
+}
+
 fn convert_alert_response(response: AlertResponse) -> AppMsg {
     match response {
         AlertResponse::Confirm => AppMsg::Close,
@@ -591,20 +686,33 @@ fn convert_alert_response(response: AlertResponse) -> AppMsg {
 }
 
 impl AppModelWidgets {
-    fn save_window_size(&self) -> Result<(), glib::BoolError> {
+    fn save_window_size(
+        &self,
+        open_tabs: Vec<OpenTab>,
+        active_tab: Option<OpenTab>,
+    ) -> Result<(), glib::BoolError> {
         let (width, height) = self.main_window.default_size();
         let is_maximized = self.main_window.is_maximized();
-        // let separator = if self.main_paned.position() < 405 {
-        //     405
-        // } else {
-        //     self.main_paned.position()
-        // };
-        let new_state = State {
-            width,
-            height,
-            separator_position: 300,
-            is_maximized,
-        };
+        let mut new_state = State::read().unwrap_or_default();
+        new_state.width = width;
+        new_state.height = height;
+        new_state.is_maximized = is_maximized;
+        let window_width = self.main_window.width();
+        let sidebar_width = self
+            .main_paned
+            .sidebar()
+            .map(|sidebar| sidebar.width())
+            .unwrap_or_default();
+        if window_width > 0 && sidebar_width > 0 && !self.main_paned.is_collapsed() {
+            // relative to the default window width, which is the one restored at startup
+            new_state.separator_position = sidebar_width * width / window_width;
+        }
+        let messages_separator = MESSAGES_SEPARATOR_POSITION.load(Ordering::Relaxed);
+        if messages_separator > 0 {
+            new_state.messages_separator_position = Some(messages_separator);
+        }
+        new_state.open_tabs = open_tabs;
+        new_state.active_tab = active_tab;
 
         if let Err(e) = new_state.write() {
             warn!("unable to write application state: {}", e);
@@ -639,11 +747,17 @@ impl AppModelWidgets {
             .unwrap_or_default();
         let width = &state.width;
         let height = &state.height;
-        let _paned_position = &state.separator_position;
+        let paned_position = &state.separator_position;
         let is_maximized = &state.is_maximized;
 
         self.main_window.set_default_size(*width, *height);
-        //self.main_paned.set_position(*paned_position);
+        if *paned_position > 0 && *width > 0 {
+            self.main_paned
+                .set_sidebar_width_fraction(*paned_position as f64 / *width as f64);
+        }
+        if let Some(position) = state.messages_separator_position {
+            MESSAGES_SEPARATOR_POSITION.store(position, Ordering::Relaxed);
+        }
 
         if *is_maximized {
             info!("should maximize");
@@ -6,7 +6,7 @@ use adw::prelude::*;
 use fs_extra::dir::get_size;
 use gtk::{glib::SignalHandlerId, ColumnViewColumn};
 use humansize::{format_size, DECIMAL};
-use std::{cell::RefCell, cmp::Ordering, fs, path::Path};
+use std::{cell::RefCell, cmp::Ordering, fs, io, path::Path};
 use sysinfo::Disks;
 
 use relm4::{
@@ -19,14 +19,19 @@ use relm4::{
 };
 
 use tracing::*;
+use uuid::Uuid;
 
 use crate::{
     backend::{
         repository::MessagesRepository,
         settings::Settings,
-        worker::{MessagesCleanupRequest, MessagesWorker},
+        worker::{CacheWriteGuard, MessagesCleanupRequest, MessagesWorker},
     },
-    Repository,
+    component::messages::messages_page::{MessagesPageMsg, MESSAGES_PAGE_BROKER},
+    component::task_manager::{Task, TaskManagerMsg, TaskVariant, TASK_MANAGER_BROKER},
+    config::ExternalError,
+    modals::utils::{build_confirmation_alert, show_error_alert},
+    AppMsg, Repository, TOASTER_BROKER,
 };
 
 // Table: start
@@ -37,6 +42,8 @@ pub struct TopicListItem {
     connection_id: usize,
     cache_folder_size: usize,
     topic_size: usize,
+    /// Cache of a removed connection or of a topic no longer on the broker.
+    orphan: bool,
     sender: ComponentSender<CacheManagerDialogModel>,
     clicked_handler_id: RefCell<Option<SignalHandlerId>>,
 }
@@ -48,6 +55,7 @@ impl TopicListItem {
         conn_id: usize,
         cache_folder_size: usize,
         topic_size: usize,
+        orphan: bool,
         sender: ComponentSender<CacheManagerDialogModel>,
     ) -> Self {
         Self {
@@ -56,6 +64,7 @@ impl TopicListItem {
             connection_id: conn_id,
             cache_folder_size,
             topic_size,
+            orphan,
             sender,
             clicked_handler_id: RefCell::new(None),
         }
@@ -151,6 +160,42 @@ impl RelmColumn for ConnectionColumn {
     }
 }
 
+struct StatusColumn;
+
+impl RelmColumn for StatusColumn {
+    type Item = TopicListItem;
+    type Root = gtk::Label;
+    type Widgets = ();
+
+    const COLUMN_NAME: &'static str = "Status";
+    const ENABLE_RESIZE: bool = true;
+    const ENABLE_EXPAND: bool = false;
+    fn setup(_item: &gtk::ListItem) -> (Self::Root, Self::Widgets) {
+        let label = gtk::Label::new(None);
+        label.set_halign(gtk::Align::Start);
+        label.add_css_class("warning");
+        (label, ())
+    }
+
+    fn bind(item: &mut Self::Item, _: &mut Self::Widgets, label: &mut Self::Root) {
+        if item.orphan {
+            label.set_label("Orphaned");
+            label.set_tooltip_text(Some(
+                "The connection was removed or the topic no longer exists",
+            ));
+        } else {
+            label.set_label("");
+            label.set_tooltip_text(None);
+        }
+    }
+
+    fn sort_fn() -> OrdFn<Self::Item> {
+        Some(Box::new(|a: &TopicListItem, b: &TopicListItem| {
+            a.orphan.cmp(&b.orphan)
+        }))
+    }
+}
+
 struct DiskUsageColumnWidgets {
     bar: gtk::LevelBar,
     bar_text: gtk::Label,
@@ -259,7 +304,11 @@ impl RelmColumn for DiskUsageColumn {
 #[derive(Debug)]
 pub struct CacheManagerDialogModel {
     cache_dir: String,
-    pub topics_wrapper: TypedColumnView<TopicListItem, gtk::SingleSelection>,
+    pub topics_wrapper: TypedColumnView<TopicListItem, gtk::MultiSelection>,
+    deleting: bool,
+    finding_orphans: bool,
+    /// Connection id and topic name of the caches found orphaned.
+    orphans: Vec<(usize, String)>,
 }
 
 #[derive(Debug)]
@@ -270,13 +319,25 @@ pub enum CacheManagerDialogMsg {
         topic_name: String,
     },
     Refresh,
+    DeleteSelectedCaches,
+    DeleteAllCaches,
+    ConfirmDeleteCaches(Vec<(usize, String)>),
+    FindOrphans,
+    RemoveOrphans,
+}
+
+#[derive(Debug)]
+pub enum CacheManagerCommandOutput {
+    /// Caches deleted by the task and the ones that could not be deleted.
+    CachesDeleted(Task, usize, Vec<String>),
+    OrphansFound(Result<Vec<(usize, String)>, ExternalError>),
 }
 
 pub struct CacheManagerDialogInit {}
 
 #[relm4::component(pub)]
 impl Component for CacheManagerDialogModel {
-    type CommandOutput = ();
+    type CommandOutput = CacheManagerCommandOutput;
     type Input = CacheManagerDialogMsg;
     type Output = ();
     type Init = CacheManagerDialogInit;
@@ -290,6 +351,53 @@ impl Component for CacheManagerDialogModel {
                 adw::HeaderBar {
                     pack_end = &gtk::Box {
                         set_orientation: gtk::Orientation::Horizontal,
+                        gtk::Button {
+                            set_tooltip_text: Some("Find caches of removed connections or deleted topics"),
+                            set_icon_name: "edit-find-symbolic",
+                            set_margin_end: 5,
+                            add_css_class: "circular",
+                            #[watch]
+                            set_sensitive: !model.finding_orphans && !model.deleting,
+                            connect_clicked[sender] => move |_| {
+                                sender.input(CacheManagerDialogMsg::FindOrphans);
+                            },
+                        },
+                        gtk::Button {
+                            set_tooltip_text: Some("Remove orphaned caches"),
+                            set_icon_name: "edit-clear-all-symbolic",
+                            set_margin_end: 5,
+                            add_css_class: "circular",
+                            #[watch]
+                            set_visible: !model.orphans.is_empty(),
+                            #[watch]
+                            set_sensitive: !model.deleting,
+                            connect_clicked[sender] => move |_| {
+                                sender.input(CacheManagerDialogMsg::RemoveOrphans);
+                            },
+                        },
+                        gtk::Button {
+                            set_tooltip_text: Some("Delete selected caches"),
+                            set_icon_name: "edit-delete-symbolic",
+                            set_margin_end: 5,
+                            add_css_class: "circular",
+                            #[watch]
+                            set_sensitive: !model.deleting,
+                            connect_clicked[sender] => move |_| {
+                                sender.input(CacheManagerDialogMsg::DeleteSelectedCaches);
+                            },
+                        },
+                        gtk::Button {
+                            set_tooltip_text: Some("Delete all caches"),
+                            set_icon_name: "user-trash-full-symbolic",
+                            set_margin_end: 5,
+                            add_css_class: "circular",
+                            add_css_class: "krust-destroy",
+                            #[watch]
+                            set_sensitive: !model.deleting,
+                            connect_clicked[sender] => move |_| {
+                                sender.input(CacheManagerDialogMsg::DeleteAllCaches);
+                            },
+                        },
                         gtk::Button {
                             set_tooltip_text: Some("Refresh disk/cache usage"),
                             set_icon_name: "media-playlist-repeat-symbolic",
@@ -347,6 +455,7 @@ impl Component for CacheManagerDialogModel {
                             gtk::Label {},
                         },
                     },
+                    #[name(topics_group)]
                     adw::PreferencesGroup {
                         set_title: "Topics",
                         #[name(topics_scrolled_windows)]
@@ -374,10 +483,11 @@ impl Component for CacheManagerDialogModel {
     ) -> ComponentParts<Self> {
         let settings = Settings::read().unwrap_or_default();
         // Initialize the ListView wrapper
-        let mut view_wrapper = TypedColumnView::<TopicListItem, gtk::SingleSelection>::new();
+        let mut view_wrapper = TypedColumnView::<TopicListItem, gtk::MultiSelection>::new();
         view_wrapper.append_column::<DiskUsageColumn>();
         view_wrapper.append_column::<ConnectionColumn>();
         view_wrapper.append_column::<TopicColumn>();
+        view_wrapper.append_column::<StatusColumn>();
         let sort_column: Option<&ColumnViewColumn> =
             view_wrapper.get_columns().get(DiskUsageColumn::COLUMN_NAME);
         let sort_type = gtk::SortType::Descending;
@@ -391,6 +501,9 @@ impl Component for CacheManagerDialogModel {
         let model = CacheManagerDialogModel {
             cache_dir: settings.cache_dir,
             topics_wrapper: view_wrapper,
+            deleting: false,
+            finding_orphans: false,
+            orphans: vec![],
         };
         let widgets = view_output!();
 
@@ -420,9 +533,13 @@ impl Component for CacheManagerDialogModel {
                 let cache_dir_path = Path::new(&self.cache_dir);
                 let cache_dir_size = get_size(cache_dir_path).unwrap_or(0) as usize;
                 let paths = fs::read_dir(cache_dir_path).unwrap();
+                let mut caches_size = 0;
                 for path in paths {
                     let file = path.unwrap();
                     let file_name = file.file_name();
+                    if !MessagesRepository::is_cache_file(file_name.to_str().unwrap_or_default()) {
+                        continue;
+                    }
                     let cache_size = get_size(file.path()).unwrap_or(0) as usize;
                     let cache_size_formatted =
                         format_size(get_size(file.path()).unwrap_or(0), DECIMAL);
@@ -430,23 +547,108 @@ impl Component for CacheManagerDialogModel {
                         file_name.to_str().unwrap_or_default().to_string(),
                     );
                     let conn = Repository::new().connection_by_id(repo.connection_id);
-                    if let Some(conn) = conn {
-                        let item = TopicListItem::new(
-                            repo.topic_name,
-                            conn.name,
-                            conn.id.unwrap_or_default(),
-                            cache_dir_size,
-                            cache_size,
-                            sender.clone(),
-                        );
-                        self.topics_wrapper.append(item);
-                        info!(
-                            "Name: {} - {}",
-                            file_name.to_str().unwrap_or_default(),
-                            cache_size_formatted,
-                        );
-                    }
+                    let orphan = conn.is_none()
+                        || self.orphans.iter().any(|(id, topic)| {
+                            *id == repo.connection_id && *topic == repo.topic_name
+                        });
+                    caches_size += cache_size;
+                    let item = TopicListItem::new(
+                        repo.topic_name,
+                        conn.map(|c| c.name)
+                            .unwrap_or_else(|| "(removed connection)".to_string()),
+                        repo.connection_id,
+                        cache_dir_size,
+                        cache_size,
+                        orphan,
+                        sender.clone(),
+                    );
+                    self.topics_wrapper.append(item);
+                    info!(
+                        "Name: {} - {}",
+                        file_name.to_str().unwrap_or_default(),
+                        cache_size_formatted,
+                    );
+                }
+                let mut description = format!(
+                    "{} caches, {} total",
+                    self.topics_wrapper.len(),
+                    format_size(caches_size, DECIMAL)
+                );
+                if !self.orphans.is_empty() {
+                    description.push_str(&format!(", {} orphaned", self.orphans.len()));
                 }
+                widgets
+                    .topics_group
+                    .set_description(Some(description.as_str()));
+            }
+            CacheManagerDialogMsg::DeleteSelectedCaches => {
+                let selection_model = &self.topics_wrapper.selection_model;
+                let caches: Vec<(usize, String)> = (0..selection_model.n_items())
+                    .filter(|i| selection_model.is_selected(*i))
+                    .filter_map(|i| self.topics_wrapper.get_visible(i))
+                    .map(|item| {
+                        let item = item.borrow();
+                        (item.connection_id, item.topic_name.clone())
+                    })
+                    .collect();
+                self.confirm_delete_caches(caches, root, &sender);
+            }
+            CacheManagerDialogMsg::DeleteAllCaches => {
+                let caches: Vec<(usize, String)> = (0..self.topics_wrapper.len())
+                    .filter_map(|i| self.topics_wrapper.get(i))
+                    .map(|item| {
+                        let item = item.borrow();
+                        (item.connection_id, item.topic_name.clone())
+                    })
+                    .collect();
+                self.confirm_delete_caches(caches, root, &sender);
+            }
+            CacheManagerDialogMsg::FindOrphans => {
+                self.finding_orphans = true;
+                sender.oneshot_command(async move {
+                    let orphans = MessagesWorker::new().find_orphan_caches().await;
+                    CacheManagerCommandOutput::OrphansFound(orphans)
+                });
+            }
+            CacheManagerDialogMsg::RemoveOrphans => {
+                self.confirm_delete_caches(self.orphans.clone(), root, &sender);
+            }
+            CacheManagerDialogMsg::ConfirmDeleteCaches(caches) => {
+                self.deleting = true;
+                let task = Task::new(
+                    TaskVariant::ClearCaches,
+                    Some(format!("{} caches", caches.len())),
+                    None,
+                );
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::AddTask(task.clone()));
+                sender.spawn_oneshot_command(move || {
+                    let total = caches.len();
+                    let mut failures = vec![];
+                    for (idx, (connection_id, topic_name)) in caches.into_iter().enumerate() {
+                        // a messages tab or a background refresh may be writing to the cache
+                        let result = match CacheWriteGuard::acquire(connection_id, &topic_name) {
+                            Some(_guard) => delete_cache(connection_id, &topic_name),
+                            None => Err(ExternalError::DisplayError(
+                                "deleting cache".to_string(),
+                                "the cache is being written".to_string(),
+                            )),
+                        };
+                        if let Err(e) = result {
+                            warn!("unable to delete cache of {}: {:?}", &topic_name, e);
+                            failures.push(topic_name.clone());
+                        } else {
+                            MESSAGES_PAGE_BROKER.send(MessagesPageMsg::RefreshTopicTab {
+                                connection_id,
+                                topic_name,
+                            });
+                        }
+                        TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(
+                            task.clone(),
+                            (idx + 1) as f64 / (total + 1) as f64,
+                        ));
+                    }
+                    CacheManagerCommandOutput::CachesDeleted(task, total, failures)
+                });
             }
             CacheManagerDialogMsg::DeleteTopicCache {
                 connection_id,
@@ -472,10 +674,80 @@ impl Component for CacheManagerDialogModel {
                 }
             }
         }
+
+        self.update_view(widgets, sender);
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            CacheManagerCommandOutput::CachesDeleted(task, total, failures) => {
+                self.deleting = false;
+                self.orphans.clear();
+                if failures.is_empty() {
+                    TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), 1.0));
+                    TOASTER_BROKER.send(AppMsg::ShowToast(
+                        task.id.clone(),
+                        format!("{} caches deleted", total),
+                    ));
+                    TOASTER_BROKER.send(AppMsg::HideToast(task.id));
+                } else {
+                    TASK_MANAGER_BROKER.send(TaskManagerMsg::Failed(
+                        task,
+                        format!("Unable to delete caches: {}", failures.join(", ")),
+                    ));
+                }
+                sender.input(CacheManagerDialogMsg::Refresh);
+            }
+            CacheManagerCommandOutput::OrphansFound(Err(e)) => {
+                self.finding_orphans = false;
+                let error_message = format!("Unable to find orphaned caches: {}", e);
+                warn!(error_message);
+                show_error_alert(root, error_message);
+            }
+            CacheManagerCommandOutput::OrphansFound(Ok(orphans)) => {
+                self.finding_orphans = false;
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(
+                    id.clone(),
+                    format!("{} orphaned caches found", orphans.len()),
+                ));
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+                self.orphans = orphans;
+                sender.input(CacheManagerDialogMsg::Refresh);
+            }
+        }
+
+        self.update_view(widgets, sender);
     }
 }
 
 impl CacheManagerDialogModel {
+    fn confirm_delete_caches(
+        &self,
+        caches: Vec<(usize, String)>,
+        root: &adw::Dialog,
+        sender: &ComponentSender<Self>,
+    ) {
+        if caches.is_empty() {
+            return;
+        }
+        let message = format!(
+            "Delete {} topic cache(s)? Cached messages will have to be fetched again.",
+            caches.len()
+        );
+        let confirmation_alert = build_confirmation_alert("Delete".to_string(), message);
+        let snd = sender.clone();
+        confirmation_alert.connect_response(Some("confirm"), move |_, _| {
+            snd.input(CacheManagerDialogMsg::ConfirmDeleteCaches(caches.clone()));
+        });
+        confirmation_alert.present(root);
+    }
     fn load_disk_usage_info(
         &mut self,
         settings: Settings,
@@ -540,3 +812,14 @@ impl CacheManagerDialogModel {
         }
     }
 }
+
+/// Removes the cache file and its database row.
+fn delete_cache(connection_id: usize, topic_name: &str) -> Result<(), ExternalError> {
+    // caches of removed topics may only have the database row left
+    match MessagesRepository::new(connection_id, topic_name).destroy() {
+        Err(ExternalError::FileSystemError(e)) if e.kind() == io::ErrorKind::NotFound => (),
+        result => result?,
+    };
+    Repository::new().delete_topic_cache(connection_id, topic_name.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,136 @@
+// Copyright (c) 2024, Miguel A. Baldi Hörlle <miguel.horlle@gmail.com>. All rights reserved. Use of
+// this source code is governed by the GPL-3.0 license that can be
+// found in the COPYING file.
+
+//! Background refresh of topic caches with an auto refresh interval.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::Utc;
+use relm4::{ComponentSender, Worker};
+use tracing::*;
+
+use crate::backend::worker::{CacheWriteGuard, MessagesWorker};
+use crate::component::task_manager::{Task, TaskManagerMsg, TaskVariant, TASK_MANAGER_BROKER};
+use crate::config::ExternalError;
+use crate::Repository;
+
+/// How often the caches are checked for a due refresh.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Longest wait between retries of a failing refresh, in minutes.
+const MAX_BACKOFF_MINUTES: i64 = 24 * 60;
+
+/// Last failure of a cache refresh.
+struct RefreshFailure {
+    attempts: u32,
+    retry_at: i64,
+    task: Task,
+}
+
+pub struct CacheSchedulerModel {
+    /// Failing refreshes, by connection id and topic name.
+    failures: HashMap<(usize, String), RefreshFailure>,
+}
+
+#[derive(Debug)]
+pub enum CacheSchedulerMsg {
+    Tick,
+    Refreshed((usize, String), i64, Task, Result<(), ExternalError>),
+}
+
+impl Worker for CacheSchedulerModel {
+    type Init = ();
+    type Input = CacheSchedulerMsg;
+    type Output = ();
+
+    fn init(_init: Self::Init, sender: ComponentSender<Self>) -> Self {
+        let tick_sender = sender.clone();
+        gtk::glib::timeout_add_local(CHECK_INTERVAL, move || {
+            tick_sender.input(CacheSchedulerMsg::Tick);
+            gtk::glib::ControlFlow::Continue
+        });
+        Self {
+            failures: HashMap::new(),
+        }
+    }
+
+    fn update(&mut self, msg: CacheSchedulerMsg, sender: ComponentSender<Self>) {
+        match msg {
+            CacheSchedulerMsg::Tick => {
+                let mut repo = Repository::new();
+                let caches = match repo.list_auto_refresh_caches() {
+                    Ok(caches) => caches,
+                    Err(e) => {
+                        warn!("unable to list auto refresh caches: {:?}", e);
+                        return;
+                    }
+                };
+                let now = Utc::now().timestamp_millis();
+                for cache in caches {
+                    let key = (cache.connection_id, cache.topic_name.clone());
+                    let minutes = cache.auto_refresh_minutes.unwrap_or_default();
+                    let due = match self.failures.get(&key) {
+                        Some(failure) => failure.retry_at <= now,
+                        None => cache.last_updated.unwrap_or_default() + minutes * 60_000 <= now,
+                    };
+                    if !due {
+                        continue;
+                    }
+                    let Some(connection) = repo.connection_by_id(cache.connection_id) else {
+                        debug!("skipping cache of removed connection::{:?}", &key);
+                        continue;
+                    };
+                    // a messages tab may be writing to the same cache
+                    let Some(guard) = CacheWriteGuard::acquire(key.0, &key.1) else {
+                        debug!("skipping cache being written::{:?}", &key);
+                        continue;
+                    };
+                    if let Some(failure) = self.failures.get(&key) {
+                        TASK_MANAGER_BROKER.send(TaskManagerMsg::RemoveTask(failure.task.clone()));
+                    }
+                    info!("auto refreshing cache::{:?}", &key);
+                    let task = Task::new(
+                        TaskVariant::FetchMessages,
+                        Some(cache.topic_name.clone()),
+                        None,
+                    );
+                    TASK_MANAGER_BROKER.send(TaskManagerMsg::AddTask(task.clone()));
+                    let sender = sender.clone();
+                    relm4::spawn(async move {
+                        let result = MessagesWorker::new()
+                            .refresh_cache(&connection, cache, task.clone())
+                            .await;
+                        drop(guard);
+                        sender.input(CacheSchedulerMsg::Refreshed(key, minutes, task, result));
+                    });
+                }
+            }
+            CacheSchedulerMsg::Refreshed(key, minutes, task, result) => match result {
+                Ok(_) => {
+                    info!("cache auto refreshed::{:?}", &key);
+                    self.failures.remove(&key);
+                    TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task, 1.0));
+                }
+                Err(e) => {
+                    warn!("unable to auto refresh cache {:?}: {:?}", &key, e);
+                    let attempts = self.failures.get(&key).map_or(0, |f| f.attempts) + 1;
+                    // twice the refresh interval after a failure, doubling with each further one
+                    let backoff_minutes = minutes
+                        .saturating_mul(1 << attempts.min(16))
+                        .min(MAX_BACKOFF_MINUTES);
+                    let retry_at = Utc::now().timestamp_millis() + backoff_minutes * 60_000;
+                    TASK_MANAGER_BROKER.send(TaskManagerMsg::Failed(task.clone(), e.to_string()));
+                    self.failures.insert(
+                        key,
+                        RefreshFailure {
+                            attempts,
+                            retry_at,
+                            task,
+                        },
+                    );
+                }
+            },
+        }
+    }
+}
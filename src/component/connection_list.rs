@@ -10,7 +10,7 @@ use relm4::{
 use tracing::info;
 
 use crate::{
-    backend::repository::{KrustConnection, KrustConnectionSecurityType},
+    backend::repository::{KrustConnection, KrustConnectionSecurityType, SaslMechanism},
     modals::utils::build_confirmation_alert,
     Repository,
 };
@@ -38,11 +38,23 @@ pub struct ConnectionListModel {
     pub name: String,
     pub brokers_list: String,
     pub security_type: KrustConnectionSecurityType,
-    pub sasl_mechanism: Option<String>,
+    pub sasl_mechanism: Option<SaslMechanism>,
     pub sasl_username: Option<String>,
     pub sasl_password: Option<String>,
     pub color: Option<String>,
     pub timeout: Option<usize>,
+    pub ssl_ca_location: Option<String>,
+    pub ssl_certificate_location: Option<String>,
+    pub ssl_key_location: Option<String>,
+    pub extra_config: Option<String>,
+    pub group_id: Option<String>,
+    pub default_fetch: Option<KafkaFetch>,
+    pub schema_registry_url: Option<String>,
+    pub oauth_token_endpoint: Option<String>,
+    pub oauth_client_id: Option<String>,
+    pub oauth_client_secret: Option<String>,
+    pub client_id: Option<String>,
+    pub client_rack: Option<String>,
     pub is_connected: bool,
     pub confirm_delete_alert: AlertDialog,
     pub selected: Option<DynamicIndex>,
@@ -55,11 +67,23 @@ impl From<&mut ConnectionListModel> for KrustConnection {
             name: value.name.clone(),
             brokers_list: value.brokers_list.clone(),
             security_type: value.security_type.clone(),
-            sasl_mechanism: value.sasl_mechanism.clone(),
+            sasl_mechanism: value.sasl_mechanism,
             sasl_username: value.sasl_username.clone(),
             sasl_password: value.sasl_password.clone(),
             color: value.color.clone(),
             timeout: value.timeout,
+            ssl_ca_location: value.ssl_ca_location.clone(),
+            ssl_certificate_location: value.ssl_certificate_location.clone(),
+            ssl_key_location: value.ssl_key_location.clone(),
+            extra_config: value.extra_config.clone(),
+            group_id: value.group_id.clone(),
+            default_fetch: value.default_fetch.clone(),
+            schema_registry_url: value.schema_registry_url.clone(),
+            oauth_token_endpoint: value.oauth_token_endpoint.clone(),
+            oauth_client_id: value.oauth_client_id.clone(),
+            oauth_client_secret: value.oauth_client_secret.clone(),
+            client_id: value.client_id.clone(),
+            client_rack: value.client_rack.clone(),
         }
     }
 }
@@ -141,6 +165,18 @@ impl FactoryComponent for ConnectionListModel {
             sasl_password: conn.sasl_password,
             color: conn.color,
             timeout: conn.timeout,
+            ssl_ca_location: conn.ssl_ca_location,
+            ssl_certificate_location: conn.ssl_certificate_location,
+            ssl_key_location: conn.ssl_key_location,
+            extra_config: conn.extra_config,
+            group_id: conn.group_id,
+            default_fetch: conn.default_fetch,
+            schema_registry_url: conn.schema_registry_url,
+            oauth_token_endpoint: conn.oauth_token_endpoint,
+            oauth_client_id: conn.oauth_client_id,
+            oauth_client_secret: conn.oauth_client_secret,
+            client_id: conn.client_id,
+            client_rack: conn.client_rack,
             is_connected: false,
             confirm_delete_alert,
             selected: None,
@@ -4,16 +4,25 @@
 // found in the COPYING file.
 
 use std::borrow::Borrow;
+use std::path::PathBuf;
 
 use adw::prelude::*;
 use gtk::{gdk, gio, Adjustment};
 use relm4::{factory::DynamicIndex, *};
-use relm4_components::simple_adw_combo_row::{SimpleComboRow, SimpleComboRowMsg};
-use tracing::info;
+use relm4_components::{
+    open_dialog::{OpenDialog, OpenDialogMsg, OpenDialogResponse, OpenDialogSettings},
+    simple_adw_combo_row::{SimpleComboRow, SimpleComboRowMsg},
+};
+use tracing::{info, warn};
+use uuid::Uuid;
 
 use crate::{
-    backend::repository::{KrustConnection, KrustConnectionSecurityType},
-    Repository,
+    backend::{
+        kafka::{KafkaBackend, KafkaFetch},
+        repository::{KrustConnection, KrustConnectionSecurityType, SaslMechanism},
+    },
+    config::ExternalError,
+    AppMsg, Repository, TOASTER_BROKER,
 };
 
 // Color picker dialog
@@ -54,6 +63,13 @@ impl SimpleComponent for ColorPickerDialog {
 
 // Color picker dialog
 
+#[derive(Debug, Clone, Copy)]
+pub enum SslFile {
+    CaLocation,
+    CertificateLocation,
+    KeyLocation,
+}
+
 #[derive(Debug)]
 pub struct ConnectionPageModel {
     pub current_index: Option<DynamicIndex>,
@@ -61,12 +77,28 @@ pub struct ConnectionPageModel {
     name: String,
     brokers_list: String,
     security_type: KrustConnectionSecurityType,
-    sasl_mechanism: String,
+    sasl_mechanism: SaslMechanism,
     sasl_username: String,
     sasl_password: String,
     security_type_combo: Controller<SimpleComboRow<KrustConnectionSecurityType>>,
+    sasl_mechanism_combo: Controller<SimpleComboRow<SaslMechanism>>,
+    default_fetch: KafkaFetch,
+    default_fetch_combo: Controller<SimpleComboRow<KafkaFetch>>,
     color_picker_dialog: Controller<ColorPickerDialog>,
     timeout: Option<f64>,
+    ssl_ca_location: String,
+    ssl_certificate_location: String,
+    ssl_key_location: String,
+    extra_config: String,
+    group_id: String,
+    client_id: String,
+    client_rack: String,
+    schema_registry_url: String,
+    oauth_token_endpoint: String,
+    oauth_client_id: String,
+    oauth_client_secret: String,
+    ssl_file_dialog: Controller<OpenDialog>,
+    selected_ssl_file: Option<SslFile>,
 }
 
 #[derive(Debug)]
@@ -75,7 +107,18 @@ pub enum ConnectionPageMsg {
     Save,
     Edit(DynamicIndex, KrustConnection),
     SecurityTypeChanged(usize),
+    SaslMechanismChanged(usize),
+    DefaultFetchChanged(usize),
+    TestConnection,
+    ChooseSslFile(SslFile),
+    OpenSslFile(PathBuf),
+    Ignore,
 }
+#[derive(Debug)]
+pub enum AsyncCommandOutput {
+    TestConnectionResult(String, Result<(), ExternalError>),
+}
+
 #[derive(Debug)]
 pub enum ConnectionPageOutput {
     Save(Option<DynamicIndex>, KrustConnection),
@@ -83,7 +126,7 @@ pub enum ConnectionPageOutput {
 
 #[relm4::component(pub)]
 impl Component for ConnectionPageModel {
-    type CommandOutput = ();
+    type CommandOutput = AsyncCommandOutput;
 
     type Init = Option<KrustConnection>;
     type Input = ConnectionPageMsg;
@@ -104,17 +147,30 @@ impl Component for ConnectionPageModel {
                     #[name = "brokers_entry" ]
                     adw::EntryRow {
                         set_title: "Brokers",
+                        set_tooltip_text: Some("Comma-separated host:port list"),
                         set_text: model.brokers_list.as_str(),
+                        connect_changed[brokers_error] => move |entry| {
+                            entry.remove_css_class("error");
+                            brokers_error.set_visible(false);
+                        },
+                    },
+                    #[name = "brokers_error" ]
+                    gtk::Label {
+                        set_visible: false,
+                        set_halign: gtk::Align::Start,
+                        set_wrap: true,
+                        set_margin_all: 6,
+                        add_css_class: "error",
                     },
                     model.security_type_combo.widget() -> &adw::ComboRow {
                         set_title: "Security type",
                         set_subtitle: "Select security type",
                         set_use_subtitle: true,
                     },
-                    #[name = "sasl_mechanism_entry" ]
-                    adw::EntryRow {
+                    model.sasl_mechanism_combo.widget() -> &adw::ComboRow {
                         set_title: "SASL mechanism",
-                        set_text: model.sasl_mechanism.as_str(),
+                        set_subtitle: "Select SASL mechanism",
+                        set_use_subtitle: true,
                     },
                     #[name = "sasl_username_entry" ]
                     adw::EntryRow {
@@ -126,6 +182,60 @@ impl Component for ConnectionPageModel {
                         set_title: "SASL password",
                         set_text: model.sasl_password.as_str(),
                     },
+                    #[name = "oauth_token_endpoint_entry" ]
+                    adw::EntryRow {
+                        set_title: "OAuth token endpoint",
+                        set_text: model.oauth_token_endpoint.as_str(),
+                        set_visible: model.oauth_visible(),
+                    },
+                    #[name = "oauth_client_id_entry" ]
+                    adw::EntryRow {
+                        set_title: "OAuth client id",
+                        set_text: model.oauth_client_id.as_str(),
+                        set_visible: model.oauth_visible(),
+                    },
+                    #[name = "oauth_client_secret_entry" ]
+                    adw::PasswordEntryRow {
+                        set_title: "OAuth client secret",
+                        set_text: model.oauth_client_secret.as_str(),
+                        set_visible: model.oauth_visible(),
+                    },
+                    #[name = "ssl_ca_location_entry" ]
+                    adw::EntryRow {
+                        set_title: "SSL CA location",
+                        set_text: model.ssl_ca_location.as_str(),
+                        add_suffix = &gtk::Button {
+                            set_tooltip_text: Some("Select file"),
+                            set_icon_name: "document-open-symbolic",
+                            set_valign: gtk::Align::Center,
+                            add_css_class: "flat",
+                            connect_clicked => ConnectionPageMsg::ChooseSslFile(SslFile::CaLocation),
+                        },
+                    },
+                    #[name = "ssl_certificate_location_entry" ]
+                    adw::EntryRow {
+                        set_title: "SSL certificate location",
+                        set_text: model.ssl_certificate_location.as_str(),
+                        add_suffix = &gtk::Button {
+                            set_tooltip_text: Some("Select file"),
+                            set_icon_name: "document-open-symbolic",
+                            set_valign: gtk::Align::Center,
+                            add_css_class: "flat",
+                            connect_clicked => ConnectionPageMsg::ChooseSslFile(SslFile::CertificateLocation),
+                        },
+                    },
+                    #[name = "ssl_key_location_entry" ]
+                    adw::EntryRow {
+                        set_title: "SSL key location",
+                        set_text: model.ssl_key_location.as_str(),
+                        add_suffix = &gtk::Button {
+                            set_tooltip_text: Some("Select file"),
+                            set_icon_name: "document-open-symbolic",
+                            set_valign: gtk::Align::Center,
+                            add_css_class: "flat",
+                            connect_clicked => ConnectionPageMsg::ChooseSslFile(SslFile::KeyLocation),
+                        },
+                    },
                     #[name = "color_button" ]
                     gtk::ColorDialogButton {
                         set_dialog = model.color_picker_dialog.widget() -> &gtk::ColorDialog {},
@@ -134,6 +244,31 @@ impl Component for ConnectionPageModel {
 
                         },
                      },
+                    #[name = "group_id_entry" ]
+                    adw::EntryRow {
+                        set_title: "Consumer group id (optional)",
+                        set_text: model.group_id.as_str(),
+                    },
+                    #[name = "client_id_entry" ]
+                    adw::EntryRow {
+                        set_title: "Client id (optional)",
+                        set_text: model.client_id.as_str(),
+                    },
+                    #[name = "client_rack_entry" ]
+                    adw::EntryRow {
+                        set_title: "Client rack (optional)",
+                        set_text: model.client_rack.as_str(),
+                    },
+                    #[name = "schema_registry_url_entry" ]
+                    adw::EntryRow {
+                        set_title: "Schema registry URL (optional)",
+                        set_text: model.schema_registry_url.as_str(),
+                    },
+                    model.default_fetch_combo.widget() -> &adw::ComboRow {
+                        set_title: "Default fetch",
+                        set_subtitle: "Messages fetch type when opening topics",
+                        set_use_subtitle: true,
+                    },
                     #[name = "timeout_entry"]
                     adw::SpinRow {
                         set_title: "Timeout",
@@ -147,6 +282,30 @@ impl Component for ConnectionPageModel {
                         set_wrap: false,
                         // set_value: model.timeout.unwrap_or_default(),
                     },
+                },
+                add = &adw::PreferencesGroup {
+                    set_title: "Additional properties",
+                    set_description: Some("librdkafka properties, one key=value per line"),
+                    gtk::ScrolledWindow {
+                        set_hexpand: true,
+                        set_min_content_height: 100,
+                        set_overflow: gtk::Overflow::Hidden,
+                        add_css_class: "entry",
+                        #[name = "extra_config_entry"]
+                        gtk::TextView {
+                            set_top_margin: 5,
+                            set_left_margin: 5,
+                            set_monospace: true,
+                            add_css_class: "message-textview",
+                        },
+                    },
+                    gtk::Button {
+                        set_label: "Test connection",
+                        set_margin_top: 20,
+                        connect_clicked[sender] => move |_btn| {
+                            sender.input(ConnectionPageMsg::TestConnection)
+                        },
+                    },
                     gtk::Button {
                         set_label: "Save",
                         add_css_class: "suggested-action",
@@ -177,9 +336,46 @@ impl Component for ConnectionPageModel {
                 sender.input_sender(),
                 ConnectionPageMsg::SecurityTypeChanged,
             );
+        let sasl_mechanism_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: SaslMechanism::VALUES.to_vec(),
+                active_index: Some(default_idx),
+            })
+            .forward(
+                sender.input_sender(),
+                ConnectionPageMsg::SaslMechanismChanged,
+            );
+        let default_fetch_idx = current_connection
+            .as_ref()
+            .and_then(|c| c.default_fetch.clone())
+            .and_then(|f| KafkaFetch::VALUES.iter().position(|v| *v == f))
+            .unwrap_or_default();
+        let default_fetch_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: KafkaFetch::VALUES.to_vec(),
+                active_index: Some(default_fetch_idx),
+            })
+            .forward(
+                sender.input_sender(),
+                ConnectionPageMsg::DefaultFetchChanged,
+            );
         //let security_type_combo = security_type.widget();
         let current = current_connection.clone();
         let color_picker_dialog = ColorPickerDialog::builder().launch(()).detach();
+        let ssl_file_dialog = OpenDialog::builder()
+            .transient_for_native(&root)
+            .launch(OpenDialogSettings {
+                folder_mode: false,
+                accept_label: String::from("Select"),
+                cancel_label: String::from("Cancel"),
+                create_folders: false,
+                is_modal: true,
+                filters: Vec::new(),
+            })
+            .forward(sender.input_sender(), |response| match response {
+                OpenDialogResponse::Accept(path) => ConnectionPageMsg::OpenSslFile(path),
+                OpenDialogResponse::Cancel => ConnectionPageMsg::Ignore,
+            });
 
         let model = ConnectionPageModel {
             current_index: None,
@@ -199,10 +395,13 @@ impl Component for ConnectionPageModel {
                 .map(|c| c.security_type.clone())
                 .unwrap_or_default(),
             security_type_combo: security_type,
+            sasl_mechanism_combo,
+            default_fetch: KafkaFetch::VALUES[default_fetch_idx].clone(),
+            default_fetch_combo,
             sasl_mechanism: current
                 .borrow()
                 .as_ref()
-                .map(|c| c.sasl_mechanism.clone().unwrap_or_default())
+                .map(|c| c.sasl_mechanism.unwrap_or_default())
                 .unwrap_or_default(),
             sasl_username: current
                 .borrow()
@@ -221,6 +420,63 @@ impl Component for ConnectionPageModel {
                 .as_ref()
                 .map(|c| c.timeout.map(|t| t as f64))
                 .unwrap_or_default(),
+            ssl_ca_location: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.ssl_ca_location.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            ssl_certificate_location: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.ssl_certificate_location.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            ssl_key_location: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.ssl_key_location.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            extra_config: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.extra_config.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            group_id: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.group_id.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            client_id: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.client_id.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            client_rack: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.client_rack.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            schema_registry_url: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.schema_registry_url.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            oauth_token_endpoint: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.oauth_token_endpoint.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            oauth_client_id: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.oauth_client_id.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            oauth_client_secret: current
+                .borrow()
+                .as_ref()
+                .map(|c| c.oauth_client_secret.clone().unwrap_or_default())
+                .unwrap_or_default(),
+            ssl_file_dialog,
+            selected_ssl_file: None,
         };
         //let security_type_combo = model.security_type_combo.widget();
         let widgets = view_output!();
@@ -233,6 +489,10 @@ impl Component for ConnectionPageModel {
             .value(model.timeout.unwrap_or_default())
             .build();
         widgets.timeout_entry.set_adjustment(Some(&adjustment));
+        widgets
+            .extra_config_entry
+            .buffer()
+            .set_text(&model.extra_config);
         ComponentParts { model, widgets }
     }
 
@@ -252,32 +512,125 @@ impl Component for ConnectionPageModel {
                     None => KrustConnectionSecurityType::default(),
                 };
                 self.security_type = sec_type;
-                let sasl_visible = match &self.security_type {
-                    KrustConnectionSecurityType::PLAINTEXT => false,
-                    KrustConnectionSecurityType::SASL_PLAINTEXT => true,
+                let (sasl_visible, ssl_visible) = match &self.security_type {
+                    KrustConnectionSecurityType::PLAINTEXT => (false, false),
+                    KrustConnectionSecurityType::SASL_PLAINTEXT => (true, false),
+                    KrustConnectionSecurityType::SASL_SSL => (true, true),
+                    KrustConnectionSecurityType::SSL => (false, true),
                 };
-                widgets.sasl_mechanism_entry.set_visible(sasl_visible);
+                self.sasl_mechanism_combo.widget().set_visible(sasl_visible);
                 widgets.sasl_username_entry.set_visible(sasl_visible);
                 widgets.sasl_password_entry.set_visible(sasl_visible);
+                widgets.ssl_ca_location_entry.set_visible(ssl_visible);
+                widgets
+                    .ssl_certificate_location_entry
+                    .set_visible(ssl_visible);
+                widgets.ssl_key_location_entry.set_visible(ssl_visible);
+                self.update_oauth_visibility(widgets);
+            }
+            ConnectionPageMsg::SaslMechanismChanged(_idx) => {
+                self.sasl_mechanism = match self.sasl_mechanism_combo.model().get_active_elem() {
+                    Some(opt) => *opt,
+                    None => SaslMechanism::default(),
+                };
+                self.update_oauth_visibility(widgets);
+            }
+            ConnectionPageMsg::DefaultFetchChanged(_idx) => {
+                self.default_fetch = match self.default_fetch_combo.model().get_active_elem() {
+                    Some(opt) => opt.clone(),
+                    None => KafkaFetch::default(),
+                };
+            }
+            ConnectionPageMsg::TestConnection => {
+                let connection = self.connection_from_form(widgets);
+                if !validate_connection(widgets, &connection) {
+                    return;
+                }
+                info!("testing connection::{}", connection.name);
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(
+                    id.clone(),
+                    "Testing connection...".to_string(),
+                ));
+                sender.oneshot_command(async move {
+                    let kafka = KafkaBackend::new(&connection);
+                    let result = kafka.test_connection().await;
+                    AsyncCommandOutput::TestConnectionResult(id, result)
+                });
+            }
+            ConnectionPageMsg::ChooseSslFile(ssl_file) => {
+                self.selected_ssl_file = Some(ssl_file);
+                self.ssl_file_dialog.emit(OpenDialogMsg::Open);
+            }
+            ConnectionPageMsg::OpenSslFile(path_buff) => {
+                match (self.selected_ssl_file.take(), path_buff.as_path().to_str()) {
+                    (Some(ssl_file), Some(path)) => {
+                        info!("ssl file selected::{:?}::{}", ssl_file, path);
+                        let entry = match ssl_file {
+                            SslFile::CaLocation => &widgets.ssl_ca_location_entry,
+                            SslFile::CertificateLocation => &widgets.ssl_certificate_location_entry,
+                            SslFile::KeyLocation => &widgets.ssl_key_location_entry,
+                        };
+                        entry.set_text(path);
+                    }
+                    _ => info!("did not selected any ssl file"),
+                };
+            }
+            ConnectionPageMsg::Ignore => {
+                self.selected_ssl_file = None;
             }
             ConnectionPageMsg::New => {
                 widgets.name_entry.set_text("");
                 widgets.brokers_entry.set_text("");
-                widgets.sasl_mechanism_entry.set_text("");
                 widgets.sasl_username_entry.set_text("");
                 widgets.sasl_password_entry.set_text("");
-                widgets.sasl_mechanism_entry.set_visible(false);
+                widgets.ssl_ca_location_entry.set_text("");
+                widgets.ssl_certificate_location_entry.set_text("");
+                widgets.ssl_key_location_entry.set_text("");
+                widgets.extra_config_entry.buffer().set_text("");
+                widgets.group_id_entry.set_text("");
+                widgets.client_id_entry.set_text("");
+                widgets.client_rack_entry.set_text("");
+                widgets.schema_registry_url_entry.set_text("");
+                widgets.oauth_token_endpoint_entry.set_text("");
+                widgets.oauth_client_id_entry.set_text("");
+                widgets.oauth_client_secret_entry.set_text("");
+                self.sasl_mechanism_combo.widget().set_visible(false);
                 widgets.sasl_username_entry.set_visible(false);
                 widgets.sasl_password_entry.set_visible(false);
+                widgets.ssl_ca_location_entry.set_visible(false);
+                widgets.ssl_certificate_location_entry.set_visible(false);
+                widgets.ssl_key_location_entry.set_visible(false);
+                widgets.oauth_token_endpoint_entry.set_visible(false);
+                widgets.oauth_client_id_entry.set_visible(false);
+                widgets.oauth_client_secret_entry.set_visible(false);
                 self.security_type_combo
                     .sender()
                     .emit(SimpleComboRowMsg::SetActiveIdx(0));
+                self.sasl_mechanism_combo
+                    .sender()
+                    .emit(SimpleComboRowMsg::SetActiveIdx(0));
                 self.name = String::default();
                 self.brokers_list = String::default();
                 self.security_type = KrustConnectionSecurityType::default();
-                self.sasl_mechanism = String::default();
+                self.sasl_mechanism = SaslMechanism::default();
+                self.default_fetch_combo
+                    .sender()
+                    .emit(SimpleComboRowMsg::SetActiveIdx(0));
+                self.default_fetch = KafkaFetch::default();
                 self.sasl_username = String::default();
                 self.sasl_password = String::default();
+                self.ssl_ca_location = String::default();
+                self.ssl_certificate_location = String::default();
+                self.ssl_key_location = String::default();
+                self.extra_config = String::default();
+                self.group_id = String::default();
+                self.client_id = String::default();
+                self.client_rack = String::default();
+                self.schema_registry_url = String::default();
+                self.oauth_token_endpoint = String::default();
+                self.oauth_client_id = String::default();
+                self.oauth_client_secret = String::default();
                 self.current = None;
                 self.current_index = None;
                 root.queue_allocate();
@@ -285,46 +638,30 @@ impl Component for ConnectionPageModel {
                 root.present(parent);
             }
             ConnectionPageMsg::Save => {
-                let name = widgets.name_entry.text().to_string();
-                let brokers_list = widgets.brokers_entry.text().to_string();
-                let sasl_mechanism = match widgets.sasl_mechanism_entry.text().as_str() {
-                    "" => None,
-                    vstr => Some(vstr.to_string()),
-                };
-                let sasl_username = match widgets.sasl_username_entry.text().as_str() {
-                    "" => None,
-                    vstr => Some(vstr.to_string()),
-                };
-                let sasl_password = match widgets.sasl_password_entry.text().as_str() {
-                    "" => None,
-                    vstr => Some(vstr.to_string()),
-                };
-                let security_type = self.security_type.clone();
-                let color = widgets.color_button.rgba();
-                info!("selected color::{:?}", color);
-                let timeout = widgets.timeout_entry.value() as usize;
-                let timeout = if timeout < 1 { None } else { Some(timeout) };
+                let connection = self.connection_from_form(widgets);
+                if !validate_connection(widgets, &connection) {
+                    return;
+                }
                 widgets.name_entry.set_text("");
                 widgets.brokers_entry.set_text("");
                 widgets.sasl_username_entry.set_text("");
                 widgets.sasl_password_entry.set_text("");
+                widgets.ssl_ca_location_entry.set_text("");
+                widgets.ssl_certificate_location_entry.set_text("");
+                widgets.ssl_key_location_entry.set_text("");
+                widgets.extra_config_entry.buffer().set_text("");
+                widgets.group_id_entry.set_text("");
+                widgets.client_id_entry.set_text("");
+                widgets.client_rack_entry.set_text("");
+                widgets.schema_registry_url_entry.set_text("");
+                widgets.oauth_token_endpoint_entry.set_text("");
+                widgets.oauth_client_id_entry.set_text("");
+                widgets.oauth_client_secret_entry.set_text("");
                 widgets.timeout_entry.set_value(0.0);
                 sender
                     .output(ConnectionPageOutput::Save(
                         self.current_index.clone(),
-                        KrustConnection {
-                            id: (move |current: Option<KrustConnection>| current?.id)(
-                                self.current.clone(),
-                            ),
-                            name,
-                            brokers_list,
-                            sasl_username,
-                            sasl_password,
-                            sasl_mechanism,
-                            security_type,
-                            color: Some(color.to_string()),
-                            timeout,
-                        },
+                        connection,
                     ))
                     .unwrap();
                 root.close();
@@ -350,11 +687,36 @@ impl Component for ConnectionPageModel {
                 self.sasl_username = conn.sasl_username.unwrap_or_default();
                 self.sasl_password = conn.sasl_password.unwrap_or_default();
                 self.timeout = conn.timeout.map(|t| t as f64);
+                self.ssl_ca_location = conn.ssl_ca_location.unwrap_or_default();
+                self.ssl_certificate_location = conn.ssl_certificate_location.unwrap_or_default();
+                self.ssl_key_location = conn.ssl_key_location.unwrap_or_default();
+                self.extra_config = conn.extra_config.unwrap_or_default();
+                self.group_id = conn.group_id.unwrap_or_default();
+                self.client_id = conn.client_id.unwrap_or_default();
+                self.client_rack = conn.client_rack.unwrap_or_default();
+                self.schema_registry_url = conn.schema_registry_url.unwrap_or_default();
+                self.oauth_token_endpoint = conn.oauth_token_endpoint.unwrap_or_default();
+                self.oauth_client_id = conn.oauth_client_id.unwrap_or_default();
+                self.oauth_client_secret = conn.oauth_client_secret.unwrap_or_default();
+                self.default_fetch = conn.default_fetch.unwrap_or_default();
+                let combo_idx = KafkaFetch::VALUES
+                    .iter()
+                    .position(|v| *v == self.default_fetch)
+                    .expect("Should return option index");
+                self.default_fetch_combo
+                    .sender()
+                    .emit(SimpleComboRowMsg::SetActiveIdx(combo_idx));
                 widgets.name_entry.set_text(self.name.clone().as_str());
                 widgets
                     .brokers_entry
                     .set_text(self.brokers_list.clone().as_str());
-                widgets.sasl_mechanism_entry.set_text(&self.sasl_mechanism);
+                let combo_idx = SaslMechanism::VALUES
+                    .iter()
+                    .position(|v| *v == self.sasl_mechanism)
+                    .expect("Should return option index");
+                self.sasl_mechanism_combo
+                    .sender()
+                    .emit(SimpleComboRowMsg::SetActiveIdx(combo_idx));
                 let combo_idx = KrustConnectionSecurityType::VALUES
                     .iter()
                     .position(|v| *v == self.security_type)
@@ -369,13 +731,49 @@ impl Component for ConnectionPageModel {
                 widgets
                     .sasl_password_entry
                     .set_text(self.sasl_password.clone().as_str());
+                widgets
+                    .ssl_ca_location_entry
+                    .set_text(self.ssl_ca_location.clone().as_str());
+                widgets
+                    .ssl_certificate_location_entry
+                    .set_text(self.ssl_certificate_location.clone().as_str());
+                widgets
+                    .ssl_key_location_entry
+                    .set_text(self.ssl_key_location.clone().as_str());
+                widgets
+                    .extra_config_entry
+                    .buffer()
+                    .set_text(self.extra_config.as_str());
+                widgets.group_id_entry.set_text(self.group_id.as_str());
+                widgets.client_id_entry.set_text(self.client_id.as_str());
+                widgets
+                    .client_rack_entry
+                    .set_text(self.client_rack.as_str());
+                widgets
+                    .schema_registry_url_entry
+                    .set_text(self.schema_registry_url.as_str());
+                widgets
+                    .oauth_token_endpoint_entry
+                    .set_text(self.oauth_token_endpoint.as_str());
+                widgets
+                    .oauth_client_id_entry
+                    .set_text(self.oauth_client_id.as_str());
+                widgets
+                    .oauth_client_secret_entry
+                    .set_text(self.oauth_client_secret.as_str());
                 let sasl_visible = match &self.security_type {
-                    KrustConnectionSecurityType::PLAINTEXT => false,
-                    KrustConnectionSecurityType::SASL_PLAINTEXT => true,
+                    KrustConnectionSecurityType::PLAINTEXT | KrustConnectionSecurityType::SSL => {
+                        false
+                    }
+                    KrustConnectionSecurityType::SASL_PLAINTEXT
+                    | KrustConnectionSecurityType::SASL_SSL => true,
                 };
-                widgets.sasl_mechanism_entry.set_sensitive(sasl_visible);
+                self.sasl_mechanism_combo
+                    .widget()
+                    .set_sensitive(sasl_visible);
                 widgets.sasl_username_entry.set_sensitive(sasl_visible);
                 widgets.sasl_password_entry.set_sensitive(sasl_visible);
+                self.update_oauth_visibility(widgets);
                 widgets
                     .timeout_entry
                     .set_value(self.timeout.unwrap_or_default());
@@ -387,4 +785,153 @@ impl Component for ConnectionPageModel {
 
         self.update_view(widgets, sender);
     }
+
+    fn update_cmd_with_view(
+        &mut self,
+        _widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        _sender: ComponentSender<Self>,
+        _root: &Self::Root,
+    ) {
+        match message {
+            AsyncCommandOutput::TestConnectionResult(id, result) => {
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+                let text = match result {
+                    Ok(_) => "Connection successful!".to_string(),
+                    Err(e) => {
+                        warn!("test connection failed: {:?}", e);
+                        format!("Connection failed: {}", e)
+                    }
+                };
+                TOASTER_BROKER.send(AppMsg::ShowToast(Uuid::new_v4().to_string(), text));
+            }
+        }
+    }
+}
+
+/// Shows the validation error next to the brokers entry, returning whether the connection is valid.
+fn validate_connection(widgets: &ConnectionPageModelWidgets, connection: &KrustConnection) -> bool {
+    match connection.validate() {
+        Ok(_) => true,
+        Err(e) => {
+            warn!("invalid connection {}: {}", connection.name, e);
+            widgets.brokers_entry.add_css_class("error");
+            widgets.brokers_error.set_label(&e.to_string());
+            widgets.brokers_error.set_visible(true);
+            false
+        }
+    }
+}
+
+impl ConnectionPageModel {
+    /// OAuth client credentials only apply to SASL connections using OAUTHBEARER.
+    fn oauth_visible(&self) -> bool {
+        matches!(
+            self.security_type,
+            KrustConnectionSecurityType::SASL_PLAINTEXT | KrustConnectionSecurityType::SASL_SSL
+        ) && self.sasl_mechanism == SaslMechanism::OauthBearer
+    }
+
+    fn update_oauth_visibility(&self, widgets: &ConnectionPageModelWidgets) {
+        let visible = self.oauth_visible();
+        widgets.oauth_token_endpoint_entry.set_visible(visible);
+        widgets.oauth_client_id_entry.set_visible(visible);
+        widgets.oauth_client_secret_entry.set_visible(visible);
+    }
+
+    fn connection_from_form(&self, widgets: &ConnectionPageModelWidgets) -> KrustConnection {
+        let name = widgets.name_entry.text().to_string();
+        let brokers_list = widgets.brokers_entry.text().to_string();
+        let sasl_mechanism = match self.security_type {
+            KrustConnectionSecurityType::SASL_PLAINTEXT | KrustConnectionSecurityType::SASL_SSL => {
+                Some(self.sasl_mechanism)
+            }
+            _ => None,
+        };
+        let sasl_username = match widgets.sasl_username_entry.text().as_str() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let sasl_password = match widgets.sasl_password_entry.text().as_str() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let ssl_ca_location = match widgets.ssl_ca_location_entry.text().as_str() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let ssl_certificate_location = match widgets.ssl_certificate_location_entry.text().as_str()
+        {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let ssl_key_location = match widgets.ssl_key_location_entry.text().as_str() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let extra_config_buffer = widgets.extra_config_entry.buffer();
+        let (start, end) = extra_config_buffer.bounds();
+        let extra_config = extra_config_buffer.text(&start, &end, true).to_string();
+        let extra_config = if extra_config.trim().is_empty() {
+            None
+        } else {
+            Some(extra_config)
+        };
+        let group_id = match widgets.group_id_entry.text().trim() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let client_id = match widgets.client_id_entry.text().trim() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let client_rack = match widgets.client_rack_entry.text().trim() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let schema_registry_url = match widgets.schema_registry_url_entry.text().trim() {
+            "" => None,
+            vstr => Some(vstr.trim_end_matches('/').to_string()),
+        };
+        let oauth_token_endpoint = match widgets.oauth_token_endpoint_entry.text().trim() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let oauth_client_id = match widgets.oauth_client_id_entry.text().trim() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let oauth_client_secret = match widgets.oauth_client_secret_entry.text().trim() {
+            "" => None,
+            vstr => Some(vstr.to_string()),
+        };
+        let security_type = self.security_type.clone();
+        let color = widgets.color_button.rgba();
+        info!("selected color::{:?}", color);
+        let timeout = widgets.timeout_entry.value() as usize;
+        let timeout = if timeout < 1 { None } else { Some(timeout) };
+        KrustConnection {
+            id: self.current.as_ref().and_then(|current| current.id),
+            name,
+            brokers_list,
+            sasl_username,
+            sasl_password,
+            sasl_mechanism,
+            security_type,
+            color: Some(color.to_string()),
+            timeout,
+            ssl_ca_location,
+            ssl_certificate_location,
+            ssl_key_location,
+            extra_config,
+            group_id,
+            default_fetch: Some(self.default_fetch.clone()),
+            schema_registry_url,
+            oauth_token_endpoint,
+            oauth_client_id,
+            oauth_client_secret,
+            client_id,
+            client_rack,
+        }
+    }
 }
@@ -3,9 +3,11 @@
 // found in the COPYING file.
 
 use crate::backend::repository::{KrustHeader, KrustMessage};
-use chrono::prelude::*;
-use chrono_tz::America;
+use crate::backend::settings::format_timestamp;
+use chrono_tz::Tz;
+use copypasta::{ClipboardContext, ClipboardProvider};
 use gtk::prelude::*;
+use humansize::{format_size, DECIMAL};
 use relm4::{
     typed_view::{
         column::{LabelColumn, RelmColumn},
@@ -13,6 +15,7 @@ use relm4::{
     },
     *,
 };
+use tracing::*;
 
 // Table headers: start
 #[derive(Debug, PartialEq, Eq)]
@@ -86,18 +89,85 @@ impl RelmColumn for HeaderValueColumn {
         }))
     }
 }
+
+pub struct HeaderCopyColumn;
+
+impl RelmColumn for HeaderCopyColumn {
+    type Root = gtk::Button;
+    type Widgets = Option<gtk::glib::SignalHandlerId>;
+    type Item = HeaderListItem;
+
+    const COLUMN_NAME: &'static str = "";
+    const ENABLE_RESIZE: bool = false;
+    const ENABLE_EXPAND: bool = false;
+
+    fn setup(_item: &gtk::ListItem) -> (Self::Root, Self::Widgets) {
+        let button = gtk::Button::builder()
+            .icon_name("edit-copy-symbolic")
+            .tooltip_text("Copy header value")
+            .css_classes(["flat"])
+            .build();
+        (button, None)
+    }
+
+    fn bind(item: &mut Self::Item, handler: &mut Self::Widgets, button: &mut Self::Root) {
+        let value = item.value.clone().unwrap_or_default();
+        *handler = Some(button.connect_clicked(move |_| {
+            let result =
+                ClipboardContext::new().and_then(|mut ctx| ctx.set_contents(value.clone()));
+            if let Err(error) = result {
+                warn!("unable to copy header value: {:?}", error);
+            }
+        }));
+    }
+
+    fn unbind(_item: &mut Self::Item, handler: &mut Self::Widgets, button: &mut Self::Root) {
+        if let Some(handler) = handler.take() {
+            button.disconnect(handler);
+        }
+    }
+}
 // Table headers: end
 
 // Table messages: start
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MessageListItem {
     pub offset: i64,
     pub partition: i32,
-    pub key: String,
-    pub value: String,
+    /// Record key, `None` for records produced without one.
+    pub key: Option<String>,
+    /// Record payload, `None` for tombstones.
+    pub value: Option<String>,
     pub timestamp: Option<i64>,
     pub headers: Vec<KrustHeader>,
+    pub raw_value: Option<Vec<u8>>,
     pub timestamp_formatter: String,
+    pub timezone: Tz,
+    pub value_preview_max_chars: usize,
+    pub bookmarked: bool,
+    /// Raw record size in bytes: key, value and headers as received.
+    pub size: usize,
+    pub value_kind: MessageValueKind,
+}
+
+/// What the value looks like, from the same JSON parse attempt used when copying messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MessageValueKind {
+    Empty,
+    Text,
+    Json,
+}
+
+impl MessageValueKind {
+    fn of(value: &str) -> Self {
+        if value.trim().is_empty() {
+            MessageValueKind::Empty
+        } else if serde_json::from_str::<serde_json::Value>(value).is_ok() {
+            MessageValueKind::Json
+        } else {
+            MessageValueKind::Text
+        }
+    }
 }
 
 impl PartialEq for MessageListItem {
@@ -111,19 +181,89 @@ impl PartialEq for MessageListItem {
 impl Eq for MessageListItem {}
 
 impl MessageListItem {
-    pub fn new(value: KrustMessage, timestamp_formatter: String) -> Self {
+    pub fn new(
+        value: KrustMessage,
+        timestamp_formatter: String,
+        timezone: Tz,
+        value_preview_max_chars: usize,
+    ) -> Self {
+        let size = message_size(&value);
+        let value_kind = MessageValueKind::of(value.value.as_deref().unwrap_or_default());
         Self {
             offset: value.offset,
             partition: value.partition,
-            key: value.key.unwrap_or_default(),
+            key: value.key,
             value: value.value,
             timestamp: value.timestamp,
             headers: value.headers,
+            raw_value: value.raw_value,
             timestamp_formatter,
+            timezone,
+            value_preview_max_chars,
+            bookmarked: false,
+            size,
+            value_kind,
+        }
+    }
+
+    /// Message of the given topic rebuilt from the list item, keeping tombstones as such.
+    pub fn to_message(&self, topic: &str) -> KrustMessage {
+        KrustMessage {
+            headers: self.headers.clone(),
+            topic: topic.to_string(),
+            partition: self.partition,
+            offset: self.offset,
+            key: self.key.clone(),
+            value: self.value.clone(),
+            timestamp: self.timestamp,
+            raw_value: self.raw_value.clone(),
         }
     }
 }
 
+/// Decoded values keep their original payload, so it's measured instead of the decoded text.
+fn message_size(message: &KrustMessage) -> usize {
+    let key = message.key.as_ref().map(|k| k.len()).unwrap_or_default();
+    let value = match &message.raw_value {
+        Some(raw) => raw.len(),
+        None => message.value.as_ref().map(|v| v.len()).unwrap_or_default(),
+    };
+    let headers: usize = message
+        .headers
+        .iter()
+        .map(|h| h.key.len() + h.value.as_ref().map(|v| v.len()).unwrap_or_default())
+        .sum();
+    key + value + headers
+}
+
+pub struct MessageBookmarkColumn;
+
+impl RelmColumn for MessageBookmarkColumn {
+    type Root = gtk::Image;
+    type Widgets = ();
+    type Item = MessageListItem;
+
+    const COLUMN_NAME: &'static str = "★";
+    const ENABLE_RESIZE: bool = false;
+    const ENABLE_EXPAND: bool = false;
+
+    fn setup(_item: &gtk::ListItem) -> (Self::Root, Self::Widgets) {
+        let image = gtk::Image::from_icon_name("starred-symbolic");
+        image.set_tooltip_text(Some("Bookmarked"));
+        (image, ())
+    }
+
+    fn bind(item: &mut Self::Item, _: &mut Self::Widgets, image: &mut Self::Root) {
+        image.set_visible(item.bookmarked);
+    }
+
+    fn sort_fn() -> OrdFn<Self::Item> {
+        Some(Box::new(|a: &MessageListItem, b: &MessageListItem| {
+            a.bookmarked.cmp(&b.bookmarked)
+        }))
+    }
+}
+
 pub struct MessageOffsetColumn;
 
 impl LabelColumn for MessageOffsetColumn {
@@ -182,13 +322,8 @@ impl RelmColumn for MessageTimestampColumn {
     }
 
     fn bind(item: &mut Self::Item, _: &mut Self::Widgets, label: &mut Self::Root) {
-        let formatted = format!(
-            "{}",
-            Utc.timestamp_millis_opt(item.timestamp.unwrap_or_default())
-                .unwrap()
-                .with_timezone(&America::Sao_Paulo)
-                .format(&item.timestamp_formatter)
-        );
+        let formatted = format_timestamp(item.timestamp, &item.timestamp_formatter, &item.timezone)
+            .unwrap_or_else(|| "N/A".to_string());
         label.set_label(&formatted);
     }
 
@@ -201,6 +336,39 @@ impl RelmColumn for MessageTimestampColumn {
     }
 }
 
+pub struct MessageValueKindColumn;
+
+impl RelmColumn for MessageValueKindColumn {
+    type Root = gtk::Image;
+    type Widgets = ();
+    type Item = MessageListItem;
+
+    const COLUMN_NAME: &'static str = "Type";
+    const ENABLE_RESIZE: bool = false;
+    const ENABLE_EXPAND: bool = false;
+
+    fn setup(_item: &gtk::ListItem) -> (Self::Root, Self::Widgets) {
+        (gtk::Image::new(), ())
+    }
+
+    fn bind(item: &mut Self::Item, _: &mut Self::Widgets, image: &mut Self::Root) {
+        let (icon, tooltip, css_class) = match item.value_kind {
+            MessageValueKind::Json => ("emblem-ok-symbolic", "Valid JSON", "success"),
+            MessageValueKind::Text => ("text-x-generic-symbolic", "Plain text", "warning"),
+            MessageValueKind::Empty => ("action-unavailable-symbolic", "Empty", "dim-label"),
+        };
+        image.set_icon_name(Some(icon));
+        image.set_tooltip_text(Some(tooltip));
+        image.set_css_classes(&[css_class]);
+    }
+
+    fn sort_fn() -> OrdFn<Self::Item> {
+        Some(Box::new(|a: &MessageListItem, b: &MessageListItem| {
+            a.value_kind.cmp(&b.value_kind)
+        }))
+    }
+}
+
 pub struct MessageValueColumn;
 
 impl RelmColumn for MessageValueColumn {
@@ -220,10 +388,81 @@ impl RelmColumn for MessageValueColumn {
     }
 
     fn bind(item: &mut Self::Item, _widgets: &mut Self::Widgets, label: &mut Self::Root) {
-        let formatted = item.value.replace('\n', " ").clone();
-        label.set_label(&formatted);
+        let value = item.value.as_deref().unwrap_or_default();
+        let mut preview: String = value
+            .chars()
+            .take(item.value_preview_max_chars)
+            .map(|c| if c == '\n' { ' ' } else { c })
+            .collect();
+        if preview.len() < value.len() {
+            preview.push('…');
+        }
+        label.set_label(&preview);
+        label.set_tooltip_text(Some(value));
+    }
+}
+pub struct MessageHeaderColumn;
+
+impl RelmColumn for MessageHeaderColumn {
+    type Root = gtk::Box;
+    type Widgets = (gtk::Label, gtk::Label);
+    type Item = MessageListItem;
+
+    const COLUMN_NAME: &'static str = "Headers";
+    const ENABLE_RESIZE: bool = true;
+    const ENABLE_EXPAND: bool = false;
+
+    fn setup(_item: &gtk::ListItem) -> (Self::Root, Self::Widgets) {
+        let container = gtk::Box::new(gtk::Orientation::Horizontal, 5);
+        let badge = gtk::Label::new(None);
+        badge.add_css_class("numeric");
+        badge.add_css_class("dim-label");
+        let first_key = gtk::Label::new(None);
+        first_key.set_halign(gtk::Align::Start);
+        first_key.set_ellipsize(gtk::pango::EllipsizeMode::End);
+        container.append(&badge);
+        container.append(&first_key);
+        (container, (badge, first_key))
+    }
+
+    fn bind(item: &mut Self::Item, widgets: &mut Self::Widgets, _: &mut Self::Root) {
+        let (badge, first_key) = widgets;
+        badge.set_label(&format!("{}", item.headers.len()));
+        first_key.set_label(
+            item.headers
+                .first()
+                .map(|header| header.key.as_str())
+                .unwrap_or_default(),
+        );
+    }
+
+    fn sort_fn() -> OrdFn<Self::Item> {
+        Some(Box::new(|a: &MessageListItem, b: &MessageListItem| {
+            a.headers.len().cmp(&b.headers.len())
+        }))
+    }
+}
+
+pub struct MessageSizeColumn;
+
+impl LabelColumn for MessageSizeColumn {
+    type Item = MessageListItem;
+    type Value = usize;
+
+    const COLUMN_NAME: &'static str = "Size";
+
+    const ENABLE_SORT: bool = true;
+    const ENABLE_RESIZE: bool = true;
+
+    fn get_cell_value(item: &Self::Item) -> Self::Value {
+        item.size
+    }
+
+    fn format_cell_value(value: &Self::Value) -> String {
+        format_size(*value, DECIMAL)
     }
 }
+
 pub struct MessageKeyColumn;
 
 impl LabelColumn for MessageKeyColumn {
@@ -233,10 +472,10 @@ impl LabelColumn for MessageKeyColumn {
     const COLUMN_NAME: &'static str = "Key";
     const ENABLE_RESIZE: bool = true;
     const ENABLE_EXPAND: bool = true;
-    const ENABLE_SORT: bool = false;
+    const ENABLE_SORT: bool = true;
 
     fn get_cell_value(item: &Self::Item) -> Self::Value {
-        item.key.clone()
+        item.key.clone().unwrap_or_default()
     }
 
     fn format_cell_value(value: &Self::Value) -> String {
@@ -8,8 +8,12 @@ use sourceview::prelude::*;
 use sourceview5 as sourceview;
 
 use crate::{
-    backend::repository::KrustHeader,
-    component::messages::lists::{HeaderNameColumn, HeaderValueColumn},
+    backend::{
+        payload::{decode_base64, encode_base64, hex_view},
+        repository::KrustHeader,
+        settings::Settings,
+    },
+    component::messages::lists::{HeaderCopyColumn, HeaderNameColumn, HeaderValueColumn},
 };
 
 use super::lists::HeaderListItem;
@@ -18,13 +22,26 @@ use super::lists::HeaderListItem;
 pub struct MessageViewerInit {}
 #[derive(Debug)]
 pub enum MessageViewerMsg {
-    Open(String, Vec<KrustHeader>),
+    Open(String, Option<Vec<u8>>, Vec<KrustHeader>),
+    TogglePrettyJson(bool),
+    ToggleHex(bool),
+    DecodeBase64,
+    EncodeBase64,
+    RestoreValue,
     Clear,
 }
 
 #[derive(Debug)]
 pub struct MessageViewerModel {
     headers_wrapper: TypedColumnView<HeaderListItem, gtk::NoSelection>,
+    message_text: Option<String>,
+    raw_value: Option<Vec<u8>>,
+    is_pretty_json: bool,
+    is_hex: bool,
+    has_headers: bool,
+    /// The view shows a base64 decoded or encoded text instead of the message value.
+    is_transformed: bool,
+    base64_error: Option<String>,
 }
 
 #[relm4::component(pub)]
@@ -38,8 +55,70 @@ impl Component for MessageViewerModel {
         #[root]
         gtk::Stack {
             add_child = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
                 set_hexpand: true,
                 set_vexpand: true,
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_halign: gtk::Align::End,
+                    set_margin_bottom: 5,
+                    gtk::Label {
+                        #[watch]
+                        set_visible: model.base64_error.is_some(),
+                        #[watch]
+                        set_label: model.base64_error.as_deref().unwrap_or_default(),
+                        set_margin_end: 5,
+                        set_ellipsize: gtk::pango::EllipsizeMode::End,
+                        add_css_class: "error",
+                    },
+                    gtk::Button {
+                        set_tooltip_text: Some("Show the message value again"),
+                        set_icon_name: "edit-undo-symbolic",
+                        add_css_class: "flat",
+                        #[watch]
+                        set_visible: model.is_transformed,
+                        connect_clicked => MessageViewerMsg::RestoreValue,
+                    },
+                    gtk::Button {
+                        set_tooltip_text: Some("Decode the selection, or the whole value, from base64"),
+                        set_label: "Decode base64",
+                        add_css_class: "flat",
+                        connect_clicked => MessageViewerMsg::DecodeBase64,
+                    },
+                    gtk::Button {
+                        set_tooltip_text: Some("Encode the selection, or the whole value, as base64"),
+                        set_label: "Encode base64",
+                        add_css_class: "flat",
+                        connect_clicked => MessageViewerMsg::EncodeBase64,
+                    },
+                    gtk::Label {
+                        #[watch]
+                        set_visible: model.raw_value.is_some(),
+                        #[watch]
+                        set_label: &format!("Binary payload, {} bytes", model.raw_value.as_ref().map(|r| r.len()).unwrap_or_default()),
+                        set_margin_end: 5,
+                        add_css_class: "dim-label",
+                    },
+                    gtk::ToggleButton {
+                        set_tooltip_text: Some("Show raw bytes as hex"),
+                        set_icon_name: "view-dual-symbolic",
+                        add_css_class: "flat",
+                        #[watch]
+                        set_visible: model.raw_value.is_some(),
+                        connect_toggled[sender] => move |btn| {
+                            sender.input(MessageViewerMsg::ToggleHex(btn.is_active()));
+                        },
+                    },
+                    gtk::ToggleButton {
+                        set_tooltip_text: Some("Pretty-print JSON values"),
+                        set_icon_name: "format-indent-more-symbolic",
+                        add_css_class: "flat",
+                        set_active: model.is_pretty_json,
+                        connect_toggled[sender] => move |btn| {
+                            sender.input(MessageViewerMsg::TogglePrettyJson(btn.is_active()));
+                        },
+                    },
+                },
                 #[name = "value_container"]
                 gtk::ScrolledWindow {
                     add_css_class: "bordered",
@@ -63,7 +142,18 @@ impl Component for MessageViewerModel {
                 set_name: "Value",
             },
             add_child = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                gtk::Label {
+                    #[watch]
+                    set_visible: !model.has_headers,
+                    set_vexpand: true,
+                    set_hexpand: true,
+                    add_css_class: "dim-label",
+                    set_label: "No headers",
+                },
                 gtk::ScrolledWindow {
+                    #[watch]
+                    set_visible: model.has_headers,
                     set_vexpand: true,
                     set_hexpand: true,
                     set_propagate_natural_width: true,
@@ -76,21 +166,31 @@ impl Component for MessageViewerModel {
                     }
                 },
             } -> {
-                set_title: "Header",
-                set_name: "Header",
+                set_title: "Headers",
+                set_name: "Headers",
             },
         }
     }
     fn init(
         _: Self::Init,
         root: Self::Root,
-        _sender: ComponentSender<Self>,
+        sender: ComponentSender<Self>,
     ) -> ComponentParts<Self> {
         let mut headers_wrapper = TypedColumnView::<HeaderListItem, gtk::NoSelection>::new();
         headers_wrapper.append_column::<HeaderNameColumn>();
         headers_wrapper.append_column::<HeaderValueColumn>();
+        headers_wrapper.append_column::<HeaderCopyColumn>();
         let headers_view = headers_wrapper.view.clone();
-        let model = MessageViewerModel { headers_wrapper };
+        let model = MessageViewerModel {
+            headers_wrapper,
+            message_text: None,
+            raw_value: None,
+            is_pretty_json: true,
+            is_hex: false,
+            has_headers: false,
+            is_transformed: false,
+            base64_error: None,
+        };
         let widgets = view_output!();
 
         let buffer = widgets
@@ -114,43 +214,133 @@ impl Component for MessageViewerModel {
         _: &Self::Root,
     ) {
         match msg {
-            MessageViewerMsg::Open(message_text, headers) => {
-                let buffer = widgets
-                    .value_source_view
-                    .buffer()
-                    .downcast::<sourceview::Buffer>()
-                    .expect("sourceview was not backed by sourceview buffer");
-
-                let valid_json: Result<serde_json::Value, _> =
-                    serde_json::from_str(message_text.as_str());
-                let (language, formatted_text) = match valid_json {
-                    Ok(jt) => (
-                        sourceview::LanguageManager::default().language("json"),
-                        serde_json::to_string_pretty(&jt).unwrap(),
-                    ),
-                    Err(_) => (
-                        sourceview::LanguageManager::default().language("text"),
-                        message_text,
-                    ),
-                };
-
-                buffer.set_language(language.as_ref());
-                buffer.set_text(&formatted_text.clone());
-                widgets.value_source_view.queue_allocate();
+            MessageViewerMsg::Open(message_text, raw_value, headers) => {
+                self.message_text = Some(message_text);
+                self.raw_value = raw_value;
+                self.render_value(widgets);
 
                 self.headers_wrapper.clear();
+                self.has_headers = !headers.is_empty();
                 for header in headers.iter() {
                     self.headers_wrapper
                         .append(HeaderListItem::new(header.clone()));
                 }
             }
+            MessageViewerMsg::TogglePrettyJson(is_active) => {
+                self.is_pretty_json = is_active;
+                self.render_value(widgets);
+            }
+            MessageViewerMsg::ToggleHex(is_active) => {
+                self.is_hex = is_active;
+                self.render_value(widgets);
+            }
+            MessageViewerMsg::DecodeBase64 => match decode_base64(&selected_text(widgets)) {
+                Ok(bytes) => {
+                    let text = match String::from_utf8(bytes) {
+                        Ok(text) => text,
+                        Err(e) => hex_view(e.as_bytes()),
+                    };
+                    self.show_text(widgets, text);
+                    self.is_transformed = true;
+                }
+                Err(e) => self.base64_error = Some(e),
+            },
+            MessageViewerMsg::EncodeBase64 => {
+                let has_selection = widgets.value_source_view.buffer().has_selection();
+                let text = match (&self.raw_value, has_selection || self.is_transformed) {
+                    // the view only shows a lossy or hex rendering of binary payloads
+                    (Some(raw_value), false) => encode_base64(raw_value),
+                    _ => encode_base64(selected_text(widgets).as_bytes()),
+                };
+                self.show_text(widgets, text);
+                self.is_transformed = true;
+            }
+            MessageViewerMsg::RestoreValue => {
+                self.render_value(widgets);
+            }
             MessageViewerMsg::Clear => {
+                self.message_text = None;
+                self.raw_value = None;
+                self.is_transformed = false;
+                self.base64_error = None;
                 widgets.value_source_view.buffer().set_text("");
                 widgets.value_source_view.queue_allocate();
                 self.headers_wrapper.clear();
+                self.has_headers = false;
             }
         };
 
         self.update_view(widgets, sender);
     }
 }
+
+/// Text selected in the value view, or the whole text when nothing is selected.
+fn selected_text(widgets: &MessageViewerModelWidgets) -> String {
+    let buffer = widgets.value_source_view.buffer();
+    let (start, end) = buffer.selection_bounds().unwrap_or_else(|| buffer.bounds());
+    buffer.text(&start, &end, false).to_string()
+}
+
+impl MessageViewerModel {
+    fn render_value(&mut self, widgets: &MessageViewerModelWidgets) {
+        self.is_transformed = false;
+        self.base64_error = None;
+        let Some(message_text) = self.message_text.clone() else {
+            return;
+        };
+        let buffer = widgets
+            .value_source_view
+            .buffer()
+            .downcast::<sourceview::Buffer>()
+            .expect("sourceview was not backed by sourceview buffer");
+
+        if let (true, Some(raw_value)) = (self.is_hex, &self.raw_value) {
+            buffer.set_language(
+                sourceview::LanguageManager::default()
+                    .language("text")
+                    .as_ref(),
+            );
+            buffer.set_text(&hex_view(raw_value));
+            widgets.value_source_view.queue_allocate();
+            return;
+        }
+
+        self.show_text(widgets, message_text);
+    }
+
+    /// Shows text in the value view, highlighted and pretty-printed when it is JSON.
+    fn show_text(&mut self, widgets: &MessageViewerModelWidgets, message_text: String) {
+        self.base64_error = None;
+        let buffer = widgets
+            .value_source_view
+            .buffer()
+            .downcast::<sourceview::Buffer>()
+            .expect("sourceview was not backed by sourceview buffer");
+
+        let max_highlight_bytes = Settings::read()
+            .unwrap_or_default()
+            .json_highlight_max_bytes;
+        // highlighting huge payloads freezes the view
+        buffer.set_highlight_syntax(message_text.len() <= max_highlight_bytes);
+
+        let valid_json: Result<serde_json::Value, _> = serde_json::from_str(message_text.as_str());
+        let (language, formatted_text) = match valid_json {
+            Ok(jt) => (
+                sourceview::LanguageManager::default().language("json"),
+                if self.is_pretty_json {
+                    serde_json::to_string_pretty(&jt).unwrap()
+                } else {
+                    message_text
+                },
+            ),
+            Err(_) => (
+                sourceview::LanguageManager::default().language("text"),
+                message_text,
+            ),
+        };
+
+        buffer.set_language(language.as_ref());
+        buffer.set_text(&formatted_text.clone());
+        widgets.value_source_view.queue_allocate();
+    }
+}
@@ -6,20 +6,25 @@ use core::f64;
 use std::str::FromStr;
 
 use adw::{prelude::*, AlertDialog};
-use chrono::{Local, NaiveDateTime, TimeZone, Timelike, Utc};
-use chrono_tz::America;
+use chrono::{NaiveDateTime, TimeZone, Timelike, Utc};
 use copypasta::{ClipboardContext, ClipboardProvider};
 use gtk::Adjustment;
+use humansize::{format_size, DECIMAL};
 use relm4::*;
 use relm4_components::simple_adw_combo_row::SimpleComboRow;
 use tracing::*;
 use uuid::Uuid;
 
 use crate::backend::kafka::{KafkaBackend, KafkaFetch};
-use crate::backend::repository::{FetchMode, KrustConnection, KrustTopic, KrustTopicCache};
-use crate::backend::worker::{MessagesCleanupRequest, MessagesWorker};
+use crate::backend::repository::{
+    FetchMode, KrustConnection, KrustTopic, KrustTopicCache, MessagesRepository, Partition,
+};
+use crate::backend::settings::{format_timestamp, Settings};
+use crate::backend::worker::{record_partition_offsets, MessagesCleanupRequest, MessagesWorker};
 use crate::component::messages::messages_tab::AVAILABLE_PAGE_SIZES;
-use crate::modals::utils::build_confirmation_alert;
+use crate::component::task_manager::{Task, TaskManagerMsg, TaskVariant, TASK_MANAGER_BROKER};
+use crate::config::ExternalError;
+use crate::modals::utils::{build_confirmation_alert, show_error_alert};
 use crate::{AppMsg, Repository, DATE_TIME_FORMAT, TOASTER_BROKER};
 
 const DEFAULT_MESSAGES_PER_PARTITION: usize = 10000;
@@ -33,6 +38,9 @@ pub struct MessagesCacheSettingsDialogModel {
     pub selected_default_page_size: Option<u16>,
     pub confirmation_alert: AlertDialog,
     pub clipboard: Box<dyn ClipboardProvider>,
+    pub compacting: bool,
+    pub last_updated: Option<i64>,
+    pub next_refresh: String,
 }
 
 #[derive(Debug)]
@@ -47,6 +55,9 @@ pub enum MessagesCacheSettingsDialogMsg {
     RefreshTopicMessagesCounter,
     CopyToClipboard(String),
     SetCacheTimestamp,
+    SetCacheEndTimestamp,
+    CompactCache,
+    SetAutoRefresh,
 }
 
 #[derive(Debug)]
@@ -57,6 +68,7 @@ pub enum MessagesCacheSettingsDialogOutput {
 #[derive(Debug)]
 pub enum AsyncCommandOutput {
     RefreshTopicMessagesCounter(KrustTopic),
+    CacheCompacted(Task, Result<(u64, u64), ExternalError>),
 }
 
 impl Drop for MessagesCacheSettingsDialogModel {
@@ -77,7 +89,7 @@ impl Component for MessagesCacheSettingsDialogModel {
         main_dialog = adw::Dialog {
             set_title: "Cache",
             set_content_width: 730,
-            set_content_height: 350,
+            set_content_height: 560,
             #[wrap(Some)]
             set_child = &gtk::Box {
                 set_orientation: gtk::Orientation::Vertical,
@@ -158,8 +170,41 @@ impl Component for MessagesCacheSettingsDialogModel {
                                 },
                             },
                         },
+                        #[name(status_cache_size)]
+                        adw::ActionRow {
+                            set_subtitle: "Cache database size",
+                            add_suffix = &gtk::Box {
+                                set_orientation: gtk::Orientation::Horizontal,
+                                gtk::Button {
+                                    set_label: "Compact cache",
+                                    set_tooltip_text: Some("Reclaim unused space from the cache database"),
+                                    set_margin_start: 5,
+                                    set_valign: gtk::Align::Center,
+                                    #[watch]
+                                    set_sensitive: !model.compacting,
+                                    connect_clicked => MessagesCacheSettingsDialogMsg::CompactCache,
+                                },
+                            },
+                        },
+                    },
+                    adw::PreferencesGroup {
+                        set_title: "Partitions",
+                        set_margin_top: 10,
+                        set_description: Some("Low/high watermarks per partition"),
+                        gtk::ScrolledWindow {
+                            set_hexpand: true,
+                            set_propagate_natural_height: true,
+                            set_max_content_height: 150,
+                            set_hscrollbar_policy: gtk::PolicyType::Never,
+                            #[name(partitions_list)]
+                            gtk::ListBox {
+                                set_selection_mode: gtk::SelectionMode::None,
+                                add_css_class: "boxed-list",
+                            },
+                        },
                     },
                     adw::PreferencesGroup {
+                        set_margin_top: 10,
                         set_title: "Settings",
                         #[local_ref]
                         default_page_size_combo -> adw::ComboRow {
@@ -227,85 +272,34 @@ impl Component for MessagesCacheSettingsDialogModel {
                                 set_title: FetchMode::Tail.to_string().as_str(),
                                 set_name: FetchMode::Tail.to_string().as_str(),
                             },
-                            add_child: timestamp_widget = &adw::ActionRow {
-                                    set_title: "From offset date/time",
-                                    set_valign: gtk::Align::Start,
-                                    set_margin_top: 4,
-                                    add_suffix: timestamp_container = &gtk::Box {
-                                        set_orientation: gtk::Orientation::Horizontal,
-                                        #[name(formatted_date)]
-                                        gtk::Entry {
-                                            set_valign: gtk::Align::Center,
-                                            set_editable: false,
-                                            set_max_width_chars: 10,
-                                            #[watch]
-                                            set_sensitive: !model.readonly,
-                                        },
-                                        gtk::MenuButton {
-                                            set_tooltip_text: Some("Show calendar"),
-                                            set_valign: gtk::Align::Center,
-                                            set_direction: gtk::ArrowType::Down,
-                                            add_css_class: "flat",
-                                            set_margin_end: 5,
-                                            #[wrap(Some)]
-                                            set_popover: calendar_popover = &gtk::Popover {
-                                                set_position: gtk::PositionType::Bottom,
-                                                #[wrap(Some)]
-                                                set_child: calendar = &gtk::Calendar {
-                                                    #[watch]
-                                                    set_sensitive: !model.readonly,
-                                                    connect_day_selected[sender] => move |_calendar| {
-                                                        sender.input(MessagesCacheSettingsDialogMsg::SetCacheTimestamp);
-                                                    },
-                                                },
-                                            },
-                                        },
-                                        #[name(time_hours)]
-                                        gtk::SpinButton {
-                                            set_xalign: 0.5,
-                                            set_orientation: gtk::Orientation::Vertical,
-                                            set_wrap: true,
-                                            set_numeric: true,
-                                            set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
-                                            set_increments: (1.0, 1.0),
-                                            set_range: (0.0, 23.0),
-                                            set_digits: 0,
-                                            #[watch]
-                                            set_sensitive: !model.readonly,
-                                        },
-                                        gtk::Label { set_label: ":", set_margin_start: 2, set_margin_end: 2, },
-                                        #[name(time_minutes)]
-                                        gtk::SpinButton {
-                                            set_xalign: 0.5,
-                                            set_orientation: gtk::Orientation::Vertical,
-                                            set_wrap: true,
-                                            set_numeric: true,
-                                            set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
-                                            set_increments: (1.0, 1.0),
-                                            set_range: (0.0, 59.0),
-                                            set_digits: 0,
-                                            #[watch]
-                                            set_sensitive: !model.readonly,
-                                        },
-                                        gtk::Label { set_label: ":", set_margin_start: 2, set_margin_end: 2, },
-                                        #[name(time_seconds)]
-                                        gtk::SpinButton {
-                                            set_xalign: 0.5,
-                                            set_orientation: gtk::Orientation::Vertical,
-                                            set_wrap: true,
-                                            set_numeric: true,
-                                            set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
-                                            set_increments: (1.0, 1.0),
-                                            set_range: (0.0, 59.0),
-                                            set_digits: 0,
-                                            #[watch]
-                                            set_sensitive: !model.readonly,
-                                        },
-                                    },
+                            add_child = &gtk::Box {
+                                set_halign: gtk::Align::Fill,
+                                set_hexpand: true,
+                                set_orientation: gtk::Orientation::Vertical,
+                                gtk::Label {
+                                    set_label: "Messages from date/time",
+                                    set_halign: gtk::Align::Start,
+                                    set_margin_top: 8,
+                                    set_margin_start: 10,
+                                },
                             } -> {
                                 set_title: FetchMode::FromTimestamp.to_string().as_str(),
                                 set_name: FetchMode::FromTimestamp.to_string().as_str(),
                             },
+                            add_child = &gtk::Box {
+                                set_halign: gtk::Align::Fill,
+                                set_hexpand: true,
+                                set_orientation: gtk::Orientation::Vertical,
+                                gtk::Label {
+                                    set_label: "Messages between date/times",
+                                    set_halign: gtk::Align::Start,
+                                    set_margin_top: 8,
+                                    set_margin_start: 10,
+                                },
+                            } -> {
+                                set_title: FetchMode::TimeRange.to_string().as_str(),
+                                set_name: FetchMode::TimeRange.to_string().as_str(),
+                            },
                             connect_visible_child_name_notify[sender] => move |stack| {
                                 let selected = stack.visible_child_name();
                                 if let Some(selected) = selected {
@@ -314,6 +308,184 @@ impl Component for MessagesCacheSettingsDialogModel {
                                 };
                             },
                         },
+                        #[name(timestamp_widget)]
+                        adw::ActionRow {
+                            set_title: "From offset date/time",
+                            #[watch]
+                            set_visible: matches!(model.selected_fetch_mode, Some(FetchMode::FromTimestamp) | Some(FetchMode::TimeRange)),
+                            set_valign: gtk::Align::Start,
+                            set_margin_top: 4,
+                            add_suffix: timestamp_container = &gtk::Box {
+                                set_orientation: gtk::Orientation::Horizontal,
+                                #[name(formatted_date)]
+                                gtk::Entry {
+                                    set_valign: gtk::Align::Center,
+                                    set_editable: false,
+                                    set_max_width_chars: 10,
+                                    #[watch]
+                                    set_sensitive: !model.readonly,
+                                },
+                                gtk::MenuButton {
+                                    set_tooltip_text: Some("Show calendar"),
+                                    set_valign: gtk::Align::Center,
+                                    set_direction: gtk::ArrowType::Down,
+                                    add_css_class: "flat",
+                                    set_margin_end: 5,
+                                    #[wrap(Some)]
+                                    set_popover: calendar_popover = &gtk::Popover {
+                                        set_position: gtk::PositionType::Bottom,
+                                        #[wrap(Some)]
+                                        set_child: calendar = &gtk::Calendar {
+                                            #[watch]
+                                            set_sensitive: !model.readonly,
+                                            connect_day_selected[sender] => move |_calendar| {
+                                                sender.input(MessagesCacheSettingsDialogMsg::SetCacheTimestamp);
+                                            },
+                                        },
+                                    },
+                                },
+                                #[name(time_hours)]
+                                gtk::SpinButton {
+                                    set_xalign: 0.5,
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_wrap: true,
+                                    set_numeric: true,
+                                    set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                                    set_increments: (1.0, 1.0),
+                                    set_range: (0.0, 23.0),
+                                    set_digits: 0,
+                                    #[watch]
+                                    set_sensitive: !model.readonly,
+                                },
+                                gtk::Label { set_label: ":", set_margin_start: 2, set_margin_end: 2, },
+                                #[name(time_minutes)]
+                                gtk::SpinButton {
+                                    set_xalign: 0.5,
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_wrap: true,
+                                    set_numeric: true,
+                                    set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                                    set_increments: (1.0, 1.0),
+                                    set_range: (0.0, 59.0),
+                                    set_digits: 0,
+                                    #[watch]
+                                    set_sensitive: !model.readonly,
+                                },
+                                gtk::Label { set_label: ":", set_margin_start: 2, set_margin_end: 2, },
+                                #[name(time_seconds)]
+                                gtk::SpinButton {
+                                    set_xalign: 0.5,
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_wrap: true,
+                                    set_numeric: true,
+                                    set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                                    set_increments: (1.0, 1.0),
+                                    set_range: (0.0, 59.0),
+                                    set_digits: 0,
+                                    #[watch]
+                                    set_sensitive: !model.readonly,
+                                },
+                            },
+                        },
+                        #[name(end_timestamp_widget)]
+                        adw::ActionRow {
+                            set_title: "To offset date/time",
+                            #[watch]
+                            set_visible: matches!(model.selected_fetch_mode, Some(FetchMode::TimeRange)),
+                            set_valign: gtk::Align::Start,
+                            set_margin_top: 4,
+                            add_suffix: end_timestamp_container = &gtk::Box {
+                                set_orientation: gtk::Orientation::Horizontal,
+                                #[name(end_formatted_date)]
+                                gtk::Entry {
+                                    set_valign: gtk::Align::Center,
+                                    set_editable: false,
+                                    set_max_width_chars: 10,
+                                    #[watch]
+                                    set_sensitive: !model.readonly,
+                                },
+                                gtk::MenuButton {
+                                    set_tooltip_text: Some("Show calendar"),
+                                    set_valign: gtk::Align::Center,
+                                    set_direction: gtk::ArrowType::Down,
+                                    add_css_class: "flat",
+                                    set_margin_end: 5,
+                                    #[wrap(Some)]
+                                    set_popover: end_calendar_popover = &gtk::Popover {
+                                        set_position: gtk::PositionType::Bottom,
+                                        #[wrap(Some)]
+                                        set_child: end_calendar = &gtk::Calendar {
+                                            #[watch]
+                                            set_sensitive: !model.readonly,
+                                            connect_day_selected[sender] => move |_calendar| {
+                                                sender.input(MessagesCacheSettingsDialogMsg::SetCacheEndTimestamp);
+                                            },
+                                        },
+                                    },
+                                },
+                                #[name(end_time_hours)]
+                                gtk::SpinButton {
+                                    set_xalign: 0.5,
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_wrap: true,
+                                    set_numeric: true,
+                                    set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                                    set_increments: (1.0, 1.0),
+                                    set_range: (0.0, 23.0),
+                                    set_digits: 0,
+                                    #[watch]
+                                    set_sensitive: !model.readonly,
+                                },
+                                gtk::Label { set_label: ":", set_margin_start: 2, set_margin_end: 2, },
+                                #[name(end_time_minutes)]
+                                gtk::SpinButton {
+                                    set_xalign: 0.5,
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_wrap: true,
+                                    set_numeric: true,
+                                    set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                                    set_increments: (1.0, 1.0),
+                                    set_range: (0.0, 59.0),
+                                    set_digits: 0,
+                                    #[watch]
+                                    set_sensitive: !model.readonly,
+                                },
+                                gtk::Label { set_label: ":", set_margin_start: 2, set_margin_end: 2, },
+                                #[name(end_time_seconds)]
+                                gtk::SpinButton {
+                                    set_xalign: 0.5,
+                                    set_orientation: gtk::Orientation::Vertical,
+                                    set_wrap: true,
+                                    set_numeric: true,
+                                    set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                                    set_increments: (1.0, 1.0),
+                                    set_range: (0.0, 59.0),
+                                    set_digits: 0,
+                                    #[watch]
+                                    set_sensitive: !model.readonly,
+                                },
+                            },
+                        },
+                        #[name(auto_refresh_minutes)]
+                        adw::SpinRow {
+                            set_title: "Auto refresh",
+                            set_subtitle: "Refresh the cache in the background every (n) minutes, 0 disables it",
+                            set_margin_top: 4,
+                            set_numeric: true,
+                            set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                            set_adjustment = Some(&auto_refresh_adjustment),
+                            #[watch]
+                            set_sensitive: !model.readonly,
+                            connect_value_notify => MessagesCacheSettingsDialogMsg::SetAutoRefresh,
+                        },
+                        adw::ActionRow {
+                            set_title: "Next refresh",
+                            add_css_class: "property",
+                            #[watch]
+                            set_subtitle: &model.next_refresh,
+                            #[watch]
+                            set_visible: !model.next_refresh.is_empty(),
+                        },
                         gtk::Button {
                             set_label: "Apply",
                             add_css_class: "suggested-action",
@@ -369,6 +541,9 @@ impl Component for MessagesCacheSettingsDialogModel {
             selected_default_page_size: None,
             confirmation_alert,
             clipboard,
+            compacting: false,
+            last_updated: None,
+            next_refresh: String::new(),
         };
         let default_page_size_combo = model.default_page_size_combo.widget();
         let offset_adjustment = Adjustment::builder()
@@ -378,6 +553,13 @@ impl Component for MessagesCacheSettingsDialogModel {
             .step_increment(1.0)
             .value(DEFAULT_MESSAGES_PER_PARTITION as f64)
             .build();
+        let auto_refresh_adjustment = Adjustment::builder()
+            .lower(0.0)
+            .upper(10080.0)
+            .step_increment(5.0)
+            .page_increment(60.0)
+            .value(0.0)
+            .build();
         let widgets = view_output!();
         ComponentParts { model, widgets }
     }
@@ -410,6 +592,16 @@ impl Component for MessagesCacheSettingsDialogModel {
                 let date_fmt = format!("{:02}/{:02}/{}", day, month, year);
                 widgets.formatted_date.set_text(date_fmt.as_str());
             }
+            MessagesCacheSettingsDialogMsg::SetCacheEndTimestamp => {
+                let date = widgets.end_calendar.date();
+                let date_fmt = format!(
+                    "{:02}/{:02}/{}",
+                    date.day_of_month(),
+                    date.month(),
+                    date.year()
+                );
+                widgets.end_formatted_date.set_text(date_fmt.as_str());
+            }
             MessagesCacheSettingsDialogMsg::Show => {
                 let connection = self.connection.clone();
                 let conn_id = connection.id.unwrap();
@@ -417,10 +609,12 @@ impl Component for MessagesCacheSettingsDialogModel {
 
                 if let Some(topic) = self.topic.clone() {
                     widgets.status_topic_name.set_title(&topic.name);
+                    self.refresh_cache_size(widgets);
                     if !&topic.partitions.is_empty() {
                         widgets
                             .status_topic_partitions
                             .set_title(&topic.partitions.len().to_string());
+                        show_partitions(widgets, &topic.partitions);
                     }
 
                     let cached = repository.find_topic_cache(conn_id, &topic.name);
@@ -428,7 +622,23 @@ impl Component for MessagesCacheSettingsDialogModel {
                     let default_page_size_idx = cached
                         .clone()
                         .map(|c| c.default_page_size as u32)
-                        .unwrap_or_default();
+                        .unwrap_or_else(|| {
+                            let page_size =
+                                Settings::read().unwrap_or_default().default_cache_page_size;
+                            AVAILABLE_PAGE_SIZES
+                                .iter()
+                                .position(|size| *size == page_size)
+                                .unwrap_or_default() as u32
+                        });
+                    self.selected_default_page_size = Some(default_page_size_idx as u16);
+                    self.last_updated = cached.as_ref().and_then(|c| c.last_updated);
+                    widgets.auto_refresh_minutes.set_value(
+                        cached
+                            .as_ref()
+                            .and_then(|c| c.auto_refresh_minutes)
+                            .unwrap_or_default() as f64,
+                    );
+                    self.next_refresh = self.next_refresh_text(widgets);
                     let fetch_mode = cached.clone().map(|c| c.fetch_mode).unwrap_or_default();
                     let fetch_value = cached
                         .clone()
@@ -449,19 +659,33 @@ impl Component for MessagesCacheSettingsDialogModel {
                         }
                         FetchMode::All => (),
                         FetchMode::FromTimestamp => {
-                            let date_time = Utc
-                                .timestamp_millis_opt(fetch_value)
-                                .unwrap()
-                                .with_timezone(&America::Sao_Paulo);
-                            let date = gtk::glib::DateTime::from_unix_utc(date_time.timestamp())
-                                .expect("glib DateTime from Utc");
-                            widgets.calendar.select_day(&date);
-                            let hours = date_time.hour();
-                            let minutes = date_time.minute();
-                            let seconds = date_time.second();
-                            widgets.time_hours.set_value(hours as f64);
-                            widgets.time_minutes.set_value(minutes as f64);
-                            widgets.time_seconds.set_value(seconds as f64);
+                            select_date_time(
+                                fetch_value,
+                                &widgets.calendar,
+                                &widgets.time_hours,
+                                &widgets.time_minutes,
+                                &widgets.time_seconds,
+                            );
+                        }
+                        FetchMode::TimeRange => {
+                            let fetch_value_end = cached
+                                .clone()
+                                .and_then(|c| c.fetch_value_end)
+                                .unwrap_or_default();
+                            select_date_time(
+                                fetch_value,
+                                &widgets.calendar,
+                                &widgets.time_hours,
+                                &widgets.time_minutes,
+                                &widgets.time_seconds,
+                            );
+                            select_date_time(
+                                fetch_value_end,
+                                &widgets.end_calendar,
+                                &widgets.end_time_hours,
+                                &widgets.end_time_minutes,
+                                &widgets.end_time_seconds,
+                            );
                         }
                     };
                     widgets
@@ -471,6 +695,7 @@ impl Component for MessagesCacheSettingsDialogModel {
                         .fetch_mode_stack
                         .set_visible_child_name(&fetch_mode.to_string());
                     sender.input(MessagesCacheSettingsDialogMsg::SetCacheTimestamp);
+                    sender.input(MessagesCacheSettingsDialogMsg::SetCacheEndTimestamp);
                     sender.input(MessagesCacheSettingsDialogMsg::RefreshTopicMessagesCounter);
 
                     let parent = &relm4::main_application().active_window().unwrap();
@@ -514,6 +739,29 @@ impl Component for MessagesCacheSettingsDialogModel {
                     "Applying settings[connection={:?}, topic={:?}]...",
                     &self.connection, &self.topic
                 );
+                if let Some(FetchMode::TimeRange) = self.selected_fetch_mode {
+                    let start = parse_date_time(
+                        &widgets.formatted_date,
+                        &widgets.time_hours,
+                        &widgets.time_minutes,
+                        &widgets.time_seconds,
+                    );
+                    let end = parse_date_time(
+                        &widgets.end_formatted_date,
+                        &widgets.end_time_hours,
+                        &widgets.end_time_minutes,
+                        &widgets.end_time_seconds,
+                    );
+                    if let (Some(start), Some(end)) = (start, end) {
+                        if end < start {
+                            show_error_alert(
+                                &widgets.main_dialog,
+                                "The end date/time must be after the start date/time".to_string(),
+                            );
+                            return;
+                        }
+                    }
+                }
                 let mut repo = Repository::new();
                 let connection = self.connection.clone();
                 let conn_id = connection.id.unwrap();
@@ -548,36 +796,37 @@ impl Component for MessagesCacheSettingsDialogModel {
                         refresh: false,
                     });
                     let fetch_value = match self.selected_fetch_mode {
-                        Some(FetchMode::FromTimestamp) => {
-                            let date_text = widgets.formatted_date.text().to_string();
-                            let hours = widgets.time_hours.value_as_int();
-                            let minutes = widgets.time_minutes.value_as_int();
-                            let seconds = widgets.time_seconds.value_as_int();
-                            let date_time_formatted =
-                                format!("{} {:02}:{:02}:{:02}", date_text, hours, minutes, seconds);
-                            let date_time = NaiveDateTime::parse_from_str(
-                                date_time_formatted.as_str(),
-                                DATE_TIME_FORMAT,
-                            );
-                            let now = Local::now();
-                            let offset = now.offset();
-                            let date_time = date_time.unwrap().and_local_timezone(*offset).unwrap();
-                            info!("date_time::{:?}", date_time);
-                            info!("date_time::utc::{}", date_time.timestamp_millis());
-                            Some(date_time.timestamp_millis())
+                        Some(FetchMode::FromTimestamp) | Some(FetchMode::TimeRange) => {
+                            parse_date_time(
+                                &widgets.formatted_date,
+                                &widgets.time_hours,
+                                &widgets.time_minutes,
+                                &widgets.time_seconds,
+                            )
                         }
                         Some(FetchMode::Head) => Some(widgets.first_n_messages.value() as i64),
                         Some(FetchMode::Tail) => Some(widgets.last_n_messages.value() as i64),
                         _ => None,
                     };
+                    let fetch_value_end = match self.selected_fetch_mode {
+                        Some(FetchMode::TimeRange) => parse_date_time(
+                            &widgets.end_formatted_date,
+                            &widgets.end_time_hours,
+                            &widgets.end_time_minutes,
+                            &widgets.end_time_seconds,
+                        ),
+                        _ => None,
+                    };
                     let default_page_size = self.selected_default_page_size.unwrap_or_default();
                     let cache = KrustTopicCache {
                         connection_id,
                         topic_name: topic_name.clone(),
                         fetch_mode: self.selected_fetch_mode.unwrap_or_default(),
                         fetch_value,
+                        fetch_value_end,
                         default_page_size,
                         last_updated: Some(Utc::now().timestamp_millis()),
+                        auto_refresh_minutes: self.auto_refresh_minutes(widgets),
                     };
                     let result = sender.output(MessagesCacheSettingsDialogOutput::Update(cache));
                     match result {
@@ -587,6 +836,43 @@ impl Component for MessagesCacheSettingsDialogModel {
                 }
                 root.close();
             }
+            MessagesCacheSettingsDialogMsg::CompactCache => {
+                let conn_id = self.connection.id.unwrap();
+                if let Some(topic) = self.topic.clone() {
+                    self.compacting = true;
+                    let task = Task::new(TaskVariant::CompactCache, Some(topic.name.clone()), None);
+                    TOASTER_BROKER.send(AppMsg::ShowToast(
+                        task.id.clone(),
+                        "Compacting cache...".to_string(),
+                    ));
+                    TASK_MANAGER_BROKER.send(TaskManagerMsg::AddTask(task.clone()));
+                    sender.spawn_oneshot_command(move || {
+                        let mut mrepo = MessagesRepository::new(conn_id, &topic.name);
+                        let result = mrepo.database_size_bytes().and_then(|before| {
+                            mrepo.vacuum()?;
+                            Ok((before, mrepo.database_size_bytes()?))
+                        });
+                        AsyncCommandOutput::CacheCompacted(task, result)
+                    });
+                }
+            }
+            MessagesCacheSettingsDialogMsg::SetAutoRefresh => {
+                let (Some(conn_id), Some(topic)) = (self.connection.id, self.topic.as_ref()) else {
+                    return;
+                };
+                // Only existing caches are updated here, new ones take the value on apply.
+                if self.last_updated.is_some() {
+                    let minutes = self.auto_refresh_minutes(widgets);
+                    if let Err(e) = Repository::new().save_topic_cache_auto_refresh(
+                        conn_id,
+                        &topic.name,
+                        minutes,
+                    ) {
+                        error!("unable to save cache auto refresh: {:?}", e);
+                    }
+                }
+                self.next_refresh = self.next_refresh_text(widgets);
+            }
             MessagesCacheSettingsDialogMsg::Ignore => {
                 info!("Ignore settings...");
             }
@@ -598,19 +884,145 @@ impl Component for MessagesCacheSettingsDialogModel {
         &mut self,
         widgets: &mut Self::Widgets,
         message: Self::CommandOutput,
-        _sender: ComponentSender<Self>,
+        sender: ComponentSender<Self>,
         _root: &Self::Root,
     ) {
         match message {
+            AsyncCommandOutput::CacheCompacted(task, result) => {
+                self.compacting = false;
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), 1.0));
+                TOASTER_BROKER.send(AppMsg::HideToast(task.id.clone()));
+                let text = match result {
+                    Ok((before, after)) => format!(
+                        "Cache compacted, reclaimed {}",
+                        format_size(before.saturating_sub(after), DECIMAL)
+                    ),
+                    Err(e) => {
+                        error!("unable to compact cache: {:?}", e);
+                        "Unable to compact cache".to_string()
+                    }
+                };
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(id.clone(), text));
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+                self.refresh_cache_size(widgets);
+            }
             AsyncCommandOutput::RefreshTopicMessagesCounter(topic) => {
                 info!("refresh topic messages counter async::{:?}", &topic);
+                if let Some(conn_id) = self.connection.id {
+                    record_partition_offsets(conn_id, &topic.name, &topic.partitions);
+                }
                 widgets
                     .status_topic_partitions
                     .set_title(&topic.partitions.len().to_string());
                 widgets
                     .status_topic_messages_count
                     .set_title(&topic.total.unwrap_or_default().to_string());
+                show_partitions(widgets, &topic.partitions);
             }
         }
+        self.update_view(widgets, sender);
+    }
+}
+
+impl MessagesCacheSettingsDialogModel {
+    fn refresh_cache_size(&self, widgets: &MessagesCacheSettingsDialogModelWidgets) {
+        let (Some(conn_id), Some(topic)) = (self.connection.id, self.topic.as_ref()) else {
+            return;
+        };
+        let size = MessagesRepository::new(conn_id, &topic.name)
+            .database_size_bytes()
+            .map(|size| format_size(size, DECIMAL))
+            .unwrap_or_else(|_| "No cache".to_string());
+        widgets.status_cache_size.set_title(&size);
+    }
+
+    fn auto_refresh_minutes(
+        &self,
+        widgets: &MessagesCacheSettingsDialogModelWidgets,
+    ) -> Option<i64> {
+        Some(widgets.auto_refresh_minutes.value() as i64).filter(|minutes| *minutes > 0)
+    }
+
+    fn next_refresh_text(&self, widgets: &MessagesCacheSettingsDialogModelWidgets) -> String {
+        let (Some(last_updated), Some(minutes)) =
+            (self.last_updated, self.auto_refresh_minutes(widgets))
+        else {
+            return String::new();
+        };
+        let settings = Settings::read().unwrap_or_default();
+        format_timestamp(
+            Some(last_updated + minutes * 60_000),
+            &settings.timestamp_formatter(),
+            &settings.timezone(),
+        )
+        .unwrap_or_default()
+    }
+}
+
+/// Converts the date entry and time spinners into epoch millis in the configured timezone.
+fn parse_date_time(
+    formatted_date: &gtk::Entry,
+    time_hours: &gtk::SpinButton,
+    time_minutes: &gtk::SpinButton,
+    time_seconds: &gtk::SpinButton,
+) -> Option<i64> {
+    let date_time_formatted = format!(
+        "{} {:02}:{:02}:{:02}",
+        formatted_date.text(),
+        time_hours.value_as_int(),
+        time_minutes.value_as_int(),
+        time_seconds.value_as_int()
+    );
+    let timezone = Settings::read().unwrap_or_default().timezone();
+    let date_time = NaiveDateTime::parse_from_str(date_time_formatted.as_str(), DATE_TIME_FORMAT)
+        .map_err(|e| warn!("invalid date/time {}: {}", date_time_formatted, e))
+        .ok()?
+        .and_local_timezone(timezone)
+        .earliest()?;
+    info!("date_time::{:?}", date_time);
+    info!("date_time::utc::{}", date_time.timestamp_millis());
+    Some(date_time.timestamp_millis())
+}
+
+/// Selects the given epoch millis on the calendar and time spinners.
+fn select_date_time(
+    timestamp: i64,
+    calendar: &gtk::Calendar,
+    time_hours: &gtk::SpinButton,
+    time_minutes: &gtk::SpinButton,
+    time_seconds: &gtk::SpinButton,
+) {
+    let Some(date_time) = Utc.timestamp_millis_opt(timestamp).single() else {
+        warn!("invalid timestamp {}", timestamp);
+        return;
+    };
+    let date_time = date_time.with_timezone(&Settings::read().unwrap_or_default().timezone());
+    let date =
+        gtk::glib::DateTime::from_unix_utc(date_time.timestamp()).expect("glib DateTime from Utc");
+    calendar.select_day(&date);
+    time_hours.set_value(date_time.hour() as f64);
+    time_minutes.set_value(date_time.minute() as f64);
+    time_seconds.set_value(date_time.second() as f64);
+}
+
+/// Lists the low/high offsets of each partition, sorted by partition id.
+fn show_partitions(widgets: &MessagesCacheSettingsDialogModelWidgets, partitions: &[Partition]) {
+    widgets.partitions_list.remove_all();
+    let mut partitions = partitions.to_vec();
+    partitions.sort_by_key(|p| p.id);
+    for partition in partitions {
+        let low = partition.offset_low.unwrap_or_default();
+        let high = partition.offset_high.unwrap_or_default();
+        let row = adw::ActionRow::builder()
+            .title(format!("Partition {}", partition.id))
+            .subtitle(format!(
+                "Low: {}, High: {}, Messages: {}",
+                low,
+                high,
+                high - low
+            ))
+            .build();
+        widgets.partitions_list.append(&row);
     }
 }
@@ -0,0 +1,220 @@
+// Copyright (c) 2024, Miguel A. Baldi Hörlle <miguel.horlle@gmail.com>. All rights reserved. Use of
+// this source code is governed by the GPL-3.0 license that can be
+// found in the COPYING file.
+
+use adw::prelude::*;
+use relm4::*;
+use similar::{ChangeTag, TextDiff};
+use tracing::*;
+
+use crate::backend::repository::KrustMessage;
+
+const DIFF_DELETE_TAG: &str = "diff-delete";
+const DIFF_INSERT_TAG: &str = "diff-insert";
+const DIFF_EMPHASIS_TAG: &str = "diff-emphasis";
+
+#[derive(Debug)]
+pub struct MessagesCompareDialogModel {}
+
+#[derive(Debug)]
+pub enum MessagesCompareDialogMsg {
+    Show(Box<KrustMessage>, Box<KrustMessage>),
+    Close,
+}
+
+#[relm4::component(pub)]
+impl Component for MessagesCompareDialogModel {
+    type Init = ();
+    type Input = MessagesCompareDialogMsg;
+    type Output = ();
+    type CommandOutput = ();
+
+    view! {
+        #[root]
+        main_dialog = adw::Dialog {
+            set_title: "Compare messages",
+            set_content_width: 900,
+            set_content_height: 600,
+            #[wrap(Some)]
+            set_child = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                adw::HeaderBar {
+                    #[wrap(Some)]
+                    set_title_widget = &gtk::StackSwitcher {
+                        set_stack: Some(&diff_stack),
+                    },
+                },
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 10,
+                    set_vexpand: true,
+                    #[name(legend)]
+                    gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_margin_bottom: 5,
+                        add_css_class: "dim-label",
+                    },
+                    #[name(diff_stack)]
+                    gtk::Stack {
+                        set_vexpand: true,
+                        add_titled[Some("value"), "Value"] = &gtk::ScrolledWindow {
+                            #[name(value_diff)]
+                            gtk::TextView {
+                                set_editable: false,
+                                set_cursor_visible: false,
+                                set_monospace: true,
+                                set_wrap_mode: gtk::WrapMode::WordChar,
+                            },
+                        },
+                        add_titled[Some("headers"), "Headers"] = &gtk::ScrolledWindow {
+                            #[name(headers_diff)]
+                            gtk::TextView {
+                                set_editable: false,
+                                set_cursor_visible: false,
+                                set_monospace: true,
+                                set_wrap_mode: gtk::WrapMode::WordChar,
+                            },
+                        },
+                    },
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_halign: gtk::Align::End,
+                        set_margin_top: 10,
+                        gtk::Button {
+                            set_label: "Close",
+                            connect_clicked => MessagesCompareDialogMsg::Close,
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        _init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = MessagesCompareDialogModel {};
+        let widgets = view_output!();
+        add_diff_tags(&widgets.value_diff.buffer());
+        add_diff_tags(&widgets.headers_diff.buffer());
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        msg: MessagesCompareDialogMsg,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match msg {
+            MessagesCompareDialogMsg::Show(old, new) => {
+                info!(
+                    "comparing messages[{}@{}, {}@{}]",
+                    old.partition, old.offset, new.partition, new.offset
+                );
+                widgets.legend.set_label(&format!(
+                    "− partition {}, offset {}    + partition {}, offset {}",
+                    old.partition, old.offset, new.partition, new.offset
+                ));
+                render_diff(
+                    &widgets.value_diff.buffer(),
+                    &diffable_value(old.value.as_deref().unwrap_or_default()),
+                    &diffable_value(new.value.as_deref().unwrap_or_default()),
+                );
+                render_diff(
+                    &widgets.headers_diff.buffer(),
+                    &diffable_headers(&old),
+                    &diffable_headers(&new),
+                );
+                let parent = &relm4::main_application().active_window().unwrap();
+                root.present(parent);
+            }
+            MessagesCompareDialogMsg::Close => {
+                root.close();
+            }
+        };
+
+        self.update_view(widgets, sender);
+    }
+}
+
+fn add_diff_tags(buffer: &gtk::TextBuffer) {
+    let tags = buffer.tag_table();
+    tags.add(
+        &gtk::TextTag::builder()
+            .name(DIFF_DELETE_TAG)
+            .paragraph_background("rgba(224, 27, 36, 0.2)")
+            .build(),
+    );
+    tags.add(
+        &gtk::TextTag::builder()
+            .name(DIFF_INSERT_TAG)
+            .paragraph_background("rgba(46, 194, 126, 0.2)")
+            .build(),
+    );
+    tags.add(
+        &gtk::TextTag::builder()
+            .name(DIFF_EMPHASIS_TAG)
+            .weight(700)
+            .underline(gtk::pango::Underline::Single)
+            .build(),
+    );
+}
+
+/// JSON values are compared in their pretty-printed form, so changes show up per field.
+fn diffable_value(value: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(value)
+        .ok()
+        .and_then(|json| serde_json::to_string_pretty(&json).ok())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// One `key: value` line per header, sorted so only actual changes are reported.
+fn diffable_headers(message: &KrustMessage) -> String {
+    let mut lines = message
+        .headers
+        .iter()
+        .map(|h| format!("{}: {}", h.key, h.value.clone().unwrap_or_default()))
+        .collect::<Vec<_>>();
+    lines.sort();
+    lines.join("\n")
+}
+
+/// Writes a line diff of both texts, emphasizing the changed characters of each line.
+fn render_diff(buffer: &gtk::TextBuffer, old: &str, new: &str) {
+    buffer.set_text("");
+    let diff = TextDiff::from_lines(old, new);
+    for op in diff.ops() {
+        for change in diff.iter_inline_changes(op) {
+            let (sign, line_tag) = match change.tag() {
+                ChangeTag::Delete => ("- ", Some(DIFF_DELETE_TAG)),
+                ChangeTag::Insert => ("+ ", Some(DIFF_INSERT_TAG)),
+                ChangeTag::Equal => ("  ", None),
+            };
+            let line_start = buffer.create_mark(None, &buffer.end_iter(), true);
+            buffer.insert(&mut buffer.end_iter(), sign);
+            for (emphasized, text) in change.iter_strings_lossy() {
+                if emphasized {
+                    buffer.insert_with_tags_by_name(
+                        &mut buffer.end_iter(),
+                        &text,
+                        &[DIFF_EMPHASIS_TAG],
+                    );
+                } else {
+                    buffer.insert(&mut buffer.end_iter(), &text);
+                }
+            }
+            if change.missing_newline() {
+                buffer.insert(&mut buffer.end_iter(), "\n");
+            }
+            if let Some(line_tag) = line_tag {
+                let start = buffer.iter_at_mark(&line_start);
+                buffer.apply_tag_by_name(line_tag, &start, &buffer.end_iter());
+            }
+            buffer.delete_mark(&line_start);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright (c) 2024, Miguel A. Baldi Hörlle <miguel.horlle@gmail.com>. All rights reserved. Use of
+// this source code is governed by the GPL-3.0 license that can be
+// found in the COPYING file.
+
+use adw::prelude::*;
+use gtk::Adjustment;
+use relm4::*;
+use tracing::*;
+
+use crate::backend::repository::KrustTopic;
+use crate::modals::utils::show_error_alert;
+
+#[derive(Debug)]
+pub struct MessagesDeleteRangeDialogModel {
+    pub topic: Option<KrustTopic>,
+}
+
+#[derive(Debug)]
+pub enum MessagesDeleteRangeDialogMsg {
+    Show(Option<KrustTopic>),
+    Delete,
+    Cancel,
+}
+
+#[derive(Debug)]
+pub enum MessagesDeleteRangeDialogOutput {
+    Delete {
+        partition: i32,
+        offset_from: i64,
+        offset_to: i64,
+    },
+}
+
+#[relm4::component(pub)]
+impl Component for MessagesDeleteRangeDialogModel {
+    type Init = Option<KrustTopic>;
+    type Input = MessagesDeleteRangeDialogMsg;
+    type Output = MessagesDeleteRangeDialogOutput;
+    type CommandOutput = ();
+
+    view! {
+        #[root]
+        main_dialog = adw::Dialog {
+            set_title: "Delete cached messages",
+            set_content_width: 480,
+            #[wrap(Some)]
+            set_child = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                adw::HeaderBar {},
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 10,
+                    adw::PreferencesGroup {
+                        set_title: "Offset range",
+                        set_description: Some("Both offsets are inclusive"),
+                        #[name(partition)]
+                        adw::SpinRow {
+                            set_title: "Partition",
+                            set_numeric: true,
+                            set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                            set_adjustment: Some(&partition_adjustment),
+                        },
+                        #[name(offset_from)]
+                        adw::SpinRow {
+                            set_title: "From offset",
+                            set_numeric: true,
+                            set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                            set_adjustment: Some(&offset_from_adjustment),
+                        },
+                        #[name(offset_to)]
+                        adw::SpinRow {
+                            set_title: "To offset",
+                            set_numeric: true,
+                            set_update_policy: gtk::SpinButtonUpdatePolicy::IfValid,
+                            set_adjustment: Some(&offset_to_adjustment),
+                        },
+                    },
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_halign: gtk::Align::End,
+                        set_margin_top: 10,
+                        gtk::Button {
+                            set_label: "Cancel",
+                            connect_clicked => MessagesDeleteRangeDialogMsg::Cancel,
+                        },
+                        gtk::Button {
+                            set_label: "Delete",
+                            set_margin_start: 5,
+                            add_css_class: "destructive-action",
+                            connect_clicked => MessagesDeleteRangeDialogMsg::Delete,
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        topic: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = MessagesDeleteRangeDialogModel { topic };
+        let partition_adjustment = Adjustment::builder()
+            .lower(0.0)
+            .upper(i32::MAX as f64)
+            .step_increment(1.0)
+            .build();
+        let offset_from_adjustment = Adjustment::builder()
+            .lower(0.0)
+            .upper(i64::MAX as f64)
+            .step_increment(1.0)
+            .build();
+        let offset_to_adjustment = Adjustment::builder()
+            .lower(0.0)
+            .upper(i64::MAX as f64)
+            .step_increment(1.0)
+            .build();
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        msg: MessagesDeleteRangeDialogMsg,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match msg {
+            MessagesDeleteRangeDialogMsg::Show(topic) => {
+                self.topic = topic;
+                let partitions = self
+                    .topic
+                    .as_ref()
+                    .map(|t| t.partitions.len())
+                    .unwrap_or_default();
+                if partitions > 0 {
+                    widgets
+                        .partition
+                        .adjustment()
+                        .set_upper((partitions - 1) as f64);
+                }
+                let parent = &relm4::main_application().active_window().unwrap();
+                root.present(parent);
+            }
+            MessagesDeleteRangeDialogMsg::Delete => {
+                let partition = widgets.partition.value() as i32;
+                let offset_from = widgets.offset_from.value() as i64;
+                let offset_to = widgets.offset_to.value() as i64;
+                if offset_from > offset_to {
+                    show_error_alert(
+                        root,
+                        "The from offset must not be greater than the to offset".to_string(),
+                    );
+                    return;
+                }
+                info!(
+                    "deleting cached messages[partition={}, from={}, to={}]",
+                    partition, offset_from, offset_to
+                );
+                sender
+                    .output(MessagesDeleteRangeDialogOutput::Delete {
+                        partition,
+                        offset_from,
+                        offset_to,
+                    })
+                    .unwrap_or_else(|e| error!("delete range output error: {:?}", e));
+                root.close();
+            }
+            MessagesDeleteRangeDialogMsg::Cancel => {
+                root.close();
+            }
+        };
+
+        self.update_view(widgets, sender);
+    }
+}
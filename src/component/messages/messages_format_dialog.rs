@@ -0,0 +1,300 @@
+// Copyright (c) 2024, Miguel A. Baldi Hörlle <miguel.horlle@gmail.com>. All rights reserved. Use of
+// this source code is governed by the GPL-3.0 license that can be
+// found in the COPYING file.
+
+use std::path::PathBuf;
+
+use adw::prelude::*;
+use relm4::*;
+use relm4_components::open_dialog::{
+    OpenDialog, OpenDialogMsg, OpenDialogResponse, OpenDialogSettings,
+};
+use relm4_components::simple_adw_combo_row::{SimpleComboRow, SimpleComboRowMsg};
+use tracing::*;
+
+use crate::backend::payload::{compile_json_schema, ProtobufDecoder};
+use crate::backend::repository::{
+    KeyFormat, KrustConnection, KrustTopic, KrustTopicFormat, ValueFormat,
+};
+use crate::modals::utils::show_error_alert;
+use crate::Repository;
+
+pub struct MessagesFormatDialogModel {
+    pub connection: KrustConnection,
+    pub topic: Option<KrustTopic>,
+    descriptor_dialog: Controller<OpenDialog>,
+    key_format_combo: Controller<SimpleComboRow<KeyFormat>>,
+    value_format_combo: Controller<SimpleComboRow<ValueFormat>>,
+}
+
+#[derive(Debug)]
+pub enum MessagesFormatDialogMsg {
+    Show,
+    ChooseDescriptor,
+    OpenDescriptor(PathBuf),
+    Save,
+    Cancel,
+    Ignore,
+}
+
+#[relm4::component(pub)]
+impl Component for MessagesFormatDialogModel {
+    type Init = (KrustConnection, Option<KrustTopic>);
+    type Input = MessagesFormatDialogMsg;
+    type Output = ();
+    type CommandOutput = ();
+
+    view! {
+        #[root]
+        main_dialog = adw::Dialog {
+            set_title: "Payload format",
+            set_content_width: 480,
+            #[wrap(Some)]
+            set_child = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                adw::HeaderBar {},
+                gtk::Box {
+                    set_orientation: gtk::Orientation::Vertical,
+                    set_margin_all: 10,
+                    adw::PreferencesGroup {
+                        set_title: "Key",
+                        set_description: Some("Avro keys are decoded through the connection schema registry"),
+                        model.key_format_combo.widget() -> &adw::ComboRow {
+                            set_title: "Key format",
+                        },
+                    },
+                    adw::PreferencesGroup {
+                        set_title: "Value",
+                        set_margin_top: 10,
+                        set_description: Some("Values that don't match the format are shown as raw text"),
+                        model.value_format_combo.widget() -> &adw::ComboRow {
+                            set_title: "Value format",
+                        },
+                    },
+                    adw::PreferencesGroup {
+                        set_title: "Value (Protobuf)",
+                        set_margin_top: 10,
+                        set_description: Some("Decode values with a compiled FileDescriptorSet, overrides the value format"),
+                        #[name(descriptor_entry)]
+                        adw::EntryRow {
+                            set_title: "Descriptor set file",
+                            add_suffix = &gtk::Button {
+                                set_tooltip_text: Some("Select file"),
+                                set_icon_name: "document-open-symbolic",
+                                set_valign: gtk::Align::Center,
+                                add_css_class: "flat",
+                                connect_clicked => MessagesFormatDialogMsg::ChooseDescriptor,
+                            },
+                        },
+                        #[name(message_entry)]
+                        adw::EntryRow {
+                            set_title: "Fully-qualified message name",
+                        },
+                    },
+                    adw::PreferencesGroup {
+                        set_title: "Value (JSON Schema)",
+                        set_margin_top: 10,
+                        set_description: Some("Checked before producing when JSON validation is enabled, optional"),
+                        gtk::ScrolledWindow {
+                            set_hexpand: true,
+                            set_min_content_height: 100,
+                            set_overflow: gtk::Overflow::Hidden,
+                            add_css_class: "entry",
+                            #[name(json_schema_view)]
+                            gtk::TextView {
+                                set_top_margin: 5,
+                                set_left_margin: 5,
+                                set_monospace: true,
+                                add_css_class: "message-textview",
+                            },
+                        },
+                    },
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Horizontal,
+                        set_halign: gtk::Align::End,
+                        set_margin_top: 10,
+                        gtk::Button {
+                            set_label: "Cancel",
+                            connect_clicked => MessagesFormatDialogMsg::Cancel,
+                        },
+                        gtk::Button {
+                            set_label: "Save",
+                            set_margin_start: 5,
+                            add_css_class: "suggested-action",
+                            connect_clicked => MessagesFormatDialogMsg::Save,
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        init: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let (connection, topic) = init;
+        let descriptor_dialog = OpenDialog::builder()
+            .transient_for_native(&root)
+            .launch(OpenDialogSettings {
+                folder_mode: false,
+                accept_label: String::from("Select"),
+                cancel_label: String::from("Cancel"),
+                create_folders: false,
+                is_modal: true,
+                filters: Vec::new(),
+            })
+            .forward(sender.input_sender(), |response| match response {
+                OpenDialogResponse::Accept(path) => MessagesFormatDialogMsg::OpenDescriptor(path),
+                OpenDialogResponse::Cancel => MessagesFormatDialogMsg::Ignore,
+            });
+        let key_format_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: KeyFormat::VALUES.to_vec(),
+                active_index: Some(0),
+            })
+            .detach();
+        let value_format_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: ValueFormat::VALUES.to_vec(),
+                active_index: Some(0),
+            })
+            .detach();
+        let model = MessagesFormatDialogModel {
+            connection,
+            topic,
+            descriptor_dialog,
+            key_format_combo,
+            value_format_combo,
+        };
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        msg: MessagesFormatDialogMsg,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        debug!("received message: {:?}", msg);
+        match msg {
+            MessagesFormatDialogMsg::Show => {
+                let format = self.find_format().unwrap_or_default();
+                widgets
+                    .descriptor_entry
+                    .set_text(format.protobuf_descriptor.unwrap_or_default().as_str());
+                widgets
+                    .message_entry
+                    .set_text(format.protobuf_message.unwrap_or_default().as_str());
+                widgets
+                    .json_schema_view
+                    .buffer()
+                    .set_text(format.value_json_schema.unwrap_or_default().as_str());
+                let key_format_idx = KeyFormat::VALUES
+                    .iter()
+                    .position(|f| *f == format.key_format)
+                    .unwrap_or_default();
+                self.key_format_combo
+                    .emit(SimpleComboRowMsg::SetActiveIdx(key_format_idx));
+                let value_format_idx = ValueFormat::VALUES
+                    .iter()
+                    .position(|f| *f == format.value_format)
+                    .unwrap_or_default();
+                self.value_format_combo
+                    .emit(SimpleComboRowMsg::SetActiveIdx(value_format_idx));
+                let parent = &relm4::main_application().active_window().unwrap();
+                root.present(parent);
+            }
+            MessagesFormatDialogMsg::ChooseDescriptor => {
+                self.descriptor_dialog.emit(OpenDialogMsg::Open);
+            }
+            MessagesFormatDialogMsg::OpenDescriptor(path) => {
+                if let Some(path) = path.to_str() {
+                    widgets.descriptor_entry.set_text(path);
+                }
+            }
+            MessagesFormatDialogMsg::Save => {
+                let (Some(conn_id), Some(topic)) = (self.connection.id, &self.topic) else {
+                    return;
+                };
+                let descriptor = match widgets.descriptor_entry.text().trim() {
+                    "" => None,
+                    vstr => Some(vstr.to_string()),
+                };
+                let message = match widgets.message_entry.text().trim() {
+                    "" => None,
+                    vstr => Some(vstr.to_string()),
+                };
+                match (&descriptor, &message) {
+                    (Some(path), Some(message)) => {
+                        if let Err(e) = ProtobufDecoder::load(path, message) {
+                            show_error_alert(root, format!("Invalid protobuf settings: {}", e));
+                            return;
+                        }
+                    }
+                    (None, None) => (),
+                    _ => {
+                        show_error_alert(
+                            root,
+                            "Both the descriptor set file and the message name are required"
+                                .to_string(),
+                        );
+                        return;
+                    }
+                }
+                let buffer = widgets.json_schema_view.buffer();
+                let (start, end) = buffer.bounds();
+                let json_schema = buffer.text(&start, &end, true).to_string();
+                let json_schema = if json_schema.trim().is_empty() {
+                    None
+                } else {
+                    Some(json_schema)
+                };
+                if let Some(Err(e)) = json_schema.as_deref().map(compile_json_schema) {
+                    show_error_alert(root, e);
+                    return;
+                }
+                let format = KrustTopicFormat {
+                    connection_id: conn_id,
+                    topic_name: topic.name.clone(),
+                    protobuf_descriptor: descriptor,
+                    protobuf_message: message,
+                    key_format: self
+                        .key_format_combo
+                        .model()
+                        .get_active_elem()
+                        .copied()
+                        .unwrap_or_default(),
+                    value_format: self
+                        .value_format_combo
+                        .model()
+                        .get_active_elem()
+                        .copied()
+                        .unwrap_or_default(),
+                    value_json_schema: json_schema,
+                };
+                info!("saving topic format::{:?}", &format);
+                match Repository::new().save_topic_format(&format) {
+                    Ok(_) => root.close(),
+                    Err(e) => show_error_alert(root, format!("{}", e)),
+                };
+            }
+            MessagesFormatDialogMsg::Cancel => {
+                root.close();
+            }
+            MessagesFormatDialogMsg::Ignore => (),
+        };
+        self.update_view(widgets, sender);
+    }
+}
+
+impl MessagesFormatDialogModel {
+    fn find_format(&self) -> Option<KrustTopicFormat> {
+        let conn_id = self.connection.id?;
+        let topic = self.topic.as_ref()?;
+        Repository::new().find_topic_format(conn_id, &topic.name)
+    }
+}
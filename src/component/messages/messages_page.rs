@@ -8,6 +8,7 @@
 use crate::{
     backend::repository::{KrustConnection, KrustTopic},
     component::{colorize_widget_by_connection, get_tab_by_title},
+    config::OpenTab,
     AppMsg, Repository, TOASTER_BROKER,
 };
 use adw::prelude::*;
@@ -18,7 +19,10 @@ use relm4::{actions::RelmAction, factory::FactoryVecDeque, *};
 use tracing::*;
 use uuid::Uuid;
 
-use super::messages_tab::{MessagesTabInit, MessagesTabModel, MessagesTabMsg};
+use super::messages_tab::{
+    MessagesPageActionGroup, MessagesSearchAction, MessagesTabInit, MessagesTabModel,
+    MessagesTabMsg,
+};
 
 relm4::new_action_group!(pub(super) TopicTabActionGroup, "topic-tab");
 relm4::new_stateless_action!(pub(super) PinTabAction, TopicTabActionGroup, "toggle-pin");
@@ -41,6 +45,7 @@ pub enum MessagesPageMsg {
     MenuPageClosed,
     MenuPagePin,
     CopyTopicName,
+    FocusSearch,
     RefreshTopicTab {
         connection_id: usize,
         topic_name: String,
@@ -132,6 +137,13 @@ impl Component for MessagesPageModel {
         topics_tabs_actions.add_action(pin_tab_action);
         topics_tabs_actions.add_action(copy_topic_name_action);
         topics_tabs_actions.register_for_widget(&widgets.topics_tabs);
+        let mut page_actions = relm4::actions::RelmActionGroup::<MessagesPageActionGroup>::new();
+        let page_sender = sender.input_sender().clone();
+        let search_action = RelmAction::<MessagesSearchAction>::new_stateless(move |_| {
+            page_sender.send(MessagesPageMsg::FocusSearch).unwrap();
+        });
+        page_actions.add_action(search_action);
+        page_actions.register_for_widget(&root);
         let clipboard = Box::new(ClipboardContext::new().unwrap());
         let model = MessagesPageModel {
             topic: None,
@@ -204,6 +216,13 @@ impl Component for MessagesPageModel {
                     widgets.topics_viewer.set_page_pinned(&page, pinned);
                 }
             }
+            MessagesPageMsg::FocusSearch => {
+                if let Some(page) = widgets.topics_viewer.selected_page() {
+                    let position = widgets.topics_viewer.page_position(&page);
+                    self.topics
+                        .send(position as usize, MessagesTabMsg::FocusSearch);
+                }
+            }
             MessagesPageMsg::CopyTopicName => {
                 let page = widgets.topics_viewer.selected_page();
                 if let Some(page) = page {
@@ -294,6 +313,30 @@ impl Component for MessagesPageModel {
 }
 
 impl MessagesPageModel {
+    /// Open tabs in tab order, along with the selected one.
+    pub fn open_tabs(&self) -> (Vec<OpenTab>, Option<OpenTab>) {
+        let viewer = self.topics.widget();
+        let find_tab = |page: &TabPage| {
+            self.topics.iter().find_map(|tp| {
+                let conn = tp.connection.as_ref()?;
+                let topic = tp.topic.as_ref()?;
+                let title = format!("[{}] {}", conn.name, topic.name);
+                if title == page.title() {
+                    Some(OpenTab {
+                        connection_id: conn.id?,
+                        topic_name: topic.name.clone(),
+                    })
+                } else {
+                    None
+                }
+            })
+        };
+        let tabs = (0..viewer.n_pages())
+            .filter_map(|i| find_tab(&viewer.nth_page(i)))
+            .collect();
+        let active = viewer.selected_page().and_then(|page| find_tab(&page));
+        (tabs, active)
+    }
     fn get_tab_page_by_title(
         &self,
         widgets: &mut MessagesPageModelWidgets,
@@ -2,19 +2,50 @@
 // this source code is governed by the GPL-3.0 license that can be
 // found in the COPYING file.
 
-use std::cell::RefCell;
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+};
 
 use adw::prelude::*;
 use gtk::{gdk::DisplayManager, glib::SignalHandlerId};
+use once_cell::sync::Lazy;
+use rdkafka::error::KafkaError;
+use regex::Regex;
 use relm4::*;
-use relm4_components::simple_adw_combo_row::{SimpleComboRow, SimpleComboRowMsg};
+use relm4_components::{
+    open_dialog::{OpenDialog, OpenDialogMsg, OpenDialogResponse, OpenDialogSettings},
+    simple_adw_combo_row::{SimpleComboRow, SimpleComboRowMsg},
+};
 use tracing::*;
 
-use crate::backend::{
-    kafka::KafkaBackend,
-    repository::{KrustConnection, KrustMessage, KrustTopic},
+use crate::{
+    backend::{
+        kafka::{KafkaBackend, ProducerAcks, SendOptions, PARTITION_BY_KEY},
+        payload::{compile_json_schema, validate_json},
+        repository::{KrustConnection, KrustHeader, KrustMessage, KrustTopic, Repository},
+    },
+    component::task_manager::{Task, TaskManagerMsg, TaskVariant, TASK_MANAGER_BROKER},
+    config::ExternalError,
+    modals::utils::{send_failures_message, show_error_alert},
+    Settings,
 };
 
+const AUTO_PARTITION_LABEL: &str = "Auto (by key)";
+static PLACEHOLDER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*(?P<name>[\w.-]+)\s*\}\}").unwrap());
+/// UI state keys for the per topic payload history and the keep payload option.
+const PAYLOAD_HISTORY_STATE: &str = "produce_history";
+const KEEP_PAYLOAD_STATE: &str = "produce_keep_payload";
+/// UI state key for the per topic value template.
+const VALUE_TEMPLATE_STATE: &str = "produce_value_template";
+/// Number of produced values remembered per topic.
+const PAYLOAD_HISTORY_SIZE: usize = 10;
+/// Characters of a remembered value shown in the history list.
+const PAYLOAD_PREVIEW_CHARS: usize = 80;
+
 #[derive(Debug, Clone, Copy, Default)]
 pub enum MultiFormat {
     Key,
@@ -39,6 +70,14 @@ impl ToString for MultiFormat {
     }
 }
 
+#[derive(Debug)]
+pub struct HeaderRow {
+    id: usize,
+    container: gtk::Box,
+    key: gtk::Entry,
+    value: gtk::Entry,
+}
+
 #[derive(Debug)]
 pub struct MessagesSendDialogModel {
     pub connection: Option<KrustConnection>,
@@ -47,8 +86,24 @@ pub struct MessagesSendDialogModel {
     pub selected_partition: Option<i32>,
     pub multi_format_combo: Controller<SimpleComboRow<MultiFormat>>,
     pub selected_multi_format: Option<MultiFormat>,
+    pub acks_combo: Controller<SimpleComboRow<ProducerAcks>>,
+    pub selected_acks: ProducerAcks,
     pub is_multiple: bool,
+    pub is_tombstone: bool,
     pub signal_handlers: Vec<RefCell<Option<SignalHandlerId>>>,
+    pub header_rows: Vec<HeaderRow>,
+    pub next_header_id: usize,
+    pub file_dialog: Controller<OpenDialog>,
+    /// Keeps key and value in the editor after a successful send.
+    pub keep_payload: bool,
+    /// Last produced values of the topic, newest first.
+    pub payload_history: Vec<String>,
+    /// Value being sent, added to the history once the send succeeds.
+    pub sending_payload: Option<String>,
+    /// Checks values are JSON, and match the topic JSON Schema if any, before sending.
+    pub validate_json: bool,
+    /// Value the editor starts with for the topic, may hold `{{placeholder}}` tokens.
+    pub value_template: Option<String>,
 }
 
 #[derive(Debug)]
@@ -57,7 +112,21 @@ pub enum MessagesSendDialogMsg {
     PartitionSelected(usize),
     LoadPartitions,
     ToggleMultipleMessages(bool),
+    ToggleTombstone(bool),
+    ToggleKeepPayload(bool),
+    ToggleValidateJson(bool),
+    UseHistoryPayload(usize),
+    SaveTemplate,
+    RemoveTemplate,
+    FillTemplate,
+    ApplyTemplate(Vec<(String, String)>),
     MultiFormatSelected(usize),
+    AcksSelected(usize),
+    AddHeader,
+    RemoveHeader(usize),
+    ChooseFile,
+    SendFile(PathBuf),
+    Ignore,
     Cancel,
     Send,
     RecalculateDialogSize,
@@ -67,7 +136,8 @@ pub enum MessagesSendDialogMsg {
 #[derive(Debug)]
 pub enum AsyncCommandOutput {
     SetPartitions(Vec<String>),
-    SendResult,
+    SendResult(Result<(), Vec<(KrustMessage, KafkaError)>>),
+    SendFileError(ExternalError),
 }
 
 impl Drop for MessagesSendDialogModel {
@@ -127,6 +197,30 @@ impl Component for MessagesSendDialogModel {
                                 sender.input(MessagesSendDialogMsg::ToggleMultipleMessages(b.is_active()));
                             },
                         },
+                        #[name(toggle_tombstone)]
+                        adw::SwitchRow {
+                            set_title: "Tombstone",
+                            set_subtitle: "Send records with a key and no value",
+                            connect_active_notify[sender] => move |b| {
+                                sender.input(MessagesSendDialogMsg::ToggleTombstone(b.is_active()));
+                            },
+                        },
+                        #[name(toggle_keep_payload)]
+                        adw::SwitchRow {
+                            set_title: "Keep payload",
+                            set_subtitle: "Keep key and value after sending to tweak and resend",
+                            connect_active_notify[sender] => move |b| {
+                                sender.input(MessagesSendDialogMsg::ToggleKeepPayload(b.is_active()));
+                            },
+                        },
+                        #[name(toggle_validate_json)]
+                        adw::SwitchRow {
+                            set_title: "Validate JSON",
+                            set_subtitle: "Check values against the topic JSON Schema before sending",
+                            connect_active_notify[sender] => move |b| {
+                                sender.input(MessagesSendDialogMsg::ToggleValidateJson(b.is_active()));
+                            },
+                        },
                         #[local_ref]
                         multi_format_combo -> adw::ComboRow {
                             set_title: "Format",
@@ -139,7 +233,42 @@ impl Component for MessagesSendDialogModel {
                             set_show_apply_button: true,
                             set_text: ",",
                             set_visible: false,
-                        }
+                        },
+                        #[local_ref]
+                        acks_combo -> adw::ComboRow {
+                            set_title: "Acks",
+                            set_subtitle: "Replica acknowledgements required per record",
+                            set_use_subtitle: true,
+                        },
+                        #[name(message_timeout_entry)]
+                        adw::SpinRow {
+                            set_title: "Delivery timeout",
+                            set_subtitle: "Timeout in milliseconds before a send fails",
+                            set_snap_to_ticks: false,
+                            set_numeric: true,
+                            set_wrap: false,
+                        },
+                    },
+                    #[name(headers_group)]
+                    adw::PreferencesGroup {
+                        set_title: "Headers",
+                        set_description: Some("Applied to every message sent"),
+                        set_margin_top: 10,
+                        set_vexpand: false,
+                        set_hexpand: true,
+                        #[wrap(Some)]
+                        set_header_suffix = &gtk::Button {
+                            set_tooltip_text: Some("Add header"),
+                            set_icon_name: "list-add-symbolic",
+                            set_valign: gtk::Align::Center,
+                            add_css_class: "flat",
+                            connect_clicked => MessagesSendDialogMsg::AddHeader,
+                        },
+                        #[name(headers_list)]
+                        gtk::Box {
+                            set_orientation: gtk::Orientation::Vertical,
+                            set_spacing: 5,
+                        },
                     },
                     #[name(single_message_key_group)]
                     adw::PreferencesGroup {
@@ -172,6 +301,64 @@ impl Component for MessagesSendDialogModel {
                         set_hexpand: true,
                         set_valign: gtk::Align::BaselineFill,
                         add_css_class: "message-group",
+                        #[wrap(Some)]
+                        set_header_suffix = &gtk::Box {
+                            set_orientation: gtk::Orientation::Horizontal,
+                            #[name(template_button)]
+                            gtk::MenuButton {
+                                set_tooltip_text: Some("Value template"),
+                                set_icon_name: "text-x-generic-template-symbolic",
+                                set_valign: gtk::Align::Center,
+                                add_css_class: "flat",
+                                #[wrap(Some)]
+                                set_popover = &gtk::Popover {
+                                    #[wrap(Some)]
+                                    set_child = &gtk::Box {
+                                        set_orientation: gtk::Orientation::Vertical,
+                                        gtk::Button {
+                                            set_label: "Fill placeholders",
+                                            set_tooltip_text: Some("Replace {{placeholder}} tokens in the value"),
+                                            add_css_class: "flat",
+                                            connect_clicked => MessagesSendDialogMsg::FillTemplate,
+                                        },
+                                        gtk::Button {
+                                            set_label: "Save value as template",
+                                            add_css_class: "flat",
+                                            connect_clicked => MessagesSendDialogMsg::SaveTemplate,
+                                        },
+                                        #[name(remove_template_button)]
+                                        gtk::Button {
+                                            set_label: "Remove template",
+                                            add_css_class: "flat",
+                                            connect_clicked => MessagesSendDialogMsg::RemoveTemplate,
+                                        },
+                                    },
+                                },
+                            },
+                            #[name(history_button)]
+                            gtk::MenuButton {
+                                set_tooltip_text: Some("Recently sent values"),
+                                set_icon_name: "document-open-recent-symbolic",
+                                set_valign: gtk::Align::Center,
+                                add_css_class: "flat",
+                                #[wrap(Some)]
+                                set_popover = &gtk::Popover {
+                                    #[wrap(Some)]
+                                    set_child = &gtk::ScrolledWindow {
+                                        set_propagate_natural_height: true,
+                                        set_max_content_height: 400,
+                                        set_min_content_width: 300,
+                                        #[name(history_list)]
+                                        gtk::ListBox {
+                                            set_selection_mode: gtk::SelectionMode::None,
+                                            connect_row_activated[sender] => move |_, row| {
+                                                sender.input(MessagesSendDialogMsg::UseHistoryPayload(row.index() as usize));
+                                            },
+                                        },
+                                    },
+                                },
+                            },
+                        },
                         #[name(single_message_value_container)]
                         gtk::ScrolledWindow {
                             set_vexpand: true,
@@ -190,12 +377,30 @@ impl Component for MessagesSendDialogModel {
                                 add_css_class: "message-textview",
                             },
                         },
+                        #[name(value_error)]
+                        gtk::Label {
+                            set_halign: gtk::Align::Start,
+                            set_margin_top: 5,
+                            set_wrap: true,
+                            set_selectable: true,
+                            set_visible: false,
+                            add_css_class: "error",
+                        },
                     },
                     gtk::Box {
                         set_margin_top: 10,
                         set_margin_bottom: 10,
                         set_orientation: gtk::Orientation::Horizontal,
                         set_halign: gtk::Align::End,
+                        #[name(file_message_send)]
+                        gtk::Button {
+                            set_label: "Send from file",
+                            set_tooltip_text: Some("Send messages from a .ndjson or .csv file"),
+                            set_margin_end: 10,
+                            connect_clicked[sender] => move |_| {
+                                sender.input(MessagesSendDialogMsg::ChooseFile);
+                            },
+                        },
                         #[name(single_message_send)]
                         gtk::Button {
                             set_label: "Send",
@@ -246,6 +451,31 @@ impl Component for MessagesSendDialogModel {
                 sender.input_sender(),
                 MessagesSendDialogMsg::MultiFormatSelected,
             );
+        let acks_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: ProducerAcks::VALUES.to_vec(),
+                active_index: Some(default_idx),
+            })
+            .forward(sender.input_sender(), MessagesSendDialogMsg::AcksSelected);
+        let file_filter = gtk::FileFilter::new();
+        file_filter.set_name(Some("NDJSON/CSV files"));
+        for pattern in ["*.ndjson", "*.jsonl", "*.csv"] {
+            file_filter.add_pattern(pattern);
+        }
+        let file_dialog = OpenDialog::builder()
+            .transient_for_native(&root)
+            .launch(OpenDialogSettings {
+                folder_mode: false,
+                accept_label: String::from("Send"),
+                cancel_label: String::from("Cancel"),
+                create_folders: false,
+                is_modal: true,
+                filters: vec![file_filter],
+            })
+            .forward(sender.input_sender(), |response| match response {
+                OpenDialogResponse::Accept(path) => MessagesSendDialogMsg::SendFile(path),
+                OpenDialogResponse::Cancel => MessagesSendDialogMsg::Ignore,
+            });
         let model = MessagesSendDialogModel {
             connection,
             topic,
@@ -253,13 +483,35 @@ impl Component for MessagesSendDialogModel {
             selected_partition: None,
             multi_format_combo,
             selected_multi_format: None,
+            acks_combo,
+            selected_acks: ProducerAcks::default(),
             is_multiple: false,
+            is_tombstone: false,
             signal_handlers: vec![],
+            header_rows: vec![],
+            next_header_id: 0,
+            file_dialog,
+            keep_payload: false,
+            payload_history: vec![],
+            sending_payload: None,
+            validate_json: false,
+            value_template: None,
         };
         let partitions_combo = model.partitions_combo.widget();
         let multi_format_combo = model.multi_format_combo.widget();
+        let acks_combo = model.acks_combo.widget();
 
         let widgets = view_output!();
+        let adjustment_message_timeout = gtk::Adjustment::builder()
+            .lower(0.0)
+            .upper(3600000.0)
+            .page_size(0.0)
+            .step_increment(1000.0)
+            .value(Settings::read().unwrap_or_default().message_timeout_ms as f64)
+            .build();
+        widgets
+            .message_timeout_entry
+            .set_adjustment(Some(&adjustment_message_timeout));
         sender.input(MessagesSendDialogMsg::LoadPartitions);
         ComponentParts { model, widgets }
     }
@@ -288,15 +540,88 @@ impl Component for MessagesSendDialogModel {
                     MessagesSendDialogModel::get_dialog_max_geometry();
                 root.set_content_height(dialog_height);
                 root.set_content_width(dialog_width);
+                self.load_payload_history();
+                self.refresh_history_list(widgets);
+                self.value_template = self
+                    .find_ui_state(VALUE_TEMPLATE_STATE)
+                    .filter(|template| !template.is_empty());
+                widgets
+                    .remove_template_button
+                    .set_sensitive(self.value_template.is_some());
+                if let Some(template) = self.value_template.clone() {
+                    if self.get_value(widgets, false).is_empty() {
+                        widgets.single_message_value.buffer().set_text(&template);
+                    }
+                }
+                widgets.toggle_keep_payload.set_active(self.keep_payload);
                 root.queue_allocate();
                 root.present(parent);
             }
+            MessagesSendDialogMsg::ToggleKeepPayload(keep) => {
+                self.keep_payload = keep;
+                self.save_ui_state(KEEP_PAYLOAD_STATE, &keep.to_string());
+            }
+            MessagesSendDialogMsg::ToggleValidateJson(validate) => {
+                self.validate_json = validate;
+                if !validate {
+                    widgets.value_error.set_visible(false);
+                }
+            }
+            MessagesSendDialogMsg::SaveTemplate => {
+                widgets.template_button.popdown();
+                let template = self.get_value(widgets, false).first().cloned();
+                self.save_ui_state(
+                    VALUE_TEMPLATE_STATE,
+                    template.as_deref().unwrap_or_default(),
+                );
+                widgets
+                    .remove_template_button
+                    .set_sensitive(template.is_some());
+                self.value_template = template;
+            }
+            MessagesSendDialogMsg::RemoveTemplate => {
+                widgets.template_button.popdown();
+                self.save_ui_state(VALUE_TEMPLATE_STATE, "");
+                self.value_template = None;
+                widgets.remove_template_button.set_sensitive(false);
+            }
+            MessagesSendDialogMsg::FillTemplate => {
+                widgets.template_button.popdown();
+                let value = self.get_value(widgets, false).first().cloned();
+                let placeholders = template_placeholders(value.as_deref().unwrap_or_default());
+                if placeholders.is_empty() {
+                    show_error_alert(
+                        root,
+                        "The value has no {{placeholder}} tokens to fill".to_string(),
+                    );
+                } else {
+                    fill_template_form(placeholders, root, &sender);
+                }
+            }
+            MessagesSendDialogMsg::ApplyTemplate(values) => {
+                let value = self.get_value(widgets, false).first().cloned();
+                let filled = fill_template(value.as_deref().unwrap_or_default(), &values);
+                widgets.single_message_value.buffer().set_text(&filled);
+            }
+            MessagesSendDialogMsg::UseHistoryPayload(index) => {
+                if let Some(value) = self.payload_history.get(index) {
+                    widgets.single_message_value.buffer().set_text(value);
+                }
+                widgets.history_button.popdown();
+            }
             MessagesSendDialogMsg::Cancel => {
                 root.close();
             }
             MessagesSendDialogMsg::Send => {
+                self.sending_payload = if self.is_tombstone {
+                    None
+                } else {
+                    self.get_value(widgets, false).first().cloned()
+                };
                 if self.is_multiple {
                     self.send_multiple_message(widgets, sender.clone());
+                } else if self.is_tombstone && self.get_key(widgets, false).is_empty() {
+                    show_error_alert(root, "Tombstone records require a key".to_string());
                 } else {
                     self.send_single_message(widgets, sender.clone());
                 }
@@ -318,6 +643,7 @@ impl Component for MessagesSendDialogModel {
             }
             MessagesSendDialogMsg::PartitionSelected(_index) => {
                 let partition_id = match self.partitions_combo.model().get_active_elem() {
+                    Some(opt) if opt.as_str() == AUTO_PARTITION_LABEL => PARTITION_BY_KEY,
                     Some(opt) => opt.clone().parse::<i32>().unwrap_or_default(),
                     None => 0,
                 };
@@ -329,6 +655,9 @@ impl Component for MessagesSendDialogModel {
                 widgets.multi_format_combo.set_visible(is_active);
                 widgets.multiple_key_value_separator.set_visible(is_active);
                 widgets.single_message_key_group.set_visible(!is_active);
+                widgets
+                    .single_message_value_group
+                    .set_sensitive(!self.is_tombstone || is_active);
                 if is_active {
                     widgets
                         .single_message_value_group
@@ -337,6 +666,102 @@ impl Component for MessagesSendDialogModel {
                     widgets.single_message_value_group.set_title("Message");
                 }
             }
+            MessagesSendDialogMsg::Ignore => {}
+            MessagesSendDialogMsg::ChooseFile => {
+                self.file_dialog.emit(OpenDialogMsg::Open);
+            }
+            MessagesSendDialogMsg::SendFile(path) => {
+                info!("sending messages from file {:?}", &path);
+                self.sending_payload = None;
+                let topic = self.topic.clone().unwrap().name;
+                let partition = self.selected_partition.unwrap_or(0);
+                let headers = self.get_headers();
+                let connection = self.connection.clone().unwrap();
+                let options = self.send_options(widgets);
+                let validate_json = self.validate_json;
+                let schema = self.value_json_schema();
+                let task_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                sender.oneshot_command(async move {
+                    let messages = match parse_messages_file(&path, &topic, partition, &headers) {
+                        Ok(messages) => messages,
+                        Err(e) => return AsyncCommandOutput::SendFileError(e),
+                    };
+                    if validate_json {
+                        if let Some(error) = json_values_error(schema.as_deref(), &messages) {
+                            warn!("json validation failed::{}", &error);
+                            return AsyncCommandOutput::SendFileError(ExternalError::DisplayError(
+                                "validating messages file".to_string(),
+                                error,
+                            ));
+                        }
+                    }
+                    info!("sending {} messages from file {:?}", messages.len(), &path);
+                    let mut result = Ok(());
+                    if !messages.is_empty() {
+                        let task = Task::new(TaskVariant::SendMessages, Some(task_name), None);
+                        TASK_MANAGER_BROKER.send(TaskManagerMsg::AddTask(task.clone()));
+                        let kafka = KafkaBackend::new(&connection);
+                        let options = SendOptions {
+                            task: Some(task),
+                            ..options
+                        };
+                        result = kafka
+                            .send_messages_with_options(&topic, &messages, options)
+                            .await;
+                    }
+                    AsyncCommandOutput::SendResult(result)
+                });
+            }
+            MessagesSendDialogMsg::AddHeader => {
+                let id = self.next_header_id;
+                self.next_header_id += 1;
+                let container = gtk::Box::builder()
+                    .orientation(gtk::Orientation::Horizontal)
+                    .spacing(5)
+                    .build();
+                let key = gtk::Entry::builder()
+                    .placeholder_text("Key")
+                    .hexpand(true)
+                    .build();
+                let value = gtk::Entry::builder()
+                    .placeholder_text("Value")
+                    .hexpand(true)
+                    .build();
+                let remove_button = gtk::Button::builder()
+                    .tooltip_text("Remove header")
+                    .icon_name("edit-delete-symbolic")
+                    .css_classes(["flat"])
+                    .build();
+                let remove_sender = sender.clone();
+                remove_button.connect_clicked(move |_| {
+                    remove_sender.input(MessagesSendDialogMsg::RemoveHeader(id));
+                });
+                container.append(&key);
+                container.append(&value);
+                container.append(&remove_button);
+                widgets.headers_list.append(&container);
+                self.header_rows.push(HeaderRow {
+                    id,
+                    container,
+                    key,
+                    value,
+                });
+            }
+            MessagesSendDialogMsg::RemoveHeader(id) => {
+                if let Some(pos) = self.header_rows.iter().position(|row| row.id == id) {
+                    let row = self.header_rows.remove(pos);
+                    widgets.headers_list.remove(&row.container);
+                }
+            }
+            MessagesSendDialogMsg::ToggleTombstone(is_active) => {
+                self.is_tombstone = is_active;
+                widgets
+                    .single_message_value_group
+                    .set_sensitive(!is_active || self.is_multiple);
+            }
             MessagesSendDialogMsg::MultiFormatSelected(_index) => {
                 let selected_format = *self
                     .multi_format_combo
@@ -345,6 +770,13 @@ impl Component for MessagesSendDialogModel {
                     .unwrap_or(&MultiFormat::default());
                 self.selected_multi_format = Some(selected_format);
             }
+            MessagesSendDialogMsg::AcksSelected(_index) => {
+                self.selected_acks = *self
+                    .acks_combo
+                    .model()
+                    .get_active_elem()
+                    .unwrap_or(&ProducerAcks::default());
+            }
         };
 
         self.update_view(widgets, sender);
@@ -359,17 +791,34 @@ impl Component for MessagesSendDialogModel {
     ) {
         match message {
             AsyncCommandOutput::SetPartitions(partitions) => {
-                let variants = partitions.clone();
+                let mut variants = partitions.clone();
+                variants.push(AUTO_PARTITION_LABEL.to_string());
                 self.partitions_combo
                     .emit(SimpleComboRowMsg::UpdateData(SimpleComboRow {
                         variants,
                         active_index: Some(0),
                     }));
             }
-            AsyncCommandOutput::SendResult => {
+            AsyncCommandOutput::SendFileError(e) => {
+                let error_message = format!("{}", e);
+                error!(error_message);
+                show_error_alert(root, error_message);
+            }
+            AsyncCommandOutput::SendResult(Err(failures)) => {
+                let error_message = send_failures_message(&failures);
+                error!(error_message);
+                show_error_alert(root, error_message);
+            }
+            AsyncCommandOutput::SendResult(Ok(())) => {
                 info!("SendResult");
-                widgets.single_message_key.buffer().set_text("");
-                widgets.single_message_value.buffer().set_text("");
+                if let Some(payload) = self.sending_payload.take() {
+                    self.remember_payload(payload);
+                    self.refresh_history_list(widgets);
+                }
+                if !self.keep_payload {
+                    widgets.single_message_key.buffer().set_text("");
+                    widgets.single_message_value.buffer().set_text("");
+                }
                 root.close();
             }
         }
@@ -493,6 +942,8 @@ impl MessagesSendDialogModel {
                 .collect(),
             MultiFormat::KeyValue => self.get_key_value(widgets, true),
         };
+        let headers = self.get_headers();
+        let is_tombstone = self.is_tombstone;
         let messages: Vec<KrustMessage> = messages
             .iter()
             .map(|m| KrustMessage {
@@ -500,20 +951,138 @@ impl MessagesSendDialogModel {
                 partition,
                 offset: 0,
                 key: Some(m.0.clone()),
-                value: m.1.clone(),
+                value: if is_tombstone {
+                    None
+                } else {
+                    Some(m.1.clone())
+                },
                 timestamp: None,
-                headers: vec![],
+                headers: headers.clone(),
+                raw_value: None,
             })
             .collect();
         debug!("sending messages::{:?}", &messages);
+        if !self.check_json_values(widgets, &messages) {
+            return;
+        }
         let connection = self.connection.clone().unwrap();
+        let options = self.send_options(widgets);
         sender.oneshot_command(async move {
             // Run async background task
             let kafka = KafkaBackend::new(&connection);
-            kafka.send_messages(&topic, &messages).await;
-            AsyncCommandOutput::SendResult
+            let result = kafka
+                .send_messages_with_options(&topic, &messages, options)
+                .await;
+            AsyncCommandOutput::SendResult(result)
         });
     }
+    /// Validates the message values when JSON validation is enabled, reporting the
+    /// first failure below the value editor. Returns whether sending may proceed.
+    fn check_json_values(
+        &self,
+        widgets: &MessagesSendDialogModelWidgets,
+        messages: &[KrustMessage],
+    ) -> bool {
+        widgets.value_error.set_visible(false);
+        if !self.validate_json {
+            return true;
+        }
+        match json_values_error(self.value_json_schema().as_deref(), messages) {
+            Some(error) => {
+                warn!("json validation failed::{}", &error);
+                widgets.value_error.set_label(&error);
+                widgets.value_error.set_visible(true);
+                false
+            }
+            None => true,
+        }
+    }
+    /// JSON schema configured for the values of the selected topic.
+    fn value_json_schema(&self) -> Option<String> {
+        match (&self.connection, &self.topic) {
+            (Some(connection), Some(topic)) => connection
+                .id
+                .and_then(|conn_id| Repository::new().find_topic_format(conn_id, &topic.name))
+                .and_then(|format| format.value_json_schema),
+            _ => None,
+        }
+    }
+    fn send_options(&self, widgets: &MessagesSendDialogModelWidgets) -> SendOptions {
+        let message_timeout_ms = widgets.message_timeout_entry.value() as usize;
+        SendOptions {
+            acks: Some(self.selected_acks),
+            message_timeout_ms: (message_timeout_ms > 0).then_some(message_timeout_ms),
+            ..Default::default()
+        }
+    }
+    fn load_payload_history(&mut self) {
+        self.keep_payload = self
+            .find_ui_state(KEEP_PAYLOAD_STATE)
+            .and_then(|keep| keep.parse::<bool>().ok())
+            .unwrap_or_default();
+        self.payload_history = self
+            .find_ui_state(PAYLOAD_HISTORY_STATE)
+            .and_then(|json| serde_json::from_str::<Vec<String>>(&json).ok())
+            .unwrap_or_default();
+    }
+    fn remember_payload(&mut self, payload: String) {
+        self.payload_history.retain(|value| *value != payload);
+        self.payload_history.insert(0, payload);
+        self.payload_history.truncate(PAYLOAD_HISTORY_SIZE);
+        match serde_json::to_string(&self.payload_history) {
+            Ok(json) => self.save_ui_state(PAYLOAD_HISTORY_STATE, &json),
+            Err(e) => warn!("unable to encode payload history: {:?}", e),
+        }
+    }
+    fn refresh_history_list(&self, widgets: &MessagesSendDialogModelWidgets) {
+        widgets.history_list.remove_all();
+        for value in self.payload_history.iter() {
+            let first_line = value.lines().next().unwrap_or_default();
+            let mut preview: String = first_line.chars().take(PAYLOAD_PREVIEW_CHARS).collect();
+            if preview.len() < value.len() {
+                preview.push('…');
+            }
+            let label = gtk::Label::builder()
+                .label(preview)
+                .tooltip_text(value.as_str())
+                .halign(gtk::Align::Start)
+                .ellipsize(gtk::pango::EllipsizeMode::End)
+                .margin_top(5)
+                .margin_bottom(5)
+                .margin_start(5)
+                .margin_end(5)
+                .build();
+            widgets.history_list.append(&label);
+        }
+        widgets
+            .history_button
+            .set_sensitive(!self.payload_history.is_empty());
+    }
+    fn find_ui_state(&self, key: &str) -> Option<String> {
+        let conn_id = self.connection.as_ref()?.id?;
+        let topic = self.topic.as_ref()?;
+        Repository::new().find_ui_state(conn_id, &topic.name, key)
+    }
+    fn save_ui_state(&self, key: &str, value: &str) {
+        let (Some(conn_id), Some(topic)) =
+            (self.connection.as_ref().and_then(|c| c.id), &self.topic)
+        else {
+            return;
+        };
+        if let Err(e) = Repository::new().save_ui_state(conn_id, &topic.name, key, value) {
+            warn!("unable to save {}: {:?}", key, e);
+        }
+    }
+    fn get_headers(&self) -> Vec<KrustHeader> {
+        self.header_rows
+            .iter()
+            .filter(|row| !row.key.text().trim().is_empty())
+            .map(|row| KrustHeader {
+                key: row.key.text().trim().to_string(),
+                value: Some(row.value.text().to_string()),
+            })
+            .collect()
+    }
     fn get_key(
         &mut self,
         widgets: &mut MessagesSendDialogModelWidgets,
@@ -618,25 +1187,334 @@ impl MessagesSendDialogModel {
         let partition = self.selected_partition.unwrap_or(0);
         let topic = self.topic.clone().unwrap().name;
         let key = self.get_key(widgets, false);
-        let value = self.get_value(widgets, false);
-        if !value.is_empty() {
+        let value = if self.is_tombstone {
+            None
+        } else {
+            self.get_value(widgets, false).first().cloned()
+        };
+        if value.is_some() || self.is_tombstone {
             let message = KrustMessage {
                 topic: topic.clone(),
                 partition,
                 offset: 0,
                 key: key.first().cloned(),
-                value: value.first().unwrap().to_string(),
+                value,
                 timestamp: None,
-                headers: vec![],
+                headers: self.get_headers(),
+                raw_value: None,
             };
             let connection = self.connection.clone().unwrap();
             let messages = vec![message];
+            if !self.check_json_values(widgets, &messages) {
+                return;
+            }
+            let options = self.send_options(widgets);
             sender.oneshot_command(async move {
                 // Run async background task
                 let kafka = KafkaBackend::new(&connection);
-                kafka.send_messages(&topic, &messages).await;
-                AsyncCommandOutput::SendResult
+                let result = kafka
+                    .send_messages_with_options(&topic, &messages, options)
+                    .await;
+                AsyncCommandOutput::SendResult(result)
             });
         }
     }
 }
+
+/// Names of the `{{placeholder}}` tokens of a template, in order of first appearance.
+fn template_placeholders(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = vec![];
+    for captures in PLACEHOLDER_RE.captures_iter(template) {
+        let name = captures["name"].to_string();
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn fill_template(template: &str, values: &[(String, String)]) -> String {
+    PLACEHOLDER_RE
+        .replace_all(template, |captures: &regex::Captures| {
+            values
+                .iter()
+                .find(|(name, _)| *name == captures["name"])
+                .map(|(_, value)| value.clone())
+                .unwrap_or_else(|| captures[0].to_string())
+        })
+        .to_string()
+}
+
+/// Asks a value for each placeholder, applying them to the value editor when confirmed.
+fn fill_template_form(
+    placeholders: Vec<String>,
+    parent: &adw::Dialog,
+    sender: &ComponentSender<MessagesSendDialogModel>,
+) {
+    let form = gtk::ListBox::builder()
+        .selection_mode(gtk::SelectionMode::None)
+        .css_classes(["boxed-list"])
+        .build();
+    let rows: Vec<adw::EntryRow> = placeholders
+        .iter()
+        .map(|name| {
+            let row = adw::EntryRow::builder().title(name.as_str()).build();
+            form.append(&row);
+            row
+        })
+        .collect();
+    let alert = adw::AlertDialog::builder()
+        .heading("Fill template")
+        .body("Values replace the placeholders of the message value")
+        .close_response("cancel")
+        .default_response("apply")
+        .extra_child(&form)
+        .build();
+    alert.add_response("cancel", "Cancel");
+    alert.add_response("apply", "Apply");
+    alert.set_response_appearance("apply", adw::ResponseAppearance::Suggested);
+    let sender = sender.clone();
+    alert.connect_response(Some("apply"), move |_, _| {
+        let values = placeholders
+            .iter()
+            .cloned()
+            .zip(rows.iter().map(|row| row.text().to_string()))
+            .collect();
+        sender.input(MessagesSendDialogMsg::ApplyTemplate(values));
+    });
+    alert.present(parent);
+}
+
+/// First message value that is not valid JSON or does not match the schema, if any.
+fn json_values_error(schema: Option<&str>, messages: &[KrustMessage]) -> Option<String> {
+    match schema.map(compile_json_schema).transpose() {
+        Ok(schema) => messages.iter().enumerate().find_map(|(index, message)| {
+            let value = message.value.as_deref()?;
+            validate_json(value, schema.as_ref())
+                .err()
+                .map(|e| format!("Record {}: {}", index + 1, e))
+        }),
+        Err(e) => Some(e),
+    }
+}
+
+fn parse_messages_file(
+    path: &Path,
+    topic: &str,
+    default_partition: i32,
+    headers: &[KrustHeader],
+) -> Result<Vec<KrustMessage>, ExternalError> {
+    let is_csv = path
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("csv"))
+        .unwrap_or(false);
+    let file = File::open(path)?;
+    if is_csv {
+        parse_csv_messages(file, topic, default_partition, headers)
+    } else {
+        parse_ndjson_messages(file, topic, default_partition, headers)
+    }
+}
+
+/// Parses CSV files in the same layout produced by the messages copy as CSV action.
+/// CSV has no null, so an empty VALUE cell is sent as an empty value: tombstones can only be
+/// sent from NDJSON files.
+fn parse_csv_messages(
+    file: impl Read,
+    topic: &str,
+    default_partition: i32,
+    headers: &[KrustHeader],
+) -> Result<Vec<KrustMessage>, ExternalError> {
+    let parse_error =
+        |e: csv::Error| ExternalError::DisplayError("parsing csv file".to_string(), e.to_string());
+    let mut rdr = csv::ReaderBuilder::new()
+        .delimiter(b';')
+        .flexible(true)
+        .from_reader(file);
+    let columns = rdr.headers().map_err(parse_error)?.clone();
+    let column = |name: &str| columns.iter().position(|c| c.eq_ignore_ascii_case(name));
+    let (partition_idx, key_idx, value_idx) = (column("PARTITION"), column("KEY"), column("VALUE"));
+    if value_idx.is_none() {
+        return Err(ExternalError::DisplayError(
+            "parsing csv file".to_string(),
+            "missing VALUE column".to_string(),
+        ));
+    }
+    let mut messages = vec![];
+    for record in rdr.records() {
+        let record = record.map_err(parse_error)?;
+        let field = |idx: Option<usize>| idx.and_then(|i| record.get(i)).map(|f| f.to_string());
+        let partition = field(partition_idx)
+            .and_then(|p| p.trim().parse::<i32>().ok())
+            .unwrap_or(default_partition);
+        messages.push(KrustMessage {
+            topic: topic.to_string(),
+            partition,
+            offset: 0,
+            key: field(key_idx),
+            value: field(value_idx),
+            timestamp: None,
+            headers: headers.to_vec(),
+            raw_value: None,
+        });
+    }
+    Ok(messages)
+}
+
+/// Parses one JSON object per line with optional `partition`, `key`, `value` and `headers` fields.
+/// A `null` or missing `value` is sent as a tombstone, while `""` is an empty value. Values
+/// other than strings are sent as their JSON text.
+fn parse_ndjson_messages(
+    file: impl Read,
+    topic: &str,
+    default_partition: i32,
+    headers: &[KrustHeader],
+) -> Result<Vec<KrustMessage>, ExternalError> {
+    let mut messages = vec![];
+    for (line_number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let json = serde_json::from_str::<serde_json::Value>(&line).map_err(|e| {
+            ExternalError::DisplayError(format!("parsing line {}", line_number + 1), e.to_string())
+        })?;
+        let as_text = |value: &serde_json::Value| match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::String(text) => Some(text.clone()),
+            other => Some(other.to_string()),
+        };
+        let partition = json
+            .get("partition")
+            .and_then(|p| p.as_i64())
+            .map(|p| p as i32)
+            .unwrap_or(default_partition);
+        let mut message_headers = headers.to_vec();
+        if let Some(line_headers) = json.get("headers").and_then(|h| h.as_object()) {
+            for (key, value) in line_headers {
+                message_headers.push(KrustHeader {
+                    key: key.clone(),
+                    value: as_text(value),
+                });
+            }
+        }
+        messages.push(KrustMessage {
+            topic: topic.to_string(),
+            partition,
+            offset: 0,
+            key: json.get("key").and_then(as_text),
+            value: json.get("value").and_then(as_text),
+            timestamp: None,
+            headers: message_headers,
+            raw_value: None,
+        });
+    }
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> KrustHeader {
+        KrustHeader {
+            key: "source".to_string(),
+            value: Some("file".to_string()),
+        }
+    }
+
+    #[test]
+    fn csv_messages_keep_quoted_separators() {
+        let csv = "PARTITION;OFFSET;KEY;VALUE\n\
+                   2;10;key-1;\"{\"\"a\"\";1}\"\n\
+                   ;11;key-2;\"multi\nline\"\n";
+
+        let messages = parse_csv_messages(csv.as_bytes(), "topic", 0, &[header()]).unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].partition, 2);
+        assert_eq!(messages[0].key.as_deref(), Some("key-1"));
+        assert_eq!(messages[0].value.as_deref(), Some("{\"a\";1}"));
+        assert_eq!(messages[0].headers[0].key, "source");
+        assert_eq!(messages[1].partition, 0);
+        assert_eq!(messages[1].value.as_deref(), Some("multi\nline"));
+    }
+
+    #[test]
+    fn csv_messages_with_missing_columns() {
+        let csv = "VALUE;KEY\nonly-value\n;\n";
+
+        let messages = parse_csv_messages(csv.as_bytes(), "topic", 3, &[]).unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].partition, 3);
+        assert_eq!(messages[0].key, None);
+        assert_eq!(messages[0].value.as_deref(), Some("only-value"));
+        // empty cells are empty values, CSV can't express a tombstone
+        assert_eq!(messages[1].key.as_deref(), Some(""));
+        assert_eq!(messages[1].value.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn csv_without_value_column_is_an_error() {
+        let csv = "PARTITION;KEY\n0;key\n";
+
+        assert!(parse_csv_messages(csv.as_bytes(), "topic", 0, &[]).is_err());
+    }
+
+    #[test]
+    fn ndjson_messages_tell_null_from_empty_values() {
+        let ndjson = r#"{"key": "deleted", "value": null}
+{"key": "empty", "value": ""}
+{"key": "missing"}
+"#;
+
+        let messages = parse_ndjson_messages(ndjson.as_bytes(), "topic", 0, &[]).unwrap();
+
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].value, None);
+        assert_eq!(messages[1].value.as_deref(), Some(""));
+        assert_eq!(messages[2].value, None);
+    }
+
+    #[test]
+    fn ndjson_messages_with_non_string_values() {
+        let ndjson = r#"{"partition": 1, "key": 42, "value": {"a": [1, 2]}, "headers": {"n": 1, "s": "x", "z": null}}
+
+{"value": true}
+"#;
+
+        let messages = parse_ndjson_messages(ndjson.as_bytes(), "topic", 5, &[header()]).unwrap();
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].partition, 1);
+        assert_eq!(messages[0].key.as_deref(), Some("42"));
+        assert_eq!(messages[0].value.as_deref(), Some(r#"{"a":[1,2]}"#));
+        let headers: Vec<(&str, Option<&str>)> = messages[0]
+            .headers
+            .iter()
+            .map(|h| (h.key.as_str(), h.value.as_deref()))
+            .collect();
+        assert_eq!(
+            headers,
+            vec![
+                ("source", Some("file")),
+                ("n", Some("1")),
+                ("s", Some("x")),
+                ("z", None)
+            ]
+        );
+        assert_eq!(messages[1].partition, 5);
+        assert_eq!(messages[1].key, None);
+        assert_eq!(messages[1].value.as_deref(), Some("true"));
+    }
+
+    #[test]
+    fn ndjson_reports_the_invalid_line() {
+        let ndjson = "{\"value\": \"ok\"}\nnot json\n";
+
+        let error = parse_ndjson_messages(ndjson.as_bytes(), "topic", 0, &[]).unwrap_err();
+
+        assert!(error.to_string().contains("line 2"));
+    }
+}
@@ -4,14 +4,22 @@
 // found in the COPYING file.
 
 use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
+use std::time::Duration;
 
 // See: https://gitlab.gnome.org/GNOME/gtk/-/issues/5644
-use chrono::{TimeZone, Utc};
-use chrono_tz::America;
+use adw::prelude::*;
+use chrono_tz::Tz;
 use csv::StringRecord;
 use gtk::{gdk::Rectangle, ColumnViewSorter};
 use gtk::{prelude::*, ColumnViewColumn, SortType};
+use rdkafka::error::KafkaError;
+use regex::{Regex, RegexBuilder};
 use relm4::{
     actions::{RelmAction, RelmActionGroup},
     factory::{DynamicIndex, FactoryComponent},
@@ -19,30 +27,42 @@ use relm4::{
     *,
 };
 
+use relm4_components::save_dialog::{
+    SaveDialog, SaveDialogMsg, SaveDialogResponse, SaveDialogSettings,
+};
 use relm4_components::simple_combo_box::{SimpleComboBox, SimpleComboBoxMsg};
 use tokio_util::sync::CancellationToken;
 use tracing::*;
 use uuid::Uuid;
 
-use crate::backend::kafka::KafkaBackend;
-use crate::backend::repository::{KrustTopicCache, MessagesSearchOrder};
-use crate::backend::settings::Settings;
-use crate::backend::worker::MessagesTotalCounterRequest;
+use crate::backend::kafka::{KafkaBackend, SendOptions, PARTITION_BY_KEY};
+use crate::backend::repository::{
+    KrustTopicCache, MessagesRepository, MessagesSearchField, MessagesSearchMode,
+    MessagesSearchOptions, MessagesSearchOrder, Partition,
+};
+use crate::backend::settings::{format_timestamp, Settings};
+use crate::backend::worker::{MessagesTotal, MessagesTotalCounterRequest};
 use crate::component::settings_dialog::MessagesSortOrder;
-use crate::component::task_manager::{Task, TaskManagerMsg, TaskVariant, TASK_MANAGER_BROKER};
-use crate::modals::utils::show_error_alert;
+use crate::component::task_manager::{
+    Task, TaskManagerMsg, TaskRetry, TaskVariant, TASK_MANAGER_BROKER,
+};
+use crate::config::ExternalError;
+use crate::modals::utils::{build_confirmation_alert, send_failures_message, show_error_alert};
 use crate::{
     backend::{
         kafka::KafkaFetch,
+        payload::encode_base64,
         repository::{KrustConnection, KrustMessage, KrustTopic},
         worker::{
             MessagesCleanupRequest, MessagesMode, MessagesRequest, MessagesResponse, MessagesWorker,
         },
     },
     component::{
+        colorize_widget_by_connection,
         messages::lists::{
-            MessageListItem, MessageOffsetColumn, MessagePartitionColumn, MessageTimestampColumn,
-            MessageValueColumn,
+            MessageBookmarkColumn, MessageHeaderColumn, MessageListItem, MessageOffsetColumn,
+            MessagePartitionColumn, MessageSizeColumn, MessageTimestampColumn, MessageValueColumn,
+            MessageValueKindColumn,
         },
         status_bar::{StatusBarMsg, STATUS_BROKER},
     },
@@ -55,10 +75,16 @@ use super::messages_cache_settings_dialog::{
     MessagesCacheSettingsDialogModel, MessagesCacheSettingsDialogMsg,
     MessagesCacheSettingsDialogOutput,
 };
+use super::messages_compare_dialog::{MessagesCompareDialogModel, MessagesCompareDialogMsg};
+use super::messages_delete_range_dialog::{
+    MessagesDeleteRangeDialogModel, MessagesDeleteRangeDialogMsg, MessagesDeleteRangeDialogOutput,
+};
+use super::messages_format_dialog::{MessagesFormatDialogModel, MessagesFormatDialogMsg};
 use super::messages_send_dialog::MessagesSendDialogMsg;
 use super::{lists::MessageKeyColumn, messages_send_dialog::MessagesSendDialogModel};
 use copypasta::{ClipboardContext, ClipboardProvider};
 use humansize::{format_size, DECIMAL};
+use serde::{Deserialize, Serialize};
 
 // page actions
 relm4::new_action_group!(pub MessagesPageActionGroup, "messages_page");
@@ -66,14 +92,22 @@ relm4::new_stateless_action!(pub MessagesSearchAction, MessagesPageActionGroup,
 
 relm4::new_action_group!(pub(super) MessagesListActionGroup, "messages-list");
 relm4::new_stateless_action!(pub(super) CopyMessagesAsCsv, MessagesListActionGroup, "copy-messages-as-csv");
+relm4::new_stateless_action!(pub(super) CopyMessagesAsJson, MessagesListActionGroup, "copy-messages-as-json");
 relm4::new_stateless_action!(pub(super) CopyMessagesKeyValue, MessagesListActionGroup, "copy-messages-key-value");
 relm4::new_stateless_action!(pub(super) CopyMessagesValue, MessagesListActionGroup, "copy-messages-value");
 relm4::new_stateless_action!(pub(super) CopyMessagesKey, MessagesListActionGroup, "copy-messages-key");
+relm4::new_stateless_action!(pub(super) CopyMessagesAsKcat, MessagesListActionGroup, "copy-messages-as-kcat");
 relm4::new_stateless_action!(pub(super) ResendMessagesKeyValue, MessagesListActionGroup, "resend-messages-key-value");
 relm4::new_stateless_action!(pub(super) ResendMessagesValue, MessagesListActionGroup, "resend-messages-value");
+relm4::new_stateless_action!(pub(super) CompareMessages, MessagesListActionGroup, "compare-messages");
+relm4::new_stateless_action!(pub(super) ToggleMessagesBookmark, MessagesListActionGroup, "toggle-messages-bookmark");
+relm4::new_stateful_action!(pub(super) ResendPreserveTimestamp, MessagesListActionGroup, "resend-preserve-timestamp", (), bool);
+relm4::new_stateful_action!(pub(super) CopyIncludeHeaders, MessagesListActionGroup, "copy-include-headers", (), bool);
+relm4::new_stateless_action!(pub(super) ResetColumns, MessagesListActionGroup, "reset-columns");
 
 pub struct MessagesTabModel {
     token: CancellationToken,
+    fetch_token: Option<CancellationToken>,
     pub topic: Option<KrustTopic>,
     mode: MessagesMode,
     pub connection: Option<KrustConnection>,
@@ -84,12 +118,42 @@ pub struct MessagesTabModel {
     fetch_type_combo: Controller<SimpleComboBox<KafkaFetch>>,
     fetch_type: KafkaFetch,
     max_messages: f64,
+    max_messages_limit: f64,
+    quick_fetch_box: gtk::Box,
+    auto_refresh_combo: Controller<SimpleComboBox<String>>,
+    auto_refresh_interval: Duration,
+    auto_refresh: Option<gtk::glib::SourceId>,
+    /// Next offset per partition of the window shown when the stream was paused.
+    paused_offsets: Option<HashMap<i32, i64>>,
+    resume_offsets: Option<HashMap<i32, i64>>,
     messages_menu_popover: gtk::PopoverMenu,
+    columns_popover: gtk::Popover,
+    /// Leaves the columns hidden in the table out of CSV copies and exports.
+    csv_visible_columns_only: bool,
+    csv_projection_entry: gtk::Entry,
     add_messages: Controller<MessagesSendDialogModel>,
     clipboard: Box<dyn ClipboardProvider>,
     cache_search_order: Option<MessagesSearchOrder>,
+    search_mode_combo: Controller<SimpleComboBox<MessagesSearchMode>>,
+    search_field_combo: Controller<SimpleComboBox<MessagesSearchField>>,
+    search_options: MessagesSearchOptions,
     cache_settings_dialog: Controller<MessagesCacheSettingsDialogModel>,
+    delete_range_dialog: Controller<MessagesDeleteRangeDialogModel>,
+    compare_dialog: Controller<MessagesCompareDialogModel>,
+    format_dialog: Controller<MessagesFormatDialogModel>,
+    export_dialog: Controller<SaveDialog>,
     cache_settings: Option<KrustTopicCache>,
+    resend_preserve_timestamp: bool,
+    /// Messages waiting for the resend confirmation.
+    pending_resend: Vec<KrustMessage>,
+    /// Adds the headers to CSV output and key/value copies.
+    copy_include_headers: bool,
+    /// Messages of the current page not yet added to the list, built as the list is scrolled.
+    pending_messages: VecDeque<KrustMessage>,
+    /// Item formatting read from the settings once per page.
+    pending_format: MessageItemFormat,
+    bookmarks: HashSet<(i32, i64)>,
+    default_columns_layout: Vec<ColumnLayout>,
 }
 
 pub struct MessagesTabInit {
@@ -99,48 +163,156 @@ pub struct MessagesTabInit {
 #[derive(Debug)]
 pub enum Copy {
     AllAsCsv,
+    AllAsJson,
     KeyValue,
     Value,
     Key,
+    AsKcatCommand,
+}
+
+/// How resent records are assigned to partitions of the destination topic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResendPartitioning {
+    /// Each record goes to the partition it was read from.
+    Original,
+    /// The producer partitioner picks the partition from the record key.
+    ByKey,
+}
+
+impl ResendPartitioning {
+    const VALUES: [Self; 2] = [Self::Original, Self::ByKey];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Original => "Original partitions (explicit)",
+            Self::ByKey => "By key (auto)",
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum MessagesTabMsg {
     Open(Box<KrustConnection>, Box<KrustTopic>),
     GetMessages,
+    /// Runs a failed fetch again with the request it was started with.
+    RetryMessages(Box<MessagesRequest>),
     GetNextMessages,
     GetPreviousMessages,
     GotoPage,
     StopGetMessages,
     RefreshCache,
     DestroyCache,
+    ShowDeleteMessagesRange,
+    DeleteMessagesRange(i32, i64, i64),
     RefreshTotalCounter,
     UpdateMessages(Box<MessagesResponse>),
+    AppendPendingMessages,
     OpenMessage(u32),
+    StepMessage(i64),
     SearchMessages,
     LiveSearchMessages(String),
     PageSizeChanged(usize),
     FetchTypeChanged(usize),
+    SearchModeChanged(usize),
+    SearchFieldChanged(usize),
+    ToggleCaseInsensitive(bool),
     ToggleMode(bool),
     DigitsOnly(f64),
+    /// Fetches the given number of messages per partition right away.
+    QuickFetch(KafkaFetch, usize),
+    ToggleAutoRefresh(bool),
+    AutoRefreshIntervalChanged(usize),
+    AutoRefresh,
+    TogglePause(bool),
     CopyMessages(Copy),
     ResendMessages(Copy),
+    ConfirmResendMessages(String, ResendPartitioning),
+    CancelResendMessages,
+    TogglePreserveTimestamp(bool),
+    ToggleCopyIncludeHeaders(bool),
     AddMessages,
     SetCacheOrder(Option<String>, String),
     RefreshTopic,
     ShowCacheSettings,
+    ShowFormatSettings,
     UpdateCacheSettings(KrustTopicCache),
+    FocusSearch,
+    ExportCache,
+    ExportCacheTo(PathBuf),
+    ToggleBookmarks,
+    CompareMessages,
+    SelectionChanged,
+    ToggleBookmarkedOnly(bool),
+    ResetColumns,
+    ToggleCsvVisibleColumnsOnly(bool),
+    Ignore,
 }
 
 #[derive(Debug)]
 pub enum CommandMsg {
     Data(MessagesResponse),
+    DataError(Task, ExternalError),
     CopyToClipboard(String, String),
-    RefreshTotalCounterResult(String, usize),
-    MessagesResendResult(String, Option<()>),
+    CopyFailed(String, String),
+    RefreshTotalCounterResult(String, MessagesTotal),
+    ResendTargets(Vec<String>),
+    MessagesResendResult(String, Result<(), Vec<(KrustMessage, KafkaError)>>),
+    MessagesDeleted(String, Result<usize, ExternalError>),
+    CacheExported(Task, PathBuf, Result<usize, ExternalError>),
 }
 
 pub const AVAILABLE_PAGE_SIZES: [u16; 7] = [1000, 2000, 5000, 7000, 10000, 20000, 50000];
+/// Live mode auto refresh intervals, in seconds.
+const AUTO_REFRESH_INTERVALS: [u64; 3] = [2, 5, 10];
+/// Number of messages added to the list at once, more are added when scrolling reaches the end.
+const MESSAGES_APPEND_CHUNK_SIZE: usize = 2000;
+/// Last position of the separator between the messages list and the viewer, shared by all
+/// tabs and persisted with the window state.
+pub(crate) static MESSAGES_SEPARATOR_POSITION: AtomicI32 = AtomicI32::new(0);
+/// UI state keys for the per topic sort order.
+const SORT_COLUMN_STATE: &str = "sort_column";
+const SORT_ORDER_STATE: &str = "sort_order";
+/// UI state key for the per topic messages table columns.
+const COLUMNS_LAYOUT_STATE: &str = "columns_layout";
+const CSV_VISIBLE_COLUMNS_STATE: &str = "csv_visible_columns_only";
+/// UI state key for the per topic last search.
+const SEARCH_STATE: &str = "search";
+
+/// Last search of a topic, restored when it's opened again.
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedSearch {
+    term: String,
+    mode: String,
+    field: String,
+    case_insensitive: bool,
+}
+
+/// Width, visibility and position of a messages table column, saved in display order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ColumnLayout {
+    title: String,
+    width: i32,
+    visible: bool,
+}
+
+/// Settings used to build the list items of a page.
+#[derive(Debug)]
+struct MessageItemFormat {
+    timestamp_formatter: String,
+    timezone: Tz,
+    value_preview_max_chars: usize,
+}
+
+impl Default for MessageItemFormat {
+    fn default() -> Self {
+        let settings = Settings::default();
+        Self {
+            timestamp_formatter: settings.timestamp_formatter(),
+            timezone: settings.timezone(),
+            value_preview_max_chars: settings.value_preview_max_chars,
+        }
+    }
+}
 
 #[relm4::factory(pub)]
 impl FactoryComponent for MessagesTabModel {
@@ -154,11 +326,23 @@ impl FactoryComponent for MessagesTabModel {
         messages_menu: {
             section! {
                 "_Copy as CSV" => CopyMessagesAsCsv,
+                custom: "csv-projection",
+                "_Copy as JSON lines" => CopyMessagesAsJson,
                 "_Copy key,value" => CopyMessagesKeyValue,
                 "_Copy value" => CopyMessagesValue,
                 "_Copy key" => CopyMessagesKey,
+                "_Copy as kcat command" => CopyMessagesAsKcat,
+                "_Include headers in copies" => CopyIncludeHeaders,
                 "_Resend message(s) with key/value" => ResendMessagesKeyValue,
                 "_Resend message(s) with value only" => ResendMessagesValue,
+                "_Preserve original timestamp on resend" => ResendPreserveTimestamp,
+            },
+            section! {
+                "_Compare" => CompareMessages,
+                "_Toggle bookmark" => ToggleMessagesBookmark,
+            },
+            section! {
+                "_Reset columns" => ResetColumns,
             }
         }
     }
@@ -166,9 +350,13 @@ impl FactoryComponent for MessagesTabModel {
     view! {
         #[root]
         #[name(main_panel)]
+        #[name(messages_paned)]
         gtk::Paned {
             set_orientation: gtk::Orientation::Vertical,
             //set_resize_start_child: true,
+            connect_position_notify => move |paned| {
+                MESSAGES_SEPARATOR_POSITION.store(paned.position(), Ordering::Relaxed);
+            },
             #[wrap(Some)]
             set_start_child = &gtk::Box {
                 set_orientation: gtk::Orientation::Vertical,
@@ -176,7 +364,10 @@ impl FactoryComponent for MessagesTabModel {
                 set_vexpand: true,
                 container_add = &self.messages_menu_popover.clone() {
                     set_menu_model: Some(&messages_menu),
+                    add_child: (&self.csv_projection_entry, "csv-projection"),
                 },
+                container_add: &self.columns_popover,
+                #[name(toolbar)]
                 gtk::CenterBox {
                     set_orientation: gtk::Orientation::Horizontal,
                     set_halign: gtk::Align::Fill,
@@ -231,6 +422,24 @@ impl FactoryComponent for MessagesTabModel {
                                 sender.input(MessagesTabMsg::ShowCacheSettings);
                             },
                         },
+                        #[name(btn_format_settings)]
+                        gtk::Button {
+                            set_tooltip_text: Some("Payload format"),
+                            set_icon_name: "document-properties-symbolic",
+                            set_margin_start: 5,
+                            connect_clicked[sender] => move |_| {
+                                sender.input(MessagesTabMsg::ShowFormatSettings);
+                            },
+                        },
+                        #[name(btn_bookmarked_only)]
+                        gtk::ToggleButton {
+                            set_tooltip_text: Some("Show only bookmarked messages"),
+                            set_icon_name: "starred-symbolic",
+                            set_margin_start: 5,
+                            connect_toggled[sender] => move |btn| {
+                                sender.input(MessagesTabMsg::ToggleBookmarkedOnly(btn.is_active()));
+                            },
+                        },
                         #[name(btn_cache_toggle)]
                         gtk::ToggleButton {
                             set_margin_start: 5,
@@ -248,6 +457,24 @@ impl FactoryComponent for MessagesTabModel {
                             set_visible: false,
                             add_css_class: "cache-timestamp",
                         },
+                        #[name(btn_cache_delete_range)]
+                        gtk::Button {
+                            set_tooltip_text: Some("Delete cached messages range"),
+                            set_icon_name: "edit-cut-symbolic",
+                            set_margin_start: 5,
+                            connect_clicked[sender] => move |_| {
+                                sender.input(MessagesTabMsg::ShowDeleteMessagesRange);
+                            },
+                        },
+                        #[name(btn_cache_export)]
+                        gtk::Button {
+                            set_tooltip_text: Some("Export cache to a .csv or .ndjson file"),
+                            set_icon_name: "document-save-symbolic",
+                            set_margin_start: 5,
+                            connect_clicked[sender] => move |_| {
+                                sender.input(MessagesTabMsg::ExportCache);
+                            },
+                        },
                         #[name(btn_cache_destroy)]
                         gtk::Button {
                             set_tooltip_text: Some("Destroy cache"),
@@ -276,6 +503,24 @@ impl FactoryComponent for MessagesTabModel {
                                 sender.input(MessagesTabMsg::SearchMessages);
                             },
                         },
+                        #[name(btn_case_insensitive)]
+                        gtk::ToggleButton {
+                            set_tooltip_text: Some("Case-insensitive search"),
+                            set_icon_name: "format-text-rich-symbolic",
+                            set_margin_start: 5,
+                            set_active: false,
+                            connect_toggled[sender] => move |btn| {
+                                sender.input(MessagesTabMsg::ToggleCaseInsensitive(btn.is_active()));
+                            },
+                        },
+                        self.search_field_combo.widget() -> &gtk::ComboBoxText {
+                            set_tooltip_text: Some("Message field to search"),
+                            set_margin_start: 5,
+                        },
+                        self.search_mode_combo.widget() -> &gtk::ComboBoxText {
+                            set_tooltip_text: Some("Search mode for cached messages"),
+                            set_margin_start: 5,
+                        },
                         self.fetch_type_combo.widget() -> &gtk::ComboBoxText {
                             set_margin_start: 5,
                         },
@@ -285,6 +530,11 @@ impl FactoryComponent for MessagesTabModel {
                     set_vexpand: true,
                     set_hexpand: true,
                     set_propagate_natural_width: true,
+                    connect_edge_reached[sender] => move |_, position| {
+                        if position == gtk::PositionType::Bottom {
+                            sender.input(MessagesTabMsg::AppendPendingMessages);
+                        }
+                    },
                     #[name = "messages_view" ]
                     self.messages_wrapper.view.clone() -> gtk::ColumnView {
                         set_vexpand: true,
@@ -325,6 +575,12 @@ impl FactoryComponent for MessagesTabModel {
                             set_margin_start: 5,
                             set_width_chars: 10,
                         },
+                        #[name(offsets_label)]
+                        gtk::Label {
+                            set_margin_start: 10,
+                            add_css_class: "dim-label",
+                            add_css_class: "numeric",
+                        },
                     },
                     #[wrap(Some)]
                     set_center_widget = &gtk::Box {
@@ -347,6 +603,24 @@ impl FactoryComponent for MessagesTabModel {
                                 set_sensitive: true,
                                 set_margin_start: 5,
                                 set_width_chars: 10,
+                                set_tooltip_text: Some("Offset based total: high minus low watermark of every partition, including compacted records and transaction markers"),
+                            },
+                            #[name(cached_counter_box)]
+                            gtk::Box {
+                                set_orientation: gtk::Orientation::Horizontal,
+                                set_visible: false,
+                                gtk::Label {
+                                    set_label: "Cached",
+                                    set_margin_start: 5,
+                                },
+                                #[name(cached_counter_entry)]
+                                gtk::Entry {
+                                    set_editable: false,
+                                    set_sensitive: true,
+                                    set_margin_start: 5,
+                                    set_width_chars: 10,
+                                    set_tooltip_text: Some("Actual total: records stored in the topic cache, as shown in cached mode"),
+                                },
                             },
                             #[name(btn_total_counter_refresh)]
                             gtk::Button {
@@ -442,13 +716,38 @@ impl FactoryComponent for MessagesTabModel {
                                 set_width_chars: 10,
                                 set_numeric: true,
                                 set_increments: (1000.0, 10000.0),
-                                set_range: (1.0, 100000.0),
+                                set_range: (1.0, self.max_messages_limit),
                                 set_value: self.max_messages,
                                 set_digits: 0,
                                 connect_value_changed[sender] => move |sbtn| {
                                     sender.input(MessagesTabMsg::DigitsOnly(sbtn.value()));
                                 },
                             },
+                            container_add: &self.quick_fetch_box,
+                            #[name(btn_auto_refresh)]
+                            gtk::ToggleButton {
+                                set_tooltip_text: Some("Fetch messages again periodically"),
+                                set_margin_start: 5,
+                                set_label: "Auto refresh",
+                                add_css_class: "krust-toggle",
+                                connect_toggled[sender] => move |btn| {
+                                    sender.input(MessagesTabMsg::ToggleAutoRefresh(btn.is_active()));
+                                },
+                            },
+                            self.auto_refresh_combo.widget() -> &gtk::ComboBoxText {
+                                set_tooltip_text: Some("Auto refresh interval"),
+                                set_margin_start: 5,
+                            },
+                            #[name(btn_pause)]
+                            gtk::ToggleButton {
+                                set_tooltip_text: Some("Pause/resume auto refresh"),
+                                set_icon_name: "media-playback-pause-symbolic",
+                                set_margin_start: 5,
+                                set_sensitive: false,
+                                connect_toggled[sender] => move |btn| {
+                                    sender.input(MessagesTabMsg::TogglePause(btn.is_active()));
+                                },
+                            },
                         },
                     },
                 },
@@ -460,15 +759,45 @@ impl FactoryComponent for MessagesTabModel {
     fn init_model(open: Self::Init, _index: &DynamicIndex, sender: FactorySender<Self>) -> Self {
         // Initialize the messages ListView wrapper
         let mut messages_wrapper = TypedColumnView::<MessageListItem, gtk::MultiSelection>::new();
+        messages_wrapper.append_column::<MessageBookmarkColumn>();
         messages_wrapper.append_column::<MessagePartitionColumn>();
         messages_wrapper.append_column::<MessageOffsetColumn>();
         messages_wrapper.append_column::<MessageKeyColumn>();
+        messages_wrapper.append_column::<MessageValueKindColumn>();
         messages_wrapper.append_column::<MessageValueColumn>();
         messages_wrapper.append_column::<MessageTimestampColumn>();
+        messages_wrapper.append_column::<MessageHeaderColumn>();
+        messages_wrapper.append_column::<MessageSizeColumn>();
+        // first filter, kept by live searches: only bookmarked messages
+        messages_wrapper.add_filter(|item| item.bookmarked);
+        messages_wrapper.set_filter_status(0, false);
+        let default_columns_layout = columns_layout(&messages_wrapper.view);
+        let mut csv_visible_columns_only = false;
+        if let Some(conn_id) = open.connection.id {
+            let mut repo = Repository::new();
+            let saved_layout = repo
+                .find_ui_state(conn_id, &open.topic.name, COLUMNS_LAYOUT_STATE)
+                .and_then(|json| serde_json::from_str::<Vec<ColumnLayout>>(&json).ok());
+            if let Some(layout) = saved_layout {
+                apply_columns_layout(&messages_wrapper.view, &layout);
+            }
+            csv_visible_columns_only = repo
+                .find_ui_state(conn_id, &open.topic.name, CSV_VISIBLE_COLUMNS_STATE)
+                .is_some_and(|value| value == "true");
+        }
+        let columns_popover = columns_popover(
+            &messages_wrapper.view,
+            csv_visible_columns_only,
+            sender.input_sender(),
+        );
 
         // Initialize message viewer
         let message_viewer = MessageViewerModel::builder().launch(()).detach();
         let cache_settings = open.topic.cached.clone();
+        let bookmarks = match open.connection.id {
+            Some(conn_id) => Repository::new().find_bookmarks(conn_id, &open.topic.name),
+            None => HashSet::new(),
+        };
         let default_idx = cache_settings
             .clone()
             .map(|c| c.default_page_size as usize)
@@ -480,15 +809,69 @@ impl FactoryComponent for MessagesTabModel {
             })
             .forward(sender.input_sender(), MessagesTabMsg::PageSizeChanged);
         page_size_combo.widget().queue_allocate();
-        let fetch_type_default_idx = 0;
+        let fetch_type_default_idx = open
+            .connection
+            .default_fetch
+            .clone()
+            .and_then(|f| KafkaFetch::VALUES.iter().position(|v| *v == f))
+            .unwrap_or_default();
         let fetch_type_combo = SimpleComboBox::builder()
             .launch(SimpleComboBox {
                 variants: KafkaFetch::VALUES.to_vec(),
                 active_index: Some(fetch_type_default_idx),
             })
             .forward(sender.input_sender(), MessagesTabMsg::FetchTypeChanged);
+        let quick_fetch_box = gtk::Box::new(gtk::Orientation::Horizontal, 0);
+        quick_fetch_box.add_css_class("linked");
+        quick_fetch_box.set_margin_start(5);
+        let quick_fetch_presets = Settings::read()
+            .unwrap_or_default()
+            .live_quick_fetch_presets();
+        for (fetch, count) in quick_fetch_presets {
+            let label = match fetch {
+                KafkaFetch::Oldest => format!("First {}", count),
+                KafkaFetch::Newest => format!("Last {}", count),
+            };
+            let button = gtk::Button::with_label(&label);
+            button.set_tooltip_text(Some(&format!("Fetch {} messages per partition", label)));
+            let sender = sender.clone();
+            button.connect_clicked(move |_| {
+                sender.input(MessagesTabMsg::QuickFetch(fetch.clone(), count));
+            });
+            quick_fetch_box.append(&button);
+        }
+        let auto_refresh_combo = SimpleComboBox::builder()
+            .launch(SimpleComboBox {
+                variants: AUTO_REFRESH_INTERVALS
+                    .iter()
+                    .map(|secs| format!("every {}s", secs))
+                    .collect(),
+                active_index: Some(0),
+            })
+            .forward(
+                sender.input_sender(),
+                MessagesTabMsg::AutoRefreshIntervalChanged,
+            );
+        let search_mode_combo = SimpleComboBox::builder()
+            .launch(SimpleComboBox {
+                variants: MessagesSearchMode::VALUES.to_vec(),
+                active_index: Some(0),
+            })
+            .forward(sender.input_sender(), MessagesTabMsg::SearchModeChanged);
+        let search_field_combo = SimpleComboBox::builder()
+            .launch(SimpleComboBox {
+                variants: MessagesSearchField::VALUES.to_vec(),
+                active_index: Some(0),
+            })
+            .forward(sender.input_sender(), MessagesTabMsg::SearchFieldChanged);
 
         let messages_popover_menu = gtk::PopoverMenu::builder().build();
+        let csv_projection_entry = gtk::Entry::builder()
+            .placeholder_text("CSV fields, e.g. id, $.user.name")
+            .tooltip_text("Comma-separated top-level keys or JSONPaths added as CSV columns")
+            .margin_start(10)
+            .margin_end(10)
+            .build();
         let mut messages_actions = RelmActionGroup::<MessagesListActionGroup>::new();
         let messages_menu_sender = sender.input_sender().clone();
         let menu_copy_all_csv_action = RelmAction::<CopyMessagesAsCsv>::new_stateless(move |_| {
@@ -497,6 +880,13 @@ impl FactoryComponent for MessagesTabModel {
                 .unwrap();
         });
         let messages_menu_sender = sender.input_sender().clone();
+        let menu_copy_all_json_action =
+            RelmAction::<CopyMessagesAsJson>::new_stateless(move |_| {
+                messages_menu_sender
+                    .send(MessagesTabMsg::CopyMessages(Copy::AllAsJson))
+                    .unwrap();
+            });
+        let messages_menu_sender = sender.input_sender().clone();
         let menu_copy_key_value_action =
             RelmAction::<CopyMessagesKeyValue>::new_stateless(move |_| {
                 messages_menu_sender
@@ -516,6 +906,12 @@ impl FactoryComponent for MessagesTabModel {
                 .unwrap();
         });
         let messages_menu_sender = sender.input_sender().clone();
+        let menu_copy_kcat_action = RelmAction::<CopyMessagesAsKcat>::new_stateless(move |_| {
+            messages_menu_sender
+                .send(MessagesTabMsg::CopyMessages(Copy::AsKcatCommand))
+                .unwrap();
+        });
+        let messages_menu_sender = sender.input_sender().clone();
         let menu_resend_key_value_action =
             RelmAction::<ResendMessagesKeyValue>::new_stateless(move |_| {
                 messages_menu_sender
@@ -529,12 +925,57 @@ impl FactoryComponent for MessagesTabModel {
                     .send(MessagesTabMsg::ResendMessages(Copy::Value))
                     .unwrap();
             });
+        let messages_menu_sender = sender.input_sender().clone();
+        let menu_resend_preserve_timestamp_action =
+            RelmAction::<ResendPreserveTimestamp>::new_stateful(&false, move |_, state| {
+                *state = !*state;
+                messages_menu_sender
+                    .send(MessagesTabMsg::TogglePreserveTimestamp(*state))
+                    .unwrap();
+            });
+        let messages_menu_sender = sender.input_sender().clone();
+        let menu_copy_include_headers_action =
+            RelmAction::<CopyIncludeHeaders>::new_stateful(&false, move |_, state| {
+                *state = !*state;
+                messages_menu_sender
+                    .send(MessagesTabMsg::ToggleCopyIncludeHeaders(*state))
+                    .unwrap();
+            });
+        let messages_menu_sender = sender.input_sender().clone();
+        let menu_toggle_bookmark_action =
+            RelmAction::<ToggleMessagesBookmark>::new_stateless(move |_| {
+                messages_menu_sender
+                    .send(MessagesTabMsg::ToggleBookmarks)
+                    .unwrap();
+            });
+        let messages_menu_sender = sender.input_sender().clone();
+        let menu_compare_action = RelmAction::<CompareMessages>::new_stateless(move |_| {
+            messages_menu_sender
+                .send(MessagesTabMsg::CompareMessages)
+                .unwrap();
+        });
+        let messages_menu_sender = sender.input_sender().clone();
+        let menu_reset_columns_action = RelmAction::<ResetColumns>::new_stateless(move |_| {
+            messages_menu_sender
+                .send(MessagesTabMsg::ResetColumns)
+                .unwrap();
+        });
+        // enabled by the selection handler when exactly two messages are selected
+        let compare_action = menu_compare_action.gio_action().clone();
+        compare_action.set_enabled(false);
         messages_actions.add_action(menu_copy_all_csv_action);
+        messages_actions.add_action(menu_copy_all_json_action);
         messages_actions.add_action(menu_copy_key_value_action);
         messages_actions.add_action(menu_copy_value_action);
         messages_actions.add_action(menu_copy_key_action);
+        messages_actions.add_action(menu_copy_kcat_action);
         messages_actions.add_action(menu_resend_key_value_action);
         messages_actions.add_action(menu_resend_value_action);
+        messages_actions.add_action(menu_resend_preserve_timestamp_action);
+        messages_actions.add_action(menu_copy_include_headers_action);
+        messages_actions.add_action(menu_compare_action);
+        messages_actions.add_action(menu_toggle_bookmark_action);
+        messages_actions.add_action(menu_reset_columns_action);
         messages_actions.register_for_widget(&messages_popover_menu);
 
         let add_messages = MessagesSendDialogModel::builder()
@@ -549,9 +990,39 @@ impl FactoryComponent for MessagesTabModel {
                     MessagesTabMsg::UpdateCacheSettings(cache)
                 }
             });
+        let delete_range_dialog = MessagesDeleteRangeDialogModel::builder()
+            .launch(Some(open.topic.clone()))
+            .forward(sender.input_sender(), |msg| match msg {
+                MessagesDeleteRangeDialogOutput::Delete {
+                    partition,
+                    offset_from,
+                    offset_to,
+                } => MessagesTabMsg::DeleteMessagesRange(partition, offset_from, offset_to),
+            });
+        let compare_dialog = MessagesCompareDialogModel::builder().launch(()).detach();
+        let format_dialog = MessagesFormatDialogModel::builder()
+            .launch((open.connection.clone(), Some(open.topic.clone())))
+            .detach();
+        let export_dialog = SaveDialog::builder()
+            .launch(SaveDialogSettings {
+                accept_label: String::from("Export"),
+                cancel_label: String::from("Cancel"),
+                create_folders: true,
+                is_modal: true,
+                filters: Vec::new(),
+            })
+            .forward(sender.input_sender(), |response| match response {
+                SaveDialogResponse::Accept(path) => MessagesTabMsg::ExportCacheTo(path),
+                SaveDialogResponse::Cancel => MessagesTabMsg::Ignore,
+            });
+        let settings = Settings::read().unwrap_or_default();
+        let max_messages_limit = settings.live_max_messages_max.max(1) as f64;
+        let max_messages =
+            (settings.live_max_messages_default as f64).clamp(1.0, max_messages_limit);
         let clipboard = Box::new(ClipboardContext::new().unwrap());
         let model = MessagesTabModel {
             token: CancellationToken::new(),
+            fetch_token: None,
             mode: MessagesMode::Live,
             topic: Some(open.topic),
             connection: Some(open.connection),
@@ -560,14 +1031,38 @@ impl FactoryComponent for MessagesTabModel {
             page_size_combo,
             page_size: AVAILABLE_PAGE_SIZES[default_idx],
             fetch_type_combo,
-            fetch_type: KafkaFetch::default(),
-            max_messages: 1000.0,
+            fetch_type: KafkaFetch::VALUES[fetch_type_default_idx].clone(),
+            max_messages,
+            max_messages_limit,
+            quick_fetch_box,
+            auto_refresh_combo,
+            auto_refresh_interval: Duration::from_secs(AUTO_REFRESH_INTERVALS[0]),
+            auto_refresh: None,
+            paused_offsets: None,
+            resume_offsets: None,
             messages_menu_popover: messages_popover_menu,
+            columns_popover,
+            csv_visible_columns_only,
+            csv_projection_entry,
             add_messages,
             clipboard,
             cache_search_order: None,
+            search_mode_combo,
+            search_field_combo,
+            search_options: MessagesSearchOptions::default(),
             cache_settings_dialog,
+            delete_range_dialog,
+            compare_dialog,
+            format_dialog,
+            export_dialog,
             cache_settings,
+            resend_preserve_timestamp: false,
+            pending_resend: vec![],
+            copy_include_headers: false,
+            pending_messages: VecDeque::new(),
+            pending_format: MessageItemFormat::default(),
+            bookmarks,
+            default_columns_layout,
         };
         let messages_view = &model.messages_wrapper.view;
         let sender_for_selection = sender.clone();
@@ -576,6 +1071,8 @@ impl FactoryComponent for MessagesTabModel {
             .unwrap()
             .connect_selection_changed(move |selection_model, i, j| {
                 let size = selection_model.selection().size();
+                compare_action.set_enabled(size == 2);
+                sender_for_selection.input(MessagesTabMsg::SelectionChanged);
                 if size == 1 {
                     let selected = selection_model.selection().minimum();
                     trace!(
@@ -588,6 +1085,33 @@ impl FactoryComponent for MessagesTabModel {
                     sender_for_selection.input(MessagesTabMsg::OpenMessage(selected));
                 }
             });
+        let snd = sender.clone();
+        messages_view.connect_activate(move |_view, idx| {
+            snd.input(MessagesTabMsg::OpenMessage(idx));
+        });
+        // plain up/down always move a single selection, so the viewer follows the cursor
+        let key_controller = gtk::EventControllerKey::new();
+        key_controller.set_propagation_phase(gtk::PropagationPhase::Capture);
+        let snd = sender.clone();
+        key_controller.connect_key_pressed(move |_controller, key, _code, modifiers| {
+            let modified = modifiers.intersects(
+                gtk::gdk::ModifierType::SHIFT_MASK
+                    | gtk::gdk::ModifierType::CONTROL_MASK
+                    | gtk::gdk::ModifierType::ALT_MASK,
+            );
+            match key {
+                gtk::gdk::Key::Down if !modified => {
+                    snd.input(MessagesTabMsg::StepMessage(1));
+                    gtk::glib::Propagation::Stop
+                }
+                gtk::gdk::Key::Up if !modified => {
+                    snd.input(MessagesTabMsg::StepMessage(-1));
+                    gtk::glib::Propagation::Stop
+                }
+                _ => gtk::glib::Propagation::Proceed,
+            }
+        });
+        messages_view.add_controller(key_controller);
 
         let snd = sender.clone();
         messages_view
@@ -605,6 +1129,7 @@ impl FactoryComponent for MessagesTabModel {
                             let col_name = match col_name.as_str() {
                                 "Offset" => Some("offset"),
                                 "Partition" => Some("partition"),
+                                "Key" => Some("key"),
                                 "Date/time (Timestamp)" => Some("timestamp"),
                                 _ => None,
                             }
@@ -635,19 +1160,10 @@ impl FactoryComponent for MessagesTabModel {
 
     fn post_view(&self, widgets: &mut Self::Widgets) {
         widgets.max_messages.set_increments(1000.0, 10000.0);
-        // Shortcuts
-        // let mut actions = RelmActionGroup::<MessagesPageActionGroup>::new();
-
-        // let messages_search_entry = widgets.messages_search_entry.clone();
-        // let search_action = {
-        //     let messages_search_btn = widgets.messages_search_btn.clone();
-        //     RelmAction::<MessagesSearchAction>::new_stateless(move |_| {
-        //         messages_search_btn.emit_clicked();
-        //     })
-        // };
-        // actions.add_action(search_action);
-        // actions.register_for_widget(messages_search_entry);
-
+        widgets.max_messages.set_range(1.0, self.max_messages_limit);
+        if let Some(conn) = &self.connection {
+            colorize_widget_by_connection(conn, widgets.toolbar.clone().upcast());
+        }
         //self.messages_menu_popover.set_menu_model(widgets.menu)
         // Create a click gesture
         let gesture = gtk::GestureClick::new();
@@ -657,17 +1173,33 @@ impl FactoryComponent for MessagesTabModel {
 
         // Assign your handler to an event of the gesture (e.g. the `pressed` event)
         let messages_menu = self.messages_menu_popover.clone();
+        let columns_menu = self.columns_popover.clone();
+        let header = self.messages_wrapper.view.first_child();
         gesture.connect_pressed(move |gesture, _n, x, y| {
             gesture.set_state(gtk::EventSequenceState::Claimed);
+            // the first child of the column view is its header row
+            let on_header = header.as_ref().is_some_and(|h| y < h.height() as f64);
+            let menu: gtk::Popover = if on_header {
+                columns_menu.clone()
+            } else {
+                messages_menu.clone().upcast()
+            };
             let x = x as i32;
             let y = y as i32;
             info!("ColumnView: Right mouse button pressed [x={},y={}]", x, y);
-            messages_menu.set_pointing_to(Some(&Rectangle::new(x, y + 55, 1, 1)));
-            messages_menu.popup();
+            menu.set_pointing_to(Some(&Rectangle::new(x, y + 55, 1, 1)));
+            menu.popup();
         });
         self.messages_wrapper.view.add_controller(gesture);
     }
 
+    fn shutdown(&mut self, widgets: &mut Self::Widgets, _output: relm4::Sender<Self::Output>) {
+        self.stop_auto_refresh();
+        self.token.cancel();
+        self.save_columns_layout();
+        self.save_search(widgets);
+    }
+
     fn update_with_view(
         &mut self,
         widgets: &mut Self::Widgets,
@@ -686,6 +1218,31 @@ impl FactoryComponent for MessagesTabModel {
             MessagesTabMsg::SetCacheOrder(maybe_column, order) => {
                 let cache_messages_order =
                     maybe_column.map(|column| MessagesSearchOrder { column, order });
+                if let (Some(search_order), Some(conn_id), Some(topic)) = (
+                    &cache_messages_order,
+                    self.connection.as_ref().and_then(|c| c.id),
+                    &self.topic,
+                ) {
+                    let mut repo = Repository::new();
+                    repo.save_ui_state(
+                        conn_id,
+                        &topic.name,
+                        SORT_COLUMN_STATE,
+                        &search_order.column,
+                    )
+                    .and_then(|_| {
+                        repo.save_ui_state(
+                            conn_id,
+                            &topic.name,
+                            SORT_ORDER_STATE,
+                            &search_order.order,
+                        )
+                    })
+                    .unwrap_or_else(|e| {
+                        warn!("unable to save sort order state: {:?}", e);
+                        0
+                    });
+                }
                 self.cache_search_order = cache_messages_order;
                 if let MessagesMode::Cached { refresh: _ } = self.mode {
                     sender.input(MessagesTabMsg::GetMessages);
@@ -694,6 +1251,9 @@ impl FactoryComponent for MessagesTabModel {
             MessagesTabMsg::AddMessages => {
                 self.add_messages.emit(MessagesSendDialogMsg::Show);
             }
+            MessagesTabMsg::ShowFormatSettings => {
+                self.format_dialog.emit(MessagesFormatDialogMsg::Show);
+            }
             MessagesTabMsg::ShowCacheSettings => {
                 self.cache_settings_dialog
                     .emit(MessagesCacheSettingsDialogMsg::Show);
@@ -702,14 +1262,29 @@ impl FactoryComponent for MessagesTabModel {
                 self.max_messages = value;
                 info!("Max messages:{}", self.max_messages);
             }
+            MessagesTabMsg::QuickFetch(fetch, count) => {
+                info!("quick fetch {} of {}", count, fetch);
+                if let Some(idx) = KafkaFetch::VALUES.iter().position(|f| *f == fetch) {
+                    self.fetch_type_combo
+                        .emit(SimpleComboBoxMsg::SetActiveIdx(idx));
+                }
+                self.fetch_type = fetch;
+                self.max_messages = (count as f64).clamp(1.0, self.max_messages_limit);
+                widgets.max_messages.set_value(self.max_messages);
+                sender.input(MessagesTabMsg::GetMessages);
+            }
             MessagesTabMsg::ToggleMode(toggle) => {
                 self.mode = if toggle {
+                    self.stop_auto_refresh();
+                    widgets.btn_auto_refresh.set_active(false);
                     widgets.cached_controls.set_visible(true);
                     widgets.cached_centered_controls.set_visible(true);
                     widgets.live_centered_controls.set_visible(false);
                     widgets.live_controls.set_visible(false);
                     widgets.btn_cache_refresh.set_visible(true);
                     widgets.btn_cache_destroy.set_visible(true);
+                    widgets.btn_cache_delete_range.set_visible(true);
+                    widgets.btn_cache_export.set_visible(true);
                     widgets.btn_cache_settings.set_visible(true);
                     widgets.pag_total_label.set_text("Total");
                     MessagesMode::Cached { refresh: false }
@@ -717,6 +1292,8 @@ impl FactoryComponent for MessagesTabModel {
                     widgets.live_controls.set_visible(true);
                     widgets.btn_cache_refresh.set_visible(false);
                     widgets.btn_cache_destroy.set_visible(false);
+                    widgets.btn_cache_delete_range.set_visible(false);
+                    widgets.btn_cache_export.set_visible(false);
                     widgets.btn_cache_settings.set_visible(false);
                     widgets.pag_total_label.set_text("Messages");
                     widgets.cached_controls.set_visible(false);
@@ -737,6 +1314,67 @@ impl FactoryComponent for MessagesTabModel {
                     sender.input(MessagesTabMsg::SearchMessages);
                 }
             }
+            MessagesTabMsg::ToggleAutoRefresh(enabled) => {
+                if enabled {
+                    self.start_auto_refresh(&sender);
+                    sender.input(MessagesTabMsg::AutoRefresh);
+                } else {
+                    self.stop_auto_refresh();
+                    self.paused_offsets = None;
+                    widgets.btn_pause.set_active(false);
+                }
+                widgets.btn_pause.set_sensitive(enabled);
+            }
+            MessagesTabMsg::AutoRefreshIntervalChanged(idx) => {
+                let secs = AUTO_REFRESH_INTERVALS
+                    .get(idx)
+                    .copied()
+                    .unwrap_or(AUTO_REFRESH_INTERVALS[0]);
+                self.auto_refresh_interval = Duration::from_secs(secs);
+                if self.auto_refresh.is_some() {
+                    self.start_auto_refresh(&sender);
+                }
+            }
+            MessagesTabMsg::AutoRefresh => {
+                let running = self
+                    .fetch_token
+                    .as_ref()
+                    .is_some_and(|token| !token.is_cancelled());
+                if self.mode != MessagesMode::Live {
+                    trace!("auto refresh ignored outside live mode");
+                } else if self.paused_offsets.is_some() {
+                    trace!("auto refresh paused");
+                } else if running {
+                    trace!("auto refresh skipped, previous fetch still running");
+                } else {
+                    sender.input(MessagesTabMsg::GetMessages);
+                }
+            }
+            MessagesTabMsg::TogglePause(paused) => {
+                if paused {
+                    let mut offsets: HashMap<i32, i64> = HashMap::new();
+                    let shown = (0..self.messages_wrapper.len())
+                        .filter_map(|i| self.messages_wrapper.get(i))
+                        .map(|item| {
+                            let item = item.borrow();
+                            (item.partition, item.offset)
+                        });
+                    let pending = self
+                        .pending_messages
+                        .iter()
+                        .map(|m| (m.partition, m.offset));
+                    for (partition, offset) in shown.chain(pending) {
+                        let next = offsets.entry(partition).or_insert(offset + 1);
+                        *next = (*next).max(offset + 1);
+                    }
+                    info!("pausing stream at offsets {:?}", offsets);
+                    self.paused_offsets = Some(offsets);
+                } else if let Some(offsets) = self.paused_offsets.take() {
+                    info!("resuming stream from offsets {:?}", offsets);
+                    self.resume_offsets = Some(offsets);
+                    sender.input(MessagesTabMsg::GetMessages);
+                }
+            }
             MessagesTabMsg::FetchTypeChanged(_idx) => {
                 let fetch_type = match self.fetch_type_combo.model().get_active_elem() {
                     Some(ps) => ps.clone(),
@@ -745,6 +1383,129 @@ impl FactoryComponent for MessagesTabModel {
                 self.fetch_type = fetch_type;
                 self.fetch_type_combo.widget().queue_allocate();
             }
+            MessagesTabMsg::SearchModeChanged(_idx) => {
+                let search_mode = match self.search_mode_combo.model().get_active_elem() {
+                    Some(mode) => *mode,
+                    None => MessagesSearchMode::default(),
+                };
+                self.search_options.mode = search_mode;
+                let term = widgets.messages_search_entry.text().to_string();
+                sender.input(MessagesTabMsg::LiveSearchMessages(term));
+            }
+            MessagesTabMsg::ToggleCaseInsensitive(case_insensitive) => {
+                self.search_options.case_insensitive = case_insensitive;
+                let term = widgets.messages_search_entry.text().to_string();
+                sender.input(MessagesTabMsg::LiveSearchMessages(term));
+            }
+            MessagesTabMsg::SearchFieldChanged(_idx) => {
+                let search_field = match self.search_field_combo.model().get_active_elem() {
+                    Some(field) => *field,
+                    None => MessagesSearchField::default(),
+                };
+                self.search_options.field = search_field;
+                let term = widgets.messages_search_entry.text().to_string();
+                sender.input(MessagesTabMsg::LiveSearchMessages(term));
+            }
+            MessagesTabMsg::ToggleBookmarks => {
+                let (Some(conn_id), Some(topic)) = (
+                    self.connection.as_ref().and_then(|c| c.id),
+                    self.topic.as_ref(),
+                ) else {
+                    return;
+                };
+                let selection_model = &self.messages_wrapper.selection_model;
+                let selected = (0..selection_model.n_items())
+                    .filter(|i| selection_model.is_selected(*i))
+                    .filter_map(|i| self.messages_wrapper.get_visible(i))
+                    .map(|item| {
+                        let item = item.borrow();
+                        (item.partition, item.offset)
+                    })
+                    .collect::<HashSet<_>>();
+                let mut repo = Repository::new();
+                for (partition, offset) in selected.iter().copied() {
+                    match repo.toggle_bookmark(conn_id, &topic.name, partition, offset) {
+                        Ok(true) => {
+                            self.bookmarks.insert((partition, offset));
+                        }
+                        Ok(false) => {
+                            self.bookmarks.remove(&(partition, offset));
+                        }
+                        Err(e) => warn!("unable to toggle bookmark: {:?}", e),
+                    }
+                }
+                // items are replaced so the bookmark column and filter pick up the change
+                for i in 0..self.messages_wrapper.len() {
+                    let Some(item) = self.messages_wrapper.get(i) else {
+                        continue;
+                    };
+                    let mut updated = item.borrow().clone();
+                    if !selected.contains(&(updated.partition, updated.offset)) {
+                        continue;
+                    }
+                    updated.bookmarked = self
+                        .bookmarks
+                        .contains(&(updated.partition, updated.offset));
+                    self.messages_wrapper.remove(i);
+                    self.messages_wrapper.insert(i, updated);
+                }
+            }
+            MessagesTabMsg::SelectionChanged => {
+                let mut count = 0;
+                let mut bytes = 0;
+                for i in 0..self.messages_wrapper.selection_model.n_items() {
+                    if self.messages_wrapper.selection_model.is_selected(i) {
+                        if let Some(item) = self.messages_wrapper.get_visible(i) {
+                            count += 1;
+                            bytes += item.borrow().size;
+                        }
+                    }
+                }
+                let text = (count > 0)
+                    .then(|| format!("{} selected · {}", count, format_size(bytes, DECIMAL)));
+                STATUS_BROKER.send(StatusBarMsg::Selection { text });
+            }
+            MessagesTabMsg::ToggleCsvVisibleColumnsOnly(enabled) => {
+                self.csv_visible_columns_only = enabled;
+                if let (Some(conn_id), Some(topic)) =
+                    (self.connection.as_ref().and_then(|c| c.id), &self.topic)
+                {
+                    Repository::new()
+                        .save_ui_state(
+                            conn_id,
+                            &topic.name,
+                            CSV_VISIBLE_COLUMNS_STATE,
+                            &enabled.to_string(),
+                        )
+                        .unwrap_or_else(|e| {
+                            warn!("unable to save csv columns state: {:?}", e);
+                            0
+                        });
+                }
+            }
+            MessagesTabMsg::ResetColumns => {
+                apply_columns_layout(&self.messages_wrapper.view, &self.default_columns_layout);
+                self.save_columns_layout();
+            }
+            MessagesTabMsg::CompareMessages => {
+                let topic = self.topic.clone().unwrap().name;
+                let mut selected_items = vec![];
+                for i in 0..self.messages_wrapper.selection_model.n_items() {
+                    if self.messages_wrapper.selection_model.is_selected(i) {
+                        let item = self.messages_wrapper.get_visible(i).unwrap();
+                        selected_items.push(item.borrow().to_message(&topic));
+                    }
+                }
+                match <[KrustMessage; 2]>::try_from(selected_items) {
+                    Ok([old, new]) => self
+                        .compare_dialog
+                        .emit(MessagesCompareDialogMsg::Show(Box::new(old), Box::new(new))),
+                    Err(items) => warn!("compare needs two messages, {} selected", items.len()),
+                }
+            }
+            MessagesTabMsg::ToggleBookmarkedOnly(active) => {
+                self.messages_wrapper.set_filter_status(0, active);
+            }
             MessagesTabMsg::CopyMessages(copy) => {
                 info!("copy selected messages");
                 let topic = self.topic.clone().unwrap().name;
@@ -752,31 +1513,50 @@ impl FactoryComponent for MessagesTabModel {
                 for i in 0..self.messages_wrapper.selection_model.n_items() {
                     if self.messages_wrapper.selection_model.is_selected(i) {
                         let item = self.messages_wrapper.get_visible(i).unwrap();
-                        selected_items.push(KrustMessage {
-                            headers: item.borrow().headers.clone(),
-                            topic: topic.clone(),
-                            partition: item.borrow().partition,
-                            offset: item.borrow().offset,
-                            key: Some(item.borrow().key.clone()),
-                            value: item.borrow().value.clone(),
-                            timestamp: item.borrow().timestamp,
-                        });
+                        selected_items.push(item.borrow().to_message(&topic));
                     }
                 }
+                let projection = parse_csv_projection(self.csv_projection_entry.text().as_str());
+                let fields = self.csv_fields();
+                let include_headers = self.copy_include_headers;
+                let brokers = self
+                    .connection
+                    .as_ref()
+                    .map(|c| c.brokers_list.clone())
+                    .unwrap_or_default();
+                let task = Task::new(TaskVariant::CopyMessages, Some(topic), None);
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::AddTask(task.clone()));
                 sender.spawn_oneshot_command(move || {
                     let id = Uuid::new_v4();
                     TOASTER_BROKER
                         .send(AppMsg::ShowToast(id.to_string(), "Copying...".to_string()));
                     let data = match copy {
-                        Copy::AllAsCsv => copy_all_as_csv(&selected_items),
-                        Copy::KeyValue => copy_key_value(&selected_items),
-                        Copy::Value => copy_value(&selected_items),
-                        Copy::Key => copy_key(&selected_items),
+                        Copy::AllAsCsv => {
+                            copy_all_as_csv(&selected_items, &projection, &fields, &task)
+                                .map_err(|e| e.to_string())
+                        }
+                        Copy::AllAsJson => {
+                            copy_all_as_json(&selected_items).map_err(|e| e.to_string())
+                        }
+                        Copy::KeyValue => copy_key_value(&selected_items, include_headers)
+                            .map_err(|e| e.to_string()),
+                        Copy::Value => {
+                            copy_value(&selected_items, include_headers).map_err(|e| e.to_string())
+                        }
+                        Copy::Key => copy_key(&selected_items).map_err(|e| e.to_string()),
+                        Copy::AsKcatCommand => copy_as_kcat_commands(&selected_items, &brokers)
+                            .map_err(|e| e.to_string()),
                     };
-                    if let Ok(data) = data {
-                        CommandMsg::CopyToClipboard(id.to_string(), data)
-                    } else {
-                        CommandMsg::CopyToClipboard(id.to_string(), String::default())
+                    match data {
+                        Ok(data) => {
+                            TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task, 1.0));
+                            CommandMsg::CopyToClipboard(id.to_string(), data)
+                        }
+                        Err(e) => {
+                            warn!("unable to copy messages: {}", e);
+                            TASK_MANAGER_BROKER.send(TaskManagerMsg::Failed(task, e.clone()));
+                            CommandMsg::CopyFailed(id.to_string(), e)
+                        }
                     }
                 });
             }
@@ -787,22 +1567,53 @@ impl FactoryComponent for MessagesTabModel {
                 for i in 0..self.messages_wrapper.selection_model.n_items() {
                     if self.messages_wrapper.selection_model.is_selected(i) {
                         let item = self.messages_wrapper.get_visible(i).unwrap();
-                        selected_items.push(KrustMessage {
-                            headers: item.borrow().headers.clone(),
-                            topic: topic.clone(),
-                            partition: item.borrow().partition,
-                            offset: item.borrow().offset,
-                            key: match copy {
-                                Copy::KeyValue => Some(item.borrow().key.clone()),
-                                _ => None,
-                            },
-                            value: item.borrow().value.clone(),
-                            timestamp: item.borrow().timestamp,
-                        });
+                        let mut message = item.borrow().to_message(&topic);
+                        if !matches!(copy, Copy::KeyValue) {
+                            message.key = None;
+                        }
+                        selected_items.push(message);
                     }
                 }
+                if selected_items.is_empty() {
+                    return;
+                }
                 selected_items.sort_by(|a, b| a.timestamp.partial_cmp(&b.timestamp).unwrap());
+                self.pending_resend = selected_items;
                 let connection = self.connection.clone().unwrap();
+                sender.oneshot_command(async move {
+                    let kafka = KafkaBackend::new(&connection);
+                    let topics = match kafka.list_topics().await {
+                        Ok(topics) => topics.into_iter().map(|t| t.name).collect(),
+                        Err(e) => {
+                            warn!("unable to list resend destination topics: {:?}", e);
+                            vec![]
+                        }
+                    };
+                    CommandMsg::ResendTargets(topics)
+                });
+            }
+            MessagesTabMsg::CancelResendMessages => {
+                self.pending_resend.clear();
+            }
+            MessagesTabMsg::ConfirmResendMessages(topic, partitioning) => {
+                let mut selected_items = std::mem::take(&mut self.pending_resend);
+                info!(
+                    "resending {} messages to {}, partitioning {:?}",
+                    selected_items.len(),
+                    &topic,
+                    partitioning
+                );
+                for message in selected_items.iter_mut() {
+                    message.topic = topic.clone();
+                    if partitioning == ResendPartitioning::ByKey {
+                        message.partition = PARTITION_BY_KEY;
+                    }
+                }
+                let connection = self.connection.clone().unwrap();
+                let options = SendOptions {
+                    preserve_timestamp: self.resend_preserve_timestamp,
+                    ..Default::default()
+                };
                 sender.oneshot_command(async move {
                     let id = Uuid::new_v4();
                     TOASTER_BROKER.send(AppMsg::ShowToast(
@@ -812,18 +1623,44 @@ impl FactoryComponent for MessagesTabModel {
                     debug!("sending messages::{:?}", &selected_items);
                     // Run async background task
                     let kafka = KafkaBackend::new(&connection);
-                    kafka.send_messages(&topic, &selected_items).await;
-                    CommandMsg::MessagesResendResult(id.to_string(), Some(()))
+                    let result = kafka
+                        .send_messages_with_options(&topic, &selected_items, options)
+                        .await;
+                    CommandMsg::MessagesResendResult(id.to_string(), result)
                 });
             }
+            MessagesTabMsg::TogglePreserveTimestamp(preserve) => {
+                info!("resend preserving timestamp::{}", preserve);
+                self.resend_preserve_timestamp = preserve;
+            }
+            MessagesTabMsg::ToggleCopyIncludeHeaders(include) => {
+                info!("copy including headers::{}", include);
+                self.copy_include_headers = include;
+            }
             MessagesTabMsg::Open(connection, topic) => {
-                let timestamp_format = Settings::read().unwrap_or_default().timestamp_formatter();
+                let separator_position = MESSAGES_SEPARATOR_POSITION.load(Ordering::Relaxed);
+                if separator_position > 0 {
+                    widgets.messages_paned.set_position(separator_position);
+                }
+                let settings = Settings::read().unwrap_or_default();
+                let timestamp_format = settings.timestamp_formatter();
+                let timezone = settings.timezone();
                 let conn_id = &connection.id.unwrap();
                 let topic_name = &topic.name.clone();
                 self.connection = Some(*connection);
                 let mut repo = Repository::new();
                 let maybe_topic = repo.find_topic(*conn_id, topic_name);
                 self.topic = maybe_topic.clone().or(Some(*topic));
+                self.cache_search_order = repo
+                    .find_ui_state(*conn_id, topic_name, SORT_COLUMN_STATE)
+                    .zip(repo.find_ui_state(*conn_id, topic_name, SORT_ORDER_STATE))
+                    .map(|(column, order)| MessagesSearchOrder { column, order });
+                let saved_search = repo
+                    .find_ui_state(*conn_id, topic_name, SEARCH_STATE)
+                    .and_then(|json| serde_json::from_str::<SavedSearch>(&json).ok());
+                if let Some(search) = saved_search {
+                    self.restore_search(widgets, &search);
+                }
                 self.cache_settings = self.topic.clone().and_then(|t| t.cached);
                 let toggled = match &maybe_topic {
                     Some(t) => t.cached.is_some(),
@@ -831,15 +1668,8 @@ impl FactoryComponent for MessagesTabModel {
                 };
                 let cache_ts = maybe_topic
                     .and_then(|t| {
-                        t.cached.map(|c| {
-                            c.last_updated.map(|ts| {
-                                Utc.timestamp_millis_opt(ts)
-                                    .unwrap()
-                                    .with_timezone(&America::Sao_Paulo)
-                                    .format(&timestamp_format)
-                                    .to_string()
-                            })
-                        })
+                        t.cached
+                            .map(|c| format_timestamp(c.last_updated, &timestamp_format, &timezone))
                     })
                     .unwrap_or_default();
                 if cache_ts.clone().is_some() {
@@ -856,21 +1686,44 @@ impl FactoryComponent for MessagesTabModel {
                 widgets.pag_current_entry.set_text("");
                 widgets.pag_last_entry.set_text("");
                 self.messages_wrapper.clear();
+                self.pending_messages.clear();
                 self.page_size_combo.widget().queue_allocate();
                 sender.input(MessagesTabMsg::ToggleMode(toggled));
             }
             MessagesTabMsg::LiveSearchMessages(term) => {
                 match self.mode {
                     MessagesMode::Live => {
-                        self.messages_wrapper.clear_filters();
-                        let search_term = term.clone();
-                        self.messages_wrapper
-                            .add_filter(move |item| item.value.contains(search_term.as_str()));
+                        while self.messages_wrapper.filters_len() > 1 {
+                            self.messages_wrapper.pop_filter();
+                        }
+                        if let Some(matcher) =
+                            build_search_matcher(widgets, term.as_str(), &self.search_options)
+                        {
+                            // the search covers the whole page, not only the rows built so far
+                            self.append_pending_messages(self.pending_messages.len());
+                            let search_field = self.search_options.field;
+                            self.messages_wrapper
+                                .add_filter(move |item| match search_field {
+                                    MessagesSearchField::Value => {
+                                        matcher.is_match(item.value.as_deref().unwrap_or_default())
+                                    }
+                                    MessagesSearchField::Key => {
+                                        matcher.is_match(item.key.as_deref().unwrap_or_default())
+                                    }
+                                    MessagesSearchField::Both => {
+                                        matcher.is_match(item.key.as_deref().unwrap_or_default())
+                                            || matcher
+                                                .is_match(item.value.as_deref().unwrap_or_default())
+                                    }
+                                });
+                        }
                         let total = widgets.messages_view.model().unwrap().n_items();
                         info!("Total messages::{}", total);
                         fill_pagination(widgets, total as usize, 0);
                     }
-                    MessagesMode::Cached { refresh: _ } => (),
+                    MessagesMode::Cached { refresh: _ } => {
+                        build_search_matcher(widgets, term.as_str(), &self.search_options);
+                    }
                 };
             }
             MessagesTabMsg::SearchMessages => {
@@ -889,21 +1742,31 @@ impl FactoryComponent for MessagesTabModel {
             }
             MessagesTabMsg::GetMessages => {
                 info!("[GetMessages] {}", self.mode);
+                if let (MessagesMode::Cached { refresh: _ }, Some(term)) =
+                    (self.mode, get_search_term(widgets))
+                {
+                    if build_search_matcher(widgets, term.as_str(), &self.search_options).is_none()
+                    {
+                        return;
+                    }
+                }
                 STATUS_BROKER.send(StatusBarMsg::Start);
                 on_loading(widgets, false);
                 let mode = self.mode;
+                let resume_offsets = self.resume_offsets.take();
                 self.mode = match self.mode {
                     MessagesMode::Cached { refresh: _ } => MessagesMode::Cached { refresh: false },
                     MessagesMode::Live => {
-                        self.messages_wrapper.clear();
+                        // a resumed stream appends to the window shown while paused
+                        if resume_offsets.is_none() {
+                            self.messages_wrapper.clear();
+                            self.pending_messages.clear();
+                        }
                         MessagesMode::Live
                     }
                 };
                 let topic = self.topic.clone().unwrap();
                 let conn = self.connection.clone().unwrap();
-                if self.token.is_cancelled() {
-                    self.token = CancellationToken::new();
-                }
                 let page_size = self.page_size;
                 let page: usize = widgets
                     .pag_current_entry
@@ -913,43 +1776,41 @@ impl FactoryComponent for MessagesTabModel {
                     .unwrap_or(1);
                 let search_order = self.cache_search_order.clone();
                 let search = get_search_term(widgets);
+                let search_options = self.search_options.clone();
                 let fetch = self.fetch_type.clone();
                 let max_messages: i64 = self.max_messages as i64;
                 widgets
                     .pag_current_entry
                     .set_text(page.to_string().as_str());
-                let task_name = topic.name.clone();
-                let task = Task::new(
-                    TaskVariant::FetchMessages,
-                    Some(task_name),
-                    Some(self.token.clone()),
-                );
-                TOASTER_BROKER.send(AppMsg::ShowToast(task.id.clone(), "Working...".to_string()));
-                TASK_MANAGER_BROKER.send(TaskManagerMsg::AddTask(task.clone()));
                 let cache = self.cache_settings.clone();
-                sender.oneshot_command(async move {
-                    // Run async background task
-                    let messages_worker = MessagesWorker::new();
-                    let result = &messages_worker
-                        .get_messages(&MessagesRequest {
-                            task: Some(task),
-                            mode,
-                            connection: conn,
-                            topic: topic.clone(),
-                            page,
-                            search_order,
-                            page_size,
-                            search,
-                            fetch,
-                            max_messages,
-                            cache,
-                        })
-                        .await
-                        .unwrap();
-                    let total = result.total;
-                    trace!("selected topic {} with {} messages", topic.name, &total,);
-                    CommandMsg::Data(result.clone())
-                });
+                self.fetch_messages(
+                    MessagesRequest {
+                        task: None,
+                        mode,
+                        connection: conn,
+                        topic,
+                        page,
+                        search_order,
+                        page_size,
+                        search,
+                        search_options,
+                        fetch,
+                        max_messages,
+                        cache,
+                        resume_offsets,
+                    },
+                    &sender,
+                );
+            }
+            MessagesTabMsg::RetryMessages(request) => {
+                if self.fetch_token.is_some() {
+                    warn!("not retrying, messages are still being fetched");
+                    return;
+                }
+                info!("[RetryMessages] {}", request.mode);
+                STATUS_BROKER.send(StatusBarMsg::Start);
+                on_loading(widgets, false);
+                self.fetch_messages(*request, &sender);
             }
             MessagesTabMsg::GetNextMessages => {
                 let page_size = self.page_size;
@@ -1012,6 +1873,55 @@ impl FactoryComponent for MessagesTabModel {
                 widgets.btn_cache_toggle.set_active(false);
                 sender.input(MessagesTabMsg::ToggleMode(false));
             }
+            MessagesTabMsg::ShowDeleteMessagesRange => {
+                self.delete_range_dialog
+                    .emit(MessagesDeleteRangeDialogMsg::Show(self.topic.clone()));
+            }
+            MessagesTabMsg::FocusSearch => {
+                widgets.messages_search_entry.grab_focus();
+            }
+            MessagesTabMsg::ExportCache => {
+                let topic = self.topic.clone().unwrap();
+                self.export_dialog
+                    .emit(SaveDialogMsg::SaveAs(format!("{}.ndjson", topic.name)));
+            }
+            MessagesTabMsg::ExportCacheTo(path) => {
+                let conn_id = self.connection.clone().unwrap().id.unwrap();
+                let topic = self.topic.clone().unwrap();
+                let order = self.cache_search_order.clone();
+                let projection = parse_csv_projection(self.csv_projection_entry.text().as_str());
+                let fields = self.csv_fields();
+                let task = Task::new(
+                    TaskVariant::ExportMessages,
+                    Some(topic.name.clone()),
+                    Some(CancellationToken::new()),
+                );
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::AddTask(task.clone()));
+                TOASTER_BROKER.send(AppMsg::ShowToast(
+                    task.id.clone(),
+                    "Exporting...".to_string(),
+                ));
+                sender.spawn_oneshot_command(move || {
+                    let mrepo = MessagesRepository::new(conn_id, &topic.name);
+                    let result = export_cache(mrepo, order, &projection, &fields, &path, &task);
+                    CommandMsg::CacheExported(task, path, result)
+                });
+            }
+            MessagesTabMsg::Ignore => (),
+            MessagesTabMsg::DeleteMessagesRange(partition, offset_from, offset_to) => {
+                let conn_id = self.connection.clone().unwrap().id.unwrap();
+                let topic = self.topic.clone().unwrap();
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(id.clone(), "Deleting...".to_string()));
+                sender.spawn_oneshot_command(move || {
+                    let result = MessagesRepository::new(conn_id, &topic.name).delete_messages(
+                        partition,
+                        offset_from,
+                        offset_to,
+                    );
+                    CommandMsg::MessagesDeleted(id, result)
+                });
+            }
             MessagesTabMsg::RefreshTopic => {
                 let conn = self.connection.clone().unwrap();
                 let topic = self.topic.clone().unwrap();
@@ -1050,34 +1960,59 @@ impl FactoryComponent for MessagesTabModel {
                             connection: conn,
                             topic: topic.clone(),
                         })
-                        .await
-                        .unwrap_or_default();
-                    debug!("selected topic {} with {} messages", topic.name, &total,);
+                        .await;
+                    debug!("selected topic {} with {:?} messages", topic.name, &total);
                     CommandMsg::RefreshTotalCounterResult(task.id, total)
                 });
             }
             MessagesTabMsg::StopGetMessages => {
                 info!("cancelling get messages...");
+                self.stop_auto_refresh();
+                widgets.btn_auto_refresh.set_active(false);
                 self.token.cancel();
                 on_loading(widgets, true);
                 STATUS_BROKER.send(StatusBarMsg::StopWithInfo {
                     text: Some("Operation cancelled!".to_string()),
                 });
             }
-            MessagesTabMsg::UpdateMessages(response) => {
+            MessagesTabMsg::UpdateMessages(mut response) => {
                 let settings = Settings::read().unwrap_or_default();
                 let timestamp_formatter = settings.timestamp_formatter();
-                let total = response.total;
+                let timezone = settings.timezone();
+                let total = match self.mode {
+                    MessagesMode::Live => {
+                        self.messages_wrapper.len() as usize
+                            + self.pending_messages.len()
+                            + response.messages.len()
+                    }
+                    MessagesMode::Cached { refresh: _ } => response.total,
+                };
                 self.topic = response.topic.clone();
                 self.cache_settings = self.topic.clone().and_then(|t| t.cached);
+                self.fetch_token = None;
+                self.pending_format = MessageItemFormat {
+                    timestamp_formatter: timestamp_formatter.clone(),
+                    timezone,
+                    value_preview_max_chars: settings.value_preview_max_chars,
+                };
                 match self.mode {
                     MessagesMode::Live => info!("no need to cleanup list on live mode"),
-                    MessagesMode::Cached { refresh: _ } => self.messages_wrapper.clear(),
+                    MessagesMode::Cached { refresh: _ } => {
+                        self.messages_wrapper.clear();
+                        self.pending_messages.clear();
+                    }
                 }
                 on_loading(widgets, true);
                 fill_pagination(widgets, total, response.page_size);
                 if self.mode == MessagesMode::Live {
-                    let sort_column = settings.messages_sort_column;
+                    // the topic's remembered sort order wins over the global settings
+                    let remembered_sort = self.cache_search_order.as_ref().and_then(|o| {
+                        sort_column_title(&o.column).map(|title| (title.to_string(), &o.order))
+                    });
+                    let sort_column = remembered_sort
+                        .as_ref()
+                        .map(|(title, _)| title.clone())
+                        .unwrap_or(settings.messages_sort_column);
                     let sort_column: Option<&ColumnViewColumn> = self
                         .messages_wrapper
                         .get_columns()
@@ -1085,10 +2020,12 @@ impl FactoryComponent for MessagesTabModel {
                     let sort_order =
                         MessagesSortOrder::from_str(settings.messages_sort_column_order.as_str())
                             .unwrap_or_default();
-                    let sort_type = match sort_order {
-                        MessagesSortOrder::Ascending => gtk::SortType::Ascending,
-                        MessagesSortOrder::Descending => gtk::SortType::Descending,
-                        MessagesSortOrder::Default => match self.fetch_type {
+                    let sort_type = match (remembered_sort, sort_order) {
+                        (Some((_, order)), _) if order == "DESC" => gtk::SortType::Descending,
+                        (Some(_), _) => gtk::SortType::Ascending,
+                        (None, MessagesSortOrder::Ascending) => gtk::SortType::Ascending,
+                        (None, MessagesSortOrder::Descending) => gtk::SortType::Descending,
+                        (None, MessagesSortOrder::Default) => match self.fetch_type {
                             KafkaFetch::Newest => gtk::SortType::Descending,
                             KafkaFetch::Oldest => gtk::SortType::Ascending,
                         },
@@ -1101,23 +2038,14 @@ impl FactoryComponent for MessagesTabModel {
                     widgets.messages_view.sort_by_column(sort_column, sort_type);
                 };
 
-                self.messages_wrapper.extend_from_iter(
-                    response
-                        .messages
-                        .iter()
-                        .map(|m| MessageListItem::new(m.clone(), timestamp_formatter.clone())),
-                );
+                // large pages are built in chunks as the list is scrolled
+                self.pending_messages
+                    .extend(std::mem::take(&mut response.messages));
+                self.append_pending_messages(MESSAGES_APPEND_CHUNK_SIZE);
                 self.message_viewer.emit(MessageViewerMsg::Clear);
                 let cache_ts = response.topic.and_then(|t| {
                     t.cached.map(|c| {
-                        c.last_updated
-                            .map(|ts| {
-                                Utc.timestamp_millis_opt(ts)
-                                    .unwrap()
-                                    .with_timezone(&America::Sao_Paulo)
-                                    .format(&timestamp_formatter)
-                                    .to_string()
-                            })
+                        format_timestamp(c.last_updated, &timestamp_formatter, &timezone)
                             .unwrap_or_default()
                     })
                 });
@@ -1130,15 +2058,39 @@ impl FactoryComponent for MessagesTabModel {
                 }
                 TOASTER_BROKER.send(AppMsg::HideToast(response.task.clone().unwrap().id.clone()));
                 STATUS_BROKER.send(StatusBarMsg::StopWithInfo {
-                    text: Some(format!("{} messages loaded!", self.messages_wrapper.len())),
+                    text: Some(format!(
+                        "{} messages loaded!",
+                        self.messages_wrapper.len() as usize + self.pending_messages.len()
+                    )),
                 });
             }
+            MessagesTabMsg::AppendPendingMessages => {
+                self.append_pending_messages(MESSAGES_APPEND_CHUNK_SIZE);
+            }
+            MessagesTabMsg::StepMessage(step) => {
+                let selection_model = &self.messages_wrapper.selection_model;
+                let n_items = selection_model.n_items();
+                if n_items > 0 {
+                    let current = selection_model.selection().minimum();
+                    let next = if current == u32::MAX {
+                        0
+                    } else {
+                        (current as i64 + step).clamp(0, n_items as i64 - 1) as u32
+                    };
+                    // selecting fires selection-changed, which opens the message
+                    selection_model.select_item(next, true);
+                    widgets
+                        .messages_view
+                        .scroll_to(next, None, gtk::ListScrollFlags::FOCUS, None);
+                }
+            }
             MessagesTabMsg::OpenMessage(message_idx) => {
                 let item = self.messages_wrapper.get_visible(message_idx).unwrap();
-                let message_text = item.borrow().value.clone();
+                let message_text = item.borrow().value.clone().unwrap_or_default();
+                let raw_value = item.borrow().raw_value.clone();
                 let headers = item.borrow().headers.clone();
                 self.message_viewer
-                    .emit(MessageViewerMsg::Open(message_text, headers));
+                    .emit(MessageViewerMsg::Open(message_text, raw_value, headers));
             }
         };
 
@@ -1152,30 +2104,117 @@ impl FactoryComponent for MessagesTabModel {
         sender: FactorySender<Self>,
     ) {
         match message {
+            CommandMsg::ResendTargets(topics) => {
+                self.confirm_resend(topics, &sender);
+            }
             CommandMsg::MessagesResendResult(task_id, result) => {
-                if result.is_some() {
-                    info!("messages resent!");
-                } else {
-                    let main_window = main_application().active_window().unwrap();
-                    show_error_alert(&main_window, "Unable to send messages".to_string());
+                match result {
+                    Ok(()) => info!("messages resent!"),
+                    Err(failures) => {
+                        let main_window = main_application().active_window().unwrap();
+                        show_error_alert(&main_window, send_failures_message(&failures));
+                    }
                 }
                 TOASTER_BROKER.send(AppMsg::HideToast(task_id));
             }
             CommandMsg::Data(messages) => {
                 sender.input(MessagesTabMsg::UpdateMessages(Box::new(messages)))
             }
+            CommandMsg::DataError(task, e) => {
+                error!("unable to load messages: {:?}", e);
+                self.fetch_token = None;
+                on_loading(widgets, true);
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::Failed(
+                    task.clone(),
+                    format!("Failed fetching {}", task.name.clone().unwrap_or_default()),
+                ));
+                TOASTER_BROKER.send(AppMsg::HideToast(task.id.clone()));
+                STATUS_BROKER.send(StatusBarMsg::StopWithInfo {
+                    text: Some("Unable to load messages".to_string()),
+                });
+                if let ExternalError::CachingError(_, _) = e {
+                    // a broken cache needs attention, a toast is easily missed
+                    let main_window = main_application().active_window().unwrap();
+                    show_error_alert(&main_window, format!("{}", e));
+                } else {
+                    let id = Uuid::new_v4().to_string();
+                    TOASTER_BROKER.send(AppMsg::ShowToast(
+                        id.clone(),
+                        format!("Unable to load messages: {}", e),
+                    ));
+                    TOASTER_BROKER.send(AppMsg::HideToast(id));
+                }
+            }
             CommandMsg::CopyToClipboard(id, data) => {
                 let data_size = format_size(data.len(), DECIMAL);
                 info!("setting text to clipboard: {}", data_size);
-                self.clipboard.set_contents(data).unwrap_or_else(|err| {
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+                if let Err(err) = self.clipboard.set_contents(data) {
                     warn!("Unable to store text in clipboard: {}", err);
-                });
+                    TOASTER_BROKER.send(AppMsg::ShowToast(
+                        Uuid::new_v4().to_string(),
+                        format!("Unable to copy {} to clipboard", data_size),
+                    ));
+                }
+            }
+            CommandMsg::CopyFailed(id, e) => {
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(
+                    id.clone(),
+                    format!("Unable to copy messages: {}", e),
+                ));
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+            }
+            CommandMsg::MessagesDeleted(id, result) => {
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+                let text = match result {
+                    Ok(deleted) => {
+                        info!("deleted {} cached messages", deleted);
+                        sender.input(MessagesTabMsg::GetMessages);
+                        format!("Deleted {} messages", deleted)
+                    }
+                    Err(e) => {
+                        error!("unable to delete cached messages: {:?}", e);
+                        "Unable to delete cached messages".to_string()
+                    }
+                };
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(id.clone(), text));
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+            }
+            CommandMsg::CacheExported(task, path, result) => {
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), 1.0));
+                TOASTER_BROKER.send(AppMsg::HideToast(task.id.clone()));
+                let text = match result {
+                    Ok(exported) => {
+                        info!("exported {} cached messages to {:?}", exported, &path);
+                        format!("Exported {} messages to {}", exported, path.display())
+                    }
+                    Err(e) => {
+                        error!("unable to export cached messages: {:?}", e);
+                        "Unable to export cached messages".to_string()
+                    }
+                };
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(id.clone(), text));
                 TOASTER_BROKER.send(AppMsg::HideToast(id));
             }
             CommandMsg::RefreshTotalCounterResult(id, total) => {
                 widgets
                     .total_counter_entry
-                    .set_text(total.to_string().as_str());
+                    .set_text(total.offset_based.to_string().as_str());
+                widgets
+                    .cached_counter_box
+                    .set_visible(total.cached.is_some());
+                if let Some(cached) = total.cached {
+                    widgets
+                        .cached_counter_entry
+                        .set_text(cached.to_string().as_str());
+                }
+                let (summary, details) = self.offsets_summary(&total.partitions);
+                widgets.offsets_label.set_label(&summary);
+                widgets.offsets_label.set_tooltip_text(Some(&details));
                 TOASTER_BROKER.send(AppMsg::HideToast(id));
             }
         }
@@ -1183,6 +2222,291 @@ impl FactoryComponent for MessagesTabModel {
 }
 
 impl MessagesTabModel {
+    /// Asks where the pending resend goes, defaulting to the same topic and partitions.
+    fn confirm_resend(&self, mut topics: Vec<String>, sender: &FactorySender<Self>) {
+        let source_topic = self.topic.clone().unwrap().name;
+        if !topics.contains(&source_topic) {
+            topics.push(source_topic.clone());
+        }
+        topics.sort();
+        let topic_names: Vec<&str> = topics.iter().map(String::as_str).collect();
+        let topic_row = adw::ComboRow::builder()
+            .title("Topic")
+            .model(&gtk::StringList::new(&topic_names))
+            .expression(gtk::PropertyExpression::new(
+                gtk::StringObject::static_type(),
+                None::<&gtk::Expression>,
+                "string",
+            ))
+            .enable_search(true)
+            .selected(
+                topics
+                    .iter()
+                    .position(|t| *t == source_topic)
+                    .unwrap_or_default() as u32,
+            )
+            .build();
+        let partitioning_labels: Vec<&str> = ResendPartitioning::VALUES
+            .iter()
+            .map(|p| p.label())
+            .collect();
+        let partitioning_row = adw::ComboRow::builder()
+            .title("Partitions")
+            .model(&gtk::StringList::new(&partitioning_labels))
+            .build();
+        let destination = gtk::ListBox::builder()
+            .selection_mode(gtk::SelectionMode::None)
+            .css_classes(["boxed-list"])
+            .build();
+        destination.append(&topic_row);
+        destination.append(&partitioning_row);
+
+        let message = format!(
+            "Resend {} message(s) from topic \"{}\" to the destination below?",
+            self.pending_resend.len(),
+            &source_topic
+        );
+        let confirmation_alert = build_confirmation_alert("Resend".to_string(), message);
+        confirmation_alert.set_extra_child(Some(&destination));
+        let snd = sender.clone();
+        confirmation_alert.connect_response(Some("cancel"), move |_, _| {
+            snd.input(MessagesTabMsg::CancelResendMessages);
+        });
+        let snd = sender.clone();
+        confirmation_alert.connect_response(Some("confirm"), move |_, _| {
+            let topic = topics
+                .get(topic_row.selected() as usize)
+                .cloned()
+                .unwrap_or_else(|| source_topic.clone());
+            let partitioning = ResendPartitioning::VALUES
+                .get(partitioning_row.selected() as usize)
+                .copied()
+                .unwrap_or(ResendPartitioning::Original);
+            snd.input(MessagesTabMsg::ConfirmResendMessages(topic, partitioning));
+        });
+        let main_window = main_application().active_window().unwrap();
+        confirmation_alert.present(&main_window);
+    }
+
+    /// Fetches messages in the background, tracked by a task that can be retried when it fails.
+    fn fetch_messages(&mut self, request: MessagesRequest, sender: &FactorySender<Self>) {
+        if self.token.is_cancelled() {
+            self.token = CancellationToken::new();
+        }
+        // a child token per fetch tells auto refresh whether a run is still going
+        let fetch_token = self.token.child_token();
+        self.fetch_token = Some(fetch_token.clone());
+        let retry_sender = sender.input_sender().clone();
+        let retry_request = request.clone();
+        let task = Task::new(
+            TaskVariant::FetchMessages,
+            Some(request.topic.name.clone()),
+            Some(fetch_token),
+        )
+        .with_retry(TaskRetry::new(move || {
+            retry_sender.emit(MessagesTabMsg::RetryMessages(Box::new(
+                retry_request.clone(),
+            )));
+        }));
+        TOASTER_BROKER.send(AppMsg::ShowToast(task.id.clone(), "Working...".to_string()));
+        TASK_MANAGER_BROKER.send(TaskManagerMsg::AddTask(task.clone()));
+        sender.oneshot_command(async move {
+            // Run async background task
+            let messages_worker = MessagesWorker::new();
+            let result = messages_worker
+                .get_messages(&MessagesRequest {
+                    task: Some(task.clone()),
+                    ..request
+                })
+                .await;
+            match result {
+                Ok(result) => {
+                    trace!(
+                        "selected topic {} with {} messages",
+                        task.name.clone().unwrap_or_default(),
+                        result.total
+                    );
+                    CommandMsg::Data(result)
+                }
+                Err(e) => CommandMsg::DataError(task, e),
+            }
+        });
+    }
+
+    /// (Re)starts the auto refresh timer with the current interval.
+    fn start_auto_refresh(&mut self, sender: &FactorySender<Self>) {
+        self.stop_auto_refresh();
+        info!(
+            "starting auto refresh every {:?}",
+            self.auto_refresh_interval
+        );
+        let sender = sender.clone();
+        let source_id = gtk::glib::timeout_add_local(self.auto_refresh_interval, move || {
+            sender.input(MessagesTabMsg::AutoRefresh);
+            gtk::glib::ControlFlow::Continue
+        });
+        self.auto_refresh = Some(source_id);
+    }
+
+    /// CSV fields written before the projected ones, leaving out the columns hidden in the
+    /// table when asked to.
+    fn csv_fields(&self) -> Vec<&'static str> {
+        let hidden: Vec<&str> = if self.csv_visible_columns_only {
+            columns_layout(&self.messages_wrapper.view)
+                .iter()
+                .filter(|column| !column.visible)
+                .filter_map(|column| csv_field(&column.title))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let mut fields: Vec<&'static str> = CSV_FIELDS
+            .into_iter()
+            .filter(|field| !hidden.contains(field))
+            .collect();
+        if self.copy_include_headers {
+            fields.push(CSV_HEADERS_FIELD);
+        }
+        fields
+    }
+
+    /// Adds up to `count` pending messages to the list.
+    fn append_pending_messages(&mut self, count: usize) {
+        let count = count.min(self.pending_messages.len());
+        if count == 0 {
+            return;
+        }
+        let format = &self.pending_format;
+        let bookmarks = &self.bookmarks;
+        self.messages_wrapper
+            .extend_from_iter(self.pending_messages.drain(..count).map(|m| {
+                let bookmarked = bookmarks.contains(&(m.partition, m.offset));
+                let mut item = MessageListItem::new(
+                    m,
+                    format.timestamp_formatter.clone(),
+                    format.timezone,
+                    format.value_preview_max_chars,
+                );
+                item.bookmarked = bookmarked;
+                item
+            }));
+    }
+
+    /// Lowest and highest offsets of the topic, with the messages left between the
+    /// last one shown and the high watermark in live mode.
+    fn offsets_summary(&self, partitions: &[Partition]) -> (String, String) {
+        let mut consumed: HashMap<i32, i64> = HashMap::new();
+        if self.mode == MessagesMode::Live {
+            let shown = (0..self.messages_wrapper.len())
+                .filter_map(|i| self.messages_wrapper.get(i))
+                .map(|item| {
+                    let item = item.borrow();
+                    (item.partition, item.offset)
+                });
+            let pending = self
+                .pending_messages
+                .iter()
+                .map(|m| (m.partition, m.offset));
+            for (partition, offset) in shown.chain(pending) {
+                let next = consumed.entry(partition).or_default();
+                *next = (*next).max(offset + 1);
+            }
+        }
+        let low = partitions.iter().filter_map(|p| p.offset_low).min();
+        let high = partitions.iter().filter_map(|p| p.offset_high).max();
+        let mut lag = None;
+        let mut details = Vec::with_capacity(partitions.len());
+        for p in partitions {
+            let (p_low, p_high) = (
+                p.offset_low.unwrap_or_default(),
+                p.offset_high.unwrap_or_default(),
+            );
+            match consumed.get(&p.id) {
+                Some(next) => {
+                    let p_lag = (p_high - next).max(0);
+                    *lag.get_or_insert(0) += p_lag;
+                    details.push(format!("P{}: {} – {} (lag {})", p.id, p_low, p_high, p_lag));
+                }
+                None => details.push(format!("P{}: {} – {}", p.id, p_low, p_high)),
+            }
+        }
+        let mut summary = match (low, high) {
+            (Some(low), Some(high)) => format!("Offsets {} – {}", low, high),
+            _ => String::new(),
+        };
+        if let Some(lag) = lag {
+            summary.push_str(&format!(" · Lag {}", lag));
+        }
+        (summary, details.join("\n"))
+    }
+
+    fn restore_search(&self, widgets: &mut MessagesTabModelWidgets, search: &SavedSearch) {
+        let mode = MessagesSearchMode::from_str(&search.mode).unwrap_or_default();
+        if let Some(idx) = MessagesSearchMode::VALUES.iter().position(|m| *m == mode) {
+            self.search_mode_combo
+                .emit(SimpleComboBoxMsg::SetActiveIdx(idx));
+        }
+        let field = MessagesSearchField::from_str(&search.field).unwrap_or_default();
+        if let Some(idx) = MessagesSearchField::VALUES.iter().position(|f| *f == field) {
+            self.search_field_combo
+                .emit(SimpleComboBoxMsg::SetActiveIdx(idx));
+        }
+        widgets
+            .btn_case_insensitive
+            .set_active(search.case_insensitive);
+        widgets.messages_search_entry.set_text(&search.term);
+    }
+
+    fn save_search(&self, widgets: &MessagesTabModelWidgets) {
+        let (Some(conn_id), Some(topic)) =
+            (self.connection.as_ref().and_then(|c| c.id), &self.topic)
+        else {
+            return;
+        };
+        let search = SavedSearch {
+            term: widgets.messages_search_entry.text().to_string(),
+            mode: self.search_options.mode.to_string(),
+            field: self.search_options.field.to_string(),
+            case_insensitive: self.search_options.case_insensitive,
+        };
+        let result = serde_json::to_string(&search)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|json| {
+                Repository::new()
+                    .save_ui_state(conn_id, &topic.name, SEARCH_STATE, &json)
+                    .map_err(|e| format!("{:?}", e))
+            });
+        if let Err(e) = result {
+            warn!("unable to save search: {}", e);
+        }
+    }
+
+    fn save_columns_layout(&self) {
+        let (Some(conn_id), Some(topic)) =
+            (self.connection.as_ref().and_then(|c| c.id), &self.topic)
+        else {
+            return;
+        };
+        let layout = columns_layout(&self.messages_wrapper.view);
+        let result = serde_json::to_string(&layout)
+            .map_err(|e| format!("{:?}", e))
+            .and_then(|json| {
+                Repository::new()
+                    .save_ui_state(conn_id, &topic.name, COLUMNS_LAYOUT_STATE, &json)
+                    .map_err(|e| format!("{:?}", e))
+            });
+        if let Err(e) = result {
+            warn!("unable to save columns layout: {}", e);
+        }
+    }
+
+    fn stop_auto_refresh(&mut self) {
+        if let Some(source_id) = self.auto_refresh.take() {
+            info!("stopping auto refresh");
+            source_id.remove();
+        }
+    }
+
     fn find_cache(&mut self) -> Option<KrustTopicCache> {
         let connection_id = self
             .connection
@@ -1196,6 +2520,151 @@ impl MessagesTabModel {
     }
 }
 
+/// Current width, visibility and order of the messages table columns.
+fn columns_layout(view: &gtk::ColumnView) -> Vec<ColumnLayout> {
+    let columns = view.columns();
+    (0..columns.n_items())
+        .filter_map(|i| columns.item(i).and_downcast::<ColumnViewColumn>())
+        .map(|column| ColumnLayout {
+            title: column.title().map(|t| t.to_string()).unwrap_or_default(),
+            width: column.fixed_width(),
+            visible: column.is_visible(),
+        })
+        .collect()
+}
+
+/// Columns missing from the layout, e.g. added by a newer version, are kept after the others.
+fn apply_columns_layout(view: &gtk::ColumnView, layout: &[ColumnLayout]) {
+    let columns = view.columns();
+    let mut position = 0;
+    for saved in layout {
+        let column = (0..columns.n_items())
+            .filter_map(|i| columns.item(i).and_downcast::<ColumnViewColumn>())
+            .find(|c| c.title().is_some_and(|t| t == saved.title.as_str()));
+        if let Some(column) = column {
+            column.set_fixed_width(saved.width);
+            column.set_visible(saved.visible);
+            view.insert_column(position, &column);
+            position += 1;
+        }
+    }
+}
+
+/// Header menu with a toggle per messages table column.
+fn columns_popover(
+    view: &gtk::ColumnView,
+    csv_visible_columns_only: bool,
+    sender: &relm4::Sender<MessagesTabMsg>,
+) -> gtk::Popover {
+    let content = gtk::Box::new(gtk::Orientation::Vertical, 5);
+    content.set_margin_all(5);
+    let columns = view.columns();
+    for column in
+        (0..columns.n_items()).filter_map(|i| columns.item(i).and_downcast::<ColumnViewColumn>())
+    {
+        let check = gtk::CheckButton::with_label(
+            column
+                .title()
+                .map(|t| t.to_string())
+                .unwrap_or_default()
+                .as_str(),
+        );
+        column
+            .bind_property("visible", &check, "active")
+            .bidirectional()
+            .sync_create()
+            .build();
+        content.append(&check);
+    }
+    content.append(&gtk::Separator::new(gtk::Orientation::Horizontal));
+    let csv_check = gtk::CheckButton::with_label("Leave hidden columns out of CSV");
+    csv_check.set_active(csv_visible_columns_only);
+    let sender = sender.clone();
+    csv_check.connect_toggled(move |check| {
+        sender.emit(MessagesTabMsg::ToggleCsvVisibleColumnsOnly(
+            check.is_active(),
+        ));
+    });
+    content.append(&csv_check);
+    let popover = gtk::Popover::new();
+    popover.set_child(Some(&content));
+    popover
+}
+
+/// CSV field holding the content of a messages table column.
+fn csv_field(column_title: &str) -> Option<&'static str> {
+    match column_title {
+        "Partition" => Some("PARTITION"),
+        "Offset" => Some("OFFSET"),
+        "Key" => Some("KEY"),
+        "Value" => Some("VALUE"),
+        "Date/time (Timestamp)" => Some("TIMESTAMP"),
+        _ => None,
+    }
+}
+
+/// Maps a cache sort column back to the title of its list column.
+fn sort_column_title(column: &str) -> Option<&'static str> {
+    match column {
+        "offset" => Some("Offset"),
+        "partition" => Some("Partition"),
+        "key" => Some("Key"),
+        "timestamp" => Some("Date/time (Timestamp)"),
+        _ => None,
+    }
+}
+
+enum SearchMatcher {
+    Substring(String),
+    CaseInsensitiveSubstring(String),
+    Regex(Regex),
+}
+
+impl SearchMatcher {
+    fn is_match(&self, text: &str) -> bool {
+        match self {
+            SearchMatcher::Substring(term) => text.contains(term.as_str()),
+            SearchMatcher::CaseInsensitiveSubstring(term) => {
+                text.to_lowercase().contains(term.as_str())
+            }
+            SearchMatcher::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// Builds the matcher for the current search term, flagging the search entry when it's invalid.
+fn build_search_matcher(
+    widgets: &MessagesTabModelWidgets,
+    term: &str,
+    options: &MessagesSearchOptions,
+) -> Option<SearchMatcher> {
+    let matcher = match options.mode {
+        MessagesSearchMode::Regex => RegexBuilder::new(term)
+            .case_insensitive(options.case_insensitive)
+            .build()
+            .map(SearchMatcher::Regex),
+        _ if options.case_insensitive => {
+            Ok(SearchMatcher::CaseInsensitiveSubstring(term.to_lowercase()))
+        }
+        _ => Ok(SearchMatcher::Substring(term.to_string())),
+    };
+    match matcher {
+        Ok(matcher) => {
+            widgets.messages_search_entry.remove_css_class("error");
+            widgets.messages_search_entry.set_tooltip_text(None);
+            Some(matcher)
+        }
+        Err(e) => {
+            warn!("invalid search pattern: {}", e);
+            widgets.messages_search_entry.add_css_class("error");
+            widgets
+                .messages_search_entry
+                .set_tooltip_text(Some(format!("Invalid pattern: {}", e).as_str()));
+            None
+        }
+    }
+}
+
 fn get_search_term(widgets: &mut MessagesTabModelWidgets) -> Option<String> {
     let search: Option<String> = Some(widgets.messages_search_entry.text().into());
     let search = search.clone().unwrap_or_default();
@@ -1211,6 +2680,8 @@ fn on_loading(widgets: &mut MessagesTabModelWidgets, enabled: bool) {
     widgets.btn_get_messages.set_sensitive(enabled);
     widgets.btn_cache_refresh.set_sensitive(enabled);
     widgets.btn_cache_destroy.set_sensitive(enabled);
+    widgets.btn_cache_delete_range.set_sensitive(enabled);
+    widgets.btn_cache_export.set_sensitive(enabled);
     widgets.btn_cache_toggle.set_sensitive(enabled);
     widgets.max_messages.set_sensitive(enabled);
 }
@@ -1254,77 +2725,481 @@ fn fill_pagination(widgets: &mut MessagesTabModelWidgets, total: usize, page_siz
     }
 }
 
+/// Parses comma-separated top-level keys or JSONPaths (`$.user.name`, `$.items[0]`) into
+/// pairs of CSV column header and JSON pointer.
+fn parse_csv_projection(expression: &str) -> Vec<(String, String)> {
+    expression
+        .split(',')
+        .map(str::trim)
+        .filter(|field| !field.is_empty())
+        .map(|field| (field.to_string(), json_pointer(field)))
+        .collect()
+}
+
+fn json_pointer(path: &str) -> String {
+    let path = path.strip_prefix('$').unwrap_or(path);
+    path.replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            let token = token.trim_matches(|c| c == '\'' || c == '"');
+            format!("/{}", token.replace('~', "~0").replace('/', "~1"))
+        })
+        .collect()
+}
+
+/// Projects the JSON value into one cell per field, non-JSON values and missing fields
+/// produce empty cells.
+fn project_fields(
+    json: Option<&serde_json::Value>,
+    projection: &[(String, String)],
+) -> Vec<String> {
+    projection
+        .iter()
+        .map(
+            |(_, pointer)| match json.and_then(|json| json.pointer(pointer)) {
+                Some(serde_json::Value::String(text)) => text.clone(),
+                Some(serde_json::Value::Null) | None => String::new(),
+                Some(other) => other.to_string(),
+            },
+        )
+        .collect()
+}
+
 fn copy_all_as_csv(
     selected_items: &Vec<KrustMessage>,
-) -> Result<String, std::string::FromUtf8Error> {
-    let timestamp_format = Settings::read().unwrap_or_default().timestamp_formatter();
-    let mut wtr = csv::WriterBuilder::new()
+    projection: &[(String, String)],
+    fields: &[&str],
+    task: &Task,
+) -> Result<String, ExternalError> {
+    let settings = Settings::read().unwrap_or_default();
+    let timestamp_format = settings.timestamp_formatter();
+    let timezone = settings.timezone();
+    let threads = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1);
+    let chunk_size = selected_items.len().div_ceil(threads).max(1);
+    let chunks = selected_items.chunks(chunk_size).count();
+    let done = AtomicUsize::new(0);
+    // the last progress step is left for concatenating the fragments
+    let fragments: Vec<Vec<u8>> = std::thread::scope(|scope| {
+        let (timestamp_format, done) = (&timestamp_format, &done);
+        let handles: Vec<_> = selected_items
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let fragment =
+                        csv_fragment(chunk, projection, fields, timestamp_format, timezone);
+                    let done = done.fetch_add(1, Ordering::SeqCst) + 1;
+                    TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(
+                        task.clone(),
+                        done as f64 / (chunks + 1) as f64,
+                    ));
+                    fragment
+                })
+            })
+            .collect();
+        // joins every thread before failing, an unjoined panicked thread would panic the scope
+        let results: Vec<_> = handles.into_iter().map(|handle| handle.join()).collect();
+        results.into_iter().collect::<Result<_, _>>()
+    })
+    .map_err(|_| {
+        ExternalError::DisplayError(
+            "copying messages".to_string(),
+            "unable to build CSV".to_string(),
+        )
+    })?;
+    let mut wtr = csv_writer(Vec::with_capacity(estimate_copy_size(selected_items)));
+    let _ = wtr.write_record(csv_header(projection, fields));
+    let mut data = wtr.into_inner().unwrap_or_default();
+    for fragment in fragments {
+        data.extend_from_slice(&fragment);
+    }
+    String::from_utf8(data)
+        .map_err(|e| ExternalError::DisplayError("copying messages".to_string(), e.to_string()))
+}
+fn csv_writer<W: Write>(writer: W) -> csv::Writer<W> {
+    csv::WriterBuilder::new()
         .delimiter(b';')
         .quote_style(csv::QuoteStyle::NonNumeric)
-        .from_writer(vec![]);
-    let _ = wtr.write_record(["PARTITION", "OFFSET", "KEY", "VALUE", "TIMESTAMP"]);
-    for item in selected_items {
-        let partition = item.partition;
-        let offset = item.offset;
-        let key = item.key.clone();
-        let value = item.value.clone();
-        let clean_value = match serde_json::from_str::<serde_json::Value>(value.as_str()) {
-            Ok(json) => json.to_string(),
-            Err(_) => value.replace('\n', ""),
-        };
-        let timestamp = item.borrow().timestamp;
-        let timestamp = Utc
-            .timestamp_millis_opt(timestamp.unwrap_or_default())
-            .unwrap()
-            .with_timezone(&America::Sao_Paulo)
-            .format(&timestamp_format)
-            .to_string();
-        let record = StringRecord::from(vec![
-            partition.to_string(),
-            offset.to_string(),
-            key.unwrap_or_default(),
-            clean_value,
-            timestamp,
-        ]);
+        .from_writer(writer)
+}
+fn csv_fragment(
+    items: &[KrustMessage],
+    projection: &[(String, String)],
+    fields: &[&str],
+    timestamp_format: &str,
+    timezone: Tz,
+) -> Vec<u8> {
+    let mut wtr = csv_writer(Vec::with_capacity(estimate_copy_size(items)));
+    for item in items {
+        let record = csv_record(item, projection, fields, timestamp_format, timezone);
         let _ = wtr.write_record(&record);
     }
-    String::from_utf8(wtr.into_inner().unwrap_or_default())
+    wtr.into_inner().unwrap_or_default()
+}
+const CSV_FIELDS: [&str; 5] = ["PARTITION", "OFFSET", "KEY", "VALUE", "TIMESTAMP"];
+const CSV_HEADERS_FIELD: &str = "HEADERS";
+fn csv_header<'a>(projection: &'a [(String, String)], fields: &[&'a str]) -> Vec<&'a str> {
+    let mut header = fields.to_vec();
+    header.extend(projection.iter().map(|(name, _)| name.as_str()));
+    header
+}
+fn csv_record(
+    item: &KrustMessage,
+    projection: &[(String, String)],
+    fields: &[&str],
+    timestamp_format: &str,
+    timezone: Tz,
+) -> StringRecord {
+    let value = item.value.clone().unwrap_or_default();
+    let json = serde_json::from_str::<serde_json::Value>(value.as_str()).ok();
+    let clean_value = match &json {
+        Some(json) => json.to_string(),
+        None => value.replace('\n', ""),
+    };
+    let mut record: Vec<String> = fields
+        .iter()
+        .map(|field| match *field {
+            "PARTITION" => item.partition.to_string(),
+            "OFFSET" => item.offset.to_string(),
+            "KEY" => item.key.clone().unwrap_or_default(),
+            "VALUE" => clean_value.clone(),
+            "TIMESTAMP" => {
+                format_timestamp(item.timestamp, timestamp_format, &timezone).unwrap_or_default()
+            }
+            CSV_HEADERS_FIELD => encode_headers(item),
+            _ => String::new(),
+        })
+        .collect();
+    record.extend(project_fields(json.as_ref(), projection));
+    StringRecord::from(record)
+}
+fn copy_all_as_json(
+    selected_items: &Vec<KrustMessage>,
+) -> Result<String, std::string::FromUtf8Error> {
+    let mut copy_content = String::with_capacity(estimate_copy_size(selected_items));
+    for item in selected_items {
+        copy_content.push_str(json_record(item).to_string().as_str());
+        copy_content.push('\n');
+    }
+    Ok(copy_content)
+}
+fn json_record(item: &KrustMessage) -> serde_json::Value {
+    let value = item.value.clone().map(|value| {
+        serde_json::from_str::<serde_json::Value>(value.as_str())
+            .unwrap_or(serde_json::Value::String(value))
+    });
+    let headers: Vec<serde_json::Value> = item
+        .headers
+        .iter()
+        .map(|header| serde_json::json!({ "key": header.key, "value": header.value }))
+        .collect();
+    serde_json::json!({
+        "partition": item.partition,
+        "offset": item.offset,
+        "key": item.key,
+        "value": value,
+        "timestamp": item.timestamp,
+        "headers": headers,
+    })
+}
+/// Streams the whole topic cache to a CSV file, or to NDJSON for any other extension.
+fn export_cache(
+    mut mrepo: MessagesRepository,
+    order: Option<MessagesSearchOrder>,
+    projection: &[(String, String)],
+    fields: &[&str],
+    path: &Path,
+    task: &Task,
+) -> Result<usize, ExternalError> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let is_csv = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    let written = if is_csv {
+        let settings = Settings::read().unwrap_or_default();
+        let timestamp_format = settings.timestamp_formatter();
+        let timezone = settings.timezone();
+        let mut wtr = csv_writer(file);
+        wtr.write_record(csv_header(projection, fields))
+            .map_err(std::io::Error::from)?;
+        let written = mrepo.stream_all(task, order, |item| {
+            let record = csv_record(item, projection, fields, &timestamp_format, timezone);
+            wtr.write_record(&record).map_err(std::io::Error::from)?;
+            Ok(())
+        })?;
+        wtr.flush()?;
+        written
+    } else {
+        let written = mrepo.stream_all(task, order, |item| {
+            serde_json::to_writer(&mut file, &json_record(item)).map_err(std::io::Error::from)?;
+            file.write_all(b"\n")?;
+            Ok(())
+        })?;
+        file.flush()?;
+        written
+    };
+    Ok(written)
+}
+/// Headers in the same RON encoding they're cached with.
+fn encode_headers(item: &KrustMessage) -> String {
+    ron::ser::to_string(&item.headers).unwrap_or_default()
 }
 fn copy_key_value(
     selected_items: &Vec<KrustMessage>,
+    include_headers: bool,
 ) -> Result<String, std::string::FromUtf8Error> {
-    let mut copy_content = String::default();
+    let mut copy_content = String::with_capacity(estimate_copy_size(selected_items));
     for item in selected_items {
         let key = item.key.clone();
-        let value = item.value.clone();
+        let value = item.value.clone().unwrap_or_default();
         let clean_value = match serde_json::from_str::<serde_json::Value>(value.as_str()) {
             Ok(json) => json.to_string(),
             Err(_) => value.replace('\n', ""),
         };
-        let copy_text = format!("{},{}\n", key.unwrap_or_default(), clean_value);
-        copy_content.push_str(copy_text.as_str());
+        copy_content.push_str(key.unwrap_or_default().as_str());
+        copy_content.push(',');
+        copy_content.push_str(clean_value.as_str());
+        if include_headers {
+            copy_content.push(',');
+            copy_content.push_str(encode_headers(item).as_str());
+        }
+        copy_content.push('\n');
     }
     Ok(copy_content)
 }
-fn copy_value(selected_items: &Vec<KrustMessage>) -> Result<String, std::string::FromUtf8Error> {
-    let mut copy_content = String::default();
+fn copy_value(
+    selected_items: &Vec<KrustMessage>,
+    include_headers: bool,
+) -> Result<String, std::string::FromUtf8Error> {
+    let mut copy_content = String::with_capacity(estimate_copy_size(selected_items));
     for item in selected_items {
-        let value = item.value.clone();
+        let value = item.value.clone().unwrap_or_default();
         let clean_value = match serde_json::from_str::<serde_json::Value>(value.as_str()) {
             Ok(json) => json.to_string(),
             Err(_) => value.replace('\n', ""),
         };
-        let copy_text = format!("{}\n", clean_value);
-        copy_content.push_str(copy_text.as_str());
+        copy_content.push_str(clean_value.as_str());
+        if include_headers {
+            copy_content.push(',');
+            copy_content.push_str(encode_headers(item).as_str());
+        }
+        copy_content.push('\n');
     }
     Ok(copy_content)
 }
 fn copy_key(selected_items: &Vec<KrustMessage>) -> Result<String, std::string::FromUtf8Error> {
-    let mut copy_content = String::default();
+    let mut copy_content = String::with_capacity(estimate_copy_size(selected_items));
     for item in selected_items {
         let key = item.key.clone();
-        let copy_text = format!("{}\n", key.unwrap_or_default());
-        copy_content.push_str(copy_text.as_str());
+        copy_content.push_str(key.unwrap_or_default().as_str());
+        copy_content.push('\n');
     }
     Ok(copy_content)
 }
+
+/// One `kcat` producer command per record, with the value piped in verbatim so multi-line
+/// and JSON values survive. Records are delimited by `\x1e`, which values are not expected to
+/// contain. Binary values are piped through `base64 -d` to keep their original bytes, and
+/// tombstones are produced as NULL.
+fn copy_as_kcat_commands(
+    selected_items: &[KrustMessage],
+    brokers: &str,
+) -> Result<String, std::string::FromUtf8Error> {
+    let mut copy_content = String::with_capacity(estimate_copy_size(selected_items) * 2);
+    for item in selected_items {
+        let input = match (&item.raw_value, &item.value) {
+            (Some(raw_value), _) => format!(
+                "printf '%s' {} | base64 -d",
+                shell_quote(&encode_base64(raw_value))
+            ),
+            (None, value) => format!(
+                "printf '%s' {}",
+                shell_quote(value.as_deref().unwrap_or_default())
+            ),
+        };
+        let mut command = format!(
+            "{} | kcat -P -b {} -t {} -p {} -D '\\x1e'",
+            input,
+            shell_quote(brokers),
+            shell_quote(&item.topic),
+            item.partition
+        );
+        if let Some(key) = item.key.as_ref() {
+            command.push_str(&format!(" -k {}", shell_quote(key)));
+        }
+        for header in item.headers.iter() {
+            let header = format!(
+                "{}={}",
+                header.key,
+                header.value.clone().unwrap_or_default()
+            );
+            command.push_str(&format!(" -H {}", shell_quote(&header)));
+        }
+        if item.value.is_none() && item.raw_value.is_none() {
+            command.push_str(" -Z");
+        }
+        copy_content.push_str(&command);
+        copy_content.push('\n');
+    }
+    Ok(copy_content)
+}
+
+/// Quotes a value for POSIX shells, keeping newlines and other characters as they are.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Rough upper bound of the clipboard content size, so the buffer is allocated only once.
+fn estimate_copy_size(selected_items: &[KrustMessage]) -> usize {
+    selected_items
+        .iter()
+        .map(|item| {
+            let key_size = item.key.as_ref().map(|key| key.len()).unwrap_or_default();
+            let value_size = item
+                .value
+                .as_ref()
+                .map(|value| value.len())
+                .unwrap_or_default();
+            let headers_size: usize = item
+                .headers
+                .iter()
+                .map(|header| {
+                    header.key.len() + header.value.as_ref().map(|v| v.len()).unwrap_or_default()
+                })
+                .sum();
+            // partition, offset, timestamp and separators
+            key_size + value_size + headers_size + 64
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(offset: i64, timestamp: Option<i64>) -> KrustMessage {
+        KrustMessage {
+            topic: "topic".to_string(),
+            partition: 0,
+            offset,
+            key: Some(format!("key-{}", offset)),
+            value: Some(format!("{{\"offset\":{}}}", offset)),
+            timestamp,
+            headers: vec![],
+            raw_value: None,
+        }
+    }
+
+    #[test]
+    fn csv_record_without_valid_timestamp_is_empty() {
+        for timestamp in [Some(-1), None] {
+            let record = csv_record(
+                &message(0, timestamp),
+                &[],
+                &CSV_FIELDS,
+                crate::DATE_TIME_FORMAT,
+                Tz::UTC,
+            );
+
+            assert_eq!(record.get(4), Some(""));
+        }
+    }
+
+    #[test]
+    fn copy_as_csv_handles_missing_timestamps() {
+        let task = Task::new(TaskVariant::CopyMessages, None, None);
+        let messages = vec![message(0, Some(-1)), message(1, None)];
+
+        let data = copy_all_as_csv(&messages, &[], &CSV_FIELDS, &task).unwrap();
+
+        assert_eq!(data.lines().count(), 3);
+    }
+
+    #[test]
+    fn parallel_csv_matches_serial_csv() {
+        let task = Task::new(TaskVariant::CopyMessages, None, None);
+        let mut messages: Vec<KrustMessage> = (0..1_001)
+            .map(|offset| message(offset, Some(1_700_000_000_000 + offset)))
+            .collect();
+        messages[7].value = Some("multi\nline;\"quoted\"".to_string());
+        messages[8].timestamp = None;
+        let projection = parse_csv_projection("offset");
+        let settings = Settings::read().unwrap_or_default();
+
+        let parallel = copy_all_as_csv(&messages, &projection, &CSV_FIELDS, &task).unwrap();
+        let mut wtr = csv_writer(Vec::new());
+        wtr.write_record(csv_header(&projection, &CSV_FIELDS))
+            .unwrap();
+        let mut serial = wtr.into_inner().unwrap();
+        serial.extend(csv_fragment(
+            &messages,
+            &projection,
+            &CSV_FIELDS,
+            &settings.timestamp_formatter(),
+            settings.timezone(),
+        ));
+
+        assert_eq!(parallel, String::from_utf8(serial).unwrap());
+    }
+
+    #[test]
+    fn copy_as_csv_handles_large_selections() {
+        let task = Task::new(TaskVariant::CopyMessages, None, None);
+        let messages: Vec<KrustMessage> = (0..10_000)
+            .map(|offset| message(offset, Some(1_700_000_000_000 + offset)))
+            .collect();
+
+        let data = copy_all_as_csv(&messages, &[], &CSV_FIELDS, &task).unwrap();
+
+        assert_eq!(data.lines().count(), messages.len() + 1);
+        assert!(data.len() <= estimate_copy_size(&messages) + 64);
+        assert!(data.lines().last().unwrap().contains("key-9999"));
+    }
+
+    #[test]
+    fn tombstones_survive_the_messages_list() {
+        let mut tombstone = message(0, None);
+        tombstone.value = None;
+        let item = MessageListItem::new(tombstone, String::new(), Tz::UTC, 100);
+
+        let message = item.to_message("topic");
+
+        assert_eq!(message.value, None);
+        assert_eq!(json_record(&message)["value"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn kcat_commands_keep_null_and_empty_values_apart() {
+        let mut tombstone = message(0, None);
+        tombstone.value = None;
+        let mut empty = message(1, None);
+        empty.value = Some(String::new());
+        empty.key = Some(String::new());
+        let mut keyless = message(2, None);
+        keyless.key = None;
+
+        let data = copy_as_kcat_commands(&[tombstone, empty, keyless], "localhost:9092").unwrap();
+        let commands: Vec<&str> = data.lines().collect();
+
+        assert!(commands[0].ends_with(" -k 'key-0' -Z"));
+        assert!(commands[1].starts_with("printf '%s' '' |"));
+        assert!(commands[1].ends_with(" -k ''"));
+        assert!(!commands[2].contains(" -k "));
+    }
+
+    #[test]
+    fn kcat_commands_keep_binary_values() {
+        let mut binary = message(0, None);
+        binary.value = Some("ff 00".to_string());
+        binary.raw_value = Some(vec![0xff, 0x00]);
+
+        let data = copy_as_kcat_commands(&[binary], "localhost:9092").unwrap();
+
+        assert!(data.starts_with("printf '%s' '/wA=' | base64 -d | kcat -P"));
+        assert!(!data.contains(" -Z"));
+    }
+}
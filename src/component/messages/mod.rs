@@ -5,6 +5,9 @@
 mod lists;
 pub(crate) mod message_viewer;
 pub(crate) mod messages_cache_settings_dialog;
+pub(crate) mod messages_compare_dialog;
+pub(crate) mod messages_delete_range_dialog;
+pub(crate) mod messages_format_dialog;
 pub(crate) mod messages_page;
 pub(crate) mod messages_send_dialog;
 pub(crate) mod messages_tab;
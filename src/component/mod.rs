@@ -4,6 +4,9 @@
 
 //! Relm4 components.
 
+use std::cell::RefCell;
+use std::collections::HashMap;
+
 use crate::backend::repository::KrustConnection;
 use adw::TabBar;
 use gtk::prelude::*;
@@ -13,6 +16,7 @@ pub mod messages;
 pub mod topics;
 
 pub(crate) mod cache_manager_dialog;
+pub(crate) mod cache_scheduler;
 pub(crate) mod connection_list;
 mod connection_page;
 pub(crate) mod settings_dialog;
@@ -43,9 +47,14 @@ pub fn get_tab_by_title(tabbar: &TabBar, title: String) -> Option<gtk::Widget> {
     maybe_tab
 }
 
+thread_local! {
+    /// CSS providers by connection color class, with the CSS they were loaded from.
+    static CONNECTION_CSS_PROVIDERS: RefCell<HashMap<String, (gtk::CssProvider, String)>> =
+        RefCell::new(HashMap::new());
+}
+
 pub fn colorize_widget_by_connection(conn: &KrustConnection, widget: gtk::Widget) {
-    info!("color_widget_by_connection::{:?}", widget);
-    let css_provider = gtk::CssProvider::new();
+    trace!("color_widget_by_connection::{:?}", widget);
     let color = conn
         .clone()
         .color
@@ -53,12 +62,29 @@ pub fn colorize_widget_by_connection(conn: &KrustConnection, widget: gtk::Widget
         .unwrap_or("rgb(183, 243, 155)".to_string());
     let color = color.as_str();
     let css_class = format!("custom_color_{}", conn.id.unwrap());
-    css_provider.load_from_string(format!(".{} {{ background: {};}}", css_class, color).as_str());
-    let display = widget.display();
-    gtk::style_context_add_provider_for_display(
-        &display,
-        &css_provider,
-        gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
-    );
-    widget.add_css_class(&css_class);
+    let css = format!(".{} {{ background: {};}}", css_class, color);
+    CONNECTION_CSS_PROVIDERS.with(|providers| {
+        let mut providers = providers.borrow_mut();
+        match providers.get_mut(&css_class) {
+            Some((css_provider, loaded)) => {
+                if *loaded != css {
+                    css_provider.load_from_string(css.as_str());
+                    *loaded = css;
+                }
+            }
+            None => {
+                let css_provider = gtk::CssProvider::new();
+                css_provider.load_from_string(css.as_str());
+                gtk::style_context_add_provider_for_display(
+                    &widget.display(),
+                    &css_provider,
+                    gtk::STYLE_PROVIDER_PRIORITY_APPLICATION,
+                );
+                providers.insert(css_class.clone(), (css_provider, css));
+            }
+        }
+    });
+    if !widget.has_css_class(&css_class) {
+        widget.add_css_class(&css_class);
+    }
 }
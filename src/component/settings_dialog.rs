@@ -2,9 +2,10 @@
 // this source code is governed by the GPL-3.0 license that can be
 // found in the COPYING file.
 
-use std::path::PathBuf;
+use std::{path::PathBuf, str::FromStr};
 
 use adw::prelude::*;
+use chrono_tz::Tz;
 use gtk::Adjustment;
 use relm4::{gtk, Component, ComponentController, ComponentParts, ComponentSender, Controller};
 use relm4_components::{
@@ -14,9 +15,14 @@ use relm4_components::{
 use strum::{Display, EnumString};
 use tracing::*;
 
-use crate::backend::settings::Settings;
+use crate::backend::settings::{parse_quick_fetch_presets, Settings};
+use crate::component::messages::messages_tab::AVAILABLE_PAGE_SIZES;
+use crate::modals::utils::show_error_alert;
 
 const MESSAGE_COLUMNS: [&str; 4] = ["Offset", "Partition", "Key", "Date/time (Timestamp)"];
+const AUTO_OFFSET_RESET_VALUES: [&str; 2] = ["earliest", "latest"];
+const GSK_RENDERER_VALUES: [&str; 3] = ["gl", "ngl", "cairo"];
+const LOG_LEVEL_VALUES: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
 
 #[derive(Clone, Debug, Display, EnumString, Default)]
 pub enum MessagesSortOrder {
@@ -39,7 +45,27 @@ pub struct SettingsDialogModel {
     messages_sort_column: String,
     messages_sort_column_order: String,
     threads_number: f64,
+    gsk_renderer_combo: Controller<SimpleComboRow<String>>,
+    gsk_renderer: String,
+    log_level_combo: Controller<SimpleComboRow<String>>,
+    log_level: String,
+    log_file: String,
+    cache_workers: f64,
     default_connection_timeout: f64,
+    auto_offset_reset_combo: Controller<SimpleComboRow<String>>,
+    auto_offset_reset: String,
+    session_timeout_ms: f64,
+    message_timeout_ms: f64,
+    poll_timeout_ms: f64,
+    json_highlight_max_bytes: f64,
+    value_preview_max_chars: f64,
+    live_max_messages_default: f64,
+    live_max_messages_max: f64,
+    live_quick_fetch_presets: String,
+    default_cache_page_size_combo: Controller<SimpleComboRow<u16>>,
+    default_cache_page_size: u16,
+    stale_cache_hours: f64,
+    timezone: String,
 }
 
 #[derive(Debug)]
@@ -50,10 +76,27 @@ pub enum SettingsDialogMsg {
     OpenCacheDir(PathBuf),
     SwitchFullTimestamp,
     SetThreadsNumber,
+    SetCacheWorkers,
     SetDefaultConnectionTimeout,
+    SetSessionTimeout,
+    SetMessageTimeout,
+    SetPollTimeout,
+    SetJsonHighlightMaxBytes,
+    SetValuePreviewMaxChars,
+    SetLiveMaxMessagesDefault,
+    SetLiveMaxMessagesMax,
+    SetLiveQuickFetchPresets,
+    SetTimezone,
+    SetStaleCacheHours,
+    AutoOffsetResetSelected(usize),
+    GskRendererSelected(usize),
+    LogLevelSelected(usize),
+    SetLogFile,
+    OpenLogFile,
     Ignore,
     MessagesColumnSelected(usize),
     MessagesColumnOrderSelected(usize),
+    DefaultCachePageSizeSelected(usize),
 }
 
 pub struct SettingsDialogInit {}
@@ -75,6 +118,7 @@ impl Component for SettingsDialogModel {
                 set_icon_name: Some("emblem-system-symbolic"),
                 add = &adw::PreferencesGroup {
                     set_title: "General",
+                    set_description: Some("Changes take effect after restarting the application"),
                     #[name = "threads_number"]
                     adw::SpinRow {
                         set_title: "Threads",
@@ -88,6 +132,33 @@ impl Component for SettingsDialogModel {
                         set_wrap: true,
                         connect_value_notify => SettingsDialogMsg::SetThreadsNumber,
                     },
+                    #[local_ref]
+                    gsk_renderer_combo -> adw::ComboRow {
+                        set_title: "Renderer",
+                        set_subtitle: "Rendering backend, try another one on graphics glitches (requires restart)",
+                    },
+                },
+                add = &adw::PreferencesGroup {
+                    set_title: "Logging",
+                    set_description: Some("Applied on next launch, RUST_LOG overrides the level"),
+                    #[local_ref]
+                    log_level_combo -> adw::ComboRow {
+                        set_title: "Level",
+                    },
+                    #[name = "log_file_entry"]
+                    adw::EntryRow {
+                        set_title: "Log file (empty to disable)",
+                        set_text: &model.log_file,
+                        set_show_apply_button: true,
+                        connect_apply => SettingsDialogMsg::SetLogFile,
+                        add_suffix = &gtk::Button {
+                            set_tooltip_text: Some("Open log file"),
+                            set_icon_name: "document-open-symbolic",
+                            set_valign: gtk::Align::Center,
+                            add_css_class: "flat",
+                            connect_clicked => SettingsDialogMsg::OpenLogFile,
+                        },
+                    },
                 },
                 add = &adw::PreferencesGroup {
                     set_title: "Connection",
@@ -105,6 +176,66 @@ impl Component for SettingsDialogModel {
                         connect_value_notify => SettingsDialogMsg::SetDefaultConnectionTimeout,
                     },
                 },
+                add = &adw::PreferencesGroup {
+                    set_title: "Consumer",
+                    #[local_ref]
+                    auto_offset_reset_combo -> adw::ComboRow {
+                        set_title: "Auto offset reset",
+                        set_subtitle: "Where to start when there is no committed offset",
+                    },
+                    #[name = "session_timeout_ms"]
+                    adw::SpinRow {
+                        set_title: "Session timeout",
+                        set_subtitle: "Timeout in milliseconds",
+                        set_selectable: true,
+                        set_activatable: true,
+                        set_focusable: true,
+                        set_focus_on_click: true,
+                        set_snap_to_ticks: false,
+                        set_numeric: true,
+                        set_wrap: false,
+                        connect_value_notify => SettingsDialogMsg::SetSessionTimeout,
+                    },
+                    #[name = "cache_workers"]
+                    adw::SpinRow {
+                        set_title: "Cache workers",
+                        set_subtitle: "Parallel consumers when caching, at most one per partition",
+                        set_selectable: true,
+                        set_activatable: true,
+                        set_focusable: true,
+                        set_focus_on_click: true,
+                        set_snap_to_ticks: true,
+                        set_numeric: true,
+                        set_wrap: false,
+                        connect_value_notify => SettingsDialogMsg::SetCacheWorkers,
+                    },
+                    #[name = "message_timeout_ms"]
+                    adw::SpinRow {
+                        set_title: "Message timeout",
+                        set_subtitle: "Timeout in milliseconds",
+                        set_selectable: true,
+                        set_activatable: true,
+                        set_focusable: true,
+                        set_focus_on_click: true,
+                        set_snap_to_ticks: false,
+                        set_numeric: true,
+                        set_wrap: false,
+                        connect_value_notify => SettingsDialogMsg::SetMessageTimeout,
+                    },
+                    #[name = "poll_timeout_ms"]
+                    adw::SpinRow {
+                        set_title: "Poll timeout",
+                        set_subtitle: "How long to wait for messages, in milliseconds",
+                        set_selectable: true,
+                        set_activatable: true,
+                        set_focusable: true,
+                        set_focus_on_click: true,
+                        set_snap_to_ticks: false,
+                        set_numeric: true,
+                        set_wrap: false,
+                        connect_value_notify => SettingsDialogMsg::SetPollTimeout,
+                    },
+                },
             },
             add = &adw::PreferencesPage {
                 set_title: "Messages",
@@ -119,6 +250,75 @@ impl Component for SettingsDialogModel {
                         set_active: model.is_full_timestamp,
                         connect_active_notify => SettingsDialogMsg::SwitchFullTimestamp,
                     },
+                    #[name = "timezone_entry"]
+                    adw::EntryRow {
+                        set_title: "Timezone (IANA name, empty for system timezone)",
+                        set_text: &model.timezone,
+                        set_show_apply_button: true,
+                        connect_apply => SettingsDialogMsg::SetTimezone,
+                    },
+                    #[name = "json_highlight_max_bytes"]
+                    adw::SpinRow {
+                        set_title: "JSON highlighting limit",
+                        set_subtitle: "Largest value size in bytes to highlight in the viewer",
+                        set_selectable: true,
+                        set_activatable: true,
+                        set_focusable: true,
+                        set_focus_on_click: true,
+                        set_snap_to_ticks: false,
+                        set_numeric: true,
+                        set_wrap: false,
+                        connect_value_notify => SettingsDialogMsg::SetJsonHighlightMaxBytes,
+                    },
+                    #[name = "value_preview_max_chars"]
+                    adw::SpinRow {
+                        set_title: "Value preview length",
+                        set_subtitle: "Characters of a value shown in the messages list",
+                        set_selectable: true,
+                        set_activatable: true,
+                        set_focusable: true,
+                        set_focus_on_click: true,
+                        set_snap_to_ticks: false,
+                        set_numeric: true,
+                        set_wrap: false,
+                        connect_value_notify => SettingsDialogMsg::SetValuePreviewMaxChars,
+                    },
+                },
+                add = &adw::PreferencesGroup {
+                    set_title: "Live mode",
+                    #[name = "live_max_messages_default"]
+                    adw::SpinRow {
+                        set_title: "Max messages (per partition)",
+                        set_subtitle: "Default value for new messages tabs",
+                        set_selectable: true,
+                        set_activatable: true,
+                        set_focusable: true,
+                        set_focus_on_click: true,
+                        set_snap_to_ticks: false,
+                        set_numeric: true,
+                        set_wrap: false,
+                        connect_value_notify => SettingsDialogMsg::SetLiveMaxMessagesDefault,
+                    },
+                    #[name = "live_max_messages_max"]
+                    adw::SpinRow {
+                        set_title: "Max messages limit",
+                        set_subtitle: "Highest value allowed for max messages (per partition)",
+                        set_selectable: true,
+                        set_activatable: true,
+                        set_focusable: true,
+                        set_focus_on_click: true,
+                        set_snap_to_ticks: false,
+                        set_numeric: true,
+                        set_wrap: false,
+                        connect_value_notify => SettingsDialogMsg::SetLiveMaxMessagesMax,
+                    },
+                    #[name = "live_quick_fetch_presets_entry"]
+                    adw::EntryRow {
+                        set_title: "Quick fetch buttons (e.g. First 100, Last 1000)",
+                        set_text: &model.live_quick_fetch_presets,
+                        set_show_apply_button: true,
+                        connect_apply => SettingsDialogMsg::SetLiveQuickFetchPresets,
+                    },
                 },
                 add = &adw::PreferencesGroup {
                     set_title: "Sorting",
@@ -148,7 +348,12 @@ impl Component for SettingsDialogModel {
                             set_vexpand: false,
                             connect_clicked => SettingsDialogMsg::ChooseCacheDirRequest,
                         },
-                    }
+                    },
+                    #[local_ref]
+                    default_cache_page_size_combo -> adw::ComboRow {
+                        set_title: "Default page size",
+                        set_subtitle: "Initial page size of topics without cache settings",
+                    },
                 },
             },
             add = &adw::PreferencesPage {
@@ -157,6 +362,19 @@ impl Component for SettingsDialogModel {
                 set_icon_name: Some("emblem-system-symbolic"),
                 add = &adw::PreferencesGroup {
                     set_title: "General",
+                    #[name = "stale_cache_hours"]
+                    adw::SpinRow {
+                        set_title: "Stale cache age",
+                        set_subtitle: "Hours after which a cache is flagged as stale, 0 to never flag",
+                        set_selectable: true,
+                        set_activatable: true,
+                        set_focusable: true,
+                        set_focus_on_click: true,
+                        set_snap_to_ticks: true,
+                        set_numeric: true,
+                        set_wrap: false,
+                        connect_value_notify => SettingsDialogMsg::SetStaleCacheHours,
+                    },
                 },
             },
         }
@@ -201,6 +419,61 @@ impl Component for SettingsDialogModel {
                 sender.input_sender(),
                 SettingsDialogMsg::MessagesColumnOrderSelected,
             );
+        let auto_offset_reset_idx = AUTO_OFFSET_RESET_VALUES
+            .iter()
+            .position(|v| *v == current.auto_offset_reset.as_str())
+            .unwrap_or_default();
+        let auto_offset_reset_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: AUTO_OFFSET_RESET_VALUES
+                    .iter()
+                    .map(|s| s.to_string())
+                    .collect(),
+                active_index: Some(auto_offset_reset_idx),
+            })
+            .forward(
+                sender.input_sender(),
+                SettingsDialogMsg::AutoOffsetResetSelected,
+            );
+
+        let gsk_renderer_idx = GSK_RENDERER_VALUES
+            .iter()
+            .position(|v| *v == current.gsk_renderer.as_str())
+            .unwrap_or_default();
+        let gsk_renderer_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: GSK_RENDERER_VALUES.iter().map(|s| s.to_string()).collect(),
+                active_index: Some(gsk_renderer_idx),
+            })
+            .forward(
+                sender.input_sender(),
+                SettingsDialogMsg::GskRendererSelected,
+            );
+
+        let log_level_idx = LOG_LEVEL_VALUES
+            .iter()
+            .position(|v| v.eq_ignore_ascii_case(current.log_level.trim()))
+            .unwrap_or(LOG_LEVEL_VALUES.len() - 1);
+        let log_level_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: LOG_LEVEL_VALUES.iter().map(|s| s.to_string()).collect(),
+                active_index: Some(log_level_idx),
+            })
+            .forward(sender.input_sender(), SettingsDialogMsg::LogLevelSelected);
+
+        let default_cache_page_size_idx = AVAILABLE_PAGE_SIZES
+            .iter()
+            .position(|v| *v == current.default_cache_page_size)
+            .unwrap_or_default();
+        let default_cache_page_size_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: AVAILABLE_PAGE_SIZES.to_vec(),
+                active_index: Some(default_cache_page_size_idx),
+            })
+            .forward(
+                sender.input_sender(),
+                SettingsDialogMsg::DefaultCachePageSizeSelected,
+            );
 
         let model = SettingsDialogModel {
             cache_dir: current.cache_dir,
@@ -211,10 +484,34 @@ impl Component for SettingsDialogModel {
             messages_sort_column: current.messages_sort_column,
             messages_sort_column_order: current.messages_sort_column_order,
             threads_number: current.threads_number as f64,
+            gsk_renderer_combo,
+            gsk_renderer: current.gsk_renderer.clone(),
+            log_level_combo,
+            log_level: current.log_level.clone(),
+            log_file: current.log_file.clone(),
+            cache_workers: current.cache_workers as f64,
             default_connection_timeout: current.default_connection_timeout as f64,
+            auto_offset_reset_combo,
+            auto_offset_reset: current.auto_offset_reset.clone(),
+            session_timeout_ms: current.session_timeout_ms as f64,
+            message_timeout_ms: current.message_timeout_ms as f64,
+            poll_timeout_ms: current.poll_timeout_ms as f64,
+            json_highlight_max_bytes: current.json_highlight_max_bytes as f64,
+            value_preview_max_chars: current.value_preview_max_chars as f64,
+            live_max_messages_default: current.live_max_messages_default as f64,
+            live_max_messages_max: current.live_max_messages_max as f64,
+            live_quick_fetch_presets: current.live_quick_fetch_presets.clone(),
+            default_cache_page_size_combo,
+            default_cache_page_size: current.default_cache_page_size,
+            stale_cache_hours: current.stale_cache_hours as f64,
+            timezone: current.timezone.clone(),
         };
         let messages_sort_column_combo = model.messages_sort_column_combo.widget();
         let messages_sort_column_order_combo = model.messages_sort_column_order_combo.widget();
+        let auto_offset_reset_combo = model.auto_offset_reset_combo.widget();
+        let gsk_renderer_combo = model.gsk_renderer_combo.widget();
+        let log_level_combo = model.log_level_combo.widget();
+        let default_cache_page_size_combo = model.default_cache_page_size_combo.widget();
         let widgets = view_output!();
         let adjustment_threads_number = Adjustment::builder()
             .lower(1.0)
@@ -229,6 +526,19 @@ impl Component for SettingsDialogModel {
         widgets
             .threads_number
             .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
+        let adjustment_cache_workers = Adjustment::builder()
+            .lower(1.0)
+            .upper(64.0)
+            .page_size(0.0)
+            .step_increment(1.0)
+            .value(current.cache_workers as f64)
+            .build();
+        widgets
+            .cache_workers
+            .set_adjustment(Some(&adjustment_cache_workers));
+        widgets
+            .cache_workers
+            .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
         let adjustment_default_timeout = Adjustment::builder()
             .lower(0.0)
             .upper(1800.0)
@@ -242,6 +552,110 @@ impl Component for SettingsDialogModel {
         widgets
             .default_connection_timeout
             .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
+        let adjustment_session_timeout = Adjustment::builder()
+            .lower(1000.0)
+            .upper(3600000.0)
+            .page_size(0.0)
+            .step_increment(1000.0)
+            .value(current.session_timeout_ms as f64)
+            .build();
+        widgets
+            .session_timeout_ms
+            .set_adjustment(Some(&adjustment_session_timeout));
+        widgets
+            .session_timeout_ms
+            .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
+        let adjustment_message_timeout = Adjustment::builder()
+            .lower(0.0)
+            .upper(3600000.0)
+            .page_size(0.0)
+            .step_increment(1000.0)
+            .value(current.message_timeout_ms as f64)
+            .build();
+        widgets
+            .message_timeout_ms
+            .set_adjustment(Some(&adjustment_message_timeout));
+        widgets
+            .message_timeout_ms
+            .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
+        let adjustment_poll_timeout = Adjustment::builder()
+            .lower(1000.0)
+            .upper(600000.0)
+            .page_size(0.0)
+            .step_increment(1000.0)
+            .value(current.poll_timeout_ms as f64)
+            .build();
+        widgets
+            .poll_timeout_ms
+            .set_adjustment(Some(&adjustment_poll_timeout));
+        widgets
+            .poll_timeout_ms
+            .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
+        let adjustment_json_highlight_max_bytes = Adjustment::builder()
+            .lower(0.0)
+            .upper(100.0 * 1024.0 * 1024.0)
+            .page_size(0.0)
+            .step_increment(1024.0)
+            .value(current.json_highlight_max_bytes as f64)
+            .build();
+        widgets
+            .json_highlight_max_bytes
+            .set_adjustment(Some(&adjustment_json_highlight_max_bytes));
+        widgets
+            .json_highlight_max_bytes
+            .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
+        let adjustment_value_preview_max_chars = Adjustment::builder()
+            .lower(10.0)
+            .upper(100000.0)
+            .page_size(0.0)
+            .step_increment(100.0)
+            .value(current.value_preview_max_chars as f64)
+            .build();
+        widgets
+            .value_preview_max_chars
+            .set_adjustment(Some(&adjustment_value_preview_max_chars));
+        widgets
+            .value_preview_max_chars
+            .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
+        let adjustment_live_max_messages_default = Adjustment::builder()
+            .lower(1.0)
+            .upper(10000000.0)
+            .page_size(0.0)
+            .step_increment(1000.0)
+            .value(current.live_max_messages_default as f64)
+            .build();
+        widgets
+            .live_max_messages_default
+            .set_adjustment(Some(&adjustment_live_max_messages_default));
+        widgets
+            .live_max_messages_default
+            .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
+        let adjustment_live_max_messages_max = Adjustment::builder()
+            .lower(1.0)
+            .upper(10000000.0)
+            .page_size(0.0)
+            .step_increment(10000.0)
+            .value(current.live_max_messages_max as f64)
+            .build();
+        widgets
+            .live_max_messages_max
+            .set_adjustment(Some(&adjustment_live_max_messages_max));
+        widgets
+            .live_max_messages_max
+            .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
+        let adjustment_stale_cache_hours = Adjustment::builder()
+            .lower(0.0)
+            .upper(24.0 * 365.0)
+            .page_size(0.0)
+            .step_increment(1.0)
+            .value(current.stale_cache_hours as f64)
+            .build();
+        widgets
+            .stale_cache_hours
+            .set_adjustment(Some(&adjustment_stale_cache_hours));
+        widgets
+            .stale_cache_hours
+            .set_update_policy(gtk::SpinButtonUpdatePolicy::IfValid);
         ComponentParts { model, widgets }
     }
 
@@ -275,6 +689,64 @@ impl Component for SettingsDialogModel {
                 self.messages_sort_column_order = column_order;
                 sender.input(SettingsDialogMsg::Save);
             }
+            SettingsDialogMsg::AutoOffsetResetSelected(_idx) => {
+                let value = match self.auto_offset_reset_combo.model().get_active_elem() {
+                    Some(opt) => opt.clone(),
+                    None => AUTO_OFFSET_RESET_VALUES[0].to_string(),
+                };
+                info!("selected auto.offset.reset {}", value);
+                self.auto_offset_reset = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::GskRendererSelected(_idx) => {
+                let value = match self.gsk_renderer_combo.model().get_active_elem() {
+                    Some(opt) => opt.clone(),
+                    None => GSK_RENDERER_VALUES[0].to_string(),
+                };
+                info!("selected renderer {}", value);
+                self.gsk_renderer = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::DefaultCachePageSizeSelected(_idx) => {
+                let value = match self.default_cache_page_size_combo.model().get_active_elem() {
+                    Some(opt) => *opt,
+                    None => AVAILABLE_PAGE_SIZES[0],
+                };
+                info!("selected default cache page size {}", value);
+                self.default_cache_page_size = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::LogLevelSelected(_idx) => {
+                let value = match self.log_level_combo.model().get_active_elem() {
+                    Some(opt) => opt.clone(),
+                    None => LOG_LEVEL_VALUES[LOG_LEVEL_VALUES.len() - 1].to_string(),
+                };
+                info!("selected log level {}", value);
+                self.log_level = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::SetLogFile => {
+                self.log_file = widgets.log_file_entry.text().trim().to_string();
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::OpenLogFile => {
+                let path = self.log_file.trim();
+                if path.is_empty() {
+                    show_error_alert(root, "No log file configured".to_string());
+                } else {
+                    let file = gtk::gio::File::for_path(path);
+                    let root = root.clone();
+                    gtk::FileLauncher::new(Some(&file)).launch(
+                        root.root().and_downcast_ref::<gtk::Window>(),
+                        None::<&gtk::gio::Cancellable>,
+                        move |result| {
+                            if let Err(e) = result {
+                                show_error_alert(&root, format!("Unable to open log file: {}", e));
+                            }
+                        },
+                    );
+                }
+            }
             SettingsDialogMsg::Show => {
                 let parent = &relm4::main_application().active_window().unwrap();
                 let current_settings = Settings::read().unwrap_or_default();
@@ -320,11 +792,92 @@ impl Component for SettingsDialogModel {
                 self.threads_number = value;
                 sender.input(SettingsDialogMsg::Save);
             }
+            SettingsDialogMsg::SetCacheWorkers => {
+                let value = widgets.cache_workers.value();
+                self.cache_workers = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
             SettingsDialogMsg::SetDefaultConnectionTimeout => {
                 let value = widgets.default_connection_timeout.value();
                 self.default_connection_timeout = value;
                 sender.input(SettingsDialogMsg::Save);
             }
+            SettingsDialogMsg::SetSessionTimeout => {
+                let value = widgets.session_timeout_ms.value();
+                self.session_timeout_ms = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::SetMessageTimeout => {
+                let value = widgets.message_timeout_ms.value();
+                self.message_timeout_ms = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::SetPollTimeout => {
+                let value = widgets.poll_timeout_ms.value();
+                self.poll_timeout_ms = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::SetJsonHighlightMaxBytes => {
+                let value = widgets.json_highlight_max_bytes.value();
+                self.json_highlight_max_bytes = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::SetValuePreviewMaxChars => {
+                let value = widgets.value_preview_max_chars.value();
+                self.value_preview_max_chars = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::SetLiveMaxMessagesDefault => {
+                let value = widgets.live_max_messages_default.value();
+                self.live_max_messages_default = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::SetLiveMaxMessagesMax => {
+                let value = widgets.live_max_messages_max.value();
+                self.live_max_messages_max = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::SetStaleCacheHours => {
+                let value = widgets.stale_cache_hours.value();
+                self.stale_cache_hours = value;
+                sender.input(SettingsDialogMsg::Save);
+            }
+            SettingsDialogMsg::SetLiveQuickFetchPresets => {
+                let value = widgets
+                    .live_quick_fetch_presets_entry
+                    .text()
+                    .trim()
+                    .to_string();
+                match parse_quick_fetch_presets(&value)
+                    .into_iter()
+                    .find_map(Result::err)
+                {
+                    None => {
+                        widgets
+                            .live_quick_fetch_presets_entry
+                            .remove_css_class("error");
+                        self.live_quick_fetch_presets = value;
+                        sender.input(SettingsDialogMsg::Save);
+                    }
+                    Some(e) => {
+                        warn!("{}", e);
+                        widgets
+                            .live_quick_fetch_presets_entry
+                            .add_css_class("error");
+                    }
+                }
+            }
+            SettingsDialogMsg::SetTimezone => {
+                let value = widgets.timezone_entry.text().trim().to_string();
+                if value.is_empty() || Tz::from_str(value.as_str()).is_ok() {
+                    widgets.timezone_entry.remove_css_class("error");
+                    self.timezone = value;
+                    sender.input(SettingsDialogMsg::Save);
+                } else {
+                    warn!("invalid timezone: {}", value);
+                    widgets.timezone_entry.add_css_class("error");
+                }
+            }
             SettingsDialogMsg::Save => {
                 let cache_dir = self.cache_dir.clone();
                 let settings = Settings {
@@ -333,7 +886,23 @@ impl Component for SettingsDialogModel {
                     messages_sort_column: self.messages_sort_column.clone(),
                     messages_sort_column_order: self.messages_sort_column_order.clone(),
                     threads_number: self.threads_number as u8,
+                    gsk_renderer: self.gsk_renderer.clone(),
+                    log_level: self.log_level.clone(),
+                    log_file: self.log_file.clone(),
+                    cache_workers: self.cache_workers as usize,
                     default_connection_timeout: self.default_connection_timeout as usize,
+                    auto_offset_reset: self.auto_offset_reset.clone(),
+                    session_timeout_ms: self.session_timeout_ms as usize,
+                    message_timeout_ms: self.message_timeout_ms as usize,
+                    poll_timeout_ms: self.poll_timeout_ms as usize,
+                    json_highlight_max_bytes: self.json_highlight_max_bytes as usize,
+                    value_preview_max_chars: self.value_preview_max_chars as usize,
+                    live_max_messages_default: self.live_max_messages_default as usize,
+                    live_max_messages_max: self.live_max_messages_max as usize,
+                    live_quick_fetch_presets: self.live_quick_fetch_presets.clone(),
+                    default_cache_page_size: self.default_cache_page_size,
+                    stale_cache_hours: self.stale_cache_hours as usize,
+                    timezone: self.timezone.clone(),
                 };
                 info!("settings_dialog::saving::{:?}", settings);
                 settings.write().expect("should write current settings");
@@ -20,6 +20,7 @@ pub struct StatusBarModel {
     is_loading: bool,
     text: String,
     duration: String,
+    selection: String,
     start_marker: Option<Instant>,
 }
 
@@ -27,6 +28,8 @@ pub struct StatusBarModel {
 pub enum StatusBarMsg {
     Start,
     StopWithInfo { text: Option<String> },
+    /// Describes the current selection, e.g. the size of the selected messages.
+    Selection { text: Option<String> },
 }
 
 #[relm4::component(pub)]
@@ -54,6 +57,12 @@ impl SimpleComponent for StatusBarModel {
           },
         },
         #[wrap(Some)]
+        set_center_widget = &gtk::Label {
+          add_css_class: "numeric",
+          #[watch]
+          set_label: model.selection.as_str(),
+        },
+        #[wrap(Some)]
         set_end_widget = &gtk::Box {
           gtk::Label {
             set_halign: gtk::Align::End,
@@ -69,6 +78,7 @@ impl SimpleComponent for StatusBarModel {
             is_loading: false,
             text: String::default(),
             duration: String::default(),
+            selection: String::default(),
             start_marker: None,
         };
 
@@ -95,6 +105,9 @@ impl SimpleComponent for StatusBarModel {
                         .elapsed()
                 );
             }
+            StatusBarMsg::Selection { text } => {
+                self.selection = text.unwrap_or_default();
+            }
         }
     }
 }
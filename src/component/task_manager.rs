@@ -4,7 +4,10 @@
 
 use std::borrow::Borrow;
 use std::cell::RefCell;
-use std::time::Duration;
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use gtk::glib::SignalHandlerId;
 use gtk::{pango, prelude::*};
@@ -60,11 +63,70 @@ impl SidebarListItem {
                     String::from("Fetching topic")
                 }
             }
+            TaskVariant::SendMessages => {
+                if counter > 1 {
+                    format!("Sending {} files", &counter)
+                } else {
+                    String::from("Sending file")
+                }
+            }
+            TaskVariant::CopyMessages => {
+                if counter > 1 {
+                    format!("Copying {} selections", &counter)
+                } else {
+                    String::from("Copying messages")
+                }
+            }
+            TaskVariant::CompactCache => {
+                if counter > 1 {
+                    format!("Compacting {} caches", &counter)
+                } else {
+                    String::from("Compacting cache")
+                }
+            }
+            TaskVariant::ExportMessages => {
+                if counter > 1 {
+                    format!("Exporting {} caches", &counter)
+                } else {
+                    String::from("Exporting cache")
+                }
+            }
+            TaskVariant::TopicsReport => {
+                if counter > 1 {
+                    format!("Reporting {} connections", &counter)
+                } else {
+                    String::from("Reporting topics")
+                }
+            }
+            TaskVariant::ClearCaches => {
+                if counter > 1 {
+                    format!("Clearing {} cache sets", &counter)
+                } else {
+                    String::from("Clearing caches")
+                }
+            }
+        }
+    }
+    fn label_failed(variant: &TaskVariant) -> String {
+        match variant {
+            TaskVariant::FetchMessages => String::from("Fetching failed!"),
+            TaskVariant::SendMessages => String::from("Sending failed!"),
+            TaskVariant::CopyMessages => String::from("Copying failed!"),
+            TaskVariant::CompactCache => String::from("Compacting failed!"),
+            TaskVariant::ExportMessages => String::from("Exporting failed!"),
+            TaskVariant::TopicsReport => String::from("Report failed!"),
+            TaskVariant::ClearCaches => String::from("Clearing failed!"),
         }
     }
     fn label_done(variant: &TaskVariant) -> String {
         match variant {
             TaskVariant::FetchMessages => String::from("Fetching done!"),
+            TaskVariant::SendMessages => String::from("Sending done!"),
+            TaskVariant::CopyMessages => String::from("Copying done!"),
+            TaskVariant::CompactCache => String::from("Compacting done!"),
+            TaskVariant::ExportMessages => String::from("Exporting done!"),
+            TaskVariant::TopicsReport => String::from("Report done!"),
+            TaskVariant::ClearCaches => String::from("Clearing done!"),
         }
     }
 }
@@ -104,18 +166,44 @@ impl RelmListItem for SidebarListItem {
 #[derive(Debug)]
 struct TaskListItem {
     pub value: Task,
+    label: StringBinding,
     progress: F64Binding,
+    partitions_progress: BTreeMap<i32, f64>,
+    breakdown: StringBinding,
+    has_breakdown: BoolBinding,
+    stats: StringBinding,
+    progress_value: f64,
+    items_total: Option<usize>,
+    duration: Option<Duration>,
+    failed: bool,
+    can_retry: BoolBinding,
+    can_dismiss: BoolBinding,
     sender: Sender<TaskManagerCommand>,
     cancel_handler_id: RefCell<Option<SignalHandlerId>>,
+    retry_handler_id: RefCell<Option<SignalHandlerId>>,
+    dismiss_handler_id: RefCell<Option<SignalHandlerId>>,
 }
 
 impl TaskListItem {
     fn new(value: Task, sender: Sender<TaskManagerCommand>) -> Self {
         Self {
+            label: StringBinding::new(TaskListItem::task_label(&value)),
             value,
             progress: F64Binding::new(0.0),
+            partitions_progress: BTreeMap::new(),
+            breakdown: StringBinding::default(),
+            has_breakdown: BoolBinding::new(false),
+            stats: StringBinding::default(),
+            progress_value: 0.0,
+            items_total: None,
+            duration: None,
+            failed: false,
+            can_retry: BoolBinding::new(false),
+            can_dismiss: BoolBinding::new(false),
             sender,
             cancel_handler_id: RefCell::new(None),
+            retry_handler_id: RefCell::new(None),
+            dismiss_handler_id: RefCell::new(None),
         }
     }
 }
@@ -124,6 +212,10 @@ struct TaskWidgets {
     task_progress: gtk::Box,
     task_name: gtk::Label,
     progress_bar: gtk::ProgressBar,
+    task_stats: gtk::Label,
+    partitions_breakdown: gtk::Label,
+    retry_button: gtk::Button,
+    dismiss_button: gtk::Button,
     cancel_button: gtk::Button,
 }
 
@@ -140,10 +232,53 @@ impl Drop for TaskWidgets {
 }
 
 impl TaskListItem {
-    fn task_label(&mut self) -> String {
-        match self.value.variant {
+    /// Elapsed time, with the throughput when the number of items is known, or the final
+    /// duration once the task is done.
+    fn refresh_stats(&mut self) {
+        let text = match self.duration {
+            Some(duration) => format!("Done in {}", format_elapsed(duration)),
+            None => {
+                let elapsed = self.value.started_at.elapsed();
+                match self.items_total {
+                    Some(total) if elapsed.as_secs_f64() > 0.0 => {
+                        let rate = self.progress_value * total as f64 / elapsed.as_secs_f64();
+                        format!("{} · {:.0} msg/s", format_elapsed(elapsed), rate)
+                    }
+                    _ => format_elapsed(elapsed),
+                }
+            }
+        };
+        *self.stats.guard() = text;
+    }
+    fn finish(&mut self) {
+        if self.duration.is_none() {
+            self.duration = Some(self.value.started_at.elapsed());
+            self.refresh_stats();
+        }
+    }
+    fn task_label(task: &Task) -> String {
+        let name = task.name.clone().unwrap_or_default();
+        match task.variant {
             TaskVariant::FetchMessages => {
-                format!("Fetching {}", &self.value.name.clone().unwrap_or_default())
+                format!("Fetching {}", &name)
+            }
+            TaskVariant::SendMessages => {
+                format!("Sending {}", &name)
+            }
+            TaskVariant::CopyMessages => {
+                format!("Copying {}", &name)
+            }
+            TaskVariant::CompactCache => {
+                format!("Compacting {}", &name)
+            }
+            TaskVariant::ExportMessages => {
+                format!("Exporting {}", &name)
+            }
+            TaskVariant::TopicsReport => {
+                format!("Reporting topics of {}", &name)
+            }
+            TaskVariant::ClearCaches => {
+                format!("Clearing {}", &name)
             }
         }
     }
@@ -175,6 +310,42 @@ impl RelmListItem for TaskListItem {
                         set_show_text: true,
                         set_ellipsize: pango::EllipsizeMode::End,
                     },
+                    #[name = "task_stats"]
+                    gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        add_css_class: "caption",
+                        add_css_class: "numeric",
+                    },
+                    #[name = "partitions_breakdown"]
+                    gtk::Label {
+                        set_halign: gtk::Align::Start,
+                        set_wrap: true,
+                        set_xalign: 0.0,
+                        add_css_class: "caption",
+                        add_css_class: "dim-label",
+                    },
+                },
+                #[name = "retry_button"]
+                gtk::Button {
+                    set_halign: gtk::Align::Center,
+                    set_valign: gtk::Align::Center,
+                    set_hexpand: false,
+                    set_vexpand: false,
+                    set_visible: false,
+                    add_css_class: "circular",
+                    set_icon_name: "view-refresh-symbolic",
+                    set_tooltip_text: Some("Retry task"),
+                },
+                #[name = "dismiss_button"]
+                gtk::Button {
+                    set_halign: gtk::Align::Center,
+                    set_valign: gtk::Align::Center,
+                    set_hexpand: false,
+                    set_vexpand: false,
+                    set_visible: false,
+                    add_css_class: "circular",
+                    set_icon_name: "window-close-symbolic",
+                    set_tooltip_text: Some("Dismiss failed task"),
                 },
                 #[name = "cancel_button"]
                 gtk::Button {
@@ -193,6 +364,10 @@ impl RelmListItem for TaskListItem {
             task_progress,
             task_name,
             progress_bar,
+            task_stats,
+            partitions_breakdown,
+            retry_button,
+            dismiss_button,
             cancel_button,
         };
 
@@ -204,27 +379,63 @@ impl RelmListItem for TaskListItem {
             task_progress,
             task_name,
             progress_bar,
+            task_stats,
+            partitions_breakdown,
+            retry_button,
+            dismiss_button,
             cancel_button,
         } = widgets;
-        task_progress.set_tooltip_text(Some(&self.task_label()));
-        task_name.set_label(&self.task_label());
+        task_progress.add_write_only_binding(&self.label, "tooltip-text");
+        task_name.add_write_only_binding(&self.label, "label");
         progress_bar.add_write_only_binding(&self.progress, "fraction");
+        task_stats.add_write_only_binding(&self.stats, "label");
+        partitions_breakdown.add_write_only_binding(&self.breakdown, "label");
+        partitions_breakdown.add_write_only_binding(&self.has_breakdown, "visible");
         cancel_button.set_sensitive(self.value.token.is_some());
         if self.value.token.is_some() {
             let task = self.value.clone();
             let token = self.value.clone().token.unwrap();
             let sender = self.sender.clone();
-            let signal_id = cancel_button.connect_clicked(move |_button| {
+            let signal_id = cancel_button.connect_clicked(move |button| {
+                button.set_sensitive(false);
+                if !token.is_cancelled() {
+                    token.cancel();
+                }
                 sender.emit(TaskManagerCommand::CancelTask(task.clone()));
-                token.cancel();
             });
             self.cancel_handler_id = RefCell::new(Some(signal_id));
         };
+        retry_button.add_write_only_binding(&self.can_retry, "visible");
+        if let Some(retry) = self.value.retry.clone() {
+            let task = self.value.clone();
+            let sender = self.sender.clone();
+            let signal_id = retry_button.connect_clicked(move |button| {
+                button.set_sensitive(false);
+                retry.run();
+                sender.emit(TaskManagerCommand::RemoveTask(task.clone()));
+            });
+            self.retry_handler_id = RefCell::new(Some(signal_id));
+        };
+        dismiss_button.add_write_only_binding(&self.can_dismiss, "visible");
+        dismiss_button.set_sensitive(true);
+        let task = self.value.clone();
+        let sender = self.sender.clone();
+        let signal_id = dismiss_button.connect_clicked(move |button| {
+            button.set_sensitive(false);
+            sender.emit(TaskManagerCommand::RemoveTask(task.clone()));
+        });
+        self.dismiss_handler_id = RefCell::new(Some(signal_id));
     }
     fn unbind(&mut self, widgets: &mut Self::Widgets, _root: &mut Self::Root) {
         if let Some(id) = self.cancel_handler_id.take() {
             widgets.cancel_button.disconnect(id);
         };
+        if let Some(id) = self.retry_handler_id.take() {
+            widgets.retry_button.disconnect(id);
+        };
+        if let Some(id) = self.dismiss_handler_id.take() {
+            widgets.dismiss_button.disconnect(id);
+        };
     }
 }
 // END: tasks_list
@@ -238,7 +449,32 @@ pub struct TaskManagerModel {
 #[derive(Debug, Clone, PartialEq)]
 pub enum TaskVariant {
     FetchMessages,
+    SendMessages,
+    CopyMessages,
+    CompactCache,
+    ExportMessages,
+    TopicsReport,
+    ClearCaches,
 }
+/// Starts a failed task again, usually by re-sending the message that created it.
+#[derive(Clone)]
+pub struct TaskRetry(Arc<dyn Fn() + Send + Sync>);
+
+impl TaskRetry {
+    pub fn new(retry: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Arc::new(retry))
+    }
+    pub fn run(&self) {
+        (self.0)()
+    }
+}
+
+impl Debug for TaskRetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TaskRetry")
+    }
+}
+
 #[non_exhaustive]
 #[derive(Debug, Clone)]
 pub struct Task {
@@ -246,6 +482,8 @@ pub struct Task {
     pub variant: TaskVariant,
     pub name: Option<String>,
     pub token: Option<CancellationToken>,
+    pub started_at: Instant,
+    pub retry: Option<TaskRetry>,
 }
 
 impl Task {
@@ -260,8 +498,14 @@ impl Task {
             variant,
             name,
             token,
+            started_at: Instant::now(),
+            retry: None,
         }
     }
+    pub fn with_retry(mut self, retry: TaskRetry) -> Self {
+        self.retry = Some(retry);
+        self
+    }
 }
 
 #[derive(Debug)]
@@ -269,12 +513,21 @@ pub enum TaskManagerMsg {
     AddTask(Task),
     RemoveTask(Task),
     Progress(Task, f64),
+    /// Replaces the task description, e.g. to tell how it ended.
+    Status(Task, String),
+    /// Progress of a single partition, shown as a breakdown below the task progress.
+    PartitionProgress(Task, i32, f64),
+    /// Number of items the task processes, used to tell its throughput.
+    ItemsTotal(Task, usize),
+    Tick,
+    /// Keeps the task listed as failed, offering to retry it when possible.
+    Failed(Task, String),
 }
 
 #[derive(Debug)]
 pub enum TaskManagerCommand {
     RemoveTask(Task),
-    RemoveSidebarTask(u32),
+    RemoveSidebarTask(TaskVariant),
     NeedsAttention,
     RemoveAttention,
     CancelTask(Task),
@@ -324,7 +577,7 @@ impl Component for TaskManagerModel {
         }
     }
 
-    fn init(_: (), root: Self::Root, _sender: ComponentSender<Self>) -> ComponentParts<Self> {
+    fn init(_: (), root: Self::Root, sender: ComponentSender<Self>) -> ComponentParts<Self> {
         // Initialize the ListView wrapper
         let sidebar_list_view_wrapper: TypedListView<SidebarListItem, gtk::NoSelection> =
             TypedListView::default();
@@ -337,6 +590,12 @@ impl Component for TaskManagerModel {
 
         let widgets = view_output!();
 
+        // refreshes the elapsed time of running tasks
+        gtk::glib::timeout_add_seconds_local(1, move || {
+            sender.input(TaskManagerMsg::Tick);
+            gtk::glib::ControlFlow::Continue
+        });
+
         ComponentParts { model, widgets }
     }
 
@@ -384,16 +643,91 @@ impl Component for TaskManagerModel {
                 if let Some(idx) = maybe_index {
                     let found = self.tasks_list_wrapper.get(idx).unwrap();
                     let item = &mut found.borrow_mut();
+                    item.progress_value = step;
                     let progress = &mut item.progress;
                     let mut guard = progress.guard();
                     *guard = step;
 
                     if *guard >= 1.0 {
+                        drop(guard);
+                        item.finish();
                         sender.input(TaskManagerMsg::RemoveTask(task.clone()));
                     }
                 }
             }
+            TaskManagerMsg::Status(task, text) => {
+                let maybe_index = self.tasks_list_wrapper.find(|t| t.value.id.eq(&task.id));
+                if let Some(idx) = maybe_index {
+                    let found = self.tasks_list_wrapper.get(idx).unwrap();
+                    let item = &mut found.borrow_mut();
+                    let label = &mut item.label;
+                    let mut guard = label.guard();
+                    *guard = text;
+                }
+            }
+            TaskManagerMsg::ItemsTotal(task, total) => {
+                let maybe_index = self.tasks_list_wrapper.find(|t| t.value.id.eq(&task.id));
+                if let Some(idx) = maybe_index {
+                    let found = self.tasks_list_wrapper.get(idx).unwrap();
+                    found.borrow_mut().items_total = Some(total);
+                }
+            }
+            TaskManagerMsg::Failed(task, text) => {
+                let maybe_index = self.tasks_list_wrapper.find(|t| t.value.id.eq(&task.id));
+                if let Some(idx) = maybe_index {
+                    let found = self.tasks_list_wrapper.get(idx).unwrap();
+                    let item = &mut found.borrow_mut();
+                    item.failed = true;
+                    item.finish();
+                    *item.label.guard() = text;
+                    *item.can_retry.guard() = item.value.retry.is_some();
+                    *item.can_dismiss.guard() = true;
+                }
+                let maybe_index = self
+                    .sidebar_list_wrapper
+                    .find(|t| t.variant == task.variant);
+                if let Some(idx) = maybe_index {
+                    if self.count_running_tasks_by_variant(task.variant.clone()) < 1 {
+                        let found = self.sidebar_list_wrapper.get(idx).unwrap();
+                        let item = &mut found.borrow_mut();
+                        *item.label.guard() = SidebarListItem::label_failed(&item.variant);
+                        *item.spin.guard() = false;
+                    }
+                }
+                sender
+                    .command_sender()
+                    .emit(TaskManagerCommand::NeedsAttention);
+            }
+            TaskManagerMsg::Tick => {
+                for i in 0..self.tasks_list_wrapper.len() {
+                    if let Some(item) = self.tasks_list_wrapper.get(i) {
+                        item.borrow_mut().refresh_stats();
+                    }
+                }
+            }
+            TaskManagerMsg::PartitionProgress(task, partition, step) => {
+                let maybe_index = self.tasks_list_wrapper.find(|t| t.value.id.eq(&task.id));
+                if let Some(idx) = maybe_index {
+                    let found = self.tasks_list_wrapper.get(idx).unwrap();
+                    let item = &mut found.borrow_mut();
+                    item.partitions_progress.insert(partition, step.min(1.0));
+                    let breakdown = item
+                        .partitions_progress
+                        .iter()
+                        .map(|(p, s)| format!("P{} {:.0}%", p, s * 100.0))
+                        .collect::<Vec<_>>()
+                        .join(" · ");
+                    *item.breakdown.guard() = breakdown;
+                    *item.has_breakdown.guard() = true;
+                }
+            }
             TaskManagerMsg::RemoveTask(task) => {
+                // keeps the final duration visible until the row goes away
+                let maybe_index = self.tasks_list_wrapper.find(|t| t.value.id.eq(&task.id));
+                if let Some(idx) = maybe_index {
+                    let found = self.tasks_list_wrapper.get(idx).unwrap();
+                    found.borrow_mut().finish();
+                }
                 let maybe_index = self
                     .sidebar_list_wrapper
                     .find(|t| t.variant == task.variant);
@@ -430,8 +764,13 @@ impl Component for TaskManagerModel {
             TaskManagerCommand::RemoveTask(task) => {
                 debug!("TaskManagerCommand::RemoveTask[{:?}]", task);
                 let maybe_index = self.tasks_list_wrapper.find(|t| t.value.id.eq(&task.id));
-                if let Some(idx) = maybe_index {
-                    self.tasks_list_wrapper.remove(idx);
+                match maybe_index {
+                    Some(idx) => self.tasks_list_wrapper.remove(idx),
+                    None => {
+                        // cancelled from the task manager and then removed by its owner
+                        debug!("task already removed: {}", task.id);
+                        return;
+                    }
                 }
                 let maybe_index = self
                     .sidebar_list_wrapper
@@ -444,7 +783,7 @@ impl Component for TaskManagerModel {
                     if counter < 1 {
                         sender
                             .command_sender()
-                            .emit(TaskManagerCommand::RemoveSidebarTask(idx));
+                            .emit(TaskManagerCommand::RemoveSidebarTask(task.variant.clone()));
                     } else {
                         let label = &mut item.label;
                         let mut guard = label.guard();
@@ -452,11 +791,19 @@ impl Component for TaskManagerModel {
                     }
                 }
             }
-            TaskManagerCommand::RemoveSidebarTask(idx) => {
-                debug!("TaskManagerCommand::RemoveSidebarTask[{}]", idx);
-                self.sidebar_list_wrapper.remove(idx);
-                widgets.tasks_popover.popdown();
-                widgets.tasks_button.set_sensitive(false);
+            TaskManagerCommand::RemoveSidebarTask(variant) => {
+                debug!("TaskManagerCommand::RemoveSidebarTask[{:?}]", variant);
+                let maybe_index = self.sidebar_list_wrapper.find(|t| t.variant == variant);
+                if let Some(idx) = maybe_index {
+                    // a task of the same kind may have started meanwhile
+                    if self.count_tasks_by_variant(variant) < 1 {
+                        self.sidebar_list_wrapper.remove(idx);
+                    }
+                }
+                if self.tasks_list_wrapper.len() == 0 {
+                    widgets.tasks_popover.popdown();
+                    widgets.tasks_button.set_sensitive(false);
+                }
             }
             TaskManagerCommand::NeedsAttention => {
                 root.add_css_class("needs-attention");
@@ -495,4 +842,32 @@ impl TaskManagerModel {
         }
         counter
     }
+    fn count_running_tasks_by_variant(&self, variant: TaskVariant) -> u8 {
+        let mut counter: u8 = 0;
+
+        for i in 0..self.tasks_list_wrapper.len() {
+            let item = self.tasks_list_wrapper.get(i);
+            if let Some(item) = item {
+                let item = item.borrow();
+                if item.value.variant == variant && !item.failed {
+                    counter += 1;
+                }
+            }
+        }
+        counter
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let seconds = elapsed.as_secs();
+    if seconds >= 3600 {
+        format!(
+            "{}:{:02}:{:02}",
+            seconds / 3600,
+            (seconds / 60) % 60,
+            seconds % 60
+        )
+    } else {
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
 }
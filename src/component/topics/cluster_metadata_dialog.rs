@@ -0,0 +1,212 @@
+// Copyright (c) 2024, Miguel A. Baldi Hörlle <miguel.horlle@gmail.com>. All rights reserved. Use of
+// this source code is governed by the GPL-3.0 license that can be
+// found in the COPYING file.
+
+use crate::{
+    backend::{
+        kafka::{KafkaBackend, KrustClusterMetadata},
+        repository::KrustConnection,
+    },
+    config::ExternalError,
+    modals::utils::show_error_alert,
+};
+use adw::prelude::*;
+use relm4::*;
+
+use tracing::*;
+
+#[derive(Debug)]
+pub struct ClusterMetadataDialogModel {
+    pub connection: Option<KrustConnection>,
+    pub is_loading: bool,
+    broker_rows: Vec<adw::ActionRow>,
+}
+
+#[derive(Debug)]
+pub enum ClusterMetadataDialogMsg {
+    Show,
+    Refresh,
+}
+
+#[derive(Debug)]
+pub enum AsyncCommandOutput {
+    MetadataResult(Result<KrustClusterMetadata, ExternalError>),
+}
+
+#[relm4::component(pub)]
+impl Component for ClusterMetadataDialogModel {
+    type Init = Option<KrustConnection>;
+    type Input = ClusterMetadataDialogMsg;
+    type Output = ();
+    type CommandOutput = AsyncCommandOutput;
+
+    view! {
+        #[root]
+        main_dialog = adw::Dialog {
+            set_title: "Cluster metadata",
+            set_content_width: 500,
+            set_content_height: 500,
+            #[wrap(Some)]
+            set_child = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                adw::HeaderBar {
+                    pack_end = &gtk::Button {
+                        set_tooltip_text: Some("Refresh"),
+                        set_icon_name: "media-playlist-repeat-symbolic",
+                        #[watch]
+                        set_sensitive: !model.is_loading,
+                        connect_clicked => ClusterMetadataDialogMsg::Refresh,
+                    },
+                },
+                set_valign: gtk::Align::Fill,
+                gtk::ScrolledWindow {
+                    set_vexpand: true,
+                    set_hexpand: true,
+                    set_propagate_natural_height: true,
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_valign: gtk::Align::Fill,
+                        set_margin_all: 10,
+                        adw::PreferencesGroup {
+                            set_title: "Cluster",
+                            set_vexpand: false,
+                            set_hexpand: true,
+                            #[name(cluster_id_row)]
+                            adw::ActionRow {
+                                set_title: "Cluster id",
+                                add_css_class: "property",
+                                set_subtitle_selectable: true,
+                            },
+                            #[name(controller_row)]
+                            adw::ActionRow {
+                                set_title: "Controller broker",
+                                add_css_class: "property",
+                            },
+                            #[name(orig_broker_row)]
+                            adw::ActionRow {
+                                set_title: "Metadata from",
+                                add_css_class: "property",
+                            },
+                            #[name(topic_count_row)]
+                            adw::ActionRow {
+                                set_title: "Topics",
+                                add_css_class: "property",
+                            },
+                        },
+                        #[name(brokers_group)]
+                        adw::PreferencesGroup {
+                            set_title: "Brokers",
+                            set_margin_top: 10,
+                            set_vexpand: false,
+                            set_hexpand: true,
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        current_connection: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let model = ClusterMetadataDialogModel {
+            connection: current_connection,
+            is_loading: false,
+            broker_rows: Vec::new(),
+        };
+
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        msg: ClusterMetadataDialogMsg,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        debug!("received message: {:?}", msg);
+
+        match msg {
+            ClusterMetadataDialogMsg::Show => {
+                let parent = &relm4::main_application().active_window().unwrap();
+                root.queue_allocate();
+                root.present(parent);
+                sender.input(ClusterMetadataDialogMsg::Refresh);
+            }
+            ClusterMetadataDialogMsg::Refresh => {
+                if let Some(connection) = self.connection.clone() {
+                    self.is_loading = true;
+                    sender.oneshot_command(async move {
+                        let kafka = KafkaBackend::new(&connection);
+                        let result = kafka.fetch_cluster_metadata().await;
+                        AsyncCommandOutput::MetadataResult(result)
+                    });
+                }
+            }
+        };
+
+        self.update_view(widgets, sender);
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            AsyncCommandOutput::MetadataResult(Ok(metadata)) => {
+                info!("cluster metadata::{:?}", metadata);
+                widgets
+                    .cluster_id_row
+                    .set_subtitle(metadata.cluster_id.as_deref().unwrap_or("-"));
+                widgets.controller_row.set_subtitle(
+                    metadata
+                        .controller_id
+                        .map(|id| id.to_string())
+                        .unwrap_or("-".to_string())
+                        .as_str(),
+                );
+                widgets.orig_broker_row.set_subtitle(
+                    format!(
+                        "{} (id {})",
+                        metadata.orig_broker_name, metadata.orig_broker_id
+                    )
+                    .as_str(),
+                );
+                widgets
+                    .topic_count_row
+                    .set_subtitle(metadata.topic_count.to_string().as_str());
+                for row in self.broker_rows.drain(..) {
+                    widgets.brokers_group.remove(&row);
+                }
+                for broker in metadata.brokers {
+                    let is_controller = metadata.controller_id == Some(broker.id);
+                    let subtitle = if is_controller {
+                        format!("id {} (controller)", broker.id)
+                    } else {
+                        format!("id {}", broker.id)
+                    };
+                    let row = adw::ActionRow::builder()
+                        .title(format!("{}:{}", broker.host, broker.port))
+                        .subtitle(subtitle)
+                        .build();
+                    widgets.brokers_group.add(&row);
+                    self.broker_rows.push(row);
+                }
+            }
+            AsyncCommandOutput::MetadataResult(Err(error)) => {
+                let error_message = format!("{}", error);
+                error!(error_message);
+                show_error_alert(root, error_message);
+            }
+        }
+        self.is_loading = false;
+        self.update_view(widgets, sender);
+    }
+}
@@ -0,0 +1,377 @@
+// Copyright (c) 2024, Miguel A. Baldi Hörlle <miguel.horlle@gmail.com>. All rights reserved. Use of
+// this source code is governed by the GPL-3.0 license that can be
+// found in the COPYING file.
+
+use crate::{
+    backend::{
+        kafka::{KafkaBackend, KrustConsumerGroup, KrustConsumerGroupOffset, OffsetResetTarget},
+        repository::KrustConnection,
+        settings::Settings,
+    },
+    config::ExternalError,
+    modals::utils::{build_confirmation_alert, show_error_alert},
+    AppMsg, DATE_TIME_FORMAT, TOASTER_BROKER,
+};
+use adw::{prelude::*, AlertDialog};
+use chrono::NaiveDateTime;
+use relm4::*;
+use relm4_components::simple_adw_combo_row::SimpleComboRow;
+use uuid::Uuid;
+
+use tracing::*;
+
+#[derive(Debug)]
+pub struct ConsumerGroupsDialogModel {
+    pub connection: Option<KrustConnection>,
+    pub topic_name: Option<String>,
+    pub is_loading: bool,
+    pub target_combo: Controller<SimpleComboRow<OffsetResetTarget>>,
+    pub selected_target: OffsetResetTarget,
+    pub confirmation_alert: AlertDialog,
+    pub reset_group: Option<String>,
+    group_rows: Vec<adw::ExpanderRow>,
+}
+
+#[derive(Debug)]
+pub enum ConsumerGroupsDialogMsg {
+    Show(String),
+    Refresh,
+    TargetSelected(usize),
+    ResetOffsets(String),
+    ConfirmResetOffsets,
+    Ignore,
+}
+
+#[derive(Debug)]
+pub enum AsyncCommandOutput {
+    GroupsResult(Result<Vec<(KrustConsumerGroup, Vec<KrustConsumerGroupOffset>)>, ExternalError>),
+    ResetResult(String, Result<Vec<KrustConsumerGroupOffset>, ExternalError>),
+}
+
+#[relm4::component(pub)]
+impl Component for ConsumerGroupsDialogModel {
+    type Init = Option<KrustConnection>;
+    type Input = ConsumerGroupsDialogMsg;
+    type Output = ();
+    type CommandOutput = AsyncCommandOutput;
+
+    view! {
+        #[root]
+        main_dialog = adw::Dialog {
+            set_title: "Consumer groups",
+            set_content_width: 600,
+            set_content_height: 500,
+            #[wrap(Some)]
+            set_child = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                adw::HeaderBar {
+                    pack_end = &gtk::Button {
+                        set_tooltip_text: Some("Refresh"),
+                        set_icon_name: "media-playlist-repeat-symbolic",
+                        #[watch]
+                        set_sensitive: !model.is_loading,
+                        connect_clicked => ConsumerGroupsDialogMsg::Refresh,
+                    },
+                },
+                set_valign: gtk::Align::Fill,
+                gtk::ScrolledWindow {
+                    set_vexpand: true,
+                    set_hexpand: true,
+                    set_propagate_natural_height: true,
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_valign: gtk::Align::Fill,
+                        set_margin_all: 10,
+                        adw::PreferencesGroup {
+                            set_title: "Reset offsets",
+                            set_description: Some("Target used by the reset button of each group, the group must have no active members"),
+                            #[local_ref]
+                            target_combo -> adw::ComboRow {
+                                set_title: "Reset to",
+                            },
+                            #[name(timestamp_entry)]
+                            adw::EntryRow {
+                                set_title: "Date/time (dd/mm/yyyy hh:mm:ss)",
+                                #[watch]
+                                set_visible: matches!(model.selected_target, OffsetResetTarget::Timestamp(_)),
+                            },
+                        },
+                        #[name(groups_group)]
+                        adw::PreferencesGroup {
+                            set_margin_top: 10,
+                            #[watch]
+                            set_title: model.topic_name.as_deref().unwrap_or_default(),
+                            #[watch]
+                            set_description: Some(if model.is_loading {
+                                "Loading consumer groups..."
+                            } else if model.group_rows.is_empty() {
+                                "No consumer group has committed offsets on this topic"
+                            } else {
+                                "Consumer groups with committed offsets and their lag"
+                            }),
+                            set_vexpand: false,
+                            set_hexpand: true,
+                        },
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        current_connection: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let target_combo = SimpleComboRow::builder()
+            .launch(SimpleComboRow {
+                variants: OffsetResetTarget::VALUES.to_vec(),
+                active_index: Some(0),
+            })
+            .forward(
+                sender.input_sender(),
+                ConsumerGroupsDialogMsg::TargetSelected,
+            );
+        let confirmation_alert =
+            build_confirmation_alert("Reset".to_string(), "Reset offsets?".to_string());
+        let snd: ComponentSender<ConsumerGroupsDialogModel> = sender.clone();
+        confirmation_alert.connect_response(Some("cancel"), move |_, _| {
+            snd.input(ConsumerGroupsDialogMsg::Ignore);
+        });
+        let snd: ComponentSender<ConsumerGroupsDialogModel> = sender.clone();
+        confirmation_alert.connect_response(Some("confirm"), move |_, _| {
+            snd.input(ConsumerGroupsDialogMsg::ConfirmResetOffsets);
+        });
+        let model = ConsumerGroupsDialogModel {
+            connection: current_connection,
+            topic_name: None,
+            is_loading: false,
+            target_combo,
+            selected_target: OffsetResetTarget::Earliest,
+            confirmation_alert,
+            reset_group: None,
+            group_rows: Vec::new(),
+        };
+        let target_combo = model.target_combo.widget();
+
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        msg: ConsumerGroupsDialogMsg,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        debug!("received message: {:?}", msg);
+
+        match msg {
+            ConsumerGroupsDialogMsg::Show(topic_name) => {
+                self.topic_name = Some(topic_name);
+                for row in self.group_rows.drain(..) {
+                    widgets.groups_group.remove(&row);
+                }
+                let parent = &relm4::main_application().active_window().unwrap();
+                root.queue_allocate();
+                root.present(parent);
+                sender.input(ConsumerGroupsDialogMsg::Refresh);
+            }
+            ConsumerGroupsDialogMsg::Refresh => {
+                if let (Some(connection), Some(topic_name)) =
+                    (self.connection.clone(), self.topic_name.clone())
+                {
+                    self.is_loading = true;
+                    sender.oneshot_command(async move {
+                        let kafka = KafkaBackend::new(&connection);
+                        let result = fetch_topic_groups(&kafka, &topic_name).await;
+                        AsyncCommandOutput::GroupsResult(result)
+                    });
+                }
+            }
+            ConsumerGroupsDialogMsg::TargetSelected(idx) => {
+                self.selected_target = OffsetResetTarget::VALUES[idx];
+            }
+            ConsumerGroupsDialogMsg::ResetOffsets(group) => {
+                let Some(target) = self.reset_target(widgets) else {
+                    show_error_alert(
+                        root,
+                        "Invalid date/time, expected dd/mm/yyyy hh:mm:ss".to_string(),
+                    );
+                    return;
+                };
+                self.selected_target = target;
+                let topic_name = self.topic_name.clone().unwrap_or_default();
+                self.confirmation_alert.set_body(
+                    format!(
+                        "Are you sure you want to reset the offsets of group {} on topic {} to {}?",
+                        group,
+                        topic_name,
+                        reset_target_label(target)
+                    )
+                    .as_str(),
+                );
+                self.reset_group = Some(group);
+                self.confirmation_alert.present(root);
+            }
+            ConsumerGroupsDialogMsg::ConfirmResetOffsets => {
+                if let (Some(connection), Some(topic_name), Some(group)) = (
+                    self.connection.clone(),
+                    self.topic_name.clone(),
+                    self.reset_group.take(),
+                ) {
+                    self.is_loading = true;
+                    let target = self.selected_target;
+                    sender.oneshot_command(async move {
+                        let kafka = KafkaBackend::new(&connection);
+                        let result = kafka.reset_group_offsets(&group, &topic_name, target).await;
+                        AsyncCommandOutput::ResetResult(group, result)
+                    });
+                }
+            }
+            ConsumerGroupsDialogMsg::Ignore => {
+                self.reset_group = None;
+            }
+        };
+
+        self.update_view(widgets, sender);
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            AsyncCommandOutput::GroupsResult(Ok(groups)) => {
+                info!("consumer groups::{}", groups.len());
+                for row in self.group_rows.drain(..) {
+                    widgets.groups_group.remove(&row);
+                }
+                for (group, offsets) in groups {
+                    let total_lag: i64 = offsets.iter().filter_map(|o| o.lag).sum();
+                    let row = adw::ExpanderRow::builder()
+                        .title(group.name.as_str())
+                        .subtitle(format!(
+                            "Lag: {}, State: {}, Members: {}",
+                            total_lag, group.state, group.members
+                        ))
+                        .build();
+                    // offsets can only be committed while the group has no active members
+                    let tooltip = if group.members > 0 {
+                        "Stop the group members to reset its offsets"
+                    } else {
+                        "Reset group offsets on this topic"
+                    };
+                    let reset_button = gtk::Button::builder()
+                        .label("Reset")
+                        .tooltip_text(tooltip)
+                        .valign(gtk::Align::Center)
+                        .css_classes(["destructive-action"])
+                        .sensitive(group.members == 0)
+                        .build();
+                    let snd = sender.clone();
+                    let group_name = group.name.clone();
+                    reset_button.connect_clicked(move |_| {
+                        snd.input(ConsumerGroupsDialogMsg::ResetOffsets(group_name.clone()));
+                    });
+                    row.add_suffix(&reset_button);
+                    for offset in offsets {
+                        let committed = offset
+                            .committed
+                            .map(|c| c.to_string())
+                            .unwrap_or("-".to_string());
+                        let lag = offset.lag.map(|l| l.to_string()).unwrap_or("-".to_string());
+                        let partition_row = adw::ActionRow::builder()
+                            .title(format!("Partition {}", offset.partition))
+                            .subtitle(format!(
+                                "Committed: {}, High: {}, Lag: {}",
+                                committed, offset.high_watermark, lag
+                            ))
+                            .build();
+                        row.add_row(&partition_row);
+                    }
+                    widgets.groups_group.add(&row);
+                    self.group_rows.push(row);
+                }
+            }
+            AsyncCommandOutput::ResetResult(group, Ok(offsets)) => {
+                let summary = offsets
+                    .iter()
+                    .map(|o| {
+                        format!(
+                            "{}={}",
+                            o.partition,
+                            o.committed
+                                .map(|c| c.to_string())
+                                .unwrap_or("-".to_string())
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                info!("offsets of group {} reset::{}", group, summary);
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(
+                    id.clone(),
+                    format!("Group {} offsets: {}", group, summary),
+                ));
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+                sender.input(ConsumerGroupsDialogMsg::Refresh);
+            }
+            AsyncCommandOutput::GroupsResult(Err(error))
+            | AsyncCommandOutput::ResetResult(_, Err(error)) => {
+                let error_message = format!("{}", error);
+                error!(error_message);
+                show_error_alert(root, error_message);
+            }
+        }
+        self.is_loading = false;
+        self.update_view(widgets, sender);
+    }
+}
+
+impl ConsumerGroupsDialogModel {
+    /// Selected reset target, with the timestamp parsed from the entry in the configured timezone.
+    fn reset_target(
+        &self,
+        widgets: &ConsumerGroupsDialogModelWidgets,
+    ) -> Option<OffsetResetTarget> {
+        match self.selected_target {
+            OffsetResetTarget::Timestamp(_) => {
+                let text = widgets.timestamp_entry.text().to_string();
+                let timezone = Settings::read().unwrap_or_default().timezone();
+                NaiveDateTime::parse_from_str(text.trim(), DATE_TIME_FORMAT)
+                    .ok()?
+                    .and_local_timezone(timezone)
+                    .earliest()
+                    .map(|date_time| OffsetResetTarget::Timestamp(date_time.timestamp_millis()))
+            }
+            target => Some(target),
+        }
+    }
+}
+
+fn reset_target_label(target: OffsetResetTarget) -> String {
+    match target {
+        OffsetResetTarget::Timestamp(timestamp) => format!("timestamp {}", timestamp),
+        target => target.to_string().to_lowercase(),
+    }
+}
+
+/// Consumer groups with committed offsets on the topic, along with their per-partition lag.
+async fn fetch_topic_groups(
+    kafka: &KafkaBackend,
+    topic_name: &str,
+) -> Result<Vec<(KrustConsumerGroup, Vec<KrustConsumerGroupOffset>)>, ExternalError> {
+    let mut result = Vec::new();
+    for group in kafka.list_consumer_groups().await? {
+        let offsets = kafka.fetch_group_offsets(&group.name, topic_name).await?;
+        if offsets.iter().any(|o| o.committed.is_some()) {
+            result.push((group, offsets));
+        }
+    }
+    Ok(result)
+}
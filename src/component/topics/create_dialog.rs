@@ -12,11 +12,21 @@ use relm4::*;
 
 use tracing::*;
 
+#[derive(Debug)]
+pub struct ConfigRow {
+    id: usize,
+    container: gtk::Box,
+    key: gtk::Entry,
+    value: gtk::Entry,
+}
+
 #[derive(Debug)]
 pub struct CreateTopicDialogModel {
     pub connection: Option<KrustConnection>,
     pub partition_count: Option<u16>,
     pub replica_count: Option<u8>,
+    pub config_rows: Vec<ConfigRow>,
+    pub next_config_id: usize,
 }
 
 #[derive(Debug)]
@@ -27,6 +37,8 @@ pub enum CreateTopicDialogMsg {
     Close,
     SetPartitionCount,
     SetReplicaCount,
+    AddConfig,
+    RemoveConfig(usize),
 }
 
 #[derive(Debug)]
@@ -92,6 +104,27 @@ impl Component for CreateTopicDialogModel {
                             connect_value_notify => CreateTopicDialogMsg::SetReplicaCount,
                         },
                     },
+                    #[name(configs_group)]
+                    adw::PreferencesGroup {
+                        set_title: "Configs",
+                        set_description: Some("Topic configs, e.g. cleanup.policy or retention.ms"),
+                        set_margin_top: 10,
+                        set_vexpand: false,
+                        set_hexpand: true,
+                        #[wrap(Some)]
+                        set_header_suffix = &gtk::Button {
+                            set_tooltip_text: Some("Add config"),
+                            set_icon_name: "list-add-symbolic",
+                            set_valign: gtk::Align::Center,
+                            add_css_class: "flat",
+                            connect_clicked => CreateTopicDialogMsg::AddConfig,
+                        },
+                        #[name(configs_list)]
+                        gtk::Box {
+                            set_orientation: gtk::Orientation::Vertical,
+                            set_spacing: 5,
+                        },
+                    },
                     gtk::Box {
                         set_margin_top: 10,
                         set_margin_bottom: 10,
@@ -133,6 +166,8 @@ impl Component for CreateTopicDialogModel {
             connection,
             partition_count: None,
             replica_count: None,
+            config_rows: vec![],
+            next_config_id: 0,
         };
 
         let widgets = view_output!();
@@ -182,6 +217,7 @@ impl Component for CreateTopicDialogModel {
                 let name: String = widgets.name.text().into();
                 let partition_count = self.partition_count.unwrap_or(1);
                 let replica_count = self.replica_count.unwrap_or(1);
+                let configs = self.get_configs();
                 let connection = self.connection.clone().unwrap();
                 sender.oneshot_command(async move {
                     let kafka = KafkaBackend::new(&connection);
@@ -190,6 +226,7 @@ impl Component for CreateTopicDialogModel {
                             name: name.clone(),
                             partition_count,
                             replica_count,
+                            configs,
                         })
                         .await;
                     match result {
@@ -214,6 +251,47 @@ impl Component for CreateTopicDialogModel {
                 let value = widgets.replica_count.value();
                 self.replica_count = Some(value as u8);
             }
+            CreateTopicDialogMsg::AddConfig => {
+                let id = self.next_config_id;
+                self.next_config_id += 1;
+                let container = gtk::Box::builder()
+                    .orientation(gtk::Orientation::Horizontal)
+                    .spacing(5)
+                    .build();
+                let key = gtk::Entry::builder()
+                    .placeholder_text("Key")
+                    .hexpand(true)
+                    .build();
+                let value = gtk::Entry::builder()
+                    .placeholder_text("Value")
+                    .hexpand(true)
+                    .build();
+                let remove_button = gtk::Button::builder()
+                    .tooltip_text("Remove config")
+                    .icon_name("edit-delete-symbolic")
+                    .css_classes(["flat"])
+                    .build();
+                let remove_sender = sender.clone();
+                remove_button.connect_clicked(move |_| {
+                    remove_sender.input(CreateTopicDialogMsg::RemoveConfig(id));
+                });
+                container.append(&key);
+                container.append(&value);
+                container.append(&remove_button);
+                widgets.configs_list.append(&container);
+                self.config_rows.push(ConfigRow {
+                    id,
+                    container,
+                    key,
+                    value,
+                });
+            }
+            CreateTopicDialogMsg::RemoveConfig(id) => {
+                if let Some(pos) = self.config_rows.iter().position(|row| row.id == id) {
+                    let row = self.config_rows.remove(pos);
+                    widgets.configs_list.remove(&row.container);
+                }
+            }
         };
 
         self.update_view(widgets, sender);
@@ -230,6 +308,9 @@ impl Component for CreateTopicDialogModel {
             AsyncCommandOutput::CreateResult => {
                 info!("CreateResult");
                 widgets.name.set_text("");
+                for row in self.config_rows.drain(..) {
+                    widgets.configs_list.remove(&row.container);
+                }
                 sender
                     .output(CreateTopicDialogOutput::RefreshTopics)
                     .expect("should send refresh to output");
@@ -238,3 +319,18 @@ impl Component for CreateTopicDialogModel {
         }
     }
 }
+
+impl CreateTopicDialogModel {
+    fn get_configs(&self) -> Vec<(String, String)> {
+        self.config_rows
+            .iter()
+            .filter(|row| !row.key.text().trim().is_empty())
+            .map(|row| {
+                (
+                    row.key.text().trim().to_string(),
+                    row.value.text().trim().to_string(),
+                )
+            })
+            .collect()
+    }
+}
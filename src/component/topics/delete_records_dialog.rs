@@ -0,0 +1,319 @@
+// Copyright (c) 2024, Miguel A. Baldi Hörlle <miguel.horlle@gmail.com>. All rights reserved. Use of
+// this source code is governed by the GPL-3.0 license that can be
+// found in the COPYING file.
+
+use std::collections::HashMap;
+
+use crate::{
+    backend::{
+        kafka::KafkaBackend,
+        repository::{KrustConnection, Partition},
+    },
+    config::ExternalError,
+    modals::utils::{build_confirmation_alert, show_error_alert},
+    AppMsg, TOASTER_BROKER,
+};
+use adw::{prelude::*, AlertDialog};
+use relm4::*;
+use uuid::Uuid;
+
+use tracing::*;
+
+#[derive(Debug)]
+pub struct DeleteRecordsDialogModel {
+    pub connection: Option<KrustConnection>,
+    pub topic_name: Option<String>,
+    pub is_loading: bool,
+    pub confirmation_alert: AlertDialog,
+    partition_rows: Vec<(Partition, adw::SpinRow)>,
+}
+
+#[derive(Debug)]
+pub enum DeleteRecordsDialogMsg {
+    Show(String),
+    Refresh,
+    Delete,
+    ConfirmDelete,
+    Ignore,
+}
+
+#[derive(Debug)]
+pub enum AsyncCommandOutput {
+    PartitionsResult(Vec<Partition>),
+    DeleteResult(Result<Vec<Partition>, ExternalError>),
+}
+
+#[relm4::component(pub)]
+impl Component for DeleteRecordsDialogModel {
+    type Init = Option<KrustConnection>;
+    type Input = DeleteRecordsDialogMsg;
+    type Output = ();
+    type CommandOutput = AsyncCommandOutput;
+
+    view! {
+        #[root]
+        main_dialog = adw::Dialog {
+            set_title: "Delete records",
+            set_content_width: 500,
+            set_content_height: 500,
+            #[wrap(Some)]
+            set_child = &gtk::Box {
+                set_orientation: gtk::Orientation::Vertical,
+                adw::HeaderBar {
+                    pack_end = &gtk::Button {
+                        set_tooltip_text: Some("Refresh"),
+                        set_icon_name: "media-playlist-repeat-symbolic",
+                        #[watch]
+                        set_sensitive: !model.is_loading,
+                        connect_clicked => DeleteRecordsDialogMsg::Refresh,
+                    },
+                },
+                set_valign: gtk::Align::Fill,
+                gtk::ScrolledWindow {
+                    set_vexpand: true,
+                    set_hexpand: true,
+                    set_propagate_natural_height: true,
+                    gtk::Box {
+                        set_orientation: gtk::Orientation::Vertical,
+                        set_valign: gtk::Align::Fill,
+                        set_margin_all: 10,
+                        #[name(partitions_group)]
+                        adw::PreferencesGroup {
+                            #[watch]
+                            set_title: model.topic_name.as_deref().unwrap_or_default(),
+                            set_description: Some("Records before the given offset are deleted from each partition"),
+                            set_vexpand: false,
+                            set_hexpand: true,
+                        },
+                    },
+                },
+                gtk::Box {
+                    set_margin_all: 10,
+                    set_orientation: gtk::Orientation::Horizontal,
+                    set_halign: gtk::Align::End,
+                    gtk::Button {
+                        set_label: "Delete",
+                        add_css_class: "destructive-action",
+                        #[watch]
+                        set_sensitive: !model.is_loading,
+                        connect_clicked => DeleteRecordsDialogMsg::Delete,
+                    },
+                },
+            },
+        }
+    }
+
+    fn init(
+        current_connection: Self::Init,
+        root: Self::Root,
+        sender: ComponentSender<Self>,
+    ) -> ComponentParts<Self> {
+        let confirmation_alert = build_confirmation_alert(
+            "Delete".to_string(),
+            "Are you sure you want to delete the records? This can't be undone.".to_string(),
+        );
+        let snd: ComponentSender<DeleteRecordsDialogModel> = sender.clone();
+        confirmation_alert.connect_response(Some("cancel"), move |_, _| {
+            snd.input(DeleteRecordsDialogMsg::Ignore);
+        });
+        let snd: ComponentSender<DeleteRecordsDialogModel> = sender.clone();
+        confirmation_alert.connect_response(Some("confirm"), move |_, _| {
+            snd.input(DeleteRecordsDialogMsg::ConfirmDelete);
+        });
+        let model = DeleteRecordsDialogModel {
+            connection: current_connection,
+            topic_name: None,
+            is_loading: false,
+            confirmation_alert,
+            partition_rows: Vec::new(),
+        };
+
+        let widgets = view_output!();
+        ComponentParts { model, widgets }
+    }
+
+    fn update_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        msg: DeleteRecordsDialogMsg,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        debug!("received message: {:?}", msg);
+
+        match msg {
+            DeleteRecordsDialogMsg::Show(topic_name) => {
+                self.topic_name = Some(topic_name);
+                for (_, row) in self.partition_rows.drain(..) {
+                    widgets.partitions_group.remove(&row);
+                }
+                let parent = &relm4::main_application().active_window().unwrap();
+                root.queue_allocate();
+                root.present(parent);
+                sender.input(DeleteRecordsDialogMsg::Refresh);
+            }
+            DeleteRecordsDialogMsg::Refresh => {
+                if let (Some(connection), Some(topic_name)) =
+                    (self.connection.clone(), self.topic_name.clone())
+                {
+                    self.is_loading = true;
+                    sender.oneshot_command(async move {
+                        let kafka = KafkaBackend::new(&connection);
+                        let partitions = kafka.fetch_partitions(&topic_name).await;
+                        AsyncCommandOutput::PartitionsResult(partitions)
+                    });
+                }
+            }
+            DeleteRecordsDialogMsg::Delete => {
+                self.confirmation_alert.present(root);
+            }
+            DeleteRecordsDialogMsg::ConfirmDelete => {
+                if let (Some(connection), Some(topic_name)) =
+                    (self.connection.clone(), self.topic_name.clone())
+                {
+                    let offsets = records_to_delete(
+                        self.partition_rows
+                            .iter()
+                            .map(|(partition, row)| (*partition, row.value() as i64)),
+                    );
+                    if offsets.is_empty() {
+                        show_error_alert(root, "No records selected for deletion".to_string());
+                    } else {
+                        self.is_loading = true;
+                        sender.oneshot_command(async move {
+                            let kafka = KafkaBackend::new(&connection);
+                            let result = kafka.delete_records(&topic_name, offsets).await;
+                            AsyncCommandOutput::DeleteResult(result)
+                        });
+                    }
+                }
+            }
+            DeleteRecordsDialogMsg::Ignore => (),
+        };
+
+        self.update_view(widgets, sender);
+    }
+
+    fn update_cmd_with_view(
+        &mut self,
+        widgets: &mut Self::Widgets,
+        message: Self::CommandOutput,
+        sender: ComponentSender<Self>,
+        root: &Self::Root,
+    ) {
+        match message {
+            AsyncCommandOutput::PartitionsResult(mut partitions) => {
+                for (_, row) in self.partition_rows.drain(..) {
+                    widgets.partitions_group.remove(&row);
+                }
+                partitions.sort_by_key(|p| p.id);
+                for partition in partitions {
+                    let row = match watermarks(&partition) {
+                        Some((low, high)) => {
+                            let row = adw::SpinRow::with_range(low as f64, high as f64, 1.0);
+                            row.set_subtitle(format!("Low: {}, High: {}", low, high).as_str());
+                            row.set_value(low as f64);
+                            row
+                        }
+                        None => {
+                            let row = adw::SpinRow::with_range(0.0, 0.0, 1.0);
+                            row.set_subtitle("Unable to fetch watermarks");
+                            row.set_sensitive(false);
+                            row
+                        }
+                    };
+                    row.set_title(format!("Partition {}", partition.id).as_str());
+                    widgets.partitions_group.add(&row);
+                    self.partition_rows.push((partition, row));
+                }
+            }
+            AsyncCommandOutput::DeleteResult(Ok(partitions)) => {
+                let summary = partitions
+                    .iter()
+                    .map(|p| format!("{}={}", p.id, p.offset_low.unwrap_or_default()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                info!("records deleted, low watermarks::{}", summary);
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(
+                    id.clone(),
+                    format!("Records deleted, low watermarks: {}", summary),
+                ));
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+                sender.input(DeleteRecordsDialogMsg::Refresh);
+            }
+            AsyncCommandOutput::DeleteResult(Err(error)) => {
+                let error_message = format!("{}", error);
+                error!(error_message);
+                show_error_alert(root, error_message);
+            }
+        }
+        self.is_loading = false;
+        self.update_view(widgets, sender);
+    }
+}
+
+/// Low and high watermarks of a partition, if they were fetched.
+fn watermarks(partition: &Partition) -> Option<(i64, i64)> {
+    match (partition.offset_low, partition.offset_high) {
+        (Some(low), Some(high)) if low >= 0 && high >= low => Some((low, high)),
+        _ => None,
+    }
+}
+
+/// Offsets to delete records before, by partition. Partitions without watermarks or
+/// with nothing to delete before the chosen offset are left out.
+fn records_to_delete(
+    chosen_offsets: impl IntoIterator<Item = (Partition, i64)>,
+) -> HashMap<i32, i64> {
+    chosen_offsets
+        .into_iter()
+        .filter(|(partition, offset)| {
+            watermarks(partition).is_some_and(|(low, high)| *offset > low && *offset <= high)
+        })
+        .map(|(partition, offset)| (partition.id, offset))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn partition(id: i32, low: i64, high: i64) -> Partition {
+        Partition {
+            id,
+            offset_low: Some(low),
+            offset_high: Some(high),
+        }
+    }
+
+    #[test]
+    fn partitions_without_watermarks_are_left_out() {
+        let offsets = records_to_delete([
+            (partition(0, -1, -1), -1),
+            (partition(1, 0, 10), 5),
+            (
+                Partition {
+                    id: 2,
+                    offset_low: None,
+                    offset_high: None,
+                },
+                0,
+            ),
+        ]);
+
+        assert_eq!(offsets, HashMap::from([(1, 5)]));
+    }
+
+    #[test]
+    fn partitions_with_nothing_to_delete_are_left_out() {
+        let offsets = records_to_delete([
+            (partition(0, 3, 10), 3),
+            (partition(1, 3, 10), 10),
+            (partition(2, 0, 10), 11),
+            (partition(3, 0, 0), 0),
+        ]);
+
+        assert_eq!(offsets, HashMap::from([(1, 10)]));
+    }
+}
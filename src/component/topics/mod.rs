@@ -2,6 +2,9 @@
 // this source code is governed by the GPL-3.0 license that can be
 // found in the COPYING file.
 
+pub(crate) mod cluster_metadata_dialog;
+pub(crate) mod consumer_groups_dialog;
 pub(crate) mod create_dialog;
+pub(crate) mod delete_records_dialog;
 pub(crate) mod topics_page;
 pub(crate) mod topics_tab;
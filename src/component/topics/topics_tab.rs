@@ -2,16 +2,22 @@
 // this source code is governed by the GPL-3.0 license that can be
 // found in the COPYING file.
 
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::{cell::RefCell, cmp::Ordering, collections::HashMap};
 
 use crate::backend::repository::KrustTopicCache;
+use crate::backend::settings::format_timestamp;
+use crate::component::colorize_widget_by_connection;
 use crate::component::messages::messages_cache_settings_dialog::{
     MessagesCacheSettingsDialogModel, MessagesCacheSettingsDialogMsg,
 };
+use crate::component::task_manager::{Task, TaskManagerMsg, TaskVariant, TASK_MANAGER_BROKER};
 use crate::modals::utils::build_confirmation_alert;
 use crate::{
     backend::{
-        kafka::KafkaBackend,
+        kafka::{KafkaBackend, TopicReport},
         repository::{KrustConnection, KrustTopic},
     },
     component::status_bar::{StatusBarMsg, STATUS_BROKER},
@@ -21,18 +27,26 @@ use crate::{
 };
 use crate::{AppMsg, Settings};
 use adw::{prelude::*, AlertDialog};
-use chrono::{TimeZone, Utc};
-use chrono_tz::America;
+use chrono::Utc;
+use chrono_tz::Tz;
 use gtk::glib::SignalHandlerId;
+use regex::Regex;
 use relm4::{
     factory::{DynamicIndex, FactoryComponent},
     typed_view::column::{LabelColumn, RelmColumn, TypedColumnView},
     *,
 };
-use tracing::{debug, error, info};
+use relm4_components::save_dialog::{
+    SaveDialog, SaveDialogMsg, SaveDialogResponse, SaveDialogSettings,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use super::cluster_metadata_dialog::{ClusterMetadataDialogModel, ClusterMetadataDialogMsg};
+use super::consumer_groups_dialog::{ConsumerGroupsDialogModel, ConsumerGroupsDialogMsg};
 use super::create_dialog::{CreateTopicDialogModel, CreateTopicDialogMsg, CreateTopicDialogOutput};
+use super::delete_records_dialog::{DeleteRecordsDialogModel, DeleteRecordsDialogMsg};
 
 relm4::new_action_group!(pub(super) TopicListActionGroup, "topic-list");
 relm4::new_stateless_action!(pub(super) FavouriteAction, TopicListActionGroup, "toggle-favourite");
@@ -44,6 +58,8 @@ pub struct TopicListItem {
     favourite: bool,
     cache: Option<KrustTopicCache>,
     timestamp_formatter: String,
+    timezone: Tz,
+    stale_cache_hours: usize,
     sender: FactorySender<TopicsTabModel>,
     favourite_clicked_handler: RefCell<Option<SignalHandlerId>>,
     cache_clicked_handler: RefCell<Option<SignalHandlerId>>,
@@ -53,6 +69,8 @@ impl TopicListItem {
     fn new(
         value: KrustTopic,
         timestamp_formatter: String,
+        timezone: Tz,
+        stale_cache_hours: usize,
         sender: FactorySender<TopicsTabModel>,
     ) -> Self {
         Self {
@@ -61,6 +79,8 @@ impl TopicListItem {
             favourite: value.favourite.unwrap_or(false),
             cache: value.cached.clone(),
             timestamp_formatter,
+            timezone,
+            stale_cache_hours,
             sender,
             favourite_clicked_handler: RefCell::new(None),
             cache_clicked_handler: RefCell::new(None),
@@ -215,15 +235,34 @@ impl RelmColumn for CacheColumn {
 
     fn bind(item: &mut Self::Item, widgets: &mut Self::Widgets, _box: &mut Self::Root) {
         if let Some(cache) = item.cache.clone() {
-            widgets
-                .cache_button
-                .set_label(&cache.fetch_mode.to_string());
+            let age = cache
+                .last_updated
+                .map(|last_updated| Utc::now().timestamp_millis() - last_updated);
+            let label = match age {
+                Some(age) => format!("{} · {}", cache.fetch_mode, format_age(age)),
+                None => cache.fetch_mode.to_string(),
+            };
+            widgets.cache_button.set_label(&label);
+            let is_stale = item.stale_cache_hours > 0
+                && age.map_or(true, |age| {
+                    age > item.stale_cache_hours as i64 * 60 * 60 * 1000
+                });
+            if is_stale {
+                widgets.cache_button.remove_css_class("suggested-action");
+                widgets.cache_button.add_css_class("warning");
+            } else {
+                widgets.cache_button.remove_css_class("warning");
+                widgets.cache_button.add_css_class("suggested-action");
+            }
             let formatted = format!(
-                "Last update {}",
-                Utc.timestamp_millis_opt(cache.last_updated.unwrap_or_default())
-                    .unwrap()
-                    .with_timezone(&America::Sao_Paulo)
-                    .format(&item.timestamp_formatter)
+                "Last update {}{}",
+                format_timestamp(
+                    cache.last_updated,
+                    &item.timestamp_formatter,
+                    &item.timezone
+                )
+                .unwrap_or_else(|| "N/A".to_string()),
+                if is_stale { " (stale)" } else { "" }
             );
 
             let sender = item.sender.clone();
@@ -249,6 +288,20 @@ impl RelmColumn for CacheColumn {
 }
 // Table: end
 
+/// Short human readable age, e.g. `5m ago` or `3d ago`.
+fn format_age(age_millis: i64) -> String {
+    let minutes = age_millis.max(0) / 1000 / 60;
+    match minutes {
+        0 => "just now".to_string(),
+        m if m < 60 => format!("{}m ago", m),
+        m if m < 60 * 24 => format!("{}h ago", m / 60),
+        m => format!("{}d ago", m / 60 / 24),
+    }
+}
+
+/// UI state key for the favourites filter toggle.
+const FAVOURITES_FILTER_STATE: &str = "favourites_filter";
+
 pub struct TopicsTabInit {
     pub connection: KrustConnection,
 }
@@ -258,10 +311,16 @@ pub struct TopicsTabModel {
     pub topics_wrapper: TypedColumnView<TopicListItem, gtk::SingleSelection>,
     pub is_loading: bool,
     pub search_text: String,
+    pub favourites_only: bool,
+    pub show_internal: bool,
     pub create_topic: Controller<CreateTopicDialogModel>,
+    pub cluster_metadata: Controller<ClusterMetadataDialogModel>,
+    pub consumer_groups: Controller<ConsumerGroupsDialogModel>,
+    pub delete_records: Controller<DeleteRecordsDialogModel>,
     pub confirmation_alert: AlertDialog,
     pub selected_topic_name: Option<String>,
     pub cache_settings_dialog: Controller<MessagesCacheSettingsDialogModel>,
+    pub report_dialog: Controller<SaveDialog>,
 }
 
 #[derive(Debug)]
@@ -274,8 +333,14 @@ pub enum TopicsTabMsg {
         is_active: bool,
     },
     ToggleFavouritesFilter(bool),
+    ToggleInternalTopics(bool),
     RefreshTopics,
     CreateTopic,
+    ShowClusterMetadata,
+    ShowConsumerGroups,
+    ShowDeleteRecords,
+    ExportReport,
+    ExportReportTo(PathBuf),
     DeleteTopic,
     ConfirmDeleteTopic,
     Ignore,
@@ -297,6 +362,7 @@ pub enum CommandMsg {
     ListFinished(Vec<KrustTopic>),
     ShowError(ExternalError),
     DeleteTopicResult,
+    ReportExported(Task, PathBuf, Result<usize, ExternalError>),
 }
 
 impl TopicsTabModel {
@@ -314,6 +380,38 @@ impl TopicsTabModel {
         };
         Ok(result)
     }
+
+    /// Rebuilds the topic list filters, flagging the search entry when the pattern is invalid.
+    fn apply_filters(&mut self, widgets: &TopicsTabModelWidgets) {
+        self.topics_wrapper.clear_filters();
+        if !self.show_internal {
+            self.topics_wrapper
+                .add_filter(|item| !item.name.starts_with("__"));
+        }
+        if self.favourites_only {
+            self.topics_wrapper.add_filter(|item| item.favourite);
+        }
+        if self.search_text.is_empty() {
+            widgets.topics_search_entry.remove_css_class("error");
+            widgets.topics_search_entry.set_tooltip_text(None);
+            return;
+        }
+        match Regex::new(self.search_text.as_str()) {
+            Ok(regex) => {
+                widgets.topics_search_entry.remove_css_class("error");
+                widgets.topics_search_entry.set_tooltip_text(None);
+                self.topics_wrapper
+                    .add_filter(move |item| regex.is_match(item.name.as_str()));
+            }
+            Err(e) => {
+                warn!("invalid topic search pattern: {}", e);
+                widgets.topics_search_entry.add_css_class("error");
+                widgets
+                    .topics_search_entry
+                    .set_tooltip_text(Some(format!("Invalid pattern: {}", e).as_str()));
+            }
+        }
+    }
 }
 
 #[relm4::factory(pub)]
@@ -331,6 +429,7 @@ impl FactoryComponent for TopicsTabModel {
             set_orientation: gtk::Orientation::Vertical,
             set_hexpand: true,
             set_vexpand: true,
+            #[name(toolbar)]
             gtk::CenterBox {
                 set_orientation: gtk::Orientation::Horizontal,
                 set_margin_all: 10,
@@ -350,14 +449,65 @@ impl FactoryComponent for TopicsTabModel {
                         set_margin_start: 5,
                         set_label: "Favourites",
                         add_css_class: "krust-toggle",
+                        set_active: self.favourites_only,
                         connect_toggled[sender] => move |btn| {
                             sender.input(TopicsTabMsg::ToggleFavouritesFilter(btn.is_active()));
                         },
                     },
+                    #[name(btn_internal_toggle)]
+                    gtk::ToggleButton {
+                        set_margin_start: 5,
+                        set_label: "Internal",
+                        set_tooltip_text: Some("Show internal topics"),
+                        add_css_class: "krust-toggle",
+                        connect_toggled[sender] => move |btn| {
+                            sender.input(TopicsTabMsg::ToggleInternalTopics(btn.is_active()));
+                        },
+                    },
                 },
                 #[wrap(Some)]
                 set_end_widget = &gtk::Box {
                     set_orientation: gtk::Orientation::Horizontal,
+                    #[name(btn_cluster_metadata)]
+                    gtk::Button {
+                        set_tooltip_text: Some("Cluster metadata"),
+                        set_icon_name: "network-server-symbolic",
+                        set_margin_start: 5,
+                        connect_clicked[sender] => move |_| {
+                            sender.input(TopicsTabMsg::ShowClusterMetadata);
+                        },
+                    },
+                    #[name(btn_consumer_groups)]
+                    gtk::Button {
+                        set_tooltip_text: Some("Consumer groups of selected topic"),
+                        set_icon_name: "system-users-symbolic",
+                        set_margin_start: 5,
+                        #[watch]
+                        set_sensitive: self.selected_topic_name.is_some(),
+                        connect_clicked[sender] => move |_| {
+                            sender.input(TopicsTabMsg::ShowConsumerGroups);
+                        },
+                    },
+                    #[name(btn_delete_records)]
+                    gtk::Button {
+                        set_tooltip_text: Some("Delete records of selected topic"),
+                        set_icon_name: "edit-clear-all-symbolic",
+                        set_margin_start: 5,
+                        #[watch]
+                        set_sensitive: self.selected_topic_name.is_some(),
+                        connect_clicked[sender] => move |_| {
+                            sender.input(TopicsTabMsg::ShowDeleteRecords);
+                        },
+                    },
+                    #[name(btn_topics_report)]
+                    gtk::Button {
+                        set_tooltip_text: Some("Save a report of all topics to a .csv or .json file"),
+                        set_icon_name: "x-office-spreadsheet-symbolic",
+                        set_margin_start: 5,
+                        connect_clicked[sender] => move |_| {
+                            sender.input(TopicsTabMsg::ExportReport);
+                        },
+                    },
                     #[name(btn_create_topic)]
                     gtk::Button {
                         set_tooltip_text: Some("Create topic"),
@@ -411,16 +561,34 @@ impl FactoryComponent for TopicsTabModel {
         view_wrapper.append_column::<PartitionCountColumn>();
         view_wrapper.append_column::<CacheColumn>();
 
-        // Add a filter and disable it
-        view_wrapper.add_filter(|item| item.favourite);
-        view_wrapper.set_filter_status(0, false);
+        // Internal topics are hidden by default
+        view_wrapper.add_filter(|item| !item.name.starts_with("__"));
         let connection = current.connection.clone();
+        let favourites_only = connection
+            .id
+            .and_then(|conn_id| {
+                Repository::new().find_ui_state(conn_id, "", FAVOURITES_FILTER_STATE)
+            })
+            .map(|value| value == "true")
+            .unwrap_or_default();
+        if favourites_only {
+            view_wrapper.add_filter(|item| item.favourite);
+        }
 
         let create_topic = CreateTopicDialogModel::builder()
             .launch(Some(connection.clone()))
             .forward(sender.input_sender(), |msg| match msg {
                 CreateTopicDialogOutput::RefreshTopics => TopicsTabMsg::RefreshTopics,
             });
+        let cluster_metadata = ClusterMetadataDialogModel::builder()
+            .launch(Some(connection.clone()))
+            .detach();
+        let consumer_groups = ConsumerGroupsDialogModel::builder()
+            .launch(Some(connection.clone()))
+            .detach();
+        let delete_records = DeleteRecordsDialogModel::builder()
+            .launch(Some(connection.clone()))
+            .detach();
 
         let confirmation_alert = build_confirmation_alert(
             "Delete".to_string(),
@@ -439,16 +607,34 @@ impl FactoryComponent for TopicsTabModel {
             //.transient_for(main_application())
             .launch((connection.clone(), None))
             .detach();
+        let report_dialog = SaveDialog::builder()
+            .launch(SaveDialogSettings {
+                accept_label: String::from("Save"),
+                cancel_label: String::from("Cancel"),
+                create_folders: true,
+                is_modal: true,
+                filters: Vec::new(),
+            })
+            .forward(sender.input_sender(), |response| match response {
+                SaveDialogResponse::Accept(path) => TopicsTabMsg::ExportReportTo(path),
+                SaveDialogResponse::Cancel => TopicsTabMsg::Ignore,
+            });
 
         let model = TopicsTabModel {
             current: Some(connection),
             topics_wrapper: view_wrapper,
             is_loading: false,
             search_text: String::default(),
+            favourites_only,
+            show_internal: false,
             create_topic,
+            cluster_metadata,
+            consumer_groups,
+            delete_records,
             confirmation_alert,
             selected_topic_name: None,
             cache_settings_dialog,
+            report_dialog,
         };
 
         let topics_view = &model.topics_wrapper.view;
@@ -472,6 +658,12 @@ impl FactoryComponent for TopicsTabModel {
         model
     }
 
+    fn post_view(&self, widgets: &mut Self::Widgets) {
+        if let Some(conn) = &self.current {
+            colorize_widget_by_connection(conn, widgets.toolbar.clone().upcast());
+        }
+    }
+
     fn update_with_view(
         &mut self,
         widgets: &mut Self::Widgets,
@@ -502,6 +694,53 @@ impl FactoryComponent for TopicsTabModel {
             TopicsTabMsg::CreateTopic => {
                 self.create_topic.emit(CreateTopicDialogMsg::Show);
             }
+            TopicsTabMsg::ShowClusterMetadata => {
+                self.cluster_metadata.emit(ClusterMetadataDialogMsg::Show);
+            }
+            TopicsTabMsg::ShowConsumerGroups => {
+                if let Some(topic_name) = self.selected_topic_name.clone() {
+                    self.consumer_groups
+                        .emit(ConsumerGroupsDialogMsg::Show(topic_name));
+                }
+            }
+            TopicsTabMsg::ShowDeleteRecords => {
+                if let Some(topic_name) = self.selected_topic_name.clone() {
+                    self.delete_records
+                        .emit(DeleteRecordsDialogMsg::Show(topic_name));
+                }
+            }
+            TopicsTabMsg::ExportReport => {
+                if let Some(connection) = &self.current {
+                    self.report_dialog.emit(SaveDialogMsg::SaveAs(format!(
+                        "{}-topics.csv",
+                        connection.name
+                    )));
+                }
+            }
+            TopicsTabMsg::ExportReportTo(path) => {
+                let Some(connection) = self.current.clone() else {
+                    return;
+                };
+                let task = Task::new(
+                    TaskVariant::TopicsReport,
+                    Some(connection.name.clone()),
+                    Some(CancellationToken::new()),
+                );
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::AddTask(task.clone()));
+                TOASTER_BROKER.send(AppMsg::ShowToast(
+                    task.id.clone(),
+                    "Reporting topics...".to_string(),
+                ));
+                sender.oneshot_command(async move {
+                    let kafka = KafkaBackend::new(&connection);
+                    let result = match kafka.topics_report(&task).await {
+                        Ok(_) if task.token.as_ref().is_some_and(|t| t.is_cancelled()) => Ok(0),
+                        Ok(reports) => write_topics_report(&reports, &path),
+                        Err(e) => Err(e),
+                    };
+                    CommandMsg::ReportExported(task, path, result)
+                });
+            }
             TopicsTabMsg::ConfirmDeleteTopic => {
                 info!("deleting topic {:?}", self.selected_topic_name.clone());
                 let connection = self.current.clone().unwrap();
@@ -520,10 +759,8 @@ impl FactoryComponent for TopicsTabModel {
                 self.confirmation_alert.present(&widgets.root);
             }
             TopicsTabMsg::Search(term) => {
-                self.topics_wrapper.clear_filters();
-                let search_term = term.clone();
-                self.topics_wrapper
-                    .add_filter(move |item| item.name.contains(search_term.as_str()));
+                self.search_text = term;
+                self.apply_filters(widgets);
             }
             TopicsTabMsg::List(conn) => {
                 STATUS_BROKER.send(StatusBarMsg::Start);
@@ -627,13 +864,26 @@ impl FactoryComponent for TopicsTabModel {
                 //sender.input(TopicsTabMsg::List(self.current.clone().unwrap()));
             }
             TopicsTabMsg::ToggleFavouritesFilter(is_active) => {
-                if is_active {
-                    self.topics_wrapper.clear_filters();
-                    self.topics_wrapper.add_filter(|item| item.favourite);
-                } else {
-                    self.topics_wrapper.clear_filters();
+                self.favourites_only = is_active;
+                self.apply_filters(widgets);
+                if let Some(conn_id) = self.current.as_ref().and_then(|c| c.id) {
+                    Repository::new()
+                        .save_ui_state(
+                            conn_id,
+                            "",
+                            FAVOURITES_FILTER_STATE,
+                            is_active.to_string().as_str(),
+                        )
+                        .unwrap_or_else(|e| {
+                            warn!("unable to save favourites filter state: {:?}", e);
+                            0
+                        });
                 }
             }
+            TopicsTabMsg::ToggleInternalTopics(is_active) => {
+                self.show_internal = is_active;
+                self.apply_filters(widgets);
+            }
         };
 
         self.update_view(widgets, sender);
@@ -650,10 +900,23 @@ impl FactoryComponent for TopicsTabModel {
                 self.topics_wrapper.clear();
                 let settings = Settings::read().unwrap_or_default();
                 let timestamp_formatter = settings.timestamp_formatter();
-                for topic in topics.into_iter().filter(|t| !t.name.starts_with("__")) {
+                let timezone = settings.timezone();
+                let conn_id = self.current.as_ref().and_then(|c| c.id);
+                let mut repo = Repository::new();
+                for mut topic in topics.into_iter() {
+                    // latest cache state, refreshes may have run since topics were listed
+                    if let Some(conn_id) = conn_id {
+                        topic.cached = repo.find_topic_cache(conn_id, &topic.name);
+                    }
                     let snd = sender.clone();
                     self.topics_wrapper.insert_sorted(
-                        TopicListItem::new(topic, timestamp_formatter.clone(), snd),
+                        TopicListItem::new(
+                            topic,
+                            timestamp_formatter.clone(),
+                            timezone,
+                            settings.stale_cache_hours,
+                            snd,
+                        ),
                         |a, b| a.cmp(b),
                     );
                 }
@@ -687,6 +950,66 @@ impl FactoryComponent for TopicsTabModel {
             CommandMsg::DeleteTopicResult => {
                 sender.input(TopicsTabMsg::RefreshTopics);
             }
+            CommandMsg::ReportExported(task, path, result) => {
+                TASK_MANAGER_BROKER.send(TaskManagerMsg::Progress(task.clone(), 1.0));
+                TOASTER_BROKER.send(AppMsg::HideToast(task.id.clone()));
+                let cancelled = task.token.as_ref().is_some_and(|t| t.is_cancelled());
+                let text = match result {
+                    Ok(_) if cancelled => "Topics report cancelled".to_string(),
+                    Ok(topics) => {
+                        info!("reported {} topics to {:?}", topics, &path);
+                        format!("Reported {} topics to {}", topics, path.display())
+                    }
+                    Err(e) => {
+                        error!("unable to report topics: {:?}", e);
+                        "Unable to report topics".to_string()
+                    }
+                };
+                let id = Uuid::new_v4().to_string();
+                TOASTER_BROKER.send(AppMsg::ShowToast(id.clone(), text));
+                TOASTER_BROKER.send(AppMsg::HideToast(id));
+            }
+        }
+    }
+}
+
+/// Writes one CSV row per partition, or the nested reports as JSON for any other extension.
+fn write_topics_report(reports: &[TopicReport], path: &Path) -> Result<usize, ExternalError> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let is_csv = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        let mut wtr = csv::Writer::from_writer(file);
+        wtr.write_record([
+            "topic",
+            "partition_count",
+            "total_messages",
+            "partition",
+            "offset_low",
+            "offset_high",
+            "messages",
+        ])
+        .map_err(std::io::Error::from)?;
+        for report in reports {
+            for partition in &report.partitions {
+                wtr.write_record([
+                    report.topic.clone(),
+                    report.partition_count.to_string(),
+                    report.total_messages.to_string(),
+                    partition.partition.to_string(),
+                    partition.offset_low.to_string(),
+                    partition.offset_high.to_string(),
+                    partition.messages.to_string(),
+                ])
+                .map_err(std::io::Error::from)?;
+            }
         }
+        wtr.flush()?;
+    } else {
+        serde_json::to_writer_pretty(&mut file, reports).map_err(std::io::Error::from)?;
+        file.flush()?;
     }
+    Ok(reports.len())
 }
@@ -52,8 +52,24 @@ pub struct State {
     /// Panned separator position
     pub separator_position: i32,
 
+    /// Separator position between the messages list and the message viewer.
+    pub messages_separator_position: Option<i32>,
+
     /// Whether the window should be maximized at startup.
     pub is_maximized: bool,
+
+    /// Messages tabs open at shutdown, in tab order.
+    pub open_tabs: Vec<OpenTab>,
+
+    /// Messages tab selected at shutdown.
+    pub active_tab: Option<OpenTab>,
+}
+
+/// A messages tab, identified by its connection and topic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OpenTab {
+    pub connection_id: usize,
+    pub topic_name: String,
 }
 
 impl State {
@@ -98,7 +114,10 @@ impl Default for State {
             width,
             height: 600,
             separator_position: ((width as f32) * 0.25).round() as i32,
+            messages_separator_position: None,
             is_maximized: false,
+            open_tabs: Vec::new(),
+            active_tab: None,
         }
     }
 }
@@ -4,6 +4,8 @@
 // found in the COPYING file.
 
 use std::env;
+use std::fs::OpenOptions;
+use std::sync::Mutex;
 
 use gtk::gdk;
 use gtk::gio;
@@ -25,7 +27,7 @@ use relm4::{
     gtk, RelmApp,
 };
 
-use krust::{AppModel, Repository, APP_ID};
+use krust::{AppModel, MessagesSearchAction, Repository, APP_ID};
 
 relm4::new_action_group!(AppActionGroup, "app");
 relm4::new_stateless_action!(QuitAction, AppActionGroup, "quit");
@@ -39,17 +41,38 @@ fn initialize_resources() {
 }
 
 fn main() -> Result<(), ()> {
-    let threads_number = Settings::read().unwrap_or_default().threads_number as usize;
+    let settings = Settings::read().unwrap_or_default();
+    let threads_number = settings.threads_number as usize;
     RELM_THREADS.set(threads_number).unwrap();
     RELM_BLOCKING_THREADS.set(threads_number).unwrap();
-    let filter = filter::Targets::new()
-        // Enable the `INFO` level for anything in `my_crate`
-        .with_target("relm4", Level::WARN)
-        // Enable the `DEBUG` level for a specific module.
-        .with_target("krust", Level::TRACE);
+    // RUST_LOG overrides the log level from settings
+    let env_filter = env::var(EnvFilter::DEFAULT_ENV)
+        .is_ok()
+        .then(EnvFilter::from_default_env);
+    let filter = env_filter.is_none().then(|| {
+        filter::Targets::new()
+            .with_target("relm4", Level::WARN)
+            .with_target("krust", settings.log_level())
+    });
+    let file_layer = settings
+        .log_file_path()
+        .and_then(|path| {
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .map_err(|e| eprintln!("unable to open log file {:?}: {:?}", path, e))
+                .ok()
+        })
+        .map(|file| {
+            tracing_subscriber::fmt::layer()
+                .with_ansi(false)
+                .with_writer(Mutex::new(file))
+        });
     tracing_subscriber::registry()
         .with(HierarchicalLayer::new(2))
-        .with(EnvFilter::from_default_env())
+        .with(file_layer)
+        .with(env_filter)
         .with(filter)
         .init();
 
@@ -61,9 +84,9 @@ fn main() -> Result<(), ()> {
             render
         }
         Err(_) => {
-            let render = "gl";
-            env::set_var(gsk_renderer_var, render);
-            render.to_string()
+            let render = settings.gsk_renderer;
+            env::set_var(gsk_renderer_var, &render);
+            render
         }
     };
     info!(
@@ -116,7 +139,7 @@ fn main() -> Result<(), ()> {
     Ok(())
 }
 
-pub fn setup_shortcuts(_app: &adw::Application) {
+pub fn setup_shortcuts(app: &adw::Application) {
     info!("registering application shortcuts...");
-    // app.set_accelerators_for_action::<MessagesSearchAction>(&["<Enter>"]);
+    app.set_accelerators_for_action::<MessagesSearchAction>(&["<Control>f"]);
 }
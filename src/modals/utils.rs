@@ -3,6 +3,9 @@
 // found in the COPYING file.
 
 use adw::prelude::*;
+use rdkafka::error::KafkaError;
+
+use crate::backend::repository::KrustMessage;
 
 pub(crate) fn show_error_alert(parent: &impl IsA<gtk::Widget>, message: String) {
     let alert = adw::AlertDialog::builder()
@@ -20,6 +23,36 @@ pub(crate) fn show_error_alert(parent: &impl IsA<gtk::Widget>, message: String)
     alert.present(parent);
 }
 
+/// Describes the messages a send left behind, e.g. for an error alert.
+pub(crate) fn send_failures_message(failures: &[(KrustMessage, KafkaError)]) -> String {
+    const MAX_LISTED: usize = 10;
+    let (canceled, failed): (Vec<_>, Vec<_>) = failures
+        .iter()
+        .partition(|(_, e)| matches!(e, KafkaError::Canceled));
+    let mut lines = vec![format!("Unable to send {} message(s):", failed.len())];
+    for (message, error) in failed.iter().take(MAX_LISTED) {
+        let key = message.key.clone().unwrap_or_default();
+        if message.offset > 0 {
+            lines.push(format!(
+                "• key \"{}\" (partition {}, offset {}): {}",
+                key, message.partition, message.offset, error
+            ));
+        } else {
+            lines.push(format!("• key \"{}\": {}", key, error));
+        }
+    }
+    if failed.len() > MAX_LISTED {
+        lines.push(format!("• and {} more", failed.len() - MAX_LISTED));
+    }
+    if !canceled.is_empty() {
+        lines.push(format!(
+            "{} remaining message(s) were not sent.",
+            canceled.len()
+        ));
+    }
+    lines.join("\n")
+}
+
 pub(crate) fn build_confirmation_alert(
     confirmation_label: String,
     message: String,